@@ -11,11 +11,11 @@ use itertools::Itertools;
 use rayon::prelude::*;
 use rustyms::{
     spectrum::{Score, Scores},
-    system::{e, usize::Charge},
+    system::{e, isize::Charge},
     *,
 };
 use spectrum::PeakSpectrum;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Parser)]
 struct Cli {
@@ -51,7 +51,21 @@ fn main() {
     let mut out_data = Vec::new();
 
     for (file_name, lines) in files {
-        let file = rustyms::rawfile::mgf::open(&file_name).unwrap();
+        // Stream the file and only keep the spectra that are needed
+        let scan_numbers: HashSet<usize> = lines
+            .iter()
+            .filter_map(|line| line.index_column("scan_number").ok()?.0.parse().ok())
+            .collect();
+        let spectra: HashMap<usize, _> = rustyms::rawfile::mgf::MgfReader::open(&file_name)
+            .unwrap()
+            .map(Result::unwrap)
+            .filter_map(|spectrum| {
+                spectrum
+                    .raw_scan_number
+                    .filter(|n| scan_numbers.contains(n))
+                    .map(|n| (n, spectrum))
+            })
+            .collect();
 
         let rows = lines
             .par_iter()
@@ -62,7 +76,7 @@ fn main() {
                     .0
                     .parse::<usize>()
                     .unwrap();
-                let z = line.index_column("z").unwrap().0.parse::<usize>().unwrap();
+                let z = line.index_column("z").unwrap().0.parse::<isize>().unwrap();
                 let peptide = CompoundPeptidoform::pro_forma(
                     line.index_column("sequence").unwrap().0,
                     custom_database.as_ref(),
@@ -79,8 +93,7 @@ fn main() {
                     Ok("all") => Model::all(),
                     _ => model.clone(),
                 };
-                if let Some(spectrum) = file.iter().find(|s| s.raw_scan_number == Some(scan_number))
-                {
+                if let Some(spectrum) = spectra.get(&scan_number) {
                     let fragments =
                         peptide.generate_theoretical_fragments(Charge::new::<e>(z), &model);
                     let annotated = spectrum.annotate(
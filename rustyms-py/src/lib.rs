@@ -863,6 +863,9 @@ enum FragmentationModel {
     CidHcd,
     Etd,
     Ethcd,
+    Uvpd,
+    Ecd,
+    Ead,
 }
 
 /// Helper function to match a [`FragmentationModel`] to a rustyms Model.
@@ -872,6 +875,9 @@ fn match_model(model: &FragmentationModel) -> PyResult<rustyms::Model> {
         FragmentationModel::CidHcd => Ok(rustyms::Model::cid_hcd()),
         FragmentationModel::Etd => Ok(rustyms::Model::etd()),
         FragmentationModel::Ethcd => Ok(rustyms::Model::ethcd()),
+        FragmentationModel::Uvpd => Ok(rustyms::Model::uvpd()),
+        FragmentationModel::Ecd => Ok(rustyms::Model::ecd()),
+        FragmentationModel::Ead => Ok(rustyms::Model::ead()),
     }
 }
 
@@ -928,13 +934,13 @@ impl CompoundPeptidoform {
     ///
     fn generate_theoretical_fragments(
         &self,
-        max_charge: usize,
+        max_charge: isize,
         model: &FragmentationModel,
     ) -> PyResult<Vec<Fragment>> {
         Ok(self
             .0
             .generate_theoretical_fragments(
-                rustyms::system::usize::Charge::new::<rustyms::system::e>(max_charge),
+                rustyms::system::isize::Charge::new::<rustyms::system::e>(max_charge),
                 &match_model(model)?,
             )
             .iter()
@@ -1008,13 +1014,13 @@ impl Peptidoform {
     ///
     fn generate_theoretical_fragments(
         &self,
-        max_charge: usize,
+        max_charge: isize,
         model: &FragmentationModel,
     ) -> PyResult<Vec<Fragment>> {
         Ok(self
             .0
             .generate_theoretical_fragments(
-                rustyms::system::usize::Charge::new::<rustyms::system::e>(max_charge),
+                rustyms::system::isize::Charge::new::<rustyms::system::e>(max_charge),
                 &match_model(model)?,
             )
             .iter()
@@ -1211,12 +1217,12 @@ impl LinearPeptide {
     ///
     fn generate_theoretical_fragments(
         &self,
-        max_charge: usize,
+        max_charge: isize,
         model: &FragmentationModel,
     ) -> Option<Vec<Fragment>> {
         self.0.clone().into_linear().map(|p| {
             p.generate_theoretical_fragments(
-                rustyms::system::usize::Charge::new::<rustyms::system::e>(max_charge),
+                rustyms::system::isize::Charge::new::<rustyms::system::e>(max_charge),
                 &match_model(model).unwrap(),
             )
             .iter()
@@ -1392,7 +1398,7 @@ impl RawSpectrum {
         mz_array: Vec<f64>,
         intensity_array: Vec<f64>,
         rt: Option<f64>,
-        precursor_charge: Option<usize>,
+        precursor_charge: Option<isize>,
         precursor_mass: Option<f64>,
     ) -> Self {
         let mut spectrum = rustyms::RawSpectrum::default();
@@ -1400,7 +1406,7 @@ impl RawSpectrum {
         spectrum.num_scans = num_scans;
         spectrum.rt = rt.map(rustyms::system::Time::new::<rustyms::system::s>);
         spectrum.charge =
-            precursor_charge.map(rustyms::system::usize::Charge::new::<rustyms::system::e>);
+            precursor_charge.map(rustyms::system::isize::Charge::new::<rustyms::system::e>);
         spectrum.mass = precursor_mass.map(rustyms::system::Mass::new::<rustyms::system::dalton>);
 
         let peaks = mz_array
@@ -1471,7 +1477,7 @@ impl RawSpectrum {
     /// -------
     /// float
     #[getter]
-    fn charge(&self) -> Option<usize> {
+    fn charge(&self) -> Option<isize> {
         self.0.charge.map(|v| v.value)
     }
 
@@ -1529,7 +1535,7 @@ impl RawSpectrum {
         let fragments = peptide.0.generate_theoretical_fragments(
             self.0
                 .charge
-                .unwrap_or(rustyms::system::usize::Charge::new::<rustyms::system::e>(1)),
+                .unwrap_or(rustyms::system::isize::Charge::new::<rustyms::system::e>(1)),
             &rusty_model,
         );
         Ok(AnnotatedSpectrum(self.0.annotate(
@@ -1606,7 +1612,7 @@ impl AnnotatedSpectrum {
     /// -------
     /// float
     #[getter]
-    fn charge(&self) -> Option<usize> {
+    fn charge(&self) -> Option<isize> {
         self.0.charge.map(|v| v.value)
     }
 
@@ -288,137 +288,148 @@ impl AminoAcid {
         let mut base_fragments = Vec::with_capacity(ions.size_upper_bound());
         let n_pos = PeptidePosition::n(sequence_index, sequence_length);
         let c_pos = PeptidePosition::c(sequence_index, sequence_length);
+        let hydrogens = |variant: i8| molecular_formula!(H 1) * i32::from(variant);
 
         if ions.a.0 && allow_terminal.0 {
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications - molecular_formula!(H 1 C 1 O 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::a(n_pos),
-                n_term,
-                ions.a.1,
-                charge_carriers,
-                ions.a.2,
-            ));
+            for variant in ions.a.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(self.formulas_inner(sequence_index, peptide_index)
+                        * (modifications - molecular_formula!(H 1 C 1 O 1) + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::a(n_pos, *variant),
+                    n_term,
+                    ions.a.1,
+                    charge_carriers,
+                    ions.a.2,
+                ));
+            }
         }
         if ions.b.0 && allow_terminal.0 {
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications - molecular_formula!(H 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::b(n_pos),
-                n_term,
-                ions.b.1,
-                charge_carriers,
-                ions.b.2,
-            ));
+            for variant in ions.b.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(self.formulas_inner(sequence_index, peptide_index)
+                        * (modifications - molecular_formula!(H 1) + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::b(n_pos, *variant),
+                    n_term,
+                    ions.b.1,
+                    charge_carriers,
+                    ions.b.2,
+                ));
+            }
         }
         if ions.c.0 && allow_terminal.0 {
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications + molecular_formula!(H 2 N 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::c(n_pos),
-                n_term,
-                ions.c.1,
-                charge_carriers,
-                ions.c.2,
-            ));
+            for variant in ions.c.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(self.formulas_inner(sequence_index, peptide_index)
+                        * (modifications + molecular_formula!(H 2 N 1) + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::c(n_pos, *variant),
+                    n_term,
+                    ions.c.1,
+                    charge_carriers,
+                    ions.c.2,
+                ));
+            }
         }
         if ions.d.0 && allow_terminal.0 {
-            base_fragments.extend(Fragment::generate_all(
-                &(-self.satellite_ion_fragments(sequence_index, peptide_index)
-                    * modifications
-                    * self.formulas_inner(sequence_index, peptide_index)
-                    + molecular_formula!(H 1 C 1 O 1)),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::d(n_pos),
-                n_term,
-                ions.d.1,
-                charge_carriers,
-                ions.d.2,
-            ));
+            for variant in ions.d.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(-self.satellite_ion_fragments(sequence_index, peptide_index)
+                        * modifications
+                        * self.formulas_inner(sequence_index, peptide_index)
+                        + molecular_formula!(H 1 C 1 O 1)
+                        + hydrogens(*variant)),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::d(n_pos, *variant),
+                    n_term,
+                    ions.d.1,
+                    charge_carriers,
+                    ions.d.2,
+                ));
+            }
         }
         if ions.v.0 && allow_terminal.1 {
-            base_fragments.extend(Fragment::generate_all(
-                &molecular_formula!(H 3 C 2 N 1 O 1).into(),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::v(c_pos),
-                c_term,
-                ions.v.1,
-                charge_carriers,
-                ions.v.2,
-            ));
+            for variant in ions.v.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(Multi::from(molecular_formula!(H 3 C 2 N 1 O 1) + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::v(c_pos, *variant),
+                    c_term,
+                    ions.v.1,
+                    charge_carriers,
+                    ions.v.2,
+                ));
+            }
         }
         if ions.w.0 && allow_terminal.1 {
-            base_fragments.extend(Fragment::generate_all(
-                &(-self.satellite_ion_fragments(sequence_index, peptide_index)
-                    * modifications
-                    * self.formulas_inner(sequence_index, peptide_index)
-                    + molecular_formula!(H 2 N 1)),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::w(c_pos),
-                c_term,
-                ions.w.1,
-                charge_carriers,
-                ions.w.2,
-            ));
+            for variant in ions.w.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(-self.satellite_ion_fragments(sequence_index, peptide_index)
+                        * modifications
+                        * self.formulas_inner(sequence_index, peptide_index)
+                        + molecular_formula!(H 2 N 1)
+                        + hydrogens(*variant)),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::w(c_pos, *variant),
+                    c_term,
+                    ions.w.1,
+                    charge_carriers,
+                    ions.w.2,
+                ));
+            }
         }
         if ions.x.0 && allow_terminal.1 {
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications + molecular_formula!(C 1 O 1) - molecular_formula!(H 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::x(c_pos),
-                c_term,
-                ions.x.1,
-                charge_carriers,
-                ions.x.2,
-            ));
+            for variant in ions.x.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(self.formulas_inner(sequence_index, peptide_index)
+                        * (modifications + molecular_formula!(C 1 O 1) - molecular_formula!(H 1)
+                            + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::x(c_pos, *variant),
+                    c_term,
+                    ions.x.1,
+                    charge_carriers,
+                    ions.x.2,
+                ));
+            }
         }
         if ions.y.0 && allow_terminal.1 {
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications + molecular_formula!(H 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::y(c_pos),
-                c_term,
-                ions.y.1,
-                charge_carriers,
-                ions.y.2,
-            ));
+            for variant in ions.y.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(self.formulas_inner(sequence_index, peptide_index)
+                        * (modifications + molecular_formula!(H 1) + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::y(c_pos, *variant),
+                    c_term,
+                    ions.y.1,
+                    charge_carriers,
+                    ions.y.2,
+                ));
+            }
         }
         if ions.z.0 && allow_terminal.1 {
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications - molecular_formula!(H 2 N 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::z(c_pos),
-                c_term,
-                ions.z.1,
-                charge_carriers,
-                ions.z.2,
-            ));
-            base_fragments.extend(Fragment::generate_all(
-                &(self.formulas_inner(sequence_index, peptide_index)
-                    * (modifications - molecular_formula!(H 1 N 1))),
-                peptidoform_index,
-                peptide_index,
-                &FragmentType::z·(c_pos),
-                c_term,
-                ions.z.1,
-                charge_carriers,
-                ions.z.2,
-            ));
+            for variant in ions.z.3 {
+                base_fragments.extend(Fragment::generate_all(
+                    &(self.formulas_inner(sequence_index, peptide_index)
+                        * (modifications - molecular_formula!(H 2 N 1) + hydrogens(*variant))),
+                    peptidoform_index,
+                    peptide_index,
+                    &FragmentType::z(c_pos, *variant),
+                    c_term,
+                    ions.z.1,
+                    charge_carriers,
+                    ions.z.2,
+                ));
+            }
         }
 
         if ions.immonium.0 && allow_terminal.0 && allow_terminal.1 {
@@ -0,0 +1,156 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    peptide::{AtMax, SemiAmbiguous},
+    LinearPeptide, Protease, ResidueSpecificity, SequenceElement,
+};
+
+/// A strategy to generate decoy sequences from target sequences
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum DecoyStrategy {
+    /// Reverse the full sequence
+    Reverse,
+    /// Reverse the sequence between the cleavage sites of the given protease, keeping the amino
+    /// acids that define the cleavage sites in place. For a tryptic peptide this keeps the C
+    /// terminal K/R, so the decoy peptides have the same masses and cleavage sites as the targets.
+    PseudoReverse(Protease),
+    /// Shuffle the sequence between the cleavage sites of the given protease with the given seed,
+    /// keeping the amino acids that define the cleavage sites in place (like [`Self::PseudoReverse`]).
+    #[cfg(feature = "rand")]
+    Shuffle(Protease, u64),
+}
+
+impl<Complexity: AtMax<SemiAmbiguous>> LinearPeptide<Complexity> {
+    /// Generate a decoy sequence with the given strategy. The modifications stay on their amino
+    /// acids and the terminal modifications stay on the termini.
+    #[must_use]
+    pub fn decoy(&self, strategy: &DecoyStrategy) -> Self {
+        let mut decoy = self.clone();
+        match strategy {
+            DecoyStrategy::Reverse => decoy.sequence_mut().reverse(),
+            DecoyStrategy::PseudoReverse(protease) => {
+                for indices in movable_segments(protease, self.sequence()) {
+                    for (a, b) in indices
+                        .iter()
+                        .zip(indices.iter().rev())
+                        .take(indices.len() / 2)
+                    {
+                        decoy.sequence_mut().swap(*a, *b);
+                    }
+                }
+            }
+            #[cfg(feature = "rand")]
+            DecoyStrategy::Shuffle(protease, seed) => {
+                use rand::{Rng, SeedableRng};
+                let mut rng = rand::rngs::StdRng::seed_from_u64(*seed);
+                for indices in movable_segments(protease, self.sequence()) {
+                    // Fisher-Yates shuffle of the movable amino acids
+                    for i in (1..indices.len()).rev() {
+                        decoy
+                            .sequence_mut()
+                            .swap(indices[i], indices[rng.gen_range(0..=i)]);
+                    }
+                }
+            }
+        }
+        decoy
+    }
+}
+
+/// Split the sequence on all cleavage sites of the protease and give for every segment the
+/// indices of all amino acids that do not define a cleavage site.
+///
+/// The termini of the sequence are
+/// treated as if any amino acid could be beyond it, so the C terminal K of a tryptic peptide is
+/// seen as defining a cleavage site.
+fn movable_segments<T>(protease: &Protease, sequence: &[SequenceElement<T>]) -> Vec<Vec<usize>> {
+    let mut fixed = vec![false; sequence.len()];
+    let mut sites = vec![0];
+    for site in 0..=sequence.len() {
+        let window = protease
+            .n_term
+            .iter()
+            .chain(protease.c_term.iter())
+            .enumerate()
+            .filter_map(|(offset, pattern)| {
+                (site + offset)
+                    .checked_sub(protease.n_term.len())
+                    .filter(|index| *index < sequence.len())
+                    .map(|index| (index, pattern))
+            })
+            .collect_vec();
+        if window
+            .iter()
+            .all(|(index, pattern)| pattern.allows(sequence[*index].aminoacid.aminoacid()))
+        {
+            sites.push(site);
+            for (index, pattern) in window {
+                if matches!(pattern, ResidueSpecificity::Only(_)) {
+                    fixed[index] = true;
+                }
+            }
+        }
+    }
+    sites.push(sequence.len());
+    sites.sort_unstable();
+    sites.dedup();
+    sites
+        .iter()
+        .tuple_windows()
+        .map(|(start, end)| (*start..*end).filter(|index| !fixed[*index]).collect_vec())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use crate::CompoundPeptidoform;
+
+    use super::*;
+
+    fn decoy(sequence: &str, strategy: &DecoyStrategy) -> String {
+        CompoundPeptidoform::pro_forma(sequence, None)
+            .unwrap()
+            .singular()
+            .unwrap()
+            .singular()
+            .unwrap()
+            .into_semi_ambiguous()
+            .unwrap()
+            .decoy(strategy)
+            .to_string()
+    }
+
+    #[test]
+    fn reverse() {
+        assert_eq!(
+            decoy("[Acetyl]-PEM[Oxidation]TIDEK", &DecoyStrategy::Reverse),
+            "[U:Acetyl]-KEDITM[U:Oxidation]EP"
+        );
+    }
+
+    #[test]
+    fn pseudo_reverse() {
+        let strategy = DecoyStrategy::PseudoReverse(Protease::trypsin());
+        assert_eq!(decoy("PEPTIDEK", &strategy), "EDITPEPK");
+        assert_eq!(decoy("PEPTIDEKAAGRMPR", &strategy), "EDITPEPKGAARPMR");
+        assert_eq!(decoy("AKPARGGG", &strategy), "APKARGGG");
+        let strategy = DecoyStrategy::PseudoReverse(Protease::lys_n());
+        assert_eq!(decoy("KPEPTIDE", &strategy), "KEDITPEP");
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn shuffle() {
+        let strategy = DecoyStrategy::Shuffle(Protease::trypsin(), 42);
+        let first = decoy("PEPTIDEKAAGRMPR", &strategy);
+        assert_eq!(first, decoy("PEPTIDEKAAGRMPR", &strategy));
+        assert_eq!(first.chars().nth(7), Some('K'));
+        assert_eq!(first.chars().nth(11), Some('R'));
+        assert_eq!(
+            first.chars().sorted().collect::<String>(),
+            "PEPTIDEKAAGRMPR".chars().sorted().collect::<String>()
+        );
+    }
+}
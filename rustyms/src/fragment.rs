@@ -13,8 +13,8 @@ use crate::{
     model::ChargeRange,
     molecular_charge::{CachedCharge, MolecularCharge},
     system::{
-        f64::{MassOverCharge, Ratio},
-        usize::Charge,
+        f64::{Mass, MassOverCharge, Ratio},
+        isize::Charge,
     },
     AmbiguousLabel, AminoAcid, Chemical, MassMode, Modification, MolecularFormula, Multi,
     NeutralLoss, SequencePosition,
@@ -35,13 +35,29 @@ pub struct Fragment {
     pub peptide_index: usize,
     /// Any neutral losses applied
     pub neutral_loss: Option<NeutralLoss>,
+    /// The formula of the charge carriers, if known
+    pub charge_carriers: Option<MolecularFormula>,
 }
 
 impl Fragment {
-    /// Get the mz
+    /// Get the mz, for negatively charged fragments this is the mass divided by the absolute charge
     pub fn mz(&self, mode: MassMode) -> MassOverCharge {
         self.formula.mass(mode)
-            / crate::system::f64::Charge::new::<crate::system::charge::e>(self.charge.value as f64)
+            / crate::system::f64::Charge::new::<crate::system::charge::e>(
+                self.charge.value.unsigned_abs() as f64,
+            )
+    }
+
+    /// Get the neutral mass, the mass without the charge carriers.
+    ///
+    /// If the charge carriers are not known they are assumed to be protons, or lost protons for
+    /// negative charges.
+    pub fn neutral_mass(&self, mode: MassMode) -> Mass {
+        let carriers = self
+            .charge_carriers
+            .clone()
+            .unwrap_or_else(|| molecular_formula!(H 1 Electron -1) * self.charge.value as i32);
+        (&self.formula - &carriers).mass(mode)
     }
 
     /// Get the ppm difference between two fragments
@@ -65,12 +81,11 @@ impl Fragment {
             peptidoform_index,
             peptide_index,
             neutral_loss: None,
+            charge_carriers: None,
         }
     }
 
     /// Generate a list of possible fragments from the list of possible preceding termini and neutral losses
-    /// # Panics
-    /// When the charge range results in a negative charge
     #[allow(clippy::too_many_arguments)]
     #[must_use]
     pub fn generate_all(
@@ -93,29 +108,26 @@ impl Fragment {
                     + mass
                     + charge.formula_inner(SequencePosition::default(), peptide_index)
                     + loss.unwrap_or(&NeutralLoss::Gain(MolecularFormula::default())),
-                charge: Charge::new::<crate::system::e>(charge.charge().value.try_into().unwrap()),
+                charge: charge.charge(),
                 ion: annotation.clone(),
                 peptidoform_index,
                 peptide_index,
                 neutral_loss: loss.cloned(),
+                charge_carriers: Some(charge.formula()),
             })
             .collect()
     }
 
     /// Create a copy of this fragment with the given charge
-    /// # Panics
-    /// If the charge is negative.
     #[must_use]
     fn with_charge(&self, charge: &MolecularCharge) -> Self {
         let formula = charge
             .formula()
             .with_labels(&[AmbiguousLabel::ChargeCarrier(charge.formula())]);
-        let c = Charge::new::<crate::system::charge::e>(
-            usize::try_from(formula.charge().value).unwrap(),
-        );
         Self {
+            charge: formula.charge(),
             formula: &self.formula + &formula,
-            charge: c,
+            charge_carriers: Some(charge.formula()),
             ..self.clone()
         }
     }
@@ -172,17 +184,6 @@ impl Display for Fragment {
     }
 }
 
-// /// An isotope annotation.
-// #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
-// pub struct MatchedIsotopeDistribution {
-//     /// The index of the matched peak in the spectrum, if found
-//     pub peak_index: Option<usize>,
-//     /// The isotope offset in whole daltons from the monoisotopic peak
-//     pub isotope_offset: usize,
-//     /// The theoretical abundance of this isotope (normalised to 1 for the whole distribution)
-//     pub theoretical_isotope_abundance: OrderedFloat<f64>,
-// }
-
 /// The definition of the position of an ion
 #[derive(
     Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug, Serialize, Deserialize,
@@ -305,44 +306,46 @@ pub enum DiagnosticPosition {
     Peptide(PeptidePosition, AminoAcid),
     /// Labile modification
     Labile(Modification),
+    /// Reporter ion, not tied to a single position (eg TMT or iTRAQ reporter ions)
+    Reporter,
 }
 
 /// The possible types of fragments
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize, Default)]
 #[allow(non_camel_case_types)]
 pub enum FragmentType {
-    /// a
-    a(PeptidePosition),
-    /// b
-    b(PeptidePosition),
-    /// c
-    c(PeptidePosition),
-    /// d
-    d(PeptidePosition),
-    /// v
-    v(PeptidePosition),
-    /// w
-    w(PeptidePosition),
-    /// x
-    x(PeptidePosition),
-    /// y
-    y(PeptidePosition),
-    /// z
-    z(PeptidePosition),
-    /// z·
-    z·(PeptidePosition),
-    // glycan A fragment (Never generated)
-    //A(GlycanPosition),
+    /// a, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    a(PeptidePosition, i8),
+    /// b, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    b(PeptidePosition, i8),
+    /// c, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    c(PeptidePosition, i8),
+    /// d, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    d(PeptidePosition, i8),
+    /// v, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    v(PeptidePosition, i8),
+    /// w, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    w(PeptidePosition, i8),
+    /// x, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    x(PeptidePosition, i8),
+    /// y, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    y(PeptidePosition, i8),
+    /// z, with the number of hydrogens shifted (see [`Self::hydrogen_shift`])
+    z(PeptidePosition, i8),
+    /// glycan A fragment, a cross-ring cleavage of the monosaccharide at the given position with
+    /// the two broken ring bonds (Domon–Costello numbering, eg `(0, 2)` for a 0,2A fragment)
+    A(GlycanPosition, (u8, u8)),
     /// glycan B fragment
     B(GlycanPosition),
-    // glycan C fragment (Never generated)
-    //C(GlycanPosition),
-    // glycan X fragment (Never generated)
-    //X(GlycanPosition),
+    /// glycan C fragment, a glycosidic cleavage like B but with the glycosidic oxygen retained
+    C(GlycanPosition),
+    /// glycan X fragment, the reducing end counterpart of the A fragment with the same position
+    /// and broken ring bonds
+    X(GlycanPosition, (u8, u8)),
     /// glycan Y fragment, generated by one or more branches broken
     Y(Vec<GlycanPosition>),
-    // glycan Z fragment (Never generated)
-    // Z(GlycanPosition),
+    /// glycan Z fragment, the reducing end counterpart of the C fragment (single branch broken)
+    Z(GlycanPosition),
     /// Internal glycan fragment, meaning both a B and Y breakages (and potentially multiple of both), resulting in a set of monosaccharides
     Oxonium(Vec<GlycanBreakPos>),
     /// A B or internal glycan fragment for a glycan where only the composition is known, also saves the attachment (AA + sequence index)
@@ -355,6 +358,20 @@ pub enum FragmentType {
     PrecursorSideChainLoss(PeptidePosition, AminoAcid),
     /// Diagnostic ion for a given position
     diagnostic(DiagnosticPosition),
+    /// Internal fragment, resulting from two backbone cleavages. The kinds are the ion types of
+    /// the C terminal side cleavage (a/b/c) and the N terminal side cleavage (x/y/z) in that
+    /// order, so a classic b/y internal fragment is `(b, y)`. The positions are the first and
+    /// last residue contained in the fragment.
+    internal(
+        (FragmentKind, FragmentKind),
+        PeptidePosition,
+        PeptidePosition,
+    ),
+    /// An unknown ion, with an optional ordinal to distinguish different unknown ions
+    unknown(Option<usize>),
+    /// Charge reduced precursor, with the number of electrons captured (or detached for
+    /// negatively charged precursors) without dissociation
+    ChargeReducedPrecursor(usize),
     /// precursor
     #[default]
     precursor,
@@ -364,16 +381,15 @@ impl FragmentType {
     /// Get the position of this ion (or None if it is a precursor ion)
     pub const fn position(&self) -> Option<&PeptidePosition> {
         match self {
-            Self::a(n)
-            | Self::b(n)
-            | Self::c(n)
-            | Self::d(n)
-            | Self::v(n)
-            | Self::w(n)
-            | Self::x(n)
-            | Self::y(n)
-            | Self::z(n)
-            | Self::z·(n)
+            Self::a(n, _)
+            | Self::b(n, _)
+            | Self::c(n, _)
+            | Self::d(n, _)
+            | Self::v(n, _)
+            | Self::w(n, _)
+            | Self::x(n, _)
+            | Self::y(n, _)
+            | Self::z(n, _)
             | Self::diagnostic(DiagnosticPosition::Peptide(n, _))
             | Self::immonium(n, _)
             | Self::PrecursorSideChainLoss(n, _) => Some(n),
@@ -384,7 +400,12 @@ impl FragmentType {
     /// Get the glycan position of this ion (or None not applicable)
     pub const fn glycan_position(&self) -> Option<&GlycanPosition> {
         match self {
-            Self::B(n) | Self::diagnostic(DiagnosticPosition::Glycan(n, _)) => Some(n),
+            Self::A(n, _)
+            | Self::B(n)
+            | Self::C(n)
+            | Self::X(n, _)
+            | Self::Z(n)
+            | Self::diagnostic(DiagnosticPosition::Glycan(n, _)) => Some(n),
             _ => None,
         }
     }
@@ -392,20 +413,24 @@ impl FragmentType {
     /// Get the position label, unless it is a precursor ion
     pub fn position_label(&self) -> Option<String> {
         match self {
-            Self::a(n)
-            | Self::b(n)
-            | Self::c(n)
-            | Self::d(n)
-            | Self::v(n)
-            | Self::w(n)
-            | Self::x(n)
-            | Self::y(n)
-            | Self::z(n)
-            | Self::z·(n)
+            Self::a(n, _)
+            | Self::b(n, _)
+            | Self::c(n, _)
+            | Self::d(n, _)
+            | Self::v(n, _)
+            | Self::w(n, _)
+            | Self::x(n, _)
+            | Self::y(n, _)
+            | Self::z(n, _)
             | Self::diagnostic(DiagnosticPosition::Peptide(n, _))
             | Self::immonium(n, _)
             | Self::PrecursorSideChainLoss(n, _) => Some(n.series_number.to_string()),
-            Self::B(n) | Self::diagnostic(DiagnosticPosition::Glycan(n, _)) => Some(n.label()),
+            Self::A(n, _)
+            | Self::B(n)
+            | Self::C(n)
+            | Self::X(n, _)
+            | Self::Z(n)
+            | Self::diagnostic(DiagnosticPosition::Glycan(n, _)) => Some(n.label()),
             Self::Y(bonds) => Some(bonds.iter().map(GlycanPosition::label).join("")),
             Self::Oxonium(breakages) => Some(
                 breakages
@@ -419,32 +444,82 @@ impl FragmentType {
                     .map(|(sugar, amount)| format!("{sugar}{amount}"))
                     .join(""),
             ),
+            Self::internal(_, start, end) => {
+                Some(format!("{}:{}", start.series_number, end.series_number))
+            }
+            Self::unknown(ordinal) => ordinal.map(|o| o.to_string()),
             Self::precursor
+            | Self::ChargeReducedPrecursor(_)
             | Self::diagnostic(
-                DiagnosticPosition::Labile(_) | DiagnosticPosition::GlycanCompositional(_, _),
+                DiagnosticPosition::Labile(_)
+                | DiagnosticPosition::GlycanCompositional(_, _)
+                | DiagnosticPosition::Reporter,
             ) => None,
         }
     }
 
-    /// Get the label for this fragment type
+    /// Get the number of hydrogens shifted relative to the base ion for primary series ions,
+    /// positive for gained hydrogens and negative for lost hydrogens. So z· (z+1) has a shift of
+    /// 1 and y-1 a shift of -1. Any other fragment type has no shift.
+    pub const fn hydrogen_shift(&self) -> i8 {
+        match self {
+            Self::a(_, shift)
+            | Self::b(_, shift)
+            | Self::c(_, shift)
+            | Self::d(_, shift)
+            | Self::v(_, shift)
+            | Self::w(_, shift)
+            | Self::x(_, shift)
+            | Self::y(_, shift)
+            | Self::z(_, shift) => *shift,
+            _ => 0,
+        }
+    }
+
+    /// Get the label for this fragment type.
+    ///
+    /// Hydrogen shifted primary series ions use the common literature names where these exist,
+    /// so a+1 is `a·`, c-1 is `c·`, x+1 is `x·`, z+1 is `z·`, and z+2 is `z'`. All other shifts
+    /// are only labelled with the series, the number of hydrogens gained or lost is written after
+    /// the position when displaying the fragment type, so y-2 at position 3 is `y3-2H`.
     pub fn label(&self) -> Cow<str> {
         match self {
-            Self::a(_) => Cow::Borrowed("a"),
-            Self::b(_) => Cow::Borrowed("b"),
-            Self::c(_) => Cow::Borrowed("c"),
-            Self::d(_) => Cow::Borrowed("d"),
-            Self::v(_) => Cow::Borrowed("v"),
-            Self::w(_) => Cow::Borrowed("w"),
-            Self::x(_) => Cow::Borrowed("x"),
-            Self::y(_) => Cow::Borrowed("y"),
-            Self::z(_) => Cow::Borrowed("z"),
-            Self::z·(_) => Cow::Borrowed("z·"),
+            Self::a(_, 0) => Cow::Borrowed("a"),
+            Self::b(_, 0) => Cow::Borrowed("b"),
+            Self::c(_, 0) => Cow::Borrowed("c"),
+            Self::d(_, 0) => Cow::Borrowed("d"),
+            Self::v(_, 0) => Cow::Borrowed("v"),
+            Self::w(_, 0) => Cow::Borrowed("w"),
+            Self::x(_, 0) => Cow::Borrowed("x"),
+            Self::y(_, 0) => Cow::Borrowed("y"),
+            Self::z(_, 0) => Cow::Borrowed("z"),
+            Self::a(_, shift)
+            | Self::b(_, shift)
+            | Self::c(_, shift)
+            | Self::d(_, shift)
+            | Self::v(_, shift)
+            | Self::w(_, shift)
+            | Self::x(_, shift)
+            | Self::y(_, shift)
+            | Self::z(_, shift) => match (self.kind(), shift) {
+                (FragmentKind::a, 1) => Cow::Borrowed("a·"),
+                (FragmentKind::c, -1) => Cow::Borrowed("c·"),
+                (FragmentKind::x, 1) => Cow::Borrowed("x·"),
+                (FragmentKind::z, 1) => Cow::Borrowed("z·"),
+                (FragmentKind::z, 2) => Cow::Borrowed("z'"),
+                (kind, _) => Cow::Owned(kind.to_string()),
+            },
+            Self::A(_, (first, second)) => Cow::Owned(format!("{first},{second}A")),
             Self::B(_) => Cow::Borrowed("B"),
+            Self::C(_) => Cow::Borrowed("C"),
+            Self::X(_, (first, second)) => Cow::Owned(format!("{first},{second}X")),
             Self::Y(_) | Self::YComposition(_, _) => Cow::Borrowed("Y"),
+            Self::Z(_) => Cow::Borrowed("Z"),
             Self::diagnostic(DiagnosticPosition::Peptide(_, aa)) => {
                 Cow::Owned(format!("d{}", aa.char()))
             }
             Self::diagnostic(DiagnosticPosition::Labile(m)) => Cow::Owned(format!("d{m}")),
+            Self::diagnostic(DiagnosticPosition::Reporter) => Cow::Borrowed("r"),
             Self::diagnostic(
                 DiagnosticPosition::Glycan(_, sug)
                 | DiagnosticPosition::GlycanCompositional(sug, _),
@@ -452,23 +527,47 @@ impl FragmentType {
             Self::Oxonium(_) | Self::OxoniumComposition(_, _) => Cow::Borrowed("oxonium"),
             Self::immonium(_, aa) => Cow::Owned(format!("i{}", aa.char())),
             Self::PrecursorSideChainLoss(_, aa) => Cow::Owned(format!("p-s{}", aa.char())),
+            Self::internal((FragmentKind::b, FragmentKind::y), _, _) => Cow::Borrowed("m"),
+            Self::internal((n, c), _, _) => Cow::Owned(format!("m{n}{c}")),
+            Self::unknown(_) => Cow::Borrowed("?"),
+            Self::ChargeReducedPrecursor(electrons) => {
+                Cow::Owned(format!("p{}", "·".repeat(*electrons)))
+            }
             Self::precursor => Cow::Borrowed("p"),
         }
     }
 
+    /// Get the explicit hydrogen shift that is written after the position, for all hydrogen
+    /// shifted ions that do not have a literature name (see [`Self::label`]), so `-H` or `+2H`.
+    fn hydrogen_shift_label(&self) -> Option<String> {
+        match (self.kind(), self.hydrogen_shift()) {
+            (_, 0)
+            | (FragmentKind::a | FragmentKind::x | FragmentKind::z, 1)
+            | (FragmentKind::c, -1)
+            | (FragmentKind::z, 2) => None,
+            (_, 1) => Some("+H".to_string()),
+            (_, -1) => Some("-H".to_string()),
+            (_, shift) => Some(format!("{shift:+}H")),
+        }
+    }
+
     /// Get the kind of fragment, easier to match against
     pub const fn kind(&self) -> FragmentKind {
         match self {
-            Self::a(_) => FragmentKind::a,
-            Self::b(_) => FragmentKind::b,
-            Self::c(_) => FragmentKind::c,
-            Self::d(_) => FragmentKind::d,
-            Self::v(_) => FragmentKind::v,
-            Self::w(_) => FragmentKind::w,
-            Self::x(_) => FragmentKind::x,
-            Self::y(_) => FragmentKind::y,
-            Self::z(_) | Self::z·(_) => FragmentKind::z,
+            Self::a(..) => FragmentKind::a,
+            Self::b(..) => FragmentKind::b,
+            Self::c(..) => FragmentKind::c,
+            Self::d(..) => FragmentKind::d,
+            Self::v(..) => FragmentKind::v,
+            Self::w(..) => FragmentKind::w,
+            Self::x(..) => FragmentKind::x,
+            Self::y(..) => FragmentKind::y,
+            Self::z(..) => FragmentKind::z,
+            Self::A(_, _) => FragmentKind::A,
+            Self::C(_) => FragmentKind::C,
+            Self::X(_, _) => FragmentKind::X,
             Self::Y(_) | Self::YComposition(_, _) => FragmentKind::Y,
+            Self::Z(_) => FragmentKind::Z,
             Self::diagnostic(
                 DiagnosticPosition::Glycan(_, _) | DiagnosticPosition::GlycanCompositional(_, _),
             )
@@ -478,6 +577,9 @@ impl FragmentType {
             Self::diagnostic(_) => FragmentKind::diagnostic,
             Self::immonium(_, _) => FragmentKind::immonium,
             Self::PrecursorSideChainLoss(_, _) => FragmentKind::m,
+            Self::internal(_, _, _) => FragmentKind::internal,
+            Self::unknown(_) => FragmentKind::unknown,
+            Self::ChargeReducedPrecursor(_) => FragmentKind::ChargeReducedPrecursor,
             Self::precursor => FragmentKind::precursor,
         }
     }
@@ -487,13 +589,33 @@ impl Display for FragmentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "{}{}",
+            "{}{}{}",
             self.label(),
-            self.position_label().unwrap_or_default()
+            self.position_label().unwrap_or_default(),
+            self.hydrogen_shift_label().unwrap_or_default()
         )
     }
 }
 
+/// The formula of an internal fragment relative to the sum of its residues.
+///
+/// The sum of the residues is the formula of a b/y internal fragment, the difference is based on
+/// the ion types of the C terminal side cleavage (a/b/c) and the N terminal side cleavage (x/y/z).
+pub(crate) fn internal_fragment_difference(
+    n_kind: FragmentKind,
+    c_kind: FragmentKind,
+) -> MolecularFormula {
+    (match n_kind {
+        FragmentKind::a => -molecular_formula!(C 1 O 1),
+        FragmentKind::c => molecular_formula!(H 3 N 1),
+        _ => MolecularFormula::default(),
+    }) + match c_kind {
+        FragmentKind::x => molecular_formula!(C 1 O 1 H -2),
+        FragmentKind::z => -molecular_formula!(H 3 N 1),
+        _ => MolecularFormula::default(),
+    }
+}
+
 /// The possible kinds of fragments, same options as [`FragmentType`] but without any additional data
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
 #[allow(non_camel_case_types)]
@@ -514,10 +636,18 @@ pub enum FragmentKind {
     x,
     /// y
     y,
-    /// z and z·
+    /// z, including all hydrogen shifted variants
     z,
+    /// glycan A fragment, a cross-ring cleavage
+    A,
+    /// glycan C fragment
+    C,
+    /// glycan X fragment, a cross-ring cleavage
+    X,
     /// glycan Y fragment, generated by one or more branches broken
     Y,
+    /// glycan Z fragment
+    Z,
     /// B or glycan diagnostic ion or Internal glycan fragment, meaning both a B and Y breakages (and potentially multiple of both), resulting in a set of monosaccharides
     Oxonium,
     /// Immonium ion
@@ -526,6 +656,12 @@ pub enum FragmentKind {
     m,
     /// Diagnostic ion for a given position
     diagnostic,
+    /// Internal fragment
+    internal,
+    /// Unknown ion
+    unknown,
+    /// Charge reduced precursor
+    ChargeReducedPrecursor,
     /// precursor
     precursor,
 }
@@ -545,11 +681,18 @@ impl Display for FragmentKind {
                 Self::v => "v",
                 Self::w => "w",
                 Self::z => "z",
+                Self::A => "A",
+                Self::C => "C",
+                Self::X => "X",
                 Self::Y => "Y",
+                Self::Z => "Z",
                 Self::Oxonium => "oxonium",
                 Self::immonium => "immonium",
                 Self::m => "m",
                 Self::diagnostic => "diagnostic",
+                Self::internal => "internal",
+                Self::unknown => "unknown",
+                Self::ChargeReducedPrecursor => "charge reduced precursor",
                 Self::precursor => "precursor",
             }
         )
@@ -1,8 +1,9 @@
 #![allow(clippy::missing_panics_doc)]
 use crate::{
+    fragment::FragmentKind,
     model::*,
     modification::ModificationId,
-    system::{ratio::ppm, usize::Charge, MassOverCharge, Ratio},
+    system::{isize::Charge, ratio::ppm, MassOverCharge, Ratio},
     *,
 };
 
@@ -53,6 +54,222 @@ fn triple_a() {
     );
 }
 
+#[test]
+fn internal_fragments() {
+    #[allow(clippy::unreadable_literal)]
+    let theoretical_fragments = &[
+        (145.060768, "m2:3"),
+        (159.076418, "m3:4"),
+        (216.097882, "m2:4"),
+        (117.065853, "may2:3"),
+        (131.081503, "may3:4"),
+        (188.102967, "may2:4"),
+        (376.182675, "precursor"),
+    ];
+    let model = Model::none().internal(
+        InternalIonSeries::default()
+            .ion_types(vec![
+                (FragmentKind::b, FragmentKind::y),
+                (FragmentKind::a, FragmentKind::y),
+            ])
+            .length(2..=3),
+    );
+    test(
+        theoretical_fragments,
+        LinearPeptide::pro_forma("AGSAA", None)
+            .unwrap()
+            .into_linear()
+            .unwrap(),
+        &model,
+        1,
+        false,
+        false,
+    );
+}
+
+#[test]
+fn uvpd() {
+    // Angiotensin II, a standard peptide for UVPD. The masses are calculated from the elemental
+    // compositions of the residues with the ion definitions a+1 = a + H, x+1 = x + H, and
+    // y-1 = y - H, not with the formulas used in rustyms.
+    #[allow(clippy::unreadable_literal)]
+    let theoretical_fragments = &[
+        (244.140416, "a2"),
+        (245.148241, "a·2"),
+        (343.208830, "a3"),
+        (344.216655, "a·3"),
+        (506.272158, "a4"),
+        (507.279983, "a·4"),
+        (619.356222, "a5"),
+        (620.364047, "a·5"),
+        (192.065520, "x1"),
+        (193.073345, "x·1"),
+        (289.118283, "x2"),
+        (290.126108, "x·2"),
+        (426.177195, "x3"),
+        (427.185020, "x·3"),
+        (166.086255, "y1"),
+        (165.078430, "y1-H"),
+        (164.070605, "y1-2H"),
+        (263.139019, "y2"),
+        (262.131194, "y2-H"),
+        (261.123369, "y2-2H"),
+        (400.197931, "y3"),
+        (399.190106, "y3-H"),
+        (398.182281, "y3-2H"),
+        (513.281995, "y4"),
+        (512.274170, "y4-H"),
+        (511.266345, "y4-2H"),
+    ];
+    test(
+        theoretical_fragments,
+        LinearPeptide::pro_forma("DRVYIHPF", None)
+            .unwrap()
+            .into_linear()
+            .unwrap(),
+        &Model::uvpd(),
+        1,
+        true,
+        false,
+    );
+}
+
+#[test]
+fn ecd() {
+    // Substance P (C terminally amidated), a standard peptide for ECD. The masses are calculated
+    // from the elemental compositions of the residues with the ion definitions c-1 = c - H,
+    // z· = y - NH2, and z' = y - NH, not with the formulas used in rustyms. The c and z ions from
+    // cleaving the N-Cα bond of proline are not formed in ECD so are left out.
+    #[allow(clippy::unreadable_literal)]
+    let theoretical_fragments = &[
+        (271.187700, "c2"),
+        (270.179875, "c·2"),
+        (496.335427, "c4"),
+        (495.327602, "c·4"),
+        (624.394005, "c5"),
+        (623.386180, "c·5"),
+        (752.452582, "c6"),
+        (751.444757, "c·6"),
+        (899.520996, "c7"),
+        (898.513171, "c·7"),
+        (1046.589410, "c8"),
+        (1045.581585, "c·8"),
+        (1103.610874, "c9"),
+        (1102.603049, "c·9"),
+        (1216.694938, "c10"),
+        (1215.687113, "c·10"),
+        (133.055586, "z·1"),
+        (134.063411, "z'1"),
+        (246.139650, "z·2"),
+        (247.147475, "z'2"),
+        (303.161114, "z·3"),
+        (304.168939, "z'3"),
+        (450.229528, "z·4"),
+        (451.237353, "z'4"),
+        (597.297942, "z·5"),
+        (598.305767, "z'5"),
+        (725.356519, "z·6"),
+        (726.364344, "z'6"),
+        (853.415097, "z·7"),
+        (854.422922, "z'7"),
+        (1078.562824, "z·9"),
+        (1079.570649, "z'9"),
+    ];
+    test(
+        theoretical_fragments,
+        LinearPeptide::pro_forma("RPKPQQFFGLM-[Amidated]", None)
+            .unwrap()
+            .into_linear()
+            .unwrap(),
+        &Model::ecd(),
+        1,
+        true,
+        false,
+    );
+}
+
+#[test]
+fn triple_a_negative() {
+    // The same ions as `triple_a` but deprotonated instead of protonated
+    #[allow(clippy::unreadable_literal)]
+    let theoretical_fragments = &[
+        (42.034923, "a+1"),
+        (70.029837, "b+1"),
+        (87.056386, "c+1"),
+        (113.072036, "a+2"),
+        (141.066951, "b+2"),
+        (158.093500, "c+2"),
+        (185.056780, "x+2"),
+        (159.077516, "y+2"),
+        (142.050967, "z+2"),
+        (143.058792, "z·+2"),
+        (114.019667, "x+1"),
+        (88.040402, "y+1"),
+        (71.013853, "z+1"),
+        (72.021678, "z·+1"),
+        (230.114630, "precursor"),
+    ];
+    let model = Model::none()
+        .a(PrimaryIonSeries::default())
+        .b(PrimaryIonSeries::default())
+        .c(PrimaryIonSeries::default())
+        .x(PrimaryIonSeries::default())
+        .y(PrimaryIonSeries::default())
+        .z(PrimaryIonSeries::default());
+    test(
+        theoretical_fragments,
+        LinearPeptide::pro_forma("AAA", None)
+            .unwrap()
+            .into_linear()
+            .unwrap(),
+        &model,
+        -1,
+        true,
+        false,
+    );
+}
+
+#[test]
+fn charge_reduced_precursor() {
+    #[allow(clippy::unreadable_literal)]
+    let theoretical_fragments = &[
+        (78.047912, "p+3"),
+        (117.072142, "p·+2"),
+        (234.144833, "p··+1"),
+        (108.558868, "p·-NH3+2"),
+        (217.118284, "p··-NH3+1"),
+    ];
+    let model = Model::none().charge_reduced_precursor(
+        ChargeReducedPrecursorModel::ALLOW
+            .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 3 N 1))]),
+    );
+    test(
+        theoretical_fragments,
+        LinearPeptide::pro_forma("AAA", None)
+            .unwrap()
+            .into_linear()
+            .unwrap(),
+        &model,
+        3,
+        false,
+        false,
+    );
+    // Electron detachment for negatively charged precursors
+    #[allow(clippy::unreadable_literal)]
+    let theoretical_fragments = &[(114.553677, "p-2"), (229.106805, "p·-1")];
+    test(
+        theoretical_fragments,
+        LinearPeptide::pro_forma("AAA", None)
+            .unwrap()
+            .into_linear()
+            .unwrap(),
+        &Model::none().charge_reduced_precursor(ChargeReducedPrecursorModel::ALLOW),
+        -2,
+        false,
+        false,
+    );
+}
+
 #[test]
 fn with_modifications() {
     // Compare rustyms with https://proteomicsresource.washington.edu/cgi-bin/fragment.cgi mods: -17.02655@[ 15.99491@
@@ -693,7 +910,7 @@ fn test(
     theoretical_fragments: &[(f64, &str)],
     peptide: impl Into<CompoundPeptidoform>,
     model: &Model,
-    charge: usize,
+    charge: isize,
     allow_left_over_generated: bool,
     allow_double_theoretical: bool,
 ) {
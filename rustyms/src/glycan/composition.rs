@@ -0,0 +1,484 @@
+//! Read and write glycan compositions in the notations used by other glycoproteomics tools
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use super::{glycan_parse_list, MonoSaccharide};
+use crate::{
+    error::{Context, CustomError},
+    formula::Chemical,
+};
+
+/// The notations for glycan compositions as used by different tools
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum GlycanCompositionNotation {
+    /// ProForma, eg `HexNAc4Hex5Fuc1Neu5Ac2`, accepts any monosaccharide
+    ProForma,
+    /// Byonic, eg `HexNAc(4)Hex(5)Fuc(1)NeuAc(2)`
+    Byonic,
+    /// pGlyco, eg `H5N4F1A2`, when reading the pGlyco3 style with parenthesis (`H(5)N(4)`) is
+    /// accepted as well
+    PGlyco,
+    /// MSFragger-Glyco, eg `HexNAc(4)Hex(5)Fuc(1)NeuAc(2) % 2350.8303`, the mass is optional
+    /// when reading but if present it has to match the composition
+    MSFragger,
+    /// GlyConnect, eg `Hex:5 HexNAc:4 dHex:1 NeuAc:2`
+    GlyConnect,
+    /// The numeric shorthand with the number of `Hex`, `HexNAc`, `Fuc`, `NeuAc`, and optionally
+    /// `NeuGc` as single digits, eg `5412`
+    Numeric,
+}
+
+/// The names of the monosaccharide classes in the Byonic and MSFragger notation, with the name
+/// in the glycan parse list, in the order as written
+const BYONIC: &[(&str, &str)] = &[
+    ("HexNAc", "hexnac"),
+    ("Hex", "hex"),
+    ("Fuc", "fuc"),
+    ("NeuAc", "neu5ac"),
+    ("NeuGc", "neu5gc"),
+    ("Pent", "pen"),
+    ("Phospho", "phosphate"),
+    ("Sulfo", "sulfate"),
+];
+
+/// The names of the monosaccharide classes in the pGlyco notation
+const PGLYCO: &[(&str, &str)] = &[
+    ("H", "hex"),
+    ("N", "hexnac"),
+    ("F", "fuc"),
+    ("A", "neu5ac"),
+    ("G", "neu5gc"),
+    ("X", "pen"),
+];
+
+/// The names of the monosaccharide classes in the GlyConnect notation
+const GLYCONNECT: &[(&str, &str)] = &[
+    ("Hex", "hex"),
+    ("HexNAc", "hexnac"),
+    ("dHex", "fuc"),
+    ("NeuAc", "neu5ac"),
+    ("NeuGc", "neu5gc"),
+    ("Pent", "pen"),
+    ("Phos", "phosphate"),
+    ("Sulf", "sulfate"),
+];
+
+/// The monosaccharide classes in the numeric notation, in the order of the digits
+const NUMERIC: &[(&str, &str)] = &[
+    ("Hex", "hex"),
+    ("HexNAc", "hexnac"),
+    ("Fuc", "fuc"),
+    ("NeuAc", "neu5ac"),
+    ("NeuGc", "neu5gc"),
+];
+
+impl GlycanCompositionNotation {
+    /// Get the table of names for this notation, None for ProForma
+    const fn names(self) -> Option<&'static [(&'static str, &'static str)]> {
+        match self {
+            Self::ProForma => None,
+            Self::Byonic | Self::MSFragger => Some(BYONIC),
+            Self::PGlyco => Some(PGLYCO),
+            Self::GlyConnect => Some(GLYCONNECT),
+            Self::Numeric => Some(NUMERIC),
+        }
+    }
+
+    /// Parse a glycan composition in this notation. All monosaccharides are given as the generic
+    /// class they represent in the notation (eg `Hex` not `Man`), so the result can be compared
+    /// directly to the [`MonoSaccharide::generic_composition`] of any other composition.
+    /// # Errors
+    /// If the text is not a valid composition in this notation, if a monosaccharide is not known
+    /// in this notation, or if the occurrence of one monosaccharide is outside of the valid range.
+    /// # Panics
+    /// If the notation tables contain a name that is not in the glycan parse list.
+    pub fn parse(self, text: &str) -> Result<Vec<(MonoSaccharide, isize)>, CustomError> {
+        let Some(names) = self.names() else {
+            return MonoSaccharide::from_composition(text);
+        };
+        let trimmed = text.trim();
+        let mut composition = Vec::new();
+        match self {
+            Self::Numeric => {
+                if !(4..=5).contains(&trimmed.len()) {
+                    return Err(CustomError::error(
+                        "Invalid numeric glycan composition",
+                        "A numeric glycan composition should have 4 or 5 digits",
+                        Context::show(text),
+                    ));
+                }
+                for (index, digit) in trimmed.bytes().enumerate() {
+                    if !digit.is_ascii_digit() {
+                        return Err(CustomError::error(
+                            "Invalid numeric glycan composition",
+                            "A numeric glycan composition can only contain digits",
+                            Context::line(None, trimmed, index, 1),
+                        ));
+                    }
+                    composition.push((names[index].1, isize::from(digit - b'0')));
+                }
+            }
+            Self::GlyConnect => {
+                let mut offset = 0;
+                for part in trimmed.split_whitespace() {
+                    let start = trimmed[offset..].find(part).map_or(offset, |i| i + offset);
+                    offset = start + part.len();
+                    let (name, amount) = part.split_once(':').ok_or_else(|| {
+                        CustomError::error(
+                            "Invalid GlyConnect glycan composition",
+                            "Every monosaccharide should be written as `name:amount`",
+                            Context::line(None, trimmed, start, part.len()),
+                        )
+                    })?;
+                    composition.push((
+                        find_name(names, name, trimmed, start)?,
+                        parse_amount(amount, trimmed, start + name.len() + 1)?,
+                    ));
+                }
+            }
+            Self::Byonic | Self::MSFragger | Self::PGlyco => {
+                let mut trimmed = trimmed;
+                if self == Self::MSFragger {
+                    if let Some((composition, mass)) = trimmed.split_once('%') {
+                        trimmed = composition.trim();
+                        let mass = mass.trim().parse::<f64>().map_err(|err| {
+                            CustomError::error(
+                                "Invalid MSFragger glycan composition",
+                                format!("The glycan mass is not a valid number: {err}"),
+                                Context::show(text),
+                            )
+                        })?;
+                        let parsed = Self::Byonic.parse(trimmed)?;
+                        let theoretical = composition_mass(&parsed);
+                        if (theoretical - mass).abs() > 0.01 {
+                            return Err(CustomError::error(
+                                "Invalid MSFragger glycan composition",
+                                format!("The glycan mass ({mass}) does not match the mass of the composition ({theoretical:.4})"),
+                                Context::show(text),
+                            ));
+                        }
+                        return Ok(parsed);
+                    }
+                }
+                let bytes = trimmed.as_bytes();
+                let mut index = 0;
+                while index < bytes.len() {
+                    let start = index;
+                    while index < bytes.len()
+                        && bytes[index].is_ascii_alphabetic()
+                        && (self != Self::PGlyco || index == start)
+                    {
+                        index += 1;
+                    }
+                    let name = &trimmed[start..index];
+                    let parenthesis = bytes.get(index) == Some(&b'(');
+                    if parenthesis {
+                        index += 1;
+                    } else if self != Self::PGlyco {
+                        return Err(CustomError::error(
+                            "Invalid glycan composition",
+                            "Every monosaccharide should be written as `name(amount)`",
+                            Context::line(None, trimmed, index, 1),
+                        ));
+                    }
+                    let amount_start = index;
+                    while index < bytes.len() && bytes[index].is_ascii_digit() {
+                        index += 1;
+                    }
+                    let amount =
+                        parse_amount(&trimmed[amount_start..index], trimmed, amount_start)?;
+                    if parenthesis {
+                        if bytes.get(index) != Some(&b')') {
+                            return Err(CustomError::error(
+                                "Invalid glycan composition",
+                                "Missing closing parenthesis",
+                                Context::line(None, trimmed, index, 1),
+                            ));
+                        }
+                        index += 1;
+                    }
+                    composition.push((find_name(names, name, trimmed, start)?, amount));
+                }
+            }
+            Self::ProForma => unreachable!(),
+        }
+        let list = glycan_parse_list();
+        MonoSaccharide::simplify_composition(
+            composition
+                .into_iter()
+                .map(|(key, amount)| {
+                    (
+                        list.iter()
+                            .find(|(k, _)| k == key)
+                            .expect("Invalid glycan name in composition notation list")
+                            .1
+                            .clone(),
+                        amount,
+                    )
+                })
+                .collect(),
+        )
+        .ok_or_else(|| {
+            CustomError::error(
+                "Invalid glycan composition",
+                format!(
+                    "The occurrence of one monosaccharide species is outside of the range {} to {}",
+                    isize::MIN,
+                    isize::MAX
+                ),
+                Context::show(text),
+            )
+        })
+    }
+
+    /// Write a glycan composition in this notation. All specific monosaccharides are combined
+    /// into the generic class they belong to (eg `Man` and `Gal` are both written as `Hex`).
+    /// # Errors
+    /// If the composition contains a monosaccharide that cannot be represented in this notation,
+    /// contains a negative amount, or for the numeric notation if any amount is higher than 9.
+    /// # Panics
+    /// If writing to the string fails.
+    pub fn write(self, composition: &[(MonoSaccharide, isize)]) -> Result<String, CustomError> {
+        let Some(names) = self.names() else {
+            return Ok(composition.iter().fold(String::new(), |mut acc, (g, a)| {
+                write!(&mut acc, "{g}{a}").unwrap();
+                acc
+            }));
+        };
+        let list = glycan_parse_list();
+        let mut amounts = vec![0; names.len()];
+        for (sugar, amount) in composition {
+            let index = names
+                .iter()
+                .position(|(_, key)| {
+                    list.iter()
+                        .find(|(k, _)| k == key)
+                        .is_some_and(|(_, class)| class.generic_eq(sugar))
+                })
+                .ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid glycan composition",
+                        format!("The monosaccharide '{sugar}' cannot be written in the {self:?} notation"),
+                        Context::none(),
+                    )
+                })?;
+            amounts[index] += amount;
+        }
+        if amounts.iter().any(|a| *a < 0) {
+            return Err(CustomError::error(
+                "Invalid glycan composition",
+                "A glycan composition with negative amounts cannot be written in tool specific notations",
+                Context::none(),
+            ));
+        }
+        let mut output = String::new();
+        match self {
+            Self::Numeric => {
+                if amounts.iter().any(|a| *a > 9) {
+                    return Err(CustomError::error(
+                        "Invalid glycan composition",
+                        "The numeric notation only allows up to 9 of each monosaccharide",
+                        Context::none(),
+                    ));
+                }
+                let length = if amounts[4] == 0 { 4 } else { 5 };
+                for amount in &amounts[..length] {
+                    write!(&mut output, "{amount}").unwrap();
+                }
+            }
+            Self::GlyConnect => {
+                output = names
+                    .iter()
+                    .zip(&amounts)
+                    .filter(|(_, a)| **a != 0)
+                    .map(|((name, _), a)| format!("{name}:{a}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+            }
+            Self::PGlyco => {
+                for ((name, _), amount) in names.iter().zip(&amounts).filter(|(_, a)| **a != 0) {
+                    write!(&mut output, "{name}{amount}").unwrap();
+                }
+            }
+            Self::Byonic | Self::MSFragger => {
+                for ((name, _), amount) in names.iter().zip(&amounts).filter(|(_, a)| **a != 0) {
+                    write!(&mut output, "{name}({amount})").unwrap();
+                }
+                if self == Self::MSFragger {
+                    write!(&mut output, " % {:.4}", composition_mass(composition)).unwrap();
+                }
+            }
+            Self::ProForma => unreachable!(),
+        }
+        Ok(output)
+    }
+}
+
+impl MonoSaccharide {
+    /// Check if two monosaccharides are the same when ignoring the isomeric state, ring form, and
+    /// name, so this sees `Man` and `Hex` as identical.
+    fn generic_eq(&self, other: &Self) -> bool {
+        let mut a = self.substituents.clone();
+        let mut b = other.substituents.clone();
+        a.sort_unstable();
+        b.sort_unstable();
+        std::mem::discriminant(&self.base_sugar) == std::mem::discriminant(&other.base_sugar)
+            && a == b
+    }
+
+    /// Get the generic version of a glycan composition, with all monosaccharides that belong to
+    /// one of the classes used in the tool specific notations replaced by that class (eg `Man`
+    /// and `Gal` become `Hex` and `GlcNAc` becomes `HexNAc`). The result is sorted and
+    /// deduplicated. This allows comparing compositions with specific monosaccharides to
+    /// compositions read from tools that only know the generic classes, see
+    /// [`GlycanCompositionNotation`]. Returns None if the occurrence of one class is outside of
+    /// the range of [`isize`].
+    pub fn generic_composition(composition: &[(Self, isize)]) -> Option<Vec<(Self, isize)>> {
+        let list = glycan_parse_list();
+        Self::simplify_composition(
+            composition
+                .iter()
+                .map(|(sugar, amount)| {
+                    (
+                        BYONIC
+                            .iter()
+                            .filter_map(|(_, key)| list.iter().find(|(k, _)| k == key))
+                            .find(|(_, class)| class.generic_eq(sugar))
+                            .map_or_else(|| sugar.clone(), |(_, class)| class.clone()),
+                        *amount,
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Find the monosaccharide class with this name (case insensitive)
+/// # Errors
+/// If the name is not known.
+fn find_name(
+    names: &[(&str, &'static str)],
+    name: &str,
+    line: &str,
+    offset: usize,
+) -> Result<&'static str, CustomError> {
+    names
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+        .ok_or_else(|| {
+            CustomError::error(
+                "Invalid glycan composition",
+                format!(
+                    "Unknown monosaccharide, use any of: {}",
+                    names.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+                ),
+                Context::line(None, line, offset, name.len()),
+            )
+        })
+}
+
+/// Parse the amount of a monosaccharide
+/// # Errors
+/// If the amount is not a valid number.
+fn parse_amount(amount: &str, line: &str, offset: usize) -> Result<isize, CustomError> {
+    amount.parse::<isize>().map_err(|err| {
+        CustomError::error(
+            "Invalid glycan composition",
+            format!("The amount is not a valid number: {err}"),
+            Context::line(None, line, offset, amount.len().max(1)),
+        )
+    })
+}
+
+/// Get the monoisotopic mass of a glycan composition (as residues, so without water)
+fn composition_mass(composition: &[(MonoSaccharide, isize)]) -> f64 {
+    composition
+        .iter()
+        .map(|(sugar, amount)| sugar.formula().monoisotopic_mass().value * *amount as f64)
+        .sum()
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notations() {
+        let composition = MonoSaccharide::from_composition("HexNAc4Hex5Fuc1Neu5Ac2").unwrap();
+        for (notation, text) in [
+            (
+                GlycanCompositionNotation::Byonic,
+                "HexNAc(4)Hex(5)Fuc(1)NeuAc(2)",
+            ),
+            (GlycanCompositionNotation::PGlyco, "H5N4F1A2"),
+            (
+                GlycanCompositionNotation::MSFragger,
+                "HexNAc(4)Hex(5)Fuc(1)NeuAc(2) % 2350.8303",
+            ),
+            (
+                GlycanCompositionNotation::GlyConnect,
+                "Hex:5 HexNAc:4 dHex:1 NeuAc:2",
+            ),
+            (GlycanCompositionNotation::Numeric, "5412"),
+        ] {
+            assert_eq!(notation.write(&composition).unwrap(), text, "{notation:?}");
+            assert_eq!(notation.parse(text).unwrap(), composition, "{notation:?}");
+        }
+        assert_eq!(
+            GlycanCompositionNotation::PGlyco
+                .parse("H(5)N(4)A(2)F(1)")
+                .unwrap(),
+            composition
+        );
+        assert_eq!(
+            GlycanCompositionNotation::MSFragger
+                .parse("HexNAc(4)Hex(5)Fuc(1)NeuAc(2)")
+                .unwrap(),
+            composition
+        );
+    }
+
+    #[test]
+    fn generic() {
+        let specific = MonoSaccharide::from_composition("GlcNAc2Man3Gal2").unwrap();
+        assert_eq!(
+            GlycanCompositionNotation::Byonic.write(&specific).unwrap(),
+            "HexNAc(2)Hex(5)"
+        );
+        assert_eq!(
+            MonoSaccharide::generic_composition(&specific).unwrap(),
+            GlycanCompositionNotation::PGlyco.parse("H5N2").unwrap()
+        );
+        assert_ne!(
+            specific,
+            GlycanCompositionNotation::PGlyco.parse("H5N2").unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(GlycanCompositionNotation::Byonic
+            .parse("HexNAc(4)Hex5")
+            .is_err());
+        assert!(GlycanCompositionNotation::Byonic
+            .parse("HexNAc(4)Kdn(1)")
+            .is_err());
+        assert!(GlycanCompositionNotation::PGlyco.parse("H5Q1").is_err());
+        assert!(GlycanCompositionNotation::Numeric.parse("54a0").is_err());
+        assert!(GlycanCompositionNotation::Numeric.parse("541").is_err());
+        assert!(GlycanCompositionNotation::GlyConnect
+            .parse("Hex5 HexNAc:4")
+            .is_err());
+        assert!(GlycanCompositionNotation::MSFragger
+            .parse("HexNAc(4)Hex(5) % 1000.0")
+            .is_err());
+        let kdn = MonoSaccharide::from_composition("Hex5Kdn1").unwrap();
+        assert!(GlycanCompositionNotation::PGlyco.write(&kdn).is_err());
+        let many = MonoSaccharide::from_composition("Hex10").unwrap();
+        assert!(GlycanCompositionNotation::Numeric.write(&many).is_err());
+    }
+}
@@ -78,19 +78,10 @@ impl GlycanStructure {
                     branches.push(glycan);
                     index = pos;
                 }
-                Ok((
-                    Self {
-                        sugar: name.1.clone(),
-                        branches,
-                    },
-                    end + 1,
-                ))
+                Ok((Self::new(name.1.clone(), branches), end + 1))
             } else {
                 Ok((
-                    Self {
-                        sugar: name.1.clone(),
-                        branches: Vec::new(),
-                    },
+                    Self::new(name.1.clone(), Vec::new()),
                     range.start + name.0.len(),
                 ))
             }
@@ -148,6 +139,7 @@ impl GlycanStructure {
         (
             PositionedGlycanStructure {
                 sugar: self.sugar,
+                linkage: self.linkage,
                 branches: branches.into_iter().map(|b| b.0).collect(),
                 branch: branch.to_vec(),
                 inner_depth,
@@ -181,6 +173,93 @@ impl GlycanStructure {
         output.extend(self.branches.iter().flat_map(Self::composition_inner));
         output
     }
+
+    /// The monosaccharide at the root of this structure
+    pub const fn sugar(&self) -> &MonoSaccharide {
+        &self.sugar
+    }
+
+    /// The branches on the root of this structure
+    pub fn branches(&self) -> &[Self] {
+        &self.branches
+    }
+
+    /// The linkage of the root of this structure to its parent
+    pub const fn linkage(&self) -> GlycanLinkage {
+        self.linkage
+    }
+
+    /// Check if both structures have the same topology, ignoring all linkage information and the
+    /// order of the branches. This can be used to compare a structure with full linkage
+    /// information to a GNOme topology.
+    pub fn same_topology(&self, other: &Self) -> bool {
+        self.canonical_topology() == other.canonical_topology()
+    }
+
+    /// Remove all linkage information and sort the branches
+    fn canonical_topology(&self) -> Self {
+        Self::new(
+            self.sugar.clone(),
+            self.branches
+                .iter()
+                .map(Self::canonical_topology)
+                .sorted()
+                .collect(),
+        )
+    }
+
+    /// Write this structure as short IUPAC (also known as IUPAC condensed), eg
+    /// `Gal(b1-4)GlcNAc(b1-2)Man(a1-3)[Man(a1-6)]Man(b1-4)GlcNAc`, this can be read again with
+    /// [`Self::from_short_iupac`]. The first branch is written as main chain and all other
+    /// branches are written in square brackets.
+    pub fn to_short_iupac(&self) -> String {
+        let mut output = self.short_iupac_inner();
+        if self.linkage.anomer.is_some() || self.linkage.position.is_some() {
+            output.push('(');
+            self.linkage.write_iupac(&mut output);
+        }
+        output
+    }
+
+    fn short_iupac_inner(&self) -> String {
+        let mut output = String::new();
+        for (index, branch) in self.branches.iter().enumerate() {
+            if index != 0 {
+                output.push('[');
+            }
+            output.push_str(&branch.short_iupac_inner());
+            output.push('(');
+            branch.linkage.write_iupac(&mut output);
+            if let Some(position) = branch.linkage.parent_position {
+                output.push_str(&position.to_string());
+            } else {
+                output.push('?');
+            }
+            output.push(')');
+            if index != 0 {
+                output.push(']');
+            }
+        }
+        output.push_str(&self.sugar.iupac_name());
+        output
+    }
+}
+
+impl GlycanLinkage {
+    /// Write the anomeric state and position as IUPAC, eg `b1-`
+    fn write_iupac(self, output: &mut String) {
+        output.push(match self.anomer {
+            Some(AnomericState::Alpha) => 'a',
+            Some(AnomericState::Beta) => 'b',
+            None => '?',
+        });
+        if let Some(position) = self.position {
+            output.push_str(&position.to_string());
+        } else {
+            output.push('?');
+        }
+        output.push('-');
+    }
 }
 
 #[cfg(test)]
@@ -193,8 +272,10 @@ mod test {
         assert_eq!(
             GlycanStructure::from_str("hep(hex)").unwrap(),
             GlycanStructure {
+                linkage: GlycanLinkage::default(),
                 sugar: MonoSaccharide::new(BaseSugar::Heptose(None), &[]).with_name("Hep"),
                 branches: vec![GlycanStructure {
+                    linkage: GlycanLinkage::default(),
                     sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                     branches: Vec::new()
                 }],
@@ -207,13 +288,16 @@ mod test {
         assert_eq!(
             GlycanStructure::from_str("hex(hex,hep)").unwrap(),
             GlycanStructure {
+                linkage: GlycanLinkage::default(),
                 sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                 branches: vec![
                     GlycanStructure {
+                        linkage: GlycanLinkage::default(),
                         sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                         branches: Vec::new()
                     },
                     GlycanStructure {
+                        linkage: GlycanLinkage::default(),
                         sugar: MonoSaccharide::new(BaseSugar::Heptose(None), &[]).with_name("Hep"),
                         branches: Vec::new()
                     }
@@ -227,17 +311,21 @@ mod test {
         assert_eq!(
             GlycanStructure::from_str("hex(hex(hex),hep)").unwrap(),
             GlycanStructure {
+                linkage: GlycanLinkage::default(),
                 sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                 branches: vec![
                     GlycanStructure {
+                        linkage: GlycanLinkage::default(),
                         sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                         branches: vec![GlycanStructure {
+                            linkage: GlycanLinkage::default(),
                             sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[])
                                 .with_name("Hex"),
                             branches: Vec::new()
                         }]
                     },
                     GlycanStructure {
+                        linkage: GlycanLinkage::default(),
                         sugar: MonoSaccharide::new(BaseSugar::Heptose(None), &[]).with_name("Hep"),
                         branches: Vec::new()
                     }
@@ -251,22 +339,28 @@ mod test {
         assert_eq!(
             GlycanStructure::from_str("hep(hex(hex(hex(hep),hex)))").unwrap(),
             GlycanStructure {
+                linkage: GlycanLinkage::default(),
                 sugar: MonoSaccharide::new(BaseSugar::Heptose(None), &[]).with_name("Hep"),
                 branches: vec![GlycanStructure {
+                    linkage: GlycanLinkage::default(),
                     sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                     branches: vec![GlycanStructure {
+                        linkage: GlycanLinkage::default(),
                         sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[]).with_name("Hex"),
                         branches: vec![
                             GlycanStructure {
+                                linkage: GlycanLinkage::default(),
                                 sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[])
                                     .with_name("Hex"),
                                 branches: vec![GlycanStructure {
+                                    linkage: GlycanLinkage::default(),
                                     sugar: MonoSaccharide::new(BaseSugar::Heptose(None), &[])
                                         .with_name("Hep"),
                                     branches: Vec::new(),
                                 }],
                             },
                             GlycanStructure {
+                                linkage: GlycanLinkage::default(),
                                 sugar: MonoSaccharide::new(BaseSugar::Hexose(None), &[])
                                     .with_name("Hex"),
                                 branches: Vec::new(),
@@ -362,4 +456,38 @@ mod test {
             "HexNAc(HexNAc(Hex(Hex(HexNAc,HexNAc),Hex(Hex))))"
         );
     }
+
+    #[test]
+    fn short_iupac_linkage() {
+        let parse = |text: &str| GlycanStructure::from_short_iupac(text, 0..text.len(), 0).unwrap();
+        for text in [
+            "Man(a1-3)[Man(a1-6)]Man(b1-4)GlcNAc(b1-4)GlcNAc(b1-",
+            "Neu5Ac(a2-3)Gal(b1-4)[Fuc(a1-3)]GlcNAc(b1-",
+            "Gal(?1-?)GlcNAc",
+            "Galf(b1-4)GlcA",
+        ] {
+            assert_eq!(parse(text).to_short_iupac(), text);
+        }
+        let structure = parse("Neu5Ac(a2-3)Gal(b1-4)GlcNAc");
+        assert_eq!(structure.linkage(), GlycanLinkage::default());
+        assert_eq!(
+            structure.branches()[0].branches()[0].linkage(),
+            GlycanLinkage {
+                anomer: Some(AnomericState::Alpha),
+                position: Some(2),
+                parent_position: Some(3),
+            }
+        );
+    }
+
+    #[test]
+    fn same_topology() {
+        let parse = |text: &str| GlycanStructure::from_short_iupac(text, 0..text.len(), 0).unwrap();
+        let full = parse("Man(a1-3)[Man(a1-6)]Man(b1-4)GlcNAc(b1-4)GlcNAc(b1-");
+        let topology = parse("Man(?1-?)[Man(?1-?)]Man(?1-?)GlcNAc(?1-?)GlcNAc");
+        let swapped = parse("Gal(a1-6)[Man(a1-3)]Man(b1-4)GlcNAc(b1-4)GlcNAc(b1-");
+        assert_ne!(full, topology);
+        assert!(full.same_topology(&topology));
+        assert!(!full.same_topology(&swapped));
+    }
 }
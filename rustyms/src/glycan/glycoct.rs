@@ -0,0 +1,382 @@
+//! Read and write glycan structures in the GlycoCT condensed format
+
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use super::{
+    residue_codes::{ResidueCode, RESIDUE_CODES, SUBSTITUENT_CODES},
+    AnomericState, GlycanLinkage, GlycanStructure, MonoSaccharide,
+};
+use crate::error::{Context, CustomError};
+
+/// A residue as read from the RES section
+enum Residue<'a> {
+    /// A monosaccharide with its anomeric state and its basetype
+    Base(Option<AnomericState>, &'a str),
+    /// A substituent with its name
+    Substituent(&'a str),
+}
+
+impl GlycanStructure {
+    /// Parse a glycan structure from GlycoCT condensed, eg:
+    /// ```text
+    /// RES
+    /// 1b:b-dglc-HEX-1:5
+    /// 2s:n-acetyl
+    /// 3b:b-dgal-HEX-1:5
+    /// LIN
+    /// 1:1d(2+1)2n
+    /// 2:1o(4+1)3d
+    /// ```
+    /// Only the RES and LIN sections are supported, and only the monosaccharides and
+    /// substituents that are commonly found in glycoproteomics.
+    /// # Errors
+    /// If the text is not valid GlycoCT, contains unsupported sections, or contains unsupported
+    /// monosaccharides or substituents.
+    pub fn from_glycoct(text: &str) -> Result<Self, CustomError> {
+        let error = |explanation: &str, token: &str| {
+            CustomError::error("Invalid GlycoCT", explanation, Context::show(token))
+        };
+        let mut residues = HashMap::new();
+        let mut order = Vec::new();
+        let mut links = Vec::new();
+        let mut section = "";
+        for token in text.split_whitespace() {
+            if token.chars().all(|c| c.is_ascii_uppercase()) {
+                if !matches!(token, "RES" | "LIN") {
+                    return Err(error("Only the RES and LIN sections are supported", token));
+                }
+                section = token;
+                continue;
+            }
+            match section {
+                "RES" => {
+                    let (id, residue) = parse_residue(token).ok_or_else(|| {
+                        error(
+                            "This residue is not a valid monosaccharide or substituent",
+                            token,
+                        )
+                    })?;
+                    residues.insert(id, residue);
+                    order.push(id);
+                }
+                "LIN" => links.push(parse_link(token).ok_or_else(|| {
+                    error("This is not a valid linkage, eg '1:1o(4+1)2d'", token)
+                })?),
+                _ => return Err(error("Expected a RES section first", token)),
+            }
+        }
+
+        // Attach all substituents and branches to their parents
+        let mut substituents: HashMap<usize, Vec<(Option<u8>, &str)>> = HashMap::new();
+        let mut children: HashMap<usize, Vec<(usize, GlycanLinkage)>> = HashMap::new();
+        let mut has_parent = Vec::new();
+        for (parent, parent_position, position, child) in links {
+            match residues.get(&child) {
+                Some(Residue::Substituent(name)) => substituents
+                    .entry(parent)
+                    .or_default()
+                    .push((parent_position, name)),
+                Some(Residue::Base(anomer, _)) => children.entry(parent).or_default().push((
+                    child,
+                    GlycanLinkage {
+                        anomer: *anomer,
+                        position,
+                        parent_position,
+                    },
+                )),
+                None => {
+                    return Err(error(
+                        "A linkage refers to an undefined residue",
+                        &child.to_string(),
+                    ))
+                }
+            }
+            if !residues.contains_key(&parent) {
+                return Err(error(
+                    "A linkage refers to an undefined residue",
+                    &parent.to_string(),
+                ));
+            }
+            has_parent.push(child);
+        }
+        let root = order
+            .iter()
+            .find(|id| {
+                matches!(residues.get(id), Some(Residue::Base(..))) && !has_parent.contains(id)
+            })
+            .ok_or_else(|| error("No root monosaccharide found", text))?;
+
+        build(
+            *root,
+            &residues,
+            &mut substituents,
+            &children,
+            &mut Vec::new(),
+        )
+    }
+
+    /// Write this structure as GlycoCT condensed, see [`Self::from_glycoct`] for an example.
+    /// # Errors
+    /// If any of the monosaccharides in this structure cannot be represented in GlycoCT.
+    pub fn to_glycoct(&self) -> Result<String, CustomError> {
+        let mut res = vec!["RES".to_string()];
+        let mut lin = vec!["LIN".to_string()];
+        self.glycoct_inner(&mut res, &mut lin, None)?;
+        if lin.len() > 1 {
+            res.extend(lin);
+        }
+        Ok(res.join("\n"))
+    }
+
+    /// # Errors
+    /// If any of the monosaccharides cannot be represented in GlycoCT.
+    fn glycoct_inner(
+        &self,
+        res: &mut Vec<String>,
+        lin: &mut Vec<String>,
+        parent: Option<usize>,
+    ) -> Result<(), CustomError> {
+        let (code, additional) = ResidueCode::find(&self.sugar).ok_or_else(|| {
+            CustomError::error(
+                "Unsupported monosaccharide",
+                "This monosaccharide cannot be written as GlycoCT",
+                Context::show(self.sugar.iupac_name()),
+            )
+        })?;
+        let id = res.len();
+        let ring = if self.sugar.furanose {
+            code.glycoct.replacen("1:5", "1:4", 1)
+        } else {
+            code.glycoct.to_string()
+        };
+        res.push(format!(
+            "{id}b:{}-{ring}",
+            match self.linkage.anomer {
+                Some(AnomericState::Alpha) => 'a',
+                Some(AnomericState::Beta) => 'b',
+                None => 'x',
+            }
+        ));
+        if let Some(parent) = parent {
+            lin.push(format!(
+                "{}:{parent}o({}+{}){id}d",
+                lin.len(),
+                position(self.linkage.parent_position),
+                position(self.linkage.position),
+            ));
+        }
+        for (link_position, name, link) in code
+            .glycoct_substituents
+            .iter()
+            .map(|(p, name)| (Some(*p), *name, 'd'))
+            .chain(additional.iter().map(|a| (None, a.glycoct, 'o')))
+        {
+            let substituent = res.len();
+            res.push(format!("{substituent}s:{name}"));
+            lin.push(format!(
+                "{}:{id}{link}({}+1){substituent}n",
+                lin.len(),
+                position(link_position)
+            ));
+        }
+        for branch in &self.branches {
+            branch.glycoct_inner(res, lin, Some(id))?;
+        }
+        Ok(())
+    }
+}
+
+fn position(position: Option<u8>) -> String {
+    position.map_or_else(|| "-1".to_string(), |p| p.to_string())
+}
+
+/// Parse a residue, eg `1b:b-dglc-HEX-1:5` or `2s:n-acetyl`
+fn parse_residue(token: &str) -> Option<(usize, Residue<'_>)> {
+    let (id, content) = token.split_once(':')?;
+    let kind = id.chars().last()?;
+    let id = id[..id.len() - 1].parse().ok()?;
+    match kind {
+        'b' => {
+            let (anomer, basetype) = content.split_once('-')?;
+            let anomer = match anomer {
+                "a" => Some(AnomericState::Alpha),
+                "b" => Some(AnomericState::Beta),
+                "x" | "o" => None,
+                _ => return None,
+            };
+            Some((id, Residue::Base(anomer, basetype)))
+        }
+        's' => Some((id, Residue::Substituent(content))),
+        _ => None,
+    }
+}
+
+/// Parse a linkage, eg `1:1o(4+1)2d`, into parent, parent position, child position, and child
+fn parse_link(token: &str) -> Option<(usize, Option<u8>, Option<u8>, usize)> {
+    let (_, link) = token.split_once(':')?;
+    let (parent, rest) = link.split_once('(')?;
+    let (positions, child) = rest.split_once(')')?;
+    let (parent_position, position) = positions.split_once('+')?;
+    let number = |text: &str| {
+        text.trim_end_matches(|c: char| c.is_ascii_lowercase())
+            .parse::<usize>()
+            .ok()
+    };
+    Some((
+        number(parent)?,
+        parent_position.parse().ok(),
+        position.parse().ok(),
+        number(child)?,
+    ))
+}
+
+/// Build the structure from the given root residue
+/// # Errors
+/// If the linkages contain a cycle or if any monosaccharide is not supported.
+fn build(
+    id: usize,
+    residues: &HashMap<usize, Residue<'_>>,
+    substituents: &mut HashMap<usize, Vec<(Option<u8>, &str)>>,
+    children: &HashMap<usize, Vec<(usize, GlycanLinkage)>>,
+    seen: &mut Vec<usize>,
+) -> Result<GlycanStructure, CustomError> {
+    if seen.contains(&id) {
+        return Err(CustomError::error(
+            "Invalid GlycoCT",
+            "The linkages in this glycan form a cycle, only tree structures are supported",
+            Context::show(id),
+        ));
+    }
+    seen.push(id);
+    let Some(Residue::Base(anomer, basetype)) = residues.get(&id) else {
+        unreachable!()
+    };
+    let sugar = monosaccharide(basetype, &substituents.remove(&id).unwrap_or_default())?;
+    let linkage = anomer.map_or_else(GlycanLinkage::default, |anomer| GlycanLinkage {
+        anomer: Some(anomer),
+        position: basetype
+            .rsplit_once('-')
+            .and_then(|(_, ring)| ring.split_once(':'))
+            .and_then(|(start, _)| start.parse().ok()),
+        parent_position: None,
+    });
+    let branches = children
+        .get(&id)
+        .map(|branches| {
+            branches
+                .iter()
+                .map(|(child, linkage)| {
+                    build(*child, residues, substituents, children, seen)
+                        .map(|b| b.with_linkage(*linkage))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()?
+        .unwrap_or_default();
+    Ok(GlycanStructure::new(sugar, branches).with_linkage(linkage))
+}
+
+/// Get the monosaccharide for a basetype with the given substituents
+/// # Errors
+/// If this basetype with these substituents is not supported.
+fn monosaccharide(
+    basetype: &str,
+    substituents: &[(Option<u8>, &str)],
+) -> Result<MonoSaccharide, CustomError> {
+    let furanose = basetype.contains("-1:4");
+    let basetype = basetype.replacen("-1:4", "-1:5", 1);
+    let name = RESIDUE_CODES
+        .iter()
+        .filter(|code| code.glycoct == basetype)
+        .filter_map(|code| {
+            let mut remainder = substituents.to_vec();
+            for (position, name) in code.glycoct_substituents {
+                let index = remainder
+                    .iter()
+                    .position(|(p, n)| n == name && p.map_or(true, |p| p == *position))?;
+                remainder.remove(index);
+            }
+            let additional = remainder
+                .iter()
+                .map(|(position, name)| {
+                    SUBSTITUENT_CODES
+                        .iter()
+                        .find(|code| code.glycoct == *name)
+                        .map(|code| (*position, code))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((code.glycoct_substituents.len(), code, additional))
+        })
+        .max_by_key(|(covered, _, _)| *covered)
+        .map(|(_, code, additional)| code.iupac_name(furanose, additional))
+        .ok_or_else(|| {
+            CustomError::error(
+                "Unsupported monosaccharide",
+                "This GlycoCT monosaccharide with its substituents is not supported",
+                Context::show(format!(
+                    "{basetype} {}",
+                    substituents.iter().map(|(_, name)| name).join(" ")
+                )),
+            )
+        })?;
+    MonoSaccharide::from_short_iupac(&name, 0, 0).map(|(sugar, _)| sugar)
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use super::*;
+
+    const CORE: &str = "RES
+1b:b-dglc-HEX-1:5
+2s:n-acetyl
+3b:b-dglc-HEX-1:5
+4s:n-acetyl
+5b:b-dman-HEX-1:5
+6b:a-dman-HEX-1:5
+7b:a-dman-HEX-1:5
+LIN
+1:1d(2+1)2n
+2:1o(4+1)3d
+3:3d(2+1)4n
+4:3o(4+1)5d
+5:5o(3+1)6d
+6:5o(6+1)7d";
+
+    #[test]
+    fn core() {
+        let structure = GlycanStructure::from_glycoct(CORE).unwrap();
+        let iupac = "Man(a1-3)[Man(a1-6)]Man(b1-4)GlcNAc(b1-4)GlcNAc(b1-";
+        assert_eq!(structure.to_short_iupac(), iupac);
+        assert_eq!(
+            structure,
+            GlycanStructure::from_short_iupac(iupac, 0..iupac.len(), 0).unwrap()
+        );
+        assert_eq!(structure.to_glycoct().unwrap(), CORE);
+    }
+
+    #[test]
+    fn substituents() {
+        let structure = GlycanStructure::from_glycoct(
+            "RES 1b:x-dgal-HEX-1:5 2s:n-acetyl 3s:sulfate 4b:a-dgro-dgal-NON-2:6|1:a|2:keto|3:d \
+             5s:n-acetyl 6b:a-lgal-HEX-1:5|6:d LIN 1:1d(2+1)2n 2:1o(4+1)3n 3:1o(3+2)4d \
+             4:4d(5+1)5n 5:1o(6+1)6d",
+        )
+        .unwrap();
+        assert_eq!(
+            structure.to_short_iupac(),
+            "Neu5Ac(a2-3)[Fuc(a1-6)]GalNAc?S"
+        );
+        let round_trip = GlycanStructure::from_glycoct(&structure.to_glycoct().unwrap()).unwrap();
+        assert_eq!(round_trip, structure);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(GlycanStructure::from_glycoct("RES 1b:b-dglc-HEX-1:5 REP").is_err());
+        assert!(GlycanStructure::from_glycoct("RES 1b:b-dtal-HEX-1:5|2:keto").is_err());
+        assert!(GlycanStructure::from_glycoct("RES 1b:b-dglc-HEX-1:5 LIN 1:1o(4+1)2d").is_err());
+    }
+}
@@ -1,9 +1,15 @@
 //! Handle glycan related issues, access provided if you want to work with glycans on your own.
 
+mod composition;
 mod glycan_structure;
+mod glycoct;
 mod monosaccharide;
 mod positioned_structure;
+mod render;
+mod residue_codes;
+mod wurcs;
 
+pub use composition::*;
 pub use glycan_structure::*;
 pub use monosaccharide::*;
 pub use positioned_structure::*;
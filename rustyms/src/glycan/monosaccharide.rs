@@ -3,7 +3,7 @@
 use crate::{
     fragment::{DiagnosticPosition, Fragment, FragmentType},
     molecular_charge::CachedCharge,
-    system::usize::Charge,
+    system::isize::Charge,
     AminoAcid, Model, Multi, NeutralLoss,
 };
 
@@ -217,6 +217,61 @@ impl MonoSaccharide {
         }
         result
     }
+
+    /// Get the atoms of the pyranose ring for this monosaccharide, as needed for cross-ring
+    /// cleavages. The ring atoms are given in order C1, C2, C3, C4, C5, O5, with the exocyclic
+    /// atoms included in the ring atom they are attached to (so C6 is part of C5). The glycosidic
+    /// oxygen on C1 is seen as part of the reducing end. Only hexoses and pentoses with all
+    /// substituents on known positions (N substituents on C2, deoxy and acid on C6) are
+    /// supported, for any other monosaccharide this returns None.
+    pub(crate) fn ring_layout(&self) -> Option<[MolecularFormula; 6]> {
+        let hexose = match self.base_sugar {
+            BaseSugar::Hexose(_) => true,
+            BaseSugar::Pentose(_) => false,
+            _ => return None,
+        };
+        if self.furanose {
+            return None;
+        }
+        let mut ring = [
+            molecular_formula!(C 1 H 1),
+            molecular_formula!(C 1 H 2 O 1),
+            molecular_formula!(C 1 H 2 O 1),
+            molecular_formula!(C 1 H 2 O 1),
+            if hexose {
+                molecular_formula!(C 2 H 4 O 1)
+            } else {
+                molecular_formula!(C 1 H 2)
+            },
+            molecular_formula!(O 1),
+        ];
+        let mut occupied = [false; 6];
+        for substituent in &self.substituents {
+            let index = match substituent {
+                GlycanSubstituent::Amino
+                | GlycanSubstituent::NAcetyl
+                | GlycanSubstituent::NGlycolyl => 1,
+                GlycanSubstituent::Deoxy | GlycanSubstituent::Acid if hexose => 4,
+                _ => return None,
+            };
+            if occupied[index] {
+                return None;
+            }
+            occupied[index] = true;
+            ring[index] += substituent.formula();
+        }
+        Some(ring)
+    }
+
+    /// Get the index in the ring (see [`Self::ring_layout`]) for the atom that holds the given
+    /// carbon, or None if this carbon is not known
+    pub(crate) const fn ring_index(carbon: u8) -> Option<usize> {
+        match carbon {
+            1..=4 => Some(carbon as usize - 1),
+            5 | 6 => Some(4),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -4,12 +4,12 @@ use std::hash::Hash;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-use super::MonoSaccharide;
+use super::{GlycanLinkage, MonoSaccharide};
 use crate::{
     formula::{Chemical, MolecularFormula},
     fragment::{Fragment, FragmentType, GlycanBreakPos, GlycanPosition},
     molecular_charge::CachedCharge,
-    system::usize::Charge,
+    system::isize::Charge,
     AminoAcid, Model, Multi, SequencePosition,
 };
 
@@ -19,6 +19,7 @@ use crate::uom::num_traits::Zero;
 #[derive(Debug, Eq, PartialEq, Clone, Hash, Serialize, Deserialize)]
 pub struct PositionedGlycanStructure {
     pub(super) sugar: MonoSaccharide,
+    pub(super) linkage: GlycanLinkage,
     pub(super) branches: Vec<PositionedGlycanStructure>,
     pub(super) inner_depth: usize,
     pub(super) outer_depth: usize,
@@ -106,11 +107,128 @@ impl PositionedGlycanStructure {
                             f.with_charge_range(charge_carriers, model.glycan.oxonium_charge_range)
                         }),
                 );
+                // Generate all C/Z and A/X fragments
+                base_fragments.extend(self.complementary_fragments(
+                    model,
+                    peptidoform_index,
+                    peptide_index,
+                    charge_carriers,
+                    full_formula,
+                    attachment,
+                ));
                 base_fragments
             })
             .unwrap_or_default()
     }
 
+    /// Generate the glycosidic C and Z fragments and the cross-ring A and X fragments as set in
+    /// the model for this position and all its branches. The reducing end fragments (Z, X) are
+    /// the full formula minus their non reducing end counterpart (C, A).
+    fn complementary_fragments(
+        &self,
+        model: &Model,
+        peptidoform_index: usize,
+        peptide_index: usize,
+        charge_carriers: &mut CachedCharge,
+        full_formula: &Multi<MolecularFormula>,
+        attachment: Option<(AminoAcid, usize)>,
+    ) -> Vec<Fragment> {
+        let position = self.position(attachment);
+        let mut options = Vec::new();
+        if model.glycan.allow_c_z {
+            options.push((
+                self.formula_inner(SequencePosition::default(), peptide_index)
+                    + molecular_formula!(H 2 O 1),
+                FragmentType::C(position.clone()),
+                FragmentType::Z(position.clone()),
+            ));
+        }
+        for cleavage in &model.glycan.cross_ring {
+            if let Some(formula) = self.cross_ring_formula(*cleavage, peptide_index) {
+                options.push((
+                    formula,
+                    FragmentType::A(position.clone(), *cleavage),
+                    FragmentType::X(position.clone(), *cleavage),
+                ));
+            }
+        }
+
+        let mut fragments = Vec::new();
+        for (formula, non_reducing, reducing) in options {
+            fragments.extend(
+                Fragment::new(
+                    formula.clone(),
+                    Charge::zero(),
+                    peptidoform_index,
+                    peptide_index,
+                    non_reducing,
+                )
+                .with_charge_range(charge_carriers, model.glycan.oxonium_charge_range)
+                .flat_map(|f| f.with_neutral_losses(&model.glycan.neutral_losses)),
+            );
+            fragments.extend(
+                full_formula
+                    .iter()
+                    .map(|full| {
+                        Fragment::new(
+                            full - &formula,
+                            Charge::zero(),
+                            peptidoform_index,
+                            peptide_index,
+                            reducing.clone(),
+                        )
+                    })
+                    .flat_map(|f| {
+                        f.with_charge_range(charge_carriers, model.glycan.other_charge_range)
+                    })
+                    .flat_map(|f| f.with_neutral_losses(&model.glycan.neutral_losses)),
+            );
+        }
+        for branch in &self.branches {
+            fragments.extend(branch.complementary_fragments(
+                model,
+                peptidoform_index,
+                peptide_index,
+                charge_carriers,
+                full_formula,
+                attachment,
+            ));
+        }
+        fragments
+    }
+
+    /// Get the formula of the non reducing end part of the given cross-ring cleavage of this
+    /// monosaccharide, including all branches attached to that part. Returns None if the ring
+    /// layout of the monosaccharide is not known, the cleavage is not possible in a pyranose
+    /// ring, or if the linkage position of any branch is not known.
+    fn cross_ring_formula(
+        &self,
+        (first, second): (u8, u8),
+        peptide_index: usize,
+    ) -> Option<MolecularFormula> {
+        let ring = self.sugar.ring_layout()?;
+        let (first, second) = (usize::from(first), usize::from(second));
+        if first + 2 > second || second > 5 || (first, second) == (0, 5) {
+            return None;
+        }
+        if self.linkage.position.is_some_and(|p| p != 1) {
+            return None;
+        }
+        // The non reducing end part is the part that does not contain the anomeric carbon
+        let part = if first == 0 { second..6 } else { first..second };
+        let mut formula = ring[part.clone()].iter().cloned().sum::<MolecularFormula>();
+        for branch in &self.branches {
+            let index = branch
+                .linkage
+                .parent_position
+                .and_then(MonoSaccharide::ring_index)?;
+            if part.contains(&index) {
+                formula += branch.formula_inner(SequencePosition::default(), peptide_index);
+            }
+        }
+        Some(formula)
+    }
+
     /// Get uncharged diagnostic ions from all positions
     fn diagnostic_ions(
         &self,
@@ -252,3 +370,111 @@ impl PositionedGlycanStructure {
         }
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use super::*;
+    use crate::{
+        fragment::FragmentKind, glycan::GlycanStructure, model::GlycanModel, MassMode,
+        MolecularCharge,
+    };
+
+    /// Generate all singly protonated fragments of a free reducing end glycan
+    fn generate(iupac: &str, model: &Model) -> Vec<Fragment> {
+        let structure = GlycanStructure::from_short_iupac(iupac, 0..iupac.len(), 0)
+            .unwrap()
+            .determine_positions();
+        let full = structure.formula() + molecular_formula!(H 2 O 1);
+        structure.generate_theoretical_fragments(
+            model,
+            0,
+            0,
+            &mut MolecularCharge::proton(1).into(),
+            &full.into(),
+            None,
+        )
+    }
+
+    /// Get the m/z for all singly protonated fragments of a free reducing end glycan
+    fn fragments(iupac: &str) -> Vec<(String, f64)> {
+        let model = Model::none().glycan(
+            GlycanModel::DISALLOW
+                .allow_structural(true)
+                .allow_c_z(true)
+                .cross_ring(GlycanModel::CROSS_RING_CLEAVAGES.to_vec()),
+        );
+        generate(iupac, &model)
+            .into_iter()
+            .map(|f| (f.ion.to_string(), f.mz(MassMode::Monoisotopic).value))
+            .collect()
+    }
+
+    fn assert_fragment(fragments: &[(String, f64)], label: &str, mz: Option<f64>) {
+        let found = fragments.iter().find(|(l, _)| l == label).map(|(_, m)| *m);
+        match (found, mz) {
+            (Some(found), Some(mz)) => assert!(
+                (found - mz).abs() < 1e-4,
+                "{label} expected {mz} but found {found}"
+            ),
+            (found, mz) => assert_eq!(found, mz, "{label}"),
+        }
+    }
+
+    #[test]
+    fn glycosidic_c_z() {
+        let fragments = fragments("Gal(b1-4)Glc");
+        assert_fragment(&fragments, "C1", Some(181.070_664));
+        assert_fragment(&fragments, "Z1", Some(163.060_099));
+        assert_fragment(&fragments, "C2", Some(343.123_488));
+    }
+
+    #[test]
+    fn cross_ring() {
+        let four = fragments("Gal(b1-4)Glc");
+        assert_fragment(&four, "0,2A2", Some(283.102_358));
+        assert_fragment(&four, "2,4A2", Some(223.081_229));
+        assert_fragment(&four, "0,2X2", Some(61.028_405));
+        assert_fragment(&four, "3,5A2", Some(237.096_881));
+        assert_fragment(&four, "0,2A1", Some(121.049_535));
+        let three = fragments("Gal(b1-3)Glc");
+        assert_fragment(&three, "0,2A2", Some(283.102_358));
+        assert_fragment(&three, "2,4A2", Some(223.081_229));
+        assert_fragment(&three, "3,5A2", Some(75.044_056));
+        let unknown = fragments("Gal(b1-?)Glc");
+        assert_fragment(&unknown, "0,2A2", None);
+        assert_fragment(&unknown, "0,2X2", None);
+        assert_fragment(&unknown, "0,2A1", Some(121.049_535));
+    }
+
+    #[test]
+    fn kinds() {
+        let model = Model::none().glycan(
+            GlycanModel::DISALLOW
+                .allow_structural(true)
+                .allow_c_z(true)
+                .cross_ring(GlycanModel::CROSS_RING_CLEAVAGES.to_vec()),
+        );
+        let kinds: Vec<FragmentKind> = generate("Gal(b1-4)Glc", &model)
+            .iter()
+            .map(|f| f.ion.kind())
+            .collect();
+        for kind in [
+            FragmentKind::A,
+            FragmentKind::C,
+            FragmentKind::X,
+            FragmentKind::Y,
+            FragmentKind::Z,
+            FragmentKind::Oxonium,
+        ] {
+            assert!(kinds.contains(&kind), "{kind} not generated");
+        }
+        // Not generated by default
+        assert!(!generate("Gal(b1-4)Glc", &Model::all())
+            .iter()
+            .any(|f| matches!(
+                f.ion.kind(),
+                FragmentKind::A | FragmentKind::C | FragmentKind::X | FragmentKind::Z
+            )));
+    }
+}
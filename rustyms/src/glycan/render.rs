@@ -0,0 +1,495 @@
+//! Render glycan structures as SVG following the Symbol Nomenclature for Glycans (SNFG)
+//! Source: <https://www.ncbi.nlm.nih.gov/glycans/snfg.html>.
+
+use std::fmt::Write;
+
+use super::{
+    AnomericState, BaseSugar, GlycanStructure, GlycanSubstituent, HexoseIsomer, MonoSaccharide,
+    PentoseIsomer, PositionedGlycanStructure,
+};
+use crate::fragment::{FragmentType, GlycanBreakPos, GlycanPosition};
+
+/// The size of a single monosaccharide symbol
+const SIZE: f64 = 16.0;
+/// The horizontal distance between the centres of two connected monosaccharides
+const COLUMN: f64 = 40.0;
+/// The vertical distance between the centres of two branches
+const ROW: f64 = 26.0;
+/// The empty space around the glycan
+const MARGIN: f64 = 8.0;
+/// The colour for broken bonds and cross-ring cleavages
+const HIGHLIGHT: &str = "#E8006F";
+
+const WHITE: &str = "#FFFFFF";
+const BLUE: &str = "#0072BC";
+const GREEN: &str = "#00A651";
+const YELLOW: &str = "#FFD400";
+const ORANGE: &str = "#F47920";
+const PINK: &str = "#F69EA1";
+const PURPLE: &str = "#A54399";
+const LIGHT_BLUE: &str = "#8FCCE9";
+const BROWN: &str = "#A17A4D";
+const RED: &str = "#ED1C24";
+
+/// The SNFG shapes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Shape {
+    /// Hexose
+    Circle,
+    /// N-acetylhexosamine
+    Square,
+    /// Hexosamine
+    DividedSquare,
+    /// Deoxyhexose
+    Triangle,
+    /// Deoxy-N-acetylhexosamine
+    DividedTriangle,
+    /// Dideoxyhexose
+    FlatRectangle,
+    /// Hexuronate
+    DividedDiamond,
+    /// Deoxynonulosonate (sialic acids)
+    Diamond,
+    /// Pentose
+    Star,
+    /// Unknown
+    FlatHexagon,
+}
+
+impl MonoSaccharide {
+    /// Get the SNFG shape and colour for this monosaccharide, together with all substituents that
+    /// are not represented by the shape itself.
+    fn snfg_symbol(&self) -> (Shape, &'static str, Vec<&GlycanSubstituent>) {
+        let mut remaining = self.substituents.iter().collect::<Vec<_>>();
+        let mut take = |substituent: &GlycanSubstituent| {
+            remaining
+                .iter()
+                .position(|s| *s == substituent)
+                .map(|index| remaining.remove(index))
+                .is_some()
+        };
+        let (shape, colour) = match &self.base_sugar {
+            BaseSugar::Hexose(isomer) => {
+                let deoxy = take(&GlycanSubstituent::Deoxy);
+                let dideoxy = deoxy && take(&GlycanSubstituent::Deoxy);
+                let acetyl = take(&GlycanSubstituent::NAcetyl);
+                let amino = !acetyl && take(&GlycanSubstituent::Amino);
+                let acid = !deoxy && !acetyl && !amino && take(&GlycanSubstituent::Acid);
+                let shape = if dideoxy {
+                    Shape::FlatRectangle
+                } else if deoxy && (acetyl || amino) {
+                    Shape::DividedTriangle
+                } else if deoxy {
+                    Shape::Triangle
+                } else if acetyl {
+                    Shape::Square
+                } else if amino {
+                    Shape::DividedSquare
+                } else if acid {
+                    Shape::DividedDiamond
+                } else {
+                    Shape::Circle
+                };
+                let colour = match isomer {
+                    Some(HexoseIsomer::Galactose) if deoxy && !dideoxy => RED,
+                    Some(HexoseIsomer::Glucose) => BLUE,
+                    Some(HexoseIsomer::Mannose) => GREEN,
+                    Some(HexoseIsomer::Galactose) => YELLOW,
+                    Some(HexoseIsomer::Gulose) => ORANGE,
+                    Some(HexoseIsomer::Altrose) => PINK,
+                    Some(HexoseIsomer::Allose) => PURPLE,
+                    Some(HexoseIsomer::Talose) => LIGHT_BLUE,
+                    Some(HexoseIsomer::Idose) => BROWN,
+                    _ => WHITE,
+                };
+                (shape, colour)
+            }
+            BaseSugar::Pentose(isomer) => (
+                Shape::Star,
+                match isomer {
+                    Some(PentoseIsomer::Xylose) => ORANGE,
+                    Some(PentoseIsomer::Arabinose) => GREEN,
+                    Some(PentoseIsomer::Ribose) => PINK,
+                    Some(PentoseIsomer::Lyxose) => YELLOW,
+                    _ => WHITE,
+                },
+            ),
+            BaseSugar::Nonose if take(&GlycanSubstituent::Acid) => (
+                Shape::Diamond,
+                if take(&GlycanSubstituent::Amino) {
+                    if take(&GlycanSubstituent::Acetyl) {
+                        PURPLE
+                    } else if take(&GlycanSubstituent::Glycolyl) {
+                        LIGHT_BLUE
+                    } else {
+                        take(&GlycanSubstituent::Deoxy);
+                        BROWN
+                    }
+                } else {
+                    GREEN
+                },
+            ),
+            _ => (Shape::FlatHexagon, WHITE),
+        };
+        (shape, colour, remaining)
+    }
+}
+
+/// The parts of a glycan that are highlighted for a fragment
+#[derive(Default)]
+struct Highlight<'a> {
+    /// The position where the fragment starts (B, C, internal), all residues below are lost
+    root: Option<&'a GlycanPosition>,
+    /// The positions where the bond towards the reducing end is broken and the residues are lost (Y, Z, internal)
+    broken: Vec<&'a GlycanPosition>,
+    /// The position of a cross-ring cleavage
+    cross_ring: Option<&'a GlycanPosition>,
+}
+
+impl<'a> Highlight<'a> {
+    fn new(fragment: Option<&'a FragmentType>) -> Self {
+        match fragment {
+            Some(FragmentType::B(position) | FragmentType::C(position)) => Self {
+                root: Some(position),
+                ..Self::default()
+            },
+            Some(FragmentType::Y(positions)) => Self {
+                broken: positions.iter().collect(),
+                ..Self::default()
+            },
+            Some(FragmentType::Z(position)) => Self {
+                broken: vec![position],
+                ..Self::default()
+            },
+            Some(FragmentType::Oxonium(breakages)) => Self {
+                root: breakages.iter().find_map(|b| match b {
+                    GlycanBreakPos::B(position) => Some(position),
+                    _ => None,
+                }),
+                broken: breakages
+                    .iter()
+                    .filter_map(|b| match b {
+                        GlycanBreakPos::Y(position) => Some(position),
+                        _ => None,
+                    })
+                    .collect(),
+                ..Self::default()
+            },
+            Some(FragmentType::A(position, _) | FragmentType::X(position, _)) => Self {
+                cross_ring: Some(position),
+                ..Self::default()
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
+/// The SVG output under construction, the bonds are kept separately so that they can be drawn
+/// below the symbols
+#[derive(Default)]
+struct Canvas {
+    bonds: String,
+    symbols: String,
+    next_row: usize,
+}
+
+impl GlycanStructure {
+    /// Render this glycan as an SVG image using the Symbol Nomenclature for Glycans (SNFG), see
+    /// [`PositionedGlycanStructure::to_svg`].
+    pub fn to_svg(&self) -> String {
+        self.clone().determine_positions().to_svg(None)
+    }
+}
+
+impl PositionedGlycanStructure {
+    /// Render this glycan as an SVG image using the Symbol Nomenclature for Glycans (SNFG). The
+    /// reducing end is placed on the right with the branches extending to the left. Any known
+    /// linkages are written next to the bonds and any substituents not covered by the symbol are
+    /// written next to the symbol.
+    ///
+    /// If a glycan fragment of this structure is given (B, C, Y, Z, internal, A, or X) the broken
+    /// bonds or rings are highlighted and the monosaccharides that are lost in the fragment are
+    /// faded out (for cross-ring fragments only the broken ring is highlighted).
+    /// # Panics
+    /// If writing to the string fails.
+    pub fn to_svg(&self, fragment: Option<&FragmentType>) -> String {
+        let highlight = Highlight::new(fragment);
+        let columns = self.max_inner_depth();
+        let mut canvas = Canvas::default();
+        let (x, y, highlighted) =
+            self.render(columns, highlight.root.is_none(), &highlight, &mut canvas);
+        // The bond towards the reducing end
+        write_bond(
+            &mut canvas.bonds,
+            (x, y),
+            (x + COLUMN / 2.0, y),
+            highlighted,
+        );
+        let width = (columns as f64).mul_add(COLUMN, MARGIN.mul_add(2.0, SIZE + COLUMN / 2.0));
+        let height =
+            (canvas.next_row.saturating_sub(1) as f64).mul_add(ROW, MARGIN.mul_add(2.0, SIZE));
+        let mut output = String::new();
+        write!(
+            &mut output,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.1}\" height=\"{height:.1}\" viewBox=\"0 0 {width:.1} {height:.1}\">{}{}</svg>",
+            canvas.bonds, canvas.symbols
+        )
+        .unwrap();
+        output
+    }
+
+    /// Get the maximal inner depth of any monosaccharide in this structure
+    fn max_inner_depth(&self) -> usize {
+        self.branches
+            .iter()
+            .map(Self::max_inner_depth)
+            .max()
+            .unwrap_or(self.inner_depth)
+    }
+
+    /// Check if this monosaccharide is at the given position
+    fn is_at(&self, position: &GlycanPosition) -> bool {
+        self.inner_depth == position.inner_depth
+            && self.outer_depth + 1 == position.series_number
+            && self.branch == position.branch
+    }
+
+    /// Render this monosaccharide and all branches, returns the centre of this symbol and if the
+    /// bond towards the reducing end is broken.
+    /// # Panics
+    /// If writing to the string fails.
+    fn render(
+        &self,
+        columns: usize,
+        kept: bool,
+        highlight: &Highlight,
+        canvas: &mut Canvas,
+    ) -> (f64, f64, bool) {
+        let is_root = highlight.root.is_some_and(|p| self.is_at(p));
+        let is_broken = highlight.broken.iter().any(|p| self.is_at(p));
+        let kept = if is_root {
+            true
+        } else if is_broken {
+            false
+        } else {
+            kept
+        };
+
+        let x = ((columns - self.inner_depth) as f64).mul_add(COLUMN, MARGIN + SIZE / 2.0);
+        let branches = self
+            .branches
+            .iter()
+            .map(|branch| {
+                (
+                    branch.render(columns, kept, highlight, canvas),
+                    branch.linkage,
+                )
+            })
+            .collect::<Vec<_>>();
+        // Leaves get the next free row, other monosaccharides are centred on their branches
+        let y = if let (Some(((_, first, _), _)), Some(((_, last, _), _))) =
+            (branches.first(), branches.last())
+        {
+            (first + last) / 2.0
+        } else {
+            canvas.next_row += 1;
+            ((canvas.next_row - 1) as f64).mul_add(ROW, MARGIN + SIZE / 2.0)
+        };
+
+        for ((bx, by, highlighted), linkage) in branches {
+            write_bond(&mut canvas.bonds, (bx, by), (x, y), highlighted);
+            if linkage.anomer.is_some() || linkage.parent_position.is_some() {
+                write!(
+                    &mut canvas.bonds,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"7\" font-family=\"sans-serif\" text-anchor=\"middle\">{}{}</text>",
+                    (bx + x) / 2.0,
+                    (by + y) / 2.0 - 2.0,
+                    match linkage.anomer {
+                        Some(AnomericState::Alpha) => "α",
+                        Some(AnomericState::Beta) => "β",
+                        None => "?",
+                    },
+                    linkage
+                        .parent_position
+                        .map_or_else(|| "?".to_string(), |p| p.to_string())
+                )
+                .unwrap();
+            }
+        }
+
+        let (shape, colour, substituents) = self.sugar.snfg_symbol();
+        if !kept {
+            canvas.symbols.push_str("<g opacity=\"0.3\">");
+        }
+        write_symbol(&mut canvas.symbols, shape, colour, (x, y));
+        if !substituents.is_empty() {
+            write!(
+                &mut canvas.symbols,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"7\" font-family=\"sans-serif\">{}</text>",
+                x + SIZE / 2.0,
+                y - SIZE / 2.0,
+                substituents.iter().map(ToString::to_string).collect::<String>()
+            )
+            .unwrap();
+        }
+        if highlight.cross_ring.is_some_and(|p| self.is_at(p)) {
+            write!(
+                &mut canvas.symbols,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{HIGHLIGHT}\" stroke-width=\"2\"/>",
+                x - SIZE / 2.0,
+                y + SIZE / 2.0,
+                x + SIZE / 2.0,
+                y - SIZE / 2.0,
+            )
+            .unwrap();
+        }
+        if !kept {
+            canvas.symbols.push_str("</g>");
+        }
+        (x, y, is_root || is_broken)
+    }
+}
+
+/// Write a bond between the two given points
+/// # Panics
+/// If writing to the string fails.
+fn write_bond(output: &mut String, from: (f64, f64), to: (f64, f64), highlighted: bool) {
+    write!(
+        output,
+        "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+        from.0,
+        from.1,
+        to.0,
+        to.1,
+        if highlighted { HIGHLIGHT } else { "black" },
+        if highlighted { 2 } else { 1 },
+        if highlighted {
+            " stroke-dasharray=\"3,2\""
+        } else {
+            ""
+        }
+    )
+    .unwrap();
+}
+
+/// Write a single SNFG symbol centred on the given point
+/// # Panics
+/// If writing to the string fails.
+fn write_symbol(output: &mut String, shape: Shape, colour: &str, (x, y): (f64, f64)) {
+    let h = SIZE / 2.0;
+    let polygon = |output: &mut String, points: &[(f64, f64)], fill: &str| {
+        write!(
+            output,
+            "<polygon points=\"{}\" fill=\"{fill}\" stroke=\"black\"/>",
+            points
+                .iter()
+                .map(|(px, py)| format!("{:.1},{:.1}", x + px, y + py))
+                .collect::<Vec<_>>()
+                .join(" ")
+        )
+        .unwrap();
+    };
+    match shape {
+        Shape::Circle => write!(
+            output,
+            "<circle cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"{h:.1}\" fill=\"{colour}\" stroke=\"black\"/>"
+        )
+        .unwrap(),
+        Shape::Square => polygon(output, &[(-h, -h), (h, -h), (h, h), (-h, h)], colour),
+        Shape::DividedSquare => {
+            polygon(output, &[(-h, -h), (h, -h), (h, h), (-h, h)], WHITE);
+            polygon(output, &[(-h, -h), (h, -h), (h, h)], colour);
+        }
+        Shape::Triangle => polygon(output, &[(0.0, -h), (h, h), (-h, h)], colour),
+        Shape::DividedTriangle => {
+            polygon(output, &[(0.0, -h), (h, h), (-h, h)], WHITE);
+            polygon(output, &[(0.0, -h), (h, h), (0.0, h)], colour);
+        }
+        Shape::FlatRectangle => polygon(
+            output,
+            &[(-h, -h / 2.0), (h, -h / 2.0), (h, h / 2.0), (-h, h / 2.0)],
+            colour,
+        ),
+        Shape::Diamond => polygon(output, &[(0.0, -h), (h, 0.0), (0.0, h), (-h, 0.0)], colour),
+        Shape::DividedDiamond => {
+            polygon(output, &[(0.0, -h), (h, 0.0), (0.0, h), (-h, 0.0)], WHITE);
+            polygon(output, &[(0.0, -h), (h, 0.0), (-h, 0.0)], colour);
+        }
+        Shape::Star => polygon(
+            output,
+            &(0..10)
+                .map(|i| {
+                    let radius = if i % 2 == 0 { h } else { h * 0.4 };
+                    let angle = std::f64::consts::PI * (f64::from(i) / 5.0 - 0.5);
+                    (radius * angle.cos(), radius * angle.sin())
+                })
+                .collect::<Vec<_>>(),
+            colour,
+        ),
+        Shape::FlatHexagon => polygon(
+            output,
+            &[
+                (-h, 0.0),
+                (-h / 2.0, -h / 2.0),
+                (h / 2.0, -h / 2.0),
+                (h, 0.0),
+                (h / 2.0, h / 2.0),
+                (-h / 2.0, h / 2.0),
+            ],
+            colour,
+        ),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use super::*;
+
+    fn structure(iupac: &str) -> GlycanStructure {
+        GlycanStructure::from_short_iupac(iupac, 0..iupac.len(), 0).unwrap()
+    }
+
+    #[test]
+    fn symbols() {
+        let svg = structure("Neu5Ac(a2-3)Gal(b1-4)GlcNAc(b1-2)Man(a1-3)[Fuc(a1-6)]Man").to_svg();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches(PURPLE).count(), 1);
+        assert_eq!(svg.matches(YELLOW).count(), 1);
+        assert_eq!(svg.matches(BLUE).count(), 1);
+        assert_eq!(svg.matches(GREEN).count(), 2);
+        assert_eq!(svg.matches(RED).count(), 1);
+        assert_eq!(svg.matches("<circle").count(), 3);
+        assert_eq!(svg.matches("<line").count(), 6);
+        assert!(svg.contains(">α3</text>"));
+        assert!(svg.contains(">β4</text>"));
+        assert!(!svg.contains(HIGHLIGHT));
+    }
+
+    #[test]
+    fn substituents() {
+        let svg = structure("GalNAc4S(b1-4)GlcNAc").to_svg();
+        assert!(svg.contains(">S</text>"));
+    }
+
+    #[test]
+    fn highlight() {
+        let structure = structure("Gal(b1-4)GlcNAc(b1-2)Man").determine_positions();
+        let position = GlycanPosition {
+            inner_depth: 1,
+            series_number: 2,
+            branch: Vec::new(),
+            attachment: None,
+        };
+        let svg = structure.to_svg(Some(&FragmentType::Y(vec![position.clone()])));
+        assert_eq!(svg.matches(HIGHLIGHT).count(), 1);
+        assert_eq!(svg.matches("<g opacity").count(), 2);
+        let svg = structure.to_svg(Some(&FragmentType::B(position.clone())));
+        assert_eq!(svg.matches(HIGHLIGHT).count(), 1);
+        assert_eq!(svg.matches("<g opacity").count(), 1);
+        let svg = structure.to_svg(Some(&FragmentType::A(position, (0, 2))));
+        assert_eq!(svg.matches(HIGHLIGHT).count(), 1);
+        assert_eq!(svg.matches("<g opacity").count(), 0);
+    }
+}
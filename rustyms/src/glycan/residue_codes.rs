@@ -0,0 +1,245 @@
+//! Translation of monosaccharides between IUPAC condensed, GlycoCT, and WURCS
+
+use std::sync::OnceLock;
+
+use super::{GlycanSubstituent, MonoSaccharide};
+
+/// A monosaccharide with its representation in all supported formats
+pub(super) struct ResidueCode {
+    /// The IUPAC base name, a furanose has an 'f' added after this name
+    pub iupac: &'static str,
+    /// The IUPAC suffix for the substituents that are part of this residue
+    pub iupac_suffix: &'static str,
+    /// The GlycoCT basetype without anomeric state (pyranose form)
+    pub glycoct: &'static str,
+    /// The GlycoCT substituents that are part of this residue with their position
+    pub glycoct_substituents: &'static [(u8, &'static str)],
+    /// The WURCS residue code with unknown anomeric state (pyranose form)
+    pub wurcs: &'static str,
+}
+
+/// A substituent that can be placed on any [`ResidueCode`]
+pub(super) struct SubstituentCode {
+    /// The substituent itself
+    pub substituent: GlycanSubstituent,
+    /// The IUPAC name
+    pub iupac: &'static str,
+    /// The GlycoCT name
+    pub glycoct: &'static str,
+    /// The WURCS MAP code
+    pub wurcs: &'static str,
+}
+
+pub(super) const RESIDUE_CODES: &[ResidueCode] = &[
+    residue("Glc", "", "dglc-HEX-1:5", &[], "a2122h-1x_1-5"),
+    residue("Gal", "", "dgal-HEX-1:5", &[], "a2112h-1x_1-5"),
+    residue("Man", "", "dman-HEX-1:5", &[], "a1122h-1x_1-5"),
+    residue("Hex", "", "HEX-1:5", &[], "axxxxh-1x_1-5"),
+    residue(
+        "Glc",
+        "NAc",
+        "dglc-HEX-1:5",
+        &[(2, "n-acetyl")],
+        "a2122h-1x_1-5_2*NCC/3=O",
+    ),
+    residue(
+        "Gal",
+        "NAc",
+        "dgal-HEX-1:5",
+        &[(2, "n-acetyl")],
+        "a2112h-1x_1-5_2*NCC/3=O",
+    ),
+    residue(
+        "Man",
+        "NAc",
+        "dman-HEX-1:5",
+        &[(2, "n-acetyl")],
+        "a1122h-1x_1-5_2*NCC/3=O",
+    ),
+    residue(
+        "Hex",
+        "NAc",
+        "HEX-1:5",
+        &[(2, "n-acetyl")],
+        "axxxxh-1x_1-5_2*NCC/3=O",
+    ),
+    residue(
+        "Glc",
+        "N",
+        "dglc-HEX-1:5",
+        &[(2, "amino")],
+        "a2122h-1x_1-5_2*N",
+    ),
+    residue(
+        "Gal",
+        "N",
+        "dgal-HEX-1:5",
+        &[(2, "amino")],
+        "a2112h-1x_1-5_2*N",
+    ),
+    residue("Hex", "N", "HEX-1:5", &[(2, "amino")], "axxxxh-1x_1-5_2*N"),
+    residue("Fuc", "", "lgal-HEX-1:5|6:d", &[], "a1221m-1x_1-5"),
+    residue("Rha", "", "lman-HEX-1:5|6:d", &[], "a2211m-1x_1-5"),
+    residue("dHex", "", "HEX-1:5|6:d", &[], "axxxxm-1x_1-5"),
+    residue("Xyl", "", "dxyl-PEN-1:5", &[], "a212h-1x_1-5"),
+    residue("Glc", "A", "dglc-HEX-1:5|6:a", &[], "a2122A-1x_1-5"),
+    residue("Gal", "A", "dgal-HEX-1:5|6:a", &[], "a2112A-1x_1-5"),
+    residue("Ido", "A", "lido-HEX-1:5|6:a", &[], "a2121A-1x_1-5"),
+    residue("Hex", "A", "HEX-1:5|6:a", &[], "axxxxA-1x_1-5"),
+    residue(
+        "Neu",
+        "",
+        "dgro-dgal-NON-2:6|1:a|2:keto|3:d",
+        &[(5, "amino")],
+        "Aad21122h-2x_2-6_5*N",
+    ),
+    residue(
+        "Neu",
+        "5Ac",
+        "dgro-dgal-NON-2:6|1:a|2:keto|3:d",
+        &[(5, "n-acetyl")],
+        "Aad21122h-2x_2-6_5*NCC/3=O",
+    ),
+    residue(
+        "Neu",
+        "5Gc",
+        "dgro-dgal-NON-2:6|1:a|2:keto|3:d",
+        &[(5, "n-glycolyl")],
+        "Aad21122h-2x_2-6_5*NCCO/3=O",
+    ),
+    residue(
+        "Kdn",
+        "",
+        "dgro-dgal-NON-2:6|1:a|2:keto|3:d",
+        &[],
+        "Aad21122h-2x_2-6",
+    ),
+];
+
+pub(super) const SUBSTITUENT_CODES: &[SubstituentCode] = &[
+    SubstituentCode {
+        substituent: GlycanSubstituent::Sulfate,
+        iupac: "S",
+        glycoct: "sulfate",
+        wurcs: "*OSO/3=O/3=O",
+    },
+    SubstituentCode {
+        substituent: GlycanSubstituent::Phosphate,
+        iupac: "P",
+        glycoct: "phosphate",
+        wurcs: "*OPO/3O/3=O",
+    },
+    SubstituentCode {
+        substituent: GlycanSubstituent::Acetyl,
+        iupac: "Ac",
+        glycoct: "acetyl",
+        wurcs: "*OCC/3=O",
+    },
+    SubstituentCode {
+        substituent: GlycanSubstituent::Methyl,
+        iupac: "Me",
+        glycoct: "methyl",
+        wurcs: "*OC",
+    },
+];
+
+const fn residue(
+    iupac: &'static str,
+    iupac_suffix: &'static str,
+    glycoct: &'static str,
+    glycoct_substituents: &'static [(u8, &'static str)],
+    wurcs: &'static str,
+) -> ResidueCode {
+    ResidueCode {
+        iupac,
+        iupac_suffix,
+        glycoct,
+        glycoct_substituents,
+        wurcs,
+    }
+}
+
+static RESIDUE_SUGARS: OnceLock<Vec<MonoSaccharide>> = OnceLock::new();
+
+impl ResidueCode {
+    /// Get the IUPAC name for this residue with the given additional substituents (with optional
+    /// positions)
+    pub fn iupac_name<'a>(
+        &self,
+        furanose: bool,
+        additional: impl IntoIterator<Item = (Option<u8>, &'a SubstituentCode)>,
+    ) -> String {
+        let mut name = format!(
+            "{}{}{}",
+            self.iupac,
+            if furanose { "f" } else { "" },
+            self.iupac_suffix
+        );
+        for (position, substituent) in additional {
+            name.push_str(&position.map_or_else(|| "?".to_string(), |p| p.to_string()));
+            name.push_str(substituent.iupac);
+        }
+        name
+    }
+
+    /// Get the monosaccharide as defined by this residue (pyranose form)
+    /// # Panics
+    /// If any of the residue codes has an invalid IUPAC name.
+    fn sugar(index: usize) -> &'static MonoSaccharide {
+        &RESIDUE_SUGARS.get_or_init(|| {
+            RESIDUE_CODES
+                .iter()
+                .map(|code| {
+                    MonoSaccharide::from_short_iupac(&code.iupac_name(false, []), 0, 0)
+                        .expect("Invalid IUPAC name in the residue code list")
+                        .0
+                })
+                .collect()
+        })[index]
+    }
+
+    /// Find the residue code that describes this monosaccharide, together with the additional
+    /// substituents that are placed on top of this residue. If multiple residues match the one
+    /// that covers the most substituents is chosen.
+    pub fn find(sugar: &MonoSaccharide) -> Option<(&'static Self, Vec<&'static SubstituentCode>)> {
+        RESIDUE_CODES
+            .iter()
+            .enumerate()
+            .filter_map(|(index, code)| {
+                let base = Self::sugar(index);
+                if base.base_sugar != sugar.base_sugar {
+                    return None;
+                }
+                let mut remainder = sugar.substituents.clone();
+                for substituent in &base.substituents {
+                    let position = remainder.iter().position(|s| s == substituent)?;
+                    remainder.remove(position);
+                }
+                let additional = remainder
+                    .iter()
+                    .map(|substituent| {
+                        SUBSTITUENT_CODES
+                            .iter()
+                            .find(|code| code.substituent == *substituent)
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some((base.substituents.len(), code, additional))
+            })
+            .max_by_key(|(covered, _, _)| *covered)
+            .map(|(_, code, additional)| (code, additional))
+    }
+}
+
+impl MonoSaccharide {
+    /// Get the IUPAC condensed name for this monosaccharide. If the monosaccharide is not known
+    /// the normal name (as used in ProForma) is given. The position of additional substituents
+    /// is not stored, so these are given an unknown position, eg `Gal?S`.
+    pub fn iupac_name(&self) -> String {
+        ResidueCode::find(self).map_or_else(
+            || self.to_string(),
+            |(code, additional)| {
+                code.iupac_name(self.furanose, additional.into_iter().map(|a| (None, a)))
+            },
+        )
+    }
+}
@@ -0,0 +1,352 @@
+//! Read and write glycan structures in the WURCS 2.0 format
+
+use itertools::Itertools;
+
+use super::{
+    residue_codes::{ResidueCode, RESIDUE_CODES, SUBSTITUENT_CODES},
+    AnomericState, GlycanLinkage, GlycanStructure, MonoSaccharide,
+};
+use crate::error::{Context, CustomError};
+
+impl GlycanStructure {
+    /// Parse a glycan structure from WURCS 2.0, eg
+    /// `WURCS=2.0/2,3,2/[a2122h-1b_1-5_2*NCC/3=O][a1122h-1b_1-5]/1-1-2/a4-b1_b4-c1`.
+    /// Only tree structures with the monosaccharides and substituents that are commonly found in
+    /// glycoproteomics are supported. Alternative linkage positions (eg `a3|a6-b1`) are read as
+    /// unknown positions.
+    /// # Errors
+    /// If the text is not valid WURCS, contains repeating units or cyclic structures, or contains
+    /// unsupported monosaccharides or substituents.
+    pub fn from_wurcs(text: &str) -> Result<Self, CustomError> {
+        let error = |explanation: &str, part: &str| {
+            CustomError::error("Invalid WURCS", explanation, Context::show(part))
+        };
+        let content = text.trim().strip_prefix("WURCS=2.0/").ok_or_else(|| {
+            error(
+                "A WURCS 2.0 definition should start with 'WURCS=2.0/'",
+                text,
+            )
+        })?;
+        let (_counts, content) = content
+            .split_once('/')
+            .ok_or_else(|| error("Missing the unique residues section", text))?;
+        let (unique, content) = content
+            .strip_prefix('[')
+            .and_then(|c| c.split_once("]/"))
+            .ok_or_else(|| error("Missing the unique residues section", text))?;
+        let unique = unique
+            .split("][")
+            .map(|code| {
+                parse_residue(code).ok_or_else(|| {
+                    error(
+                        "This residue is not a supported monosaccharide with supported substituents",
+                        code,
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let (sequence, links) = content.split_once('/').unwrap_or((content, ""));
+        let residues = sequence
+            .split('-')
+            .map(|index| {
+                index
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| unique.get(index.checked_sub(1)?))
+                    .ok_or_else(|| error("Invalid residue index", index))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut children: Vec<Vec<(usize, GlycanLinkage)>> = vec![Vec::new(); residues.len()];
+        let mut has_parent = vec![false; residues.len()];
+        for link in links.split('_').filter(|l| !l.is_empty()) {
+            let (parent, parent_position, child, position) = parse_link(link)
+                .filter(|(parent, _, child, _)| *parent < residues.len() && *child < residues.len())
+                .ok_or_else(|| {
+                    error("This is not a valid or supported linkage, eg 'a4-b1'", link)
+                })?;
+            if has_parent[child] || parent == child {
+                return Err(error(
+                    "Only tree structures are supported, a residue has multiple parents",
+                    link,
+                ));
+            }
+            has_parent[child] = true;
+            children[parent].push((
+                child,
+                GlycanLinkage {
+                    anomer: residues[child].1,
+                    position,
+                    parent_position,
+                },
+            ));
+        }
+        let root = has_parent
+            .iter()
+            .position(|p| !p)
+            .ok_or_else(|| error("No root monosaccharide found", text))?;
+        if has_parent.iter().filter(|p| !**p).count() > 1 {
+            return Err(error(
+                "Not all monosaccharides are linked into a single structure",
+                text,
+            ));
+        }
+
+        let build = |index: usize| {
+            let (sugar, anomer, anomeric_position) = residues[index];
+            Self::new(sugar.clone(), Vec::new()).with_linkage(anomer.map_or_else(
+                GlycanLinkage::default,
+                |anomer| GlycanLinkage {
+                    anomer: Some(anomer),
+                    position: *anomeric_position,
+                    parent_position: None,
+                },
+            ))
+        };
+        Ok(build_tree(root, &children, &build))
+    }
+
+    /// Write this structure as WURCS 2.0, see [`Self::from_wurcs`] for an example. The
+    /// residues are numbered depth first from the root, so the result is not necessarily
+    /// the canonical WURCS as used by GlyTouCan.
+    /// # Errors
+    /// If any of the monosaccharides in this structure cannot be represented in WURCS.
+    pub fn to_wurcs(&self) -> Result<String, CustomError> {
+        let mut residues = Vec::new();
+        let mut links = Vec::new();
+        self.wurcs_inner(&mut residues, &mut links, None)?;
+        let unique = residues.iter().unique().cloned().collect_vec();
+        Ok(format!(
+            "WURCS=2.0/{},{},{}/{}/{}/{}",
+            unique.len(),
+            residues.len(),
+            links.len(),
+            unique.iter().map(|r| format!("[{r}]")).join(""),
+            residues
+                .iter()
+                .map(|r| unique.iter().position(|u| u == r).unwrap_or_default() + 1)
+                .join("-"),
+            links.join("_")
+        ))
+    }
+
+    /// # Errors
+    /// If any of the monosaccharides cannot be represented in WURCS.
+    fn wurcs_inner(
+        &self,
+        residues: &mut Vec<String>,
+        links: &mut Vec<String>,
+        parent: Option<usize>,
+    ) -> Result<(), CustomError> {
+        let (code, additional) = ResidueCode::find(&self.sugar).ok_or_else(|| {
+            CustomError::error(
+                "Unsupported monosaccharide",
+                "This monosaccharide cannot be written as WURCS",
+                Context::show(self.sugar.iupac_name()),
+            )
+        })?;
+        let mut residue = code.wurcs.replacen(
+            "x_",
+            match self.linkage.anomer {
+                Some(AnomericState::Alpha) => "a_",
+                Some(AnomericState::Beta) => "b_",
+                None => "x_",
+            },
+            1,
+        );
+        if self.sugar.furanose {
+            residue = residue.replacen("_1-5", "_1-4", 1);
+        }
+        for substituent in additional {
+            residue.push_str("_?");
+            residue.push_str(substituent.wurcs);
+        }
+        let index = residues.len();
+        residues.push(residue);
+        if let Some(parent) = parent {
+            let position = |p: Option<u8>| p.map_or_else(|| "?".to_string(), |p| p.to_string());
+            links.push(format!(
+                "{}{}-{}{}",
+                label(parent)?,
+                position(self.linkage.parent_position),
+                label(index)?,
+                position(self.linkage.position)
+            ));
+        }
+        for branch in &self.branches {
+            branch.wurcs_inner(residues, links, Some(index))?;
+        }
+        Ok(())
+    }
+}
+
+/// Build the tree starting at the given index
+fn build_tree(
+    index: usize,
+    children: &[Vec<(usize, GlycanLinkage)>],
+    build: &impl Fn(usize) -> GlycanStructure,
+) -> GlycanStructure {
+    let mut structure = build(index);
+    structure.branches = children[index]
+        .iter()
+        .map(|(child, linkage)| build_tree(*child, children, build).with_linkage(*linkage))
+        .collect();
+    structure
+}
+
+/// The label of a residue in the linkage section, `a` to `z` followed by `A` to `Z`
+/// # Errors
+/// If the index is too big to be represented.
+fn label(index: usize) -> Result<char, CustomError> {
+    u8::try_from(index)
+        .ok()
+        .and_then(|i| match i {
+            0..=25 => Some((b'a' + i) as char),
+            26..=51 => Some((b'A' + i - 26) as char),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            CustomError::error(
+                "Glycan too big",
+                "WURCS can only be written for glycans with at most 52 monosaccharides",
+                Context::none(),
+            )
+        })
+}
+
+/// Get the index for a residue label
+const fn index(label: char) -> Option<usize> {
+    match label {
+        'a'..='z' => Some(label as usize - 'a' as usize),
+        'A'..='Z' => Some(label as usize - 'A' as usize + 26),
+        _ => None,
+    }
+}
+
+/// Parse a linkage, eg `a4-b1`, into parent, parent position, child, and child position. The
+/// residue that comes later in the sequence is the child.
+fn parse_link(link: &str) -> Option<(usize, Option<u8>, usize, Option<u8>)> {
+    let (first, second) = link.split_once('-')?;
+    let side = |side: &str| {
+        let mut chars = side.chars();
+        let residue = index(chars.next()?)?;
+        let position = chars.as_str();
+        if position.contains(['|', '*', '}', '~']) {
+            // Alternative positions are read as unknown, repeats and substituents are unsupported
+            (position.contains('|')
+                && position
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '|'))
+            .then_some((residue, None))
+        } else {
+            Some((residue, position.parse().ok()))
+        }
+    };
+    let (first, second) = (side(first)?, side(second)?);
+    if first.0 <= second.0 {
+        Some((first.0, first.1, second.0, second.1))
+    } else {
+        Some((second.0, second.1, first.0, first.1))
+    }
+}
+
+/// Parse a residue code, eg `a2122h-1b_1-5_2*NCC/3=O`, into the monosaccharide, anomeric
+/// state, and anomeric position
+fn parse_residue(code: &str) -> Option<(MonoSaccharide, Option<AnomericState>, Option<u8>)> {
+    let mut parts = code.split('_');
+    let (skeleton, anomeric) = parts.next()?.split_once('-')?;
+    let (anomeric_position, anomer) = anomeric.split_at(anomeric.len().checked_sub(1)?);
+    let anomer = match anomer {
+        "a" => Some(AnomericState::Alpha),
+        "b" => Some(AnomericState::Beta),
+        "x" => None,
+        _ => return None,
+    };
+    let mut rest = parts.collect_vec();
+    let furanose = rest.first() == Some(&"1-4");
+    if furanose {
+        rest[0] = "1-5";
+    }
+    let name = RESIDUE_CODES
+        .iter()
+        .filter_map(|residue| {
+            let mut parts = residue.wurcs.split('_');
+            if parts.next()? != format!("{skeleton}-{anomeric_position}x") {
+                return None;
+            }
+            let mut remainder = rest.clone();
+            let mut covered = 0;
+            for part in parts {
+                let index = remainder.iter().position(|p| *p == part)?;
+                remainder.remove(index);
+                covered += 1;
+            }
+            let additional = remainder
+                .iter()
+                .map(|part| {
+                    let (position, map) = part.split_at(part.find('*')?);
+                    SUBSTITUENT_CODES
+                        .iter()
+                        .find(|code| code.wurcs == map)
+                        .map(|code| (position.parse().ok(), code))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((covered, residue, additional))
+        })
+        .max_by_key(|(covered, _, _)| *covered)
+        .map(|(_, residue, additional)| residue.iupac_name(furanose, additional))?;
+    MonoSaccharide::from_short_iupac(&name, 0, 0)
+        .ok()
+        .map(|(sugar, _)| (sugar, anomer, anomeric_position.parse().ok()))
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn core() {
+        let wurcs = "WURCS=2.0/3,5,4/[a2122h-1b_1-5_2*NCC/3=O][a1122h-1b_1-5][a1122h-1a_1-5]/1-1-2-3-3/a4-b1_b4-c1_c3-d1_c6-e1";
+        let structure = GlycanStructure::from_wurcs(wurcs).unwrap();
+        let iupac = "Man(a1-3)[Man(a1-6)]Man(b1-4)GlcNAc(b1-4)GlcNAc(b1-";
+        assert_eq!(structure.to_short_iupac(), iupac);
+        assert_eq!(
+            structure,
+            GlycanStructure::from_short_iupac(iupac, 0..iupac.len(), 0).unwrap()
+        );
+        assert_eq!(structure.to_wurcs().unwrap(), wurcs);
+    }
+
+    #[test]
+    fn substituents() {
+        let structure = GlycanStructure::from_wurcs(
+            "WURCS=2.0/3,3,2/[a2112h-1x_1-5_2*NCC/3=O_4*OSO/3=O/3=O][Aad21122h-2a_2-6_5*NCC/3=O][a1221m-1a_1-5]/1-2-3/a3-b2_a6-c1",
+        )
+        .unwrap();
+        assert_eq!(
+            structure.to_short_iupac(),
+            "Neu5Ac(a2-3)[Fuc(a1-6)]GalNAc?S"
+        );
+        let round_trip = GlycanStructure::from_wurcs(&structure.to_wurcs().unwrap()).unwrap();
+        assert_eq!(round_trip, structure);
+    }
+
+    #[test]
+    fn unknown_linkage() {
+        let structure = GlycanStructure::from_wurcs(
+            "WURCS=2.0/2,3,2/[a1122h-1x_1-5][a1122h-1a_1-5]/1-2-2/a3|a6-b1_a?-c1",
+        )
+        .unwrap();
+        assert_eq!(structure.to_short_iupac(), "Man(a1-?)[Man(a1-?)]Man");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(GlycanStructure::from_wurcs("WURCS=2.0/1,1,0/[a2122h-1b_1-5_2*XYZ]/1/").is_err());
+        assert!(
+            GlycanStructure::from_wurcs("WURCS=2.0/1,2,1/[a2122h-1b_1-5]/1-1/a4-b1~n").is_err()
+        );
+        assert!(GlycanStructure::from_wurcs("[a2122h-1b_1-5]").is_err());
+    }
+}
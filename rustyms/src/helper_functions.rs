@@ -449,3 +449,25 @@ pub fn explain_number_error(error: &ParseIntError) -> &'static str {
         _ => "is not a valid number",
     }
 }
+
+/// Get a path in the temporary directory with the given extension, the name is made unique with
+/// the process id and a counter so concurrent test runs do not interfere
+#[cfg(test)]
+pub fn temporary_path(extension: &str) -> std::path::PathBuf {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    std::env::temp_dir().join(format!(
+        "rustyms_test_{}_{}.{extension}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Write the given content to a temporary file with the given extension, see [`temporary_path`]
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+pub fn temporary_file(extension: &str, content: &str) -> std::path::PathBuf {
+    let path = temporary_path(extension);
+    std::fs::write(&path, content).unwrap();
+    path
+}
@@ -0,0 +1,180 @@
+use crate::{
+    error::CustomError,
+    glycan::{GlycanCompositionNotation, MonoSaccharide},
+    helper_functions::InvertResult,
+    modification::{Modification, SimpleModification},
+    ontologies::CustomDatabase,
+    peptide::{SemiAmbiguous, SloppyParsingParameters},
+    system::{usize::Charge, Mass, MassOverCharge, Time},
+    Chemical, LinearPeptide,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common_parser::{Location, OptionalLocation},
+    csv::{parse_csv, CsvLine},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+static NUMBER_ERROR: (&str, &str) = (
+    "Invalid Byonic line",
+    "This column is not a number but it is required to be a number in this Byonic format",
+);
+
+format_family!(
+    /// The format for any Byonic CSV export
+    ByonicFormat,
+    /// The data from any Byonic CSV export
+    ByonicData,
+    ByonicVersion, [&CSV_EXPORT], b',';
+    required {
+        /// The peptide (without the flanking residues) with the glycans placed on their sites
+        peptide: LinearPeptide<SemiAmbiguous>, |location: Location, custom_database: Option<&CustomDatabase>| LinearPeptide::sloppy_pro_forma(
+            location.full_line(),
+            strip_flanking(&location),
+            custom_database,
+            SloppyParsingParameters::default(),
+        );
+        glycans: Vec<Vec<(MonoSaccharide, isize)>>, |location: Location, _| location
+            .array(',')
+            .filter(|part| !part.as_str().trim().is_empty())
+            .map(|part| GlycanCompositionNotation::Byonic.parse(part.as_str()).map_err(|err| err.with_context(part.context())))
+            .collect::<Result<Vec<_>, _>>();
+        score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        delta_score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        /// The delta modification score, the difference in score with the best match with the modifications placed on other sites
+        delta_modification_score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        pep_2d: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        z: Charge, |location: Location, _| location.parse::<usize>(NUMBER_ERROR).map(Charge::new::<crate::system::e>);
+        mz: MassOverCharge, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(MassOverCharge::new::<crate::system::mz>);
+        scan: usize, |location: Location, _| location.parse_with(|location| {
+            let text = location.as_str();
+            text.rsplit_once("scan=").map_or(text, |(_, scan)| scan).trim().parse().map_err(|_| CustomError::error(NUMBER_ERROR.0, NUMBER_ERROR.1, location.context()))
+        });
+        rt: Time, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Time::new::<crate::system::time::min>);
+    }
+    optional {
+        pep_1d: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        log_probability: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        modifications: String, |location: Location, _| Ok(Some(location.get_string()));
+        theoretical_mz: MassOverCharge, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|mz| mz.map(MassOverCharge::new::<crate::system::mz>));
+        mh: Mass, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|m| m.map(Mass::new::<crate::system::dalton>));
+        theoretical_mh: Mass, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|m| m.map(Mass::new::<crate::system::dalton>));
+        protein_rank: usize, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        protein_name: String, |location: Location, _| Ok(Some(location.get_string()));
+        protein_start: usize, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        cleavage: String, |location: Location, _| Ok(Some(location.get_string()));
+        comment: String, |location: Location, _| Ok(Some(location.get_string()));
+    }
+    post_process: ByonicData::place_glycans;
+);
+
+impl From<ByonicData> for IdentifiedPeptide {
+    fn from(value: ByonicData) -> Self {
+        Self {
+            score: Some(1.0 - value.pep_2d),
+            metadata: MetaData::Byonic(value),
+        }
+    }
+}
+
+impl ByonicData {
+    /// Byonic writes glycans as a mass modification on the peptide, replace these masses with the
+    /// glycan compositions from the glycans column.
+    /// # Errors
+    /// If there is no mass modification on the peptide that matches a glycan.
+    fn place_glycans(
+        source: &CsvLine,
+        mut parsed: Self,
+        _custom_database: Option<&CustomDatabase>,
+    ) -> Result<Self, CustomError> {
+        for glycan in &parsed.glycans {
+            let mass = SimpleModification::Glycan(glycan.clone())
+                .formula()
+                .monoisotopic_mass()
+                .value;
+            let placed = parsed
+                .peptide
+                .sequence_mut()
+                .iter_mut()
+                .flat_map(|element| element.modifications.iter_mut())
+                .find(|modification| {
+                    matches!(modification, Modification::Simple(SimpleModification::Mass(m)) if (m.value - mass).abs() < 0.01)
+                })
+                .map(|modification| {
+                    *modification = Modification::Simple(SimpleModification::Glycan(glycan.clone()));
+                })
+                .is_some();
+            if !placed {
+                return Err(CustomError::error(
+                    "Invalid Byonic line",
+                    format!(
+                        "The glycan {} ({mass:.3} Da) could not be placed as there is no modification on the peptide with this mass",
+                        GlycanCompositionNotation::Byonic.write(glycan)?
+                    ),
+                    source.full_context(),
+                ));
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Get the range of the peptide without the flanking residues, eg `K.N[+2350.830]GTSK.A`
+fn strip_flanking(location: &Location) -> std::ops::Range<usize> {
+    let bytes = location.as_str().as_bytes();
+    let mut range = location.location.clone();
+    if bytes.len() > 4 && bytes[1] == b'.' {
+        range.start += 2;
+    }
+    if bytes.len() > 4 && bytes[bytes.len() - 2] == b'.' {
+        range.end -= 2;
+    }
+    range
+}
+
+/// All possible Byonic versions
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum ByonicVersion {
+    /// The CSV export of the spectrum level results
+    #[default]
+    CsvExport,
+}
+
+impl std::fmt::Display for ByonicVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::CsvExport => "CSV export",
+            }
+        )
+    }
+}
+
+/// The CSV export of the spectrum level results
+pub const CSV_EXPORT: ByonicFormat = ByonicFormat {
+    version: ByonicVersion::CsvExport,
+    peptide: "sequence",
+    glycans: "glycans",
+    score: "score",
+    delta_score: "delta",
+    delta_modification_score: "delta mod",
+    pep_2d: "pep 2d",
+    z: "z",
+    mz: "obs. m/z",
+    scan: "scan #",
+    rt: "scan time",
+    pep_1d: Some("pep 1d"),
+    log_probability: Some("|log prob|"),
+    modifications: Some("mods (variable)"),
+    theoretical_mz: Some("calc. m/z"),
+    mh: Some("obs. mh"),
+    theoretical_mh: Some("calc. mh"),
+    protein_rank: Some("protein rank"),
+    protein_name: Some("protein name"),
+    protein_start: Some("pos."),
+    cleavage: Some("cleavage"),
+    comment: Some("comment"),
+};
@@ -0,0 +1,89 @@
+#![allow(clippy::missing_panics_doc)]
+use std::io::BufReader;
+
+use crate::{
+    glycan::GlycanCompositionNotation,
+    helper_functions::temporary_file,
+    modification::SimpleModification,
+    system::{e, time::min, usize::Charge},
+    Modification,
+};
+
+use super::{
+    byonic, csv::parse_csv_raw, detect_identified_peptides_file_format,
+    open_identified_peptides_file, ByonicData, ByonicVersion, IdentifiedPeptide,
+    IdentifiedPeptideSource, KnownFileFormat,
+};
+
+#[test]
+fn byonic() {
+    let reader = BufReader::new(DATA.as_bytes());
+    let lines = parse_csv_raw(reader, b',', None).unwrap();
+    let peptides: Vec<ByonicData> = lines
+        .map(|line| ByonicData::parse_specific(&line.unwrap(), &byonic::CSV_EXPORT, None).unwrap())
+        .collect();
+    assert_eq!(peptides.len(), 2);
+
+    let glycan = GlycanCompositionNotation::Byonic
+        .parse("HexNAc(4)Hex(3)Fuc(1)")
+        .unwrap();
+    assert_eq!(peptides[0].peptide.len(), 9);
+    assert_eq!(
+        peptides[0].peptide.sequence()[4].modifications,
+        vec![Modification::Simple(SimpleModification::Glycan(glycan))]
+    );
+
+    // Multiple O-glycans are placed on the site with the matching mass
+    let peptide = &peptides[1].peptide;
+    assert_eq!(peptides[1].glycans.len(), 2);
+    assert_eq!(
+        peptide.sequence()[2].modifications,
+        vec![Modification::Simple(SimpleModification::Glycan(
+            peptides[1].glycans[0].clone()
+        ))]
+    );
+    assert_eq!(
+        peptide.sequence()[4].modifications,
+        vec![Modification::Simple(SimpleModification::Glycan(
+            peptides[1].glycans[1].clone()
+        ))]
+    );
+    assert_eq!(peptide.sequence()[5].modifications.len(), 1);
+    assert!((peptides[1].delta_modification_score - 12.4).abs() < 1e-9);
+
+    let identified: IdentifiedPeptide = peptides[1].clone().into();
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(3)));
+    assert!((identified.retention_time().unwrap().get::<min>() - 61.204).abs() < 1e-9);
+    assert_eq!(identified.scan_indices(), Some(vec![13102]));
+    assert!((identified.score.unwrap() - 0.9979).abs() < 1e-9);
+}
+
+#[test]
+fn byonic_detect() {
+    let path = temporary_file("csv", DATA);
+    let format = detect_identified_peptides_file_format(&path);
+    let peptides =
+        open_identified_peptides_file(&path, None).map(Iterator::collect::<Result<Vec<_>, _>>);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        format.unwrap(),
+        KnownFileFormat::Byonic(ByonicVersion::CsvExport)
+    );
+    assert_eq!(peptides.unwrap().unwrap().len(), 2);
+}
+
+#[test]
+fn byonic_invalid() {
+    let data = DATA.replace("HexNAc(4)Hex(3)Fuc(1)", "HexNAc(4)Hex(4)Fuc(1)");
+    let reader = BufReader::new(data.as_bytes());
+    let mut lines = parse_csv_raw(reader, b',', None).unwrap();
+    assert!(
+        ByonicData::parse_specific(&lines.next().unwrap().unwrap(), &byonic::CSV_EXPORT, None)
+            .is_err()
+    );
+}
+
+const DATA: &str = r#"Protein Rank,Pos.,Sequence,Mods (variable),Glycans,PEP 2D,PEP 1D,|Log Prob|,Score,Delta,Delta Mod,Obs. m/z,Calc. m/z,z,Obs. MH,Calc. MH,Cleavage,Scan #,Scan Time,Protein Name,Comment
+1,176,K.EEQYN[+1444.534]STYR.V,N5(NGlycan / 1444.5339),HexNAc(4)Hex(3)Fuc(1),1.2e-5,3.4e-4,4.92,512.3,210.4,210.4,878.6827,878.6838,3,2634.0336,2634.0296,Specific,12011,55.902,">sp|P01857|IGHG1_HUMAN Immunoglobulin heavy constant gamma 1",
+2,24,"R.TPS[+365.132]PT[+656.228]M[+15.995]PR.A","S3(OGlycan / 365.1322); T5(OGlycan / 656.2276); M6(Oxidation / 15.9949)","HexNAc(1)Hex(1),HexNAc(1)Hex(1)NeuAc(1)",0.0021,0.013,2.68,301.7,88.1,12.4,644.2901,644.2912,3,1930.8557,1930.8590,Specific,controllerType=0 controllerNumber=1 scan=13102,61.204,">sp|Q9Y6R7|FCGBP_HUMAN IgGFc-binding protein",
+"#;
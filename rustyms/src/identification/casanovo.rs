@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Context, CustomError},
+    ontologies::CustomDatabase,
+    peptide::SemiAmbiguous,
+    system::{e, mz, time::s, usize::Charge, MassOverCharge, Time},
+    LinearPeptide, PeptideModificationSearch,
+};
+
+use super::{
+    helper_functions::{mass_modification_search, read_file, read_to_string},
+    mztab::{optional, parse_spectra_ref, psm_section},
+    IdentifiedPeptide, MetaData,
+};
+
+/// A single de novo prediction from a Casanovo mzTab file
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct CasanovoData {
+    /// The predicted peptide
+    pub peptide: LinearPeptide<SemiAmbiguous>,
+    /// The PSM id
+    pub psm_id: String,
+    /// The peptide score -1.0..=1.0, this is penalised if the precursor mass does not match
+    pub score: Option<f64>,
+    /// The score for each amino acid 0.0..=1.0
+    pub local_confidence: Option<Vec<f64>>,
+    /// The retention time, if given
+    pub rt: Option<Time>,
+    /// The precursor charge, if given
+    pub z: Option<Charge>,
+    /// The experimental precursor m/z, if given
+    pub experimental_mz: Option<MassOverCharge>,
+    /// The calculated precursor m/z, if given
+    pub calculated_mz: Option<MassOverCharge>,
+    /// The spectra for this prediction, with the location of the raw file (if known) and the
+    /// spectrum id
+    pub spectra_ref: Vec<(Option<PathBuf>, String)>,
+}
+
+impl CasanovoData {
+    /// Parse a Casanovo mzTab file, the file is decompressed if the extension is `.gz`.
+    /// # Errors
+    /// If the file could not be opened or is not a valid Casanovo mzTab file.
+    pub fn parse_file(
+        path: impl AsRef<Path>,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        Self::parse_str(&read_file(path.as_ref())?, custom_database)
+    }
+
+    /// Parse a Casanovo mzTab file from a raw reader.
+    /// # Errors
+    /// If the reader could not be read or does not contain a valid Casanovo mzTab file.
+    pub fn parse_reader(
+        reader: impl Read,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        Self::parse_str(&read_to_string(reader)?, custom_database)
+    }
+
+    /// Parse the text of a Casanovo mzTab file. Both the mass delta sequences of Casanovo v3
+    /// (`+42.011M+15.995PEPC+57.021K`) and the ProForma sequences of later versions are
+    /// supported.
+    /// # Errors
+    /// If the text is not a valid mzTab file, or if it was not generated by Casanovo.
+    pub fn parse_str(
+        text: &str,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        let section = psm_section(text)?;
+        if !section
+            .software
+            .iter()
+            .any(|software| software.eq_ignore_ascii_case("casanovo"))
+        {
+            return Err(CustomError::error(
+                "Invalid Casanovo file",
+                "This mzTab file does not list Casanovo as software",
+                Context::none(),
+            ));
+        }
+        // The masses in Casanovo v3 are rounded to three decimals
+        let mut search = mass_modification_search(custom_database, 0.01);
+        section
+            .rows
+            .iter()
+            .map(|(line_index, line, row)| {
+                Self::parse_row(row, &section.ms_runs, custom_database, &mut search).map_err(
+                    |err| {
+                        CustomError::error(
+                            "Invalid Casanovo PSM line",
+                            err,
+                            Context::full_line(*line_index, *line),
+                        )
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Parse a single PSM row
+    /// # Errors
+    /// If any column contains an invalid value.
+    fn parse_row(
+        row: &HashMap<&str, &str>,
+        ms_runs: &HashMap<String, PathBuf>,
+        custom_database: Option<&CustomDatabase>,
+        search: &mut PeptideModificationSearch,
+    ) -> Result<Self, String> {
+        let column = |name: &str| row.get(name).copied().and_then(optional);
+        let number = |name: &str| {
+            column(name)
+                .map(|v| {
+                    v.parse::<f64>()
+                        .map_err(|_| format!("The column '{name}' is not a valid number"))
+                })
+                .transpose()
+        };
+        let local_confidence = row
+            .iter()
+            .find(|(name, _)| name.ends_with("_aa_scores"))
+            .and_then(|(_, value)| optional(value))
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(|v| v.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| "The amino acid scores are not valid numbers".to_string())
+            })
+            .transpose()?;
+
+        Ok(Self {
+            peptide: parse_sequence(
+                column("sequence").ok_or("The sequence column is required")?,
+                custom_database,
+                search,
+            )?,
+            psm_id: column("PSM_ID").unwrap_or_default().to_string(),
+            score: number("search_engine_score[1]")?,
+            local_confidence,
+            rt: number("retention_time")?.map(Time::new::<s>),
+            z: number("charge")?.map(|c| Charge::new::<e>(c.abs() as usize)),
+            experimental_mz: number("exp_mass_to_charge")?.map(MassOverCharge::new::<mz>),
+            calculated_mz: number("calc_mass_to_charge")?.map(MassOverCharge::new::<mz>),
+            spectra_ref: column("spectra_ref")
+                .map(|v| parse_spectra_ref(v, ms_runs))
+                .unwrap_or_default(),
+        })
+    }
+}
+
+impl From<CasanovoData> for IdentifiedPeptide {
+    fn from(value: CasanovoData) -> Self {
+        Self {
+            score: value.score.map(|score| score.clamp(-1.0, 1.0)),
+            metadata: MetaData::Casanovo(value),
+        }
+    }
+}
+
+/// Parse a Casanovo sequence.
+///
+/// This is either a ProForma sequence or a sequence with mass deltas following the amino acids
+/// and leading mass deltas for the N terminus. All mass deltas are resolved to Unimod
+/// modifications if possible, using the given search.
+/// # Errors
+/// If the sequence is not valid.
+fn parse_sequence(
+    sequence: &str,
+    custom_database: Option<&CustomDatabase>,
+    search: &mut PeptideModificationSearch,
+) -> Result<LinearPeptide<SemiAmbiguous>, String> {
+    let pro_forma = if sequence.contains('[') {
+        sequence.to_string()
+    } else {
+        let mut n_term = None;
+        let mut pro_forma = String::new();
+        let mut chars = sequence.char_indices().peekable();
+        while let Some((index, c)) = chars.next() {
+            if c == '+' || c == '-' {
+                let mut end = index + 1;
+                while let Some((i, _)) = chars.next_if(|(_, c)| c.is_ascii_digit() || *c == '.') {
+                    end = i + 1;
+                }
+                let delta: f64 = sequence[index..end].parse().map_err(|_| {
+                    format!("The mass delta '{}' is invalid", &sequence[index..end])
+                })?;
+                if pro_forma.is_empty() {
+                    *n_term.get_or_insert(0.0) += delta;
+                } else {
+                    write!(&mut pro_forma, "[{delta:+}]").unwrap();
+                }
+            } else {
+                pro_forma.push(c);
+            }
+        }
+        if let Some(n_term) = n_term {
+            pro_forma.insert_str(0, &format!("[{n_term:+}]-"));
+        }
+        pro_forma
+    };
+    let peptide = LinearPeptide::pro_forma(&pro_forma, custom_database)
+        .map_err(|err| err.to_string())?
+        .into_semi_ambiguous()
+        .ok_or("The sequence is not a simple linear peptide")?;
+    Ok(search.search(peptide))
+}
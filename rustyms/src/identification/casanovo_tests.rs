@@ -0,0 +1,56 @@
+#![allow(clippy::missing_panics_doc)]
+use crate::system::{e, usize::Charge};
+
+use super::{CasanovoData, IdentifiedPeptide};
+
+#[test]
+fn casanovo() {
+    let peptides = CasanovoData::parse_str(DATA, None).unwrap();
+    assert_eq!(peptides.len(), 4);
+    assert_eq!(peptides[0].peptide.to_string(), "LESLIEK");
+    assert_eq!(
+        peptides[1].peptide.to_string(),
+        "[U:Acetyl]-M[U:Oxidation]PEPC[U:Carbamidomethyl]K"
+    );
+    // Deamidation has the same mass as Asn->Asp, so this cannot be resolved
+    assert_eq!(peptides[2].peptide.to_string(), "QN[+0.984]K");
+    assert_eq!(
+        peptides[3].peptide.to_string(),
+        "[U:Acetyl]-M[U:Oxidation]DEK"
+    );
+
+    let identified: IdentifiedPeptide = peptides[1].clone().into();
+    assert_eq!(identified.score, Some(-0.25));
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(3)));
+    assert_eq!(identified.local_confidence().map(<[f64]>::len), Some(6));
+    assert_eq!(
+        identified.spectrum_native_ids(),
+        Some(vec!["index=1".to_string()])
+    );
+    assert_eq!(
+        identified.raw_file().unwrap().to_string_lossy(),
+        "/data/sample.mgf"
+    );
+}
+
+#[test]
+fn casanovo_invalid() {
+    // Not generated by Casanovo
+    assert!(CasanovoData::parse_str(&DATA.replace("Casanovo", "Other"), None).is_err());
+    assert!(CasanovoData::parse_str(&DATA.replace("LESLIEK", "LES+LIEK"), None).is_err());
+    assert!(CasanovoData::parse_str(&DATA.replace("0.8,0.95", "0.8,high"), None).is_err());
+}
+
+const DATA: &str = "MTD\tmzTab-version\t1.0.0
+MTD\tmzTab-mode\tSummary
+MTD\tmzTab-type\tIdentification
+MTD\tdescription\tCasanovo identification file sample.mztab
+MTD\tsoftware[1]\t[MS, MS:1003281, Casanovo, 3.2.0]
+MTD\tpsm_search_engine_score[1]\t[MS, MS:1001143, search engine specific score for PSMs, ]
+MTD\tms_run[1]-location\tfile:///data/sample.mgf
+PSH\tsequence\tPSM_ID\taccession\tunique\tdatabase\tdatabase_version\tsearch_engine\tsearch_engine_score[1]\tmodifications\tretention_time\tcharge\texp_mass_to_charge\tcalc_mass_to_charge\tspectra_ref\tpre\tpost\tstart\tend\topt_ms_run[1]_aa_scores
+PSM\tLESLIEK\t1\tnull\tnull\tnull\tnull\t[MS, MS:1003281, Casanovo, 3.2.0]\t0.91\tnull\tnull\t2.0\t416.2425\t416.2418\tms_run[1]:index=0\tnull\tnull\tnull\tnull\t0.9,0.8,0.95,0.9,0.91,0.92,0.99
+PSM\t+42.011M+15.995PEPC+57.021K\t2\tnull\tnull\tnull\tnull\t[MS, MS:1003281, Casanovo, 3.2.0]\t-0.25\tnull\tnull\t3.0\t270.1\t265.7\tms_run[1]:index=1\tnull\tnull\tnull\tnull\t0.7,0.75,0.8,0.7,0.72,0.76
+PSM\tQN+0.984K\t3\tnull\tnull\tnull\tnull\t[MS, MS:1003281, Casanovo, 3.2.0]\t0.8\tnull\tnull\t2.0\t196.6\t196.6\tms_run[1]:index=2\tnull\tnull\tnull\tnull\t0.8,0.85,0.9
+PSM\t[Acetyl]-M[Oxidation]DEK\t4\tnull\tnull\tnull\tnull\t[MS, MS:1003281, Casanovo, 4.0.0]\t0.85\tnull\tnull\t2.0\t290.1\t290.1\tms_run[1]:index=3\tnull\tnull\tnull\tnull\t0.8,0.85,0.9,0.92
+";
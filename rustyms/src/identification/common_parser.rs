@@ -10,14 +10,27 @@ use std::{ops::Range, str::FromStr};
 // * XXFormat
 // * XXData
 // * XXParser?
+// Optionally a post processing function can be given that is called with the source line and the
+// parsed data, this can be used to combine the information from multiple columns.
+// Optionally a mass modification tolerance (in Dalton) can be given for formats that only report
+// rounded mass differences, the mass modifications in the `peptide` column are then resolved with
+// a search that is built once per file.
 macro_rules! format_family {
+    (@post_process $parsed:ident, $source:ident, $custom_database:ident) => {
+        Ok($parsed)
+    };
+    (@post_process $parsed:ident, $source:ident, $custom_database:ident, $post_process:path) => {
+        $post_process($source, $parsed, $custom_database)
+    };
     (#[doc = $format_doc:expr]
      $format:ident,
      #[doc = $data_doc:expr]
      $data:ident,
      $version:ident, $versions:expr, $separator:expr;
      required { $($(#[doc = $rdoc:expr])? $rname:ident: $rtyp:ty, $rf:expr;)* }
-     optional { $($(#[doc = $odoc:expr])? $oname:ident: $otyp:ty, $of:expr;)*}) => {
+     optional { $($(#[doc = $odoc:expr])? $oname:ident: $otyp:ty, $of:expr;)*}
+     $(post_process: $post_process:path;)?
+     $(mass_modification_tolerance: $tolerance:expr;)?) => {
         use super::common_parser::HasLocation;
         #[non_exhaustive]
         #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize)]
@@ -28,6 +41,52 @@ macro_rules! format_family {
             version: $version
         }
 
+        impl $format {
+            /// The column separator used in files of this format
+            pub(super) const SEPARATOR: u8 = $separator;
+
+            /// All known versions of this format, in the order they are tried when parsing.
+            pub const fn versions() -> &'static [&'static Self] {
+                &$versions
+            }
+
+            /// Get the format for the given version.
+            pub fn from_version(version: &$version) -> Option<&'static Self> {
+                Self::versions().iter().copied().find(|format| format.version == *version)
+            }
+
+            /// Get the version of this format.
+            pub const fn version(&self) -> &$version {
+                &self.version
+            }
+
+            /// Get the (lowercase) names of all columns that are required to be present for this
+            /// format.
+            pub fn required_columns(&self) -> Vec<&'static str> {
+                vec![$(self.$rname,)*]
+            }
+        }
+
+        impl $data {
+            /// Parse a file with the given format instead of automatically determining the format
+            /// from the first line, the file is decompressed if the extension is `.gz`.
+            /// # Errors
+            /// If the file could not be opened.
+            pub fn parse_file_with_format<'a>(
+                path: impl AsRef<std::path::Path>,
+                format: &$format,
+                custom_database: Option<&'a crate::ontologies::CustomDatabase>,
+            ) -> Result<BoxedIdentifiedPeptideIter<'a, Self>, CustomError> {
+                parse_csv(path, $separator, None).map(|lines| {
+                    Self::parse_many_specific::<Box<dyn Iterator<Item = Result<CsvLine, CustomError>>>>(
+                        Box::new(lines),
+                        format,
+                        custom_database,
+                    )
+                })
+            }
+        }
+
         #[non_exhaustive]
         #[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
         #[doc = $data_doc]
@@ -68,12 +127,24 @@ macro_rules! format_family {
             }
             #[allow(clippy::redundant_closure_call)] // Macro magic
             fn parse_specific(source: &Self::Source, format: &$format, custom_database: Option<&crate::ontologies::CustomDatabase>) -> Result<Self, CustomError> {
-                Ok(Self {
+                let parsed = Self {
                     $($rname: $rf(source.column(format.$rname)?, custom_database)?,)*
                     $($oname: format.$oname.and_then(|column| source.column(column).ok().map(|c| $of(c, custom_database))).invert()?,)*
                     version: format.version.clone()
-                })
+                };
+                format_family!(@post_process parsed, source, custom_database $(, $post_process)?)
+            }
+            $(
+            fn mass_modification_tolerance() -> Option<f64> {
+                Some($tolerance)
+            }
+            fn resolve_mass_modifications(self, search: &mut crate::PeptideModificationSearch) -> Self {
+                Self {
+                    peptide: self.peptide.map(|peptide| search.search(peptide)),
+                    ..self
+                }
             }
+            )?
         }
     };
 }
@@ -248,6 +319,23 @@ impl<'a> Location<'a> {
     pub fn apply(self, f: impl FnOnce(Self) -> Self) -> Self {
         f(self)
     }
+
+    /// Remove the enclosing characters (eg `[` and `]` for a Python list) if both are present
+    pub fn strip_enclosure(self, open: char, close: char) -> Self {
+        let text = self.as_str();
+        if text.len() >= open.len_utf8() + close.len_utf8()
+            && text.starts_with(open)
+            && text.ends_with(close)
+        {
+            Self {
+                line: self.line,
+                location: self.location.start + open.len_utf8()
+                    ..self.location.end - close.len_utf8(),
+            }
+        } else {
+            self
+        }
+    }
 }
 
 pub trait OptionalLocation<'a> {
@@ -0,0 +1,80 @@
+use roxmltree::Node;
+
+use crate::error::{Context, CustomError};
+
+/// Extension trait to match on the local name of a tag, ignoring the namespace
+pub trait LocalName {
+    fn has_tag_name_local(&self, name: &str) -> bool;
+}
+
+impl LocalName for Node<'_, '_> {
+    fn has_tag_name_local(&self, name: &str) -> bool {
+        self.is_element() && self.tag_name().name() == name
+    }
+}
+
+/// Get all descendant elements with the given local name
+pub fn descendants<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.descendants()
+        .filter(move |n| n.has_tag_name_local(name))
+}
+
+/// Get all direct child elements with the given local name
+pub fn children<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |n| n.has_tag_name_local(name))
+}
+
+/// Get a required attribute
+/// # Errors
+/// If the attribute is missing.
+pub fn required_attribute<'a>(node: Node<'a, '_>, attribute: &str) -> Result<&'a str, CustomError> {
+    node.attribute(attribute)
+        .ok_or_else(|| missing_attribute(node, attribute))
+}
+
+/// Parse a required numeric attribute
+/// # Errors
+/// If the attribute is missing or not a number.
+pub fn parse_number<T: std::str::FromStr>(node: Node, attribute: &str) -> Result<T, CustomError> {
+    optional_number(node, attribute)?.ok_or_else(|| missing_attribute(node, attribute))
+}
+
+/// Parse an optional numeric attribute
+/// # Errors
+/// If the attribute is present but not a number.
+pub fn optional_number<T: std::str::FromStr>(
+    node: Node,
+    attribute: &str,
+) -> Result<Option<T>, CustomError> {
+    node.attribute(attribute)
+        .map(|value| {
+            value.trim().parse::<T>().map_err(|_| {
+                CustomError::error(
+                    "Invalid XML file",
+                    format!(
+                        "The attribute '{attribute}' on '{}' is not a valid number",
+                        node.tag_name().name()
+                    ),
+                    Context::show(value),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn missing_attribute(node: Node, attribute: &str) -> CustomError {
+    CustomError::error(
+        "Invalid XML file",
+        format!(
+            "The attribute '{attribute}' is required on '{}'",
+            node.tag_name().name()
+        ),
+        Context::show(node.attribute("id").unwrap_or_default()),
+    )
+}
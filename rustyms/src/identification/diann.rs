@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::CustomError,
+    helper_functions::InvertResult,
+    ontologies::CustomDatabase,
+    peptide::{SemiAmbiguous, SloppyParsingParameters},
+    system::{usize::Charge, Time},
+    LinearPeptide,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common_parser::{Location, OptionalLocation},
+    csv::{parse_csv, CsvLine},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+static NUMBER_ERROR: (&str, &str) = (
+    "Invalid DIA-NN line",
+    "This column is not a number but it is required to be a number in this DIA-NN format",
+);
+
+format_family!(
+    /// The format for any DIA-NN report file
+    DiaNNFormat,
+    /// The data from any DIA-NN report file
+    DiaNNData,
+    DiaNNVersion, [&V1_8], b'\t';
+    required {
+        raw_file: PathBuf, |location: Location, _| Ok(Path::new(&location.get_string()).to_owned());
+        run: String, |location: Location, _| Ok(location.get_string());
+        protein_group: String, |location: Location, _| Ok(location.get_string());
+        protein_ids: Vec<String>, |location: Location, _| Ok(split_list(location.as_str()));
+        protein_names: Vec<String>, |location: Location, _| Ok(split_list(location.as_str()));
+        genes: Vec<String>, |location: Location, _| Ok(split_list(location.as_str()));
+        pg_quantity: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        pg_normalised: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        peptide: LinearPeptide<SemiAmbiguous>, |location: Location, custom_database: Option<&CustomDatabase>| LinearPeptide::sloppy_pro_forma(
+            location.full_line(),
+            location.location.clone(),
+            custom_database,
+            SloppyParsingParameters::default(),
+        );
+        precursor_id: String, |location: Location, _| Ok(location.get_string());
+        z: Charge, |location: Location, _| location.parse::<usize>(NUMBER_ERROR).map(Charge::new::<crate::system::e>);
+        q_value: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        global_q_value: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        protein_q_value: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        pg_q_value: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        proteotypic: bool, |location: Location, _| location.parse::<u8>(NUMBER_ERROR).map(|n| n != 0);
+        precursor_quantity: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        precursor_normalised: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        rt: Time, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Time::new::<crate::system::time::min>);
+        rt_start: Time, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Time::new::<crate::system::time::min>);
+        rt_stop: Time, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Time::new::<crate::system::time::min>);
+        irt: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        predicted_rt: Time, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Time::new::<crate::system::time::min>);
+        predicted_irt: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+    }
+    optional {
+        pg_maxlfq: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        global_pg_q_value: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        lib_q_value: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        cscore: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        ion_mobility: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        scan: usize, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+    }
+);
+
+impl From<DiaNNData> for IdentifiedPeptide {
+    fn from(value: DiaNNData) -> Self {
+        Self {
+            score: Some(1.0 - value.q_value),
+            metadata: MetaData::DiaNN(value),
+        }
+    }
+}
+
+/// Split a semicolon separated list, ignoring empty elements
+fn split_list(text: &str) -> Vec<String> {
+    text.split(';')
+        .filter(|s| !s.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// All possible DIA-NN versions
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum DiaNNVersion {
+    /// The main report (`report.tsv`) of version 1.8 and later
+    #[default]
+    V1_8,
+}
+
+impl std::fmt::Display for DiaNNVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::V1_8 => "v1.8",
+            }
+        )
+    }
+}
+
+/// The main report (`report.tsv`) of version 1.8 and later
+pub const V1_8: DiaNNFormat = DiaNNFormat {
+    version: DiaNNVersion::V1_8,
+    raw_file: "file.name",
+    run: "run",
+    protein_group: "protein.group",
+    protein_ids: "protein.ids",
+    protein_names: "protein.names",
+    genes: "genes",
+    pg_quantity: "pg.quantity",
+    pg_normalised: "pg.normalised",
+    pg_maxlfq: Some("pg.maxlfq"),
+    peptide: "modified.sequence",
+    precursor_id: "precursor.id",
+    z: "precursor.charge",
+    q_value: "q.value",
+    global_q_value: "global.q.value",
+    protein_q_value: "protein.q.value",
+    pg_q_value: "pg.q.value",
+    global_pg_q_value: Some("global.pg.q.value"),
+    proteotypic: "proteotypic",
+    precursor_quantity: "precursor.quantity",
+    precursor_normalised: "precursor.normalised",
+    rt: "rt",
+    rt_start: "rt.start",
+    rt_stop: "rt.stop",
+    irt: "irt",
+    predicted_rt: "predicted.rt",
+    predicted_irt: "predicted.irt",
+    lib_q_value: Some("lib.q.value"),
+    cscore: Some("cscore"),
+    ion_mobility: Some("im"),
+    scan: Some("ms2.scan"),
+};
@@ -0,0 +1,43 @@
+#![allow(clippy::missing_panics_doc)]
+use std::io::BufReader;
+
+use crate::system::{e, time::min, usize::Charge};
+
+use super::{csv::parse_csv_raw, diann, DiaNNData, IdentifiedPeptide, IdentifiedPeptideSource};
+
+#[test]
+fn diann() {
+    let reader = BufReader::new(DATA.as_bytes());
+    let lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    let peptides: Vec<DiaNNData> = lines
+        .map(|line| DiaNNData::parse_specific(&line.unwrap(), &diann::V1_8, None).unwrap())
+        .collect();
+    assert_eq!(peptides.len(), 3);
+
+    assert_eq!(peptides[0].peptide.to_string(), "AEVAHRFK");
+    assert_eq!(
+        peptides[1].peptide.to_string(),
+        "[U:Acetyl]-AC[U:Carbamidomethyl]LLPKLDELR"
+    );
+    assert_eq!(peptides[2].protein_ids, vec!["P01857", "P01859"]);
+    assert_eq!(peptides[2].pg_maxlfq, None);
+    assert!(!peptides[2].proteotypic);
+
+    let identified: IdentifiedPeptide = peptides[1].clone().into();
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(2)));
+    assert!((identified.retention_time().unwrap().get::<min>() - 51.2).abs() < 1e-9);
+    assert_eq!(identified.q_value(), Some(0.0021));
+    assert_eq!(identified.precursor_quantity(), Some(5.6e5));
+    assert_eq!(identified.scan_indices(), Some(vec![20456]));
+    assert_eq!(
+        identified.raw_file().unwrap().to_string_lossy(),
+        "D:\\data\\run01.mzML"
+    );
+    let identified: IdentifiedPeptide = peptides[2].clone().into();
+    assert_eq!(identified.scan_indices(), None);
+}
+
+const DATA: &str = r"File.Name	Run	Protein.Group	Protein.Ids	Protein.Names	Genes	PG.Quantity	PG.Normalised	PG.MaxLFQ	Genes.Quantity	Genes.Normalised	Genes.MaxLFQ	Genes.MaxLFQ.Unique	Modified.Sequence	Stripped.Sequence	Precursor.Id	Precursor.Charge	Q.Value	Global.Q.Value	Protein.Q.Value	PG.Q.Value	Global.PG.Q.Value	GG.Q.Value	Translated.Q.Value	Proteotypic	Precursor.Quantity	Precursor.Normalised	Precursor.Translated	Translated.Quality	Ms1.Translated	Quantity.Quality	RT	RT.Start	RT.Stop	iRT	Predicted.RT	Predicted.iRT	Lib.Q.Value	Lib.PG.Q.Value	Ms1.Profile.Corr	Ms1.Area	Evidence	Spectrum.Similarity	Averagine	Mass.Evidence	CScore	Decoy.Evidence	Decoy.CScore	Fragment.Quant.Raw	Fragment.Quant.Corrected	Fragment.Correlations	MS2.Scan	IM	iIM	Predicted.IM	Predicted.iIM
+D:\data\run01.mzML	run01	P02768	P02768	ALBU_HUMAN	ALB	1.2e+08	1.1e+08	1.15e+08	1.2e+08	1.1e+08	1.15e+08	1.15e+08	AEVAHRFK	AEVAHRFK	AEVAHRFK2	2	0.000123	0.0001	0.0002	0.0003	0.0003	0.0003	0	1	3.4e+06	3.3e+06	3.4e+06	0	0	0.9	24.51	24.40	24.63	12.3	24.55	12.5	0.0001	0.0002	0.98	3.1e+06	4.1	0.91	0.4	1.2	0.99	0.5	0.2	1;2;3;	1;2;3;	0.9;0.8;0.7;	10234	0	0	0	0
+D:\data\run01.mzML	run01	P02768	P02768	ALBU_HUMAN	ALB	1.2e+08	1.1e+08	1.15e+08	1.2e+08	1.1e+08	1.15e+08	1.15e+08	(UniMod:1)AC(UniMod:4)LLPKLDELR	ACLLPKLDELR	ACLLPKLDELR2	2	0.0021	0.001	0.0002	0.0003	0.0003	0.0003	0	1	5.6e+05	5.5e+05	5.6e+05	0	0	0.9	51.2	51.1	51.3	80.1	51.25	80.0	0.002	0.0002	0.85	5.1e+05	2.3	0.81	0.4	1.2	0.97	0.5	0.2	1;2;3;	1;2;3;	0.9;0.8;0.7;	20456	0	0	0	0
+D:\data\run02.mzML	run02	P01857;P01859	P01857;P01859	IGHG1_HUMAN;IGHG2_HUMAN	IGHG1;IGHG2	5.2e+07	5.0e+07		5.2e+07	5.0e+07			GPSVFPLAPSSK	GPSVFPLAPSSK	GPSVFPLAPSSK2	2	0.0005	0.0004	0.0002	0.0003	0.0003	0.0003	0	0	8.9e+05	8.7e+05	8.9e+05	0	0	0.9	40.7	40.6	40.8	55.2	40.75	55.0	0.0005	0.0002	0.92	8.1e+05	3.2	0.88	0.4	1.2	0.98	0.5	0.2	1;2;3;	1;2;3;	0.9;0.8;0.7;		0	0	0	0";
@@ -0,0 +1,285 @@
+use std::{
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    error::{Context, CustomError},
+    helper_functions::check_extension,
+};
+
+use super::{IdentifiedPeptide, MetaData};
+
+/// The columns that are written for every identified peptide, regardless of the source format
+pub const NORMALISED_COLUMNS: &[&str] = &[
+    "format",
+    "peptide",
+    "charge",
+    "retention_time",
+    "score",
+    "q_value",
+    "precursor_quantity",
+    "local_confidence",
+    "mode",
+    "raw_file",
+    "scan_indices",
+    "native_ids",
+];
+
+impl IdentifiedPeptide {
+    /// The name of the format this peptide was read from
+    pub const fn format_name(&self) -> &'static str {
+        match &self.metadata {
+            MetaData::None => "none",
+            MetaData::Peaks(_) => "peaks",
+            MetaData::Novor(_) => "novor",
+            MetaData::Opair(_) => "opair",
+            MetaData::Fasta(_) => "fasta",
+            MetaData::MaxQuant(_) => "maxquant",
+            MetaData::Sage(_) => "sage",
+            MetaData::MSFragger(_) => "msfragger",
+            MetaData::MZIdentML(_) => "mzidentml",
+            MetaData::PepXML(_) => "pepxml",
+            MetaData::MZTab(_) => "mztab",
+            MetaData::DiaNN(_) => "diann",
+            MetaData::Spectronaut(_) => "spectronaut",
+            MetaData::Casanovo(_) => "casanovo",
+            MetaData::InstaNovo(_) => "instanovo",
+            MetaData::PepNet(_) => "pepnet",
+            MetaData::PGlyco(_) => "pglyco",
+            MetaData::Byonic(_) => "byonic",
+            MetaData::Search(_) => "search",
+        }
+    }
+
+    /// Get the columns for a tabular export of this peptide.
+    ///
+    /// This always contains the [`NORMALISED_COLUMNS`], in that order. If `format_specific` is
+    /// set all fields of the format specific metadata are added, named `{format}.{field}`. Lists
+    /// are joined with semicolons, the retention time is given in seconds, and any complex format
+    /// specific field is written as JSON.
+    pub fn export_columns(&self, format_specific: bool) -> Vec<(String, String)> {
+        let optional = |value: Option<String>| value.unwrap_or_default();
+        let mut columns = vec![
+            ("format".to_string(), self.format_name().to_string()),
+            (
+                "peptide".to_string(),
+                optional(self.peptide().map(ToString::to_string)),
+            ),
+            (
+                "charge".to_string(),
+                optional(self.charge().map(|c| c.value.to_string())),
+            ),
+            (
+                "retention_time".to_string(),
+                optional(self.retention_time().map(|t| t.value.to_string())),
+            ),
+            (
+                "score".to_string(),
+                optional(self.score.map(|s| s.to_string())),
+            ),
+            (
+                "q_value".to_string(),
+                optional(self.q_value().map(|q| q.to_string())),
+            ),
+            (
+                "precursor_quantity".to_string(),
+                optional(self.precursor_quantity().map(|q| q.to_string())),
+            ),
+            (
+                "local_confidence".to_string(),
+                optional(self.local_confidence().map(|c| c.iter().join(";"))),
+            ),
+            (
+                "mode".to_string(),
+                optional(self.mode().map(ToString::to_string)),
+            ),
+            (
+                "raw_file".to_string(),
+                optional(self.raw_file().map(|p| p.to_string_lossy().to_string())),
+            ),
+            (
+                "scan_indices".to_string(),
+                optional(self.scan_indices().map(|s| s.iter().join(";"))),
+            ),
+            (
+                "native_ids".to_string(),
+                optional(self.spectrum_native_ids().map(|s| s.iter().join(";"))),
+            ),
+        ];
+        if format_specific {
+            let fields = match &self.metadata {
+                MetaData::None => Vec::new(),
+                MetaData::Peaks(data) => serialised_fields(data),
+                MetaData::Novor(data) => serialised_fields(data),
+                MetaData::Opair(data) => serialised_fields(data),
+                MetaData::Fasta(data) => serialised_fields(data),
+                MetaData::MaxQuant(data) => serialised_fields(data),
+                MetaData::Sage(data) => serialised_fields(data),
+                MetaData::MSFragger(data) => serialised_fields(data),
+                MetaData::MZIdentML(data) => serialised_fields(data),
+                MetaData::PepXML(data) => serialised_fields(data),
+                MetaData::MZTab(data) => serialised_fields(data),
+                MetaData::DiaNN(data) => serialised_fields(data),
+                MetaData::Spectronaut(data) => serialised_fields(data),
+                MetaData::Casanovo(data) => serialised_fields(data),
+                MetaData::InstaNovo(data) => serialised_fields(data),
+                MetaData::PepNet(data) => serialised_fields(data),
+                MetaData::PGlyco(data) => serialised_fields(data),
+                MetaData::Byonic(data) => serialised_fields(data),
+                MetaData::Search(data) => serialised_fields(data),
+            };
+            let format = self.format_name();
+            columns.extend(
+                fields
+                    .into_iter()
+                    .map(|(field, value)| (format!("{format}.{field}"), value)),
+            );
+        }
+        columns
+    }
+}
+
+/// Write identified peptides to a CSV file, or a TSV file if the extension is `.tsv`.
+///
+/// The file is compressed if the extension is `.gz`. See [`write_identified_peptides_raw`] for
+/// the details of the columns.
+/// # Errors
+/// If the file could not be created or written to.
+pub fn write_identified_peptides<'a>(
+    path: impl AsRef<Path>,
+    peptides: impl IntoIterator<Item = &'a IdentifiedPeptide>,
+    format_specific: bool,
+) -> Result<(), CustomError> {
+    let path = path.as_ref();
+    let file = std::fs::File::create(path).map_err(|err| {
+        CustomError::error(
+            "Could not create file",
+            format!("Additional info: {err}"),
+            Context::show(path.to_string_lossy()),
+        )
+    })?;
+    let compressed = check_extension(path, "gz");
+    let inner = if compressed {
+        path.file_stem().map(Path::new)
+    } else {
+        Some(path)
+    };
+    let separator = if inner.is_some_and(|p| check_extension(p, "tsv")) {
+        b'\t'
+    } else {
+        b','
+    };
+    if compressed {
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        write_identified_peptides_raw(&mut encoder, peptides, separator, format_specific)?;
+        encoder.try_finish().map_err(|err| {
+            CustomError::error(
+                "Could not write identified peptides",
+                format!("Additional info: {err}"),
+                Context::show(path.to_string_lossy()),
+            )
+        })
+    } else {
+        write_identified_peptides_raw(file, peptides, separator, format_specific)
+    }
+}
+
+/// Write identified peptides as a table with the given separator.
+///
+/// The table always contains the [`NORMALISED_COLUMNS`] so any downstream tool sees the same
+/// schema regardless of the source format. If `format_specific` is set all format specific fields
+/// are added as well, these are left empty for peptides from other formats. Values containing the
+/// separator, quotes, or newlines are quoted.
+/// # Errors
+/// If the writer could not be written to.
+pub fn write_identified_peptides_raw<'a>(
+    writer: impl Write,
+    peptides: impl IntoIterator<Item = &'a IdentifiedPeptide>,
+    separator: u8,
+    format_specific: bool,
+) -> Result<(), CustomError> {
+    let rows: Vec<Vec<(String, String)>> = peptides
+        .into_iter()
+        .map(|p| p.export_columns(format_specific))
+        .collect();
+    let header: Vec<&str> = NORMALISED_COLUMNS
+        .iter()
+        .copied()
+        .chain(rows.iter().flatten().map(|(column, _)| column.as_str()))
+        .unique()
+        .collect();
+    let separator = char::from(separator);
+
+    let mut writer = BufWriter::new(writer);
+    let mut write_line = |line: String| {
+        writeln!(writer, "{line}").map_err(|err| {
+            CustomError::error(
+                "Could not write identified peptides",
+                format!("Additional info: {err}"),
+                Context::none(),
+            )
+        })
+    };
+    write_line(
+        header
+            .iter()
+            .map(|column| escape(column, separator))
+            .join(&separator.to_string()),
+    )?;
+    for row in &rows {
+        write_line(
+            header
+                .iter()
+                .map(|column| {
+                    row.iter()
+                        .find(|(c, _)| c == column)
+                        .map_or_else(String::new, |(_, value)| escape(value, separator))
+                })
+                .join(&separator.to_string()),
+        )?;
+    }
+    writer.flush().map_err(|err| {
+        CustomError::error(
+            "Could not write identified peptides",
+            format!("Additional info: {err}"),
+            Context::none(),
+        )
+    })
+}
+
+/// Quote a value if it contains the separator, a quote, or a newline
+fn escape(value: &str, separator: char) -> String {
+    if value.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Get all fields of a format specific metadata struct.
+///
+/// The peptide is skipped as it is already part of the normalised columns. Strings, numbers, and
+/// booleans are written as is, all other values are written as JSON.
+fn serialised_fields(data: &impl Serialize) -> Vec<(String, String)> {
+    match serde_json::to_value(data) {
+        Ok(serde_json::Value::Object(fields)) => fields
+            .into_iter()
+            .filter(|(field, _)| field != "peptide")
+            .map(|(field, value)| {
+                let value = match value {
+                    serde_json::Value::Null => String::new(),
+                    serde_json::Value::String(s) => s,
+                    serde_json::Value::Bool(b) => b.to_string(),
+                    serde_json::Value::Number(n) => n.to_string(),
+                    other => other.to_string(),
+                };
+                (field, value)
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
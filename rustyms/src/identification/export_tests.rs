@@ -0,0 +1,67 @@
+#![allow(clippy::missing_panics_doc)]
+use super::{write_identified_peptides_raw, IdentifiedPeptide, MZTabData, NORMALISED_COLUMNS};
+
+#[test]
+fn export_normalised() {
+    let peptides: Vec<IdentifiedPeptide> = MZTabData::parse_str(DATA, None)
+        .unwrap()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let mut buffer = Vec::new();
+    write_identified_peptides_raw(&mut buffer, &peptides, b'\t', false).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next().unwrap(), NORMALISED_COLUMNS.join("\t"));
+    assert_eq!(
+        lines.next().unwrap(),
+        "mztab\t[U:Acetyl]-PEPMK\t2\t1800.5\t0.999\t\t\t\t\t/data/run01.mzML\t1234\tscan=1234"
+    );
+    assert_eq!(
+        lines.next().unwrap(),
+        "mztab\tSAMPLER\t\t\t\t\t\t\t\t/data/run01.mzML\t1300;1301\tscan=1300;scan=1301"
+    );
+    assert_eq!(lines.next(), None);
+}
+
+#[test]
+fn export_format_specific() {
+    let peptides: Vec<IdentifiedPeptide> = MZTabData::parse_str(DATA, None)
+        .unwrap()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let mut buffer = Vec::new();
+    write_identified_peptides_raw(&mut buffer, &peptides, b',', true).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let header: Vec<&str> = text.lines().next().unwrap().split(',').collect();
+    assert_eq!(&header[..NORMALISED_COLUMNS.len()], NORMALISED_COLUMNS);
+    assert!(header.contains(&"mztab.psm_id"));
+    assert!(header.contains(&"mztab.accession"));
+    assert!(!header.contains(&"mztab.peptide"));
+    // The list of spectra references contains commas so has to be quoted
+    assert!(text.lines().nth(1).unwrap().contains(",P12345,"));
+    assert!(text
+        .lines()
+        .nth(2)
+        .unwrap()
+        .contains("\"[[\"\"/data/run01.mzML\"\""));
+
+    let peptide = &peptides[0];
+    let columns = peptide.export_columns(true);
+    assert_eq!(
+        columns
+            .iter()
+            .find(|(c, _)| c == "mztab.psm_id")
+            .map(|(_, v)| v.as_str()),
+        Some("1")
+    );
+}
+
+const DATA: &str = "MTD\tmzTab-version\t1.0.0
+MTD\tms_run[1]-location\tfile:///data/run01.mzML
+MTD\tpsm_search_engine_score[1]\t[MS, MS:1002052, MS-GF:SpecEValue, ]
+PSH\tsequence\tPSM_ID\taccession\tsearch_engine_score[1]\tmodifications\tretention_time\tcharge\tspectra_ref
+PSM\tPEPMK\t1\tP12345\t0.001\t0-UNIMOD:1\t1800.5\t2\tms_run[1]:scan=1234
+PSM\tSAMPLER\t2\tnull\tnull\tnull\tnull\tnull\tms_run[1]:scan=1300|ms_run[1]:scan=1301
+";
@@ -1,7 +1,7 @@
 use crate::{
     error::{Context, CustomError},
     peptide::SemiAmbiguous,
-    CompoundPeptidoform, LinearPeptide, SequenceElement,
+    CompoundPeptidoform, DecoyStrategy, LinearPeptide, SequenceElement,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -95,6 +95,17 @@ impl FastaData {
 
         Ok(sequences)
     }
+
+    /// Generate a decoy protein with the given strategy, see [`LinearPeptide::decoy`]. The id and
+    /// header are prefixed with the given prefix (e.g. `rev_`).
+    #[must_use]
+    pub fn decoy(&self, strategy: &DecoyStrategy, prefix: &str) -> Self {
+        Self {
+            id: format!("{prefix}{}", self.id),
+            full_header: format!("{prefix}{}", self.full_header),
+            peptide: self.peptide.decoy(strategy),
+        }
+    }
 }
 
 impl From<FastaData> for IdentifiedPeptide {
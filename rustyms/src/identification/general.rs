@@ -1,17 +1,99 @@
 use std::path::Path;
 
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
 use super::{
+    csv::{header_line, parse_csv_raw},
     error::{Context, CustomError},
+    helper_functions::read_file_start,
     ontologies::CustomDatabase,
-    FastaData, IdentifiedPeptide, IdentifiedPeptideIter, IdentifiedPeptideSource, MSFraggerData,
-    MaxQuantData, NovorData, OpairData, PeaksData, SageData,
+    ByonicData, ByonicFormat, ByonicVersion, CasanovoData, DiaNNData, DiaNNFormat, DiaNNVersion,
+    FastaData, IdentifiedPeptide, IdentifiedPeptideIter, IdentifiedPeptideSource, InstaNovoData,
+    InstaNovoFormat, InstaNovoVersion, MSFraggerData, MSFraggerFormat, MSFraggerVersion,
+    MZIdentMLData, MZTabData, MaxQuantData, MaxQuantFormat, MaxQuantVersion, NovorData,
+    NovorFormat, NovorVersion, OpairData, OpairFormat, OpairVersion, PGlycoData, PGlycoFormat,
+    PGlycoVersion, PeaksData, PeaksFormat, PeaksVersion, PepNetData, PepNetFormat, PepNetVersion,
+    PepXMLData, SageData, SageFormat, SageVersion, SpectronautData, SpectronautFormat,
+    SpectronautVersion,
 };
 
 // TODO:
 // * Merge multiple annotations for the same spectrum (e.g. all candidates peaks export, take care not to lose info on chimeric spectra)
 // * Merge identical (or similar?) peptide sequences (for faster processing)
 
+/// The number of lines read from the start of a file to determine its format
+const DETECTION_LINES: usize = 500;
+
+/// All file formats with identified peptides that can be read, with the specific version for the
+/// formats that have multiple versions.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum KnownFileFormat {
+    /// A Byonic CSV export
+    Byonic(ByonicVersion),
+    /// A Casanovo mzTab file
+    Casanovo,
+    /// A DIA-NN report
+    DiaNN(DiaNNVersion),
+    /// A Fasta file
+    Fasta,
+    /// An InstaNovo CSV file
+    InstaNovo(InstaNovoVersion),
+    /// A MaxQuant TXT file
+    MaxQuant(MaxQuantVersion),
+    /// A MSFragger TSV file
+    MSFragger(MSFraggerVersion),
+    /// A mzIdentML file
+    MZIdentML,
+    /// A mzTab file
+    MZTab,
+    /// A Novor CSV file
+    Novor(NovorVersion),
+    /// An OPair PSMTSV file
+    Opair(OpairVersion),
+    /// A PEAKS CSV file
+    Peaks(PeaksVersion),
+    /// A PepNet TSV file
+    PepNet(PepNetVersion),
+    /// A pepXML file
+    PepXML,
+    /// A pGlyco result file
+    PGlyco(PGlycoVersion),
+    /// A Sage TSV file
+    Sage(SageVersion),
+    /// A Spectronaut report
+    Spectronaut(SpectronautVersion),
+}
+
+impl std::fmt::Display for KnownFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Byonic(version) => write!(f, "Byonic {version}"),
+            Self::Casanovo => write!(f, "Casanovo"),
+            Self::DiaNN(version) => write!(f, "DIA-NN {version}"),
+            Self::Fasta => write!(f, "Fasta"),
+            Self::InstaNovo(version) => write!(f, "InstaNovo {version}"),
+            Self::MaxQuant(version) => write!(f, "MaxQuant {version}"),
+            Self::MSFragger(version) => write!(f, "MSFragger {version}"),
+            Self::MZIdentML => write!(f, "mzIdentML"),
+            Self::MZTab => write!(f, "mzTab"),
+            Self::Novor(version) => write!(f, "Novor {version}"),
+            Self::Opair(_) => write!(f, "OPair"),
+            Self::Peaks(version) => write!(f, "PEAKS {version}"),
+            Self::PepNet(version) => write!(f, "PepNet {version}"),
+            Self::PepXML => write!(f, "pepXML"),
+            Self::PGlyco(version) => write!(f, "pGlyco {version}"),
+            Self::Sage(version) => write!(f, "Sage {version}"),
+            Self::Spectronaut(version) => write!(f, "Spectronaut {version}"),
+        }
+    }
+}
+
 /// Open the selected path and automatically determine the file type.
+///
+/// The file type is determined from the content of the file, see
+/// [`detect_identified_peptides_file_format`] for the details on the detection. If the content
+/// is not recognised the file type is determined by the extension instead.
 /// # Errors
 /// It errors if the file type could not be determined or if opening the file errors.
 pub fn open_identified_peptides_file<'a>(
@@ -19,6 +101,21 @@ pub fn open_identified_peptides_file<'a>(
     custom_database: Option<&'a CustomDatabase>,
 ) -> Result<Box<dyn Iterator<Item = Result<IdentifiedPeptide, CustomError>> + 'a>, CustomError> {
     let path = path.as_ref();
+    match detect_identified_peptides_file_format(path) {
+        Ok(format) => open_identified_peptides_file_with_format(path, &format, custom_database),
+        Err(err) => {
+            open_identified_peptides_file_by_extension(path, custom_database).map_err(|_| err)
+        }
+    }
+}
+
+/// Open the selected path and determine the file type from the extension.
+/// # Errors
+/// It errors if the file type could not be determined or if opening the file errors.
+fn open_identified_peptides_file_by_extension<'a>(
+    path: &Path,
+    custom_database: Option<&'a CustomDatabase>,
+) -> Result<Box<dyn Iterator<Item = Result<IdentifiedPeptide, CustomError>> + 'a>, CustomError> {
     let actual_extension = path
         .extension()
         .map(|ex| {
@@ -35,10 +132,14 @@ pub fn open_identified_peptides_file<'a>(
             .or_else(|_| {
                 NovorData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
             })
+            .or_else(|_| {
+                InstaNovoData::parse_file(path, custom_database)
+                    .map(IdentifiedPeptideIter::into_box)
+            })
             .map_err(|_| {
                 CustomError::error(
                     "Unknown file",
-                    "Could not be recognised as either a Peaks or Novor file",
+                    "Could not be recognised as either a Peaks, Novor, or InstaNovo file",
                     Context::show(path.to_string_lossy()),
                 )
             }),
@@ -47,27 +148,294 @@ pub fn open_identified_peptides_file<'a>(
             .or_else(|_| {
                 SageData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
             })
+            .or_else(|_| {
+                DiaNNData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
+            })
+            .or_else(|_| {
+                SpectronautData::parse_file(path, custom_database)
+                    .map(IdentifiedPeptideIter::into_box)
+            })
+            .or_else(|_| {
+                PepNetData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
+            })
             .map_err(|_| {
                 CustomError::error(
                     "Unknown file",
-                    "Could not be recognised as either a MSFragger or Sage file",
+                    "Could not be recognised as either a MSFragger, Sage, DIA-NN, Spectronaut, or PepNet file",
                     Context::show(path.to_string_lossy()),
                 )
             }),
         Some("psmtsv") => {
             OpairData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
         }
-        Some("fasta") => FastaData::parse_file(path).map(|peptides| {
-            Box::new(peptides.into_iter().map(|p| Ok(p.into())))
-                as Box<dyn Iterator<Item = Result<IdentifiedPeptide, CustomError>> + 'a>
-        }),
+        Some("fasta") => FastaData::parse_file(path).map(into_box_vec),
+        Some("mzid") => MZIdentMLData::parse_file(path, custom_database).map(into_box_vec),
+        Some("mztab") => CasanovoData::parse_file(path, custom_database)
+            .map(into_box_vec)
+            .or_else(|_| MZTabData::parse_file(path, custom_database).map(into_box_vec)),
+        Some("pepxml") => {
+            PepXMLData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
+        }
+        Some("xml") => PepXMLData::parse_file(path, custom_database)
+            .map(IdentifiedPeptideIter::into_box)
+            .or_else(|_| MZIdentMLData::parse_file(path, custom_database).map(into_box_vec))
+            .map_err(|_| {
+                CustomError::error(
+                    "Unknown file",
+                    "Could not be recognised as either a pepXML or mzIdentML file",
+                    Context::show(path.to_string_lossy()),
+                )
+            }),
         Some("txt") => {
             MaxQuantData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
         }
         _ => Err(CustomError::error(
             "Unknown extension",
-            "Use CSV, TSV, TXT, PSMTSV, or Fasta, or any of these as a gzipped file (eg csv.gz).",
+            "Use CSV, TSV, TXT, PSMTSV, mzID, mzTab, pepXML (pep.xml), or Fasta, or any of these as a gzipped file (eg csv.gz).",
             Context::show(path.to_string_lossy()),
         )),
     }
 }
+
+/// Open the selected path as the given file format (and version).
+/// # Errors
+/// It errors if opening the file errors.
+pub fn open_identified_peptides_file_with_format<'a>(
+    path: impl AsRef<Path>,
+    format: &KnownFileFormat,
+    custom_database: Option<&'a CustomDatabase>,
+) -> Result<Box<dyn Iterator<Item = Result<IdentifiedPeptide, CustomError>> + 'a>, CustomError> {
+    let path = path.as_ref();
+    let unknown_version = || {
+        CustomError::error(
+            "Unknown version",
+            format!("There is no known definition for {format}"),
+            Context::show(path.to_string_lossy()),
+        )
+    };
+    match format {
+        KnownFileFormat::Byonic(version) => ByonicData::parse_file_with_format(
+            path,
+            ByonicFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::Casanovo => {
+            CasanovoData::parse_file(path, custom_database).map(into_box_vec)
+        }
+        KnownFileFormat::DiaNN(version) => DiaNNData::parse_file_with_format(
+            path,
+            DiaNNFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::Fasta => FastaData::parse_file(path).map(into_box_vec),
+        KnownFileFormat::InstaNovo(version) => InstaNovoData::parse_file_with_format(
+            path,
+            InstaNovoFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::MaxQuant(version) => MaxQuantData::parse_file_with_format(
+            path,
+            MaxQuantFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::MSFragger(version) => MSFraggerData::parse_file_with_format(
+            path,
+            MSFraggerFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::MZIdentML => {
+            MZIdentMLData::parse_file(path, custom_database).map(into_box_vec)
+        }
+        KnownFileFormat::MZTab => MZTabData::parse_file(path, custom_database).map(into_box_vec),
+        KnownFileFormat::Novor(version) => NovorData::parse_file_with_format(
+            path,
+            NovorFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::Opair(version) => OpairData::parse_file_with_format(
+            path,
+            OpairFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::Peaks(version) => PeaksData::parse_file_with_format(
+            path,
+            PeaksFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::PepNet(version) => PepNetData::parse_file_with_format(
+            path,
+            PepNetFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::PepXML => {
+            PepXMLData::parse_file(path, custom_database).map(IdentifiedPeptideIter::into_box)
+        }
+        KnownFileFormat::PGlyco(version) => PGlycoData::parse_file_with_format(
+            path,
+            PGlycoFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::Sage(version) => SageData::parse_file_with_format(
+            path,
+            SageFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+        KnownFileFormat::Spectronaut(version) => SpectronautData::parse_file_with_format(
+            path,
+            SpectronautFormat::from_version(version).ok_or_else(unknown_version)?,
+            custom_database,
+        )
+        .map(IdentifiedPeptideIter::into_box),
+    }
+}
+
+/// Determine the file format (and version) of the selected path from the content of the file,
+/// the extension is ignored (except for `.gz` to decompress the file).
+///
+/// XML files are recognised by their root element, mzTab files by their metadata section (using
+/// the listed software to recognise Casanovo), and Fasta files by the leading `>`. For all other
+/// files the first line is read as a header and compared with the required columns for every
+/// version of every known CSV/TSV format. If multiple versions match the one with the most
+/// required columns is chosen.
+/// # Errors
+/// If the file could not be opened or if the format could not be determined. In the latter case
+/// the error lists for all candidate formats which columns are missing.
+pub fn detect_identified_peptides_file_format(
+    path: impl AsRef<Path>,
+) -> Result<KnownFileFormat, CustomError> {
+    let path = path.as_ref();
+    let lines = read_file_start(path, DETECTION_LINES)?;
+    let header =
+        header_line(&mut lines.iter().map(|line| Ok(line.clone())).enumerate()).map_err(|_| {
+            CustomError::error(
+                "Unknown file",
+                "The file is empty",
+                Context::show(path.to_string_lossy()),
+            )
+        })?;
+    let first = header.trim();
+
+    if first.starts_with('<') {
+        let text = lines.join("\n").to_ascii_lowercase();
+        if text.contains("<msms_pipeline_analysis") {
+            Ok(KnownFileFormat::PepXML)
+        } else if text.contains("<mzidentml") {
+            Ok(KnownFileFormat::MZIdentML)
+        } else {
+            Err(CustomError::error(
+                "Unknown file",
+                "Could not be recognised as either a pepXML or mzIdentML file",
+                Context::show(path.to_string_lossy()),
+            ))
+        }
+    } else if first.starts_with('>') {
+        Ok(KnownFileFormat::Fasta)
+    } else if first.starts_with("MTD\t") || first.starts_with("COM\t") {
+        let casanovo = lines.iter().any(|line| {
+            let mut fields = line.split('\t');
+            fields.next() == Some("MTD")
+                && fields
+                    .next()
+                    .is_some_and(|key| key.starts_with("software[") && !key.contains('-'))
+                && fields
+                    .next()
+                    .is_some_and(|value| value.to_ascii_lowercase().contains("casanovo"))
+        });
+        Ok(if casanovo {
+            KnownFileFormat::Casanovo
+        } else {
+            KnownFileFormat::MZTab
+        })
+    } else {
+        detect_csv_format(first).map_err(|errors| {
+            CustomError::error(
+                "Unknown file",
+                "Could not be recognised as any known format, the header line does not contain all required columns for any of the known CSV/TSV formats",
+                Context::show(path.to_string_lossy()),
+            )
+            .with_underlying_errors(errors)
+        })
+    }
+}
+
+/// Determine the CSV/TSV format based on the given header line.
+/// # Errors
+/// If no format matches, with an error for each candidate listing the missing columns.
+fn detect_csv_format(header_line: &str) -> Result<KnownFileFormat, Vec<CustomError>> {
+    let mut best: Option<(KnownFileFormat, usize)> = None;
+    let mut errors = Vec::new();
+    for (format, separator, required) in csv_formats() {
+        let header = parse_csv_raw(header_line.as_bytes(), separator, None)
+            .map(|lines| lines.header().to_vec())
+            .unwrap_or_default();
+        let missing = required
+            .iter()
+            .filter(|column| !header.iter().any(|h| h == *column))
+            .collect_vec();
+        if missing.is_empty() {
+            if best.as_ref().map_or(true, |(_, n)| required.len() > *n) {
+                best = Some((format, required.len()));
+            }
+        } else {
+            errors.push(CustomError::error(
+                format!("Not a {format} file"),
+                format!(
+                    "Missing the required column{} {}",
+                    if missing.len() == 1 { "" } else { "s" },
+                    missing.iter().map(|c| format!("'{c}'")).join(", ")
+                ),
+                Context::none(),
+            ));
+        }
+    }
+    best.map(|(format, _)| format).ok_or(errors)
+}
+
+/// All versions of all CSV/TSV formats, with their separator and required columns
+fn csv_formats() -> Vec<(KnownFileFormat, u8, Vec<&'static str>)> {
+    macro_rules! versions {
+        ($($format:ident => $known:ident),+ $(,)?) => {{
+            let mut output = Vec::new();
+            $(output.extend($format::versions().iter().map(|format| {
+                (
+                    KnownFileFormat::$known(format.version().clone()),
+                    $format::SEPARATOR,
+                    format.required_columns(),
+                )
+            }));)+
+            output
+        }};
+    }
+    versions!(
+        PeaksFormat => Peaks,
+        NovorFormat => Novor,
+        InstaNovoFormat => InstaNovo,
+        MSFraggerFormat => MSFragger,
+        SageFormat => Sage,
+        DiaNNFormat => DiaNN,
+        SpectronautFormat => Spectronaut,
+        PepNetFormat => PepNet,
+        OpairFormat => Opair,
+        MaxQuantFormat => MaxQuant,
+        PGlycoFormat => PGlyco,
+        ByonicFormat => Byonic,
+    )
+}
+
+/// Box a list of parsed peptides as an iterator of identified peptides
+fn into_box_vec<'a, T: Into<IdentifiedPeptide> + 'a>(
+    peptides: Vec<T>,
+) -> Box<dyn Iterator<Item = Result<IdentifiedPeptide, CustomError>> + 'a> {
+    Box::new(peptides.into_iter().map(|p| Ok(p.into())))
+}
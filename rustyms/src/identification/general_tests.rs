@@ -0,0 +1,113 @@
+#![allow(clippy::missing_panics_doc)]
+use crate::helper_functions::temporary_file;
+
+use super::{
+    detect_identified_peptides_file_format, open_identified_peptides_file,
+    open_identified_peptides_file_with_format, KnownFileFormat, PeaksVersion, PepNetVersion,
+};
+
+#[test]
+fn detect_peaks() {
+    let path = std::env::var("CARGO_MANIFEST_DIR").unwrap()
+        + "/data/200305_HER_test_04_DENOVO_excerpt.csv";
+    assert!(matches!(
+        detect_identified_peptides_file_format(&path).unwrap(),
+        KnownFileFormat::Peaks(_)
+    ));
+    assert_eq!(
+        open_identified_peptides_file(&path, None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .len(),
+        1437
+    );
+}
+
+#[test]
+fn detect_renamed() {
+    let path = temporary_file("txt", PEPNET);
+    let format = detect_identified_peptides_file_format(&path);
+    let peptides =
+        open_identified_peptides_file(&path, None).map(Iterator::collect::<Result<Vec<_>, _>>);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        format.unwrap(),
+        KnownFileFormat::PepNet(PepNetVersion::V1_0)
+    );
+    assert_eq!(peptides.unwrap().unwrap().len(), 2);
+}
+
+#[test]
+fn detect_casanovo() {
+    let path = temporary_file("mztab", CASANOVO);
+    let format = detect_identified_peptides_file_format(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(format.unwrap(), KnownFileFormat::Casanovo);
+}
+
+#[test]
+fn detect_unknown() {
+    let path = temporary_file("csv", "a,b,c\n1,2,3\n");
+    let format = detect_identified_peptides_file_format(&path);
+    std::fs::remove_file(&path).unwrap();
+    let error = format.unwrap_err().to_string();
+    assert!(error.contains("Not a PEAKS X file"), "{error}");
+    assert!(error.contains("'denovo'"), "{error}");
+}
+
+#[test]
+fn byte_order_mark_and_blank_lines() {
+    let path = temporary_file("tsv", &format!("\u{feff}\n\n{PEPNET}"));
+    let format = detect_identified_peptides_file_format(&path);
+    let peptides =
+        open_identified_peptides_file(&path, None).map(Iterator::collect::<Result<Vec<_>, _>>);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        format.unwrap(),
+        KnownFileFormat::PepNet(PepNetVersion::V1_0)
+    );
+    assert_eq!(peptides.unwrap().unwrap().len(), 2);
+}
+
+#[test]
+fn extension_fallback() {
+    // Not recognised by content, but the extension still selects the CSV readers
+    let csv = temporary_file("csv", "a,b,c\n1,2,3\n");
+    let opened = open_identified_peptides_file(&csv, None).map(|_| ());
+    std::fs::remove_file(&csv).unwrap();
+    assert!(opened.is_ok());
+    let unknown = temporary_file("unknown", "a,b,c\n1,2,3\n");
+    let opened = open_identified_peptides_file(&unknown, None).map(|_| ());
+    std::fs::remove_file(&unknown).unwrap();
+    let error = opened.unwrap_err().to_string();
+    assert!(error.contains("Not a PEAKS X file"), "{error}");
+}
+
+#[test]
+fn forced_format() {
+    let path = temporary_file("tsv", PEPNET);
+    let forced = open_identified_peptides_file_with_format(
+        &path,
+        &KnownFileFormat::Peaks(PeaksVersion::X),
+        None,
+    )
+    .map(Iterator::collect::<Result<Vec<_>, _>>);
+    std::fs::remove_file(&path).unwrap();
+    assert!(forced.unwrap().is_err());
+}
+
+const PEPNET: &str = "Title\tDENOVO\tScore\tPPM Difference\tPositional Score
+spectrum_1\tPEPTIDE\t0.95\t1.2\t[0.9, 0.8, 0.95, 0.9, 0.85, 0.9, 0.99]
+spectrum_2\tLESLIEK\t0.8\t-3.5\t[0.7, 0.8, 0.9, 0.85, 0.8, 0.75, 0.9]
+";
+
+const CASANOVO: &str = "MTD\tmzTab-version\t1.0.0
+MTD\tmzTab-mode\tSummary
+MTD\tmzTab-type\tIdentification
+MTD\tsoftware[1]\t[MS, MS:1003281, Casanovo, 4.2.0]
+MTD\tpsm_search_engine_score[1]\t[MS, MS:1001143, search engine specific score for PSMs, ]
+MTD\tms_run[1]-location\tfile:///data/sample.mgf
+PSH\tsequence\tPSM_ID\taccession\tunique\tdatabase\tdatabase_version\tsearch_engine\tsearch_engine_score[1]\tmodifications\tretention_time\tcharge\texp_mass_to_charge\tcalc_mass_to_charge\tspectra_ref\tpre\tpost\tstart\tend\topt_ms_run[1]_aa_scores
+PSM\tLESLIEK\t1\tnull\tnull\tnull\tnull\t[MS, MS:1003281, Casanovo, 4.2.0]\t0.91\tnull\tnull\t2.0\t416.2425\t416.2418\tms_run[1]:index=0\tnull\tnull\tnull\tnull\t0.9,0.8,0.95,0.9,0.91,0.92,0.99
+";
@@ -1,3 +1,18 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use flate2::bufread::GzDecoder;
+
+use crate::{
+    error::{Context, CustomError},
+    helper_functions::check_extension,
+    modification::Ontology,
+    ontologies::CustomDatabase,
+    PeptideModificationSearch, Tolerance,
+};
+
 pub trait ResultExtensions<T, E> {
     /// # Errors
     /// If any of the errors contained within has an error.
@@ -38,3 +53,86 @@ impl<T, E> InvertResult<T, E> for Option<Result<Option<T>, E>> {
         self.map_or_else(|| Ok(None), |o| o)
     }
 }
+
+/// Read a full file into a string, the file is decompressed if the extension is `.gz`.
+/// # Errors
+/// If the file could not be opened or read.
+pub fn read_file(path: &Path) -> Result<String, CustomError> {
+    let file = std::fs::File::open(path).map_err(|err| {
+        CustomError::error(
+            "Could not open file",
+            format!("Additional info: {err}"),
+            Context::show(path.to_string_lossy()),
+        )
+    })?;
+    if check_extension(path, "gz") {
+        read_to_string(GzDecoder::new(BufReader::new(file)))
+    } else {
+        read_to_string(file)
+    }
+}
+
+/// Read the first lines of a file, the file is decompressed if the extension is `.gz`. Reading
+/// stops at the given number of lines or at the first line that could not be read.
+/// # Errors
+/// If the file could not be opened.
+pub fn read_file_start(path: &Path, lines: usize) -> Result<Vec<String>, CustomError> {
+    let file = std::fs::File::open(path).map_err(|err| {
+        CustomError::error(
+            "Could not open file",
+            format!("Additional info: {err}"),
+            Context::show(path.to_string_lossy()),
+        )
+    })?;
+    let lines = if check_extension(path, "gz") {
+        BufReader::new(GzDecoder::new(BufReader::new(file)))
+            .lines()
+            .take(lines)
+            .map_while(Result::ok)
+            .collect()
+    } else {
+        BufReader::new(file)
+            .lines()
+            .take(lines)
+            .map_while(Result::ok)
+            .collect()
+    };
+    Ok(lines)
+}
+
+/// Read a full file from a raw reader into a string.
+/// # Errors
+/// If the reader could not be read.
+pub fn read_to_string(reader: impl Read) -> Result<String, CustomError> {
+    let mut text = String::new();
+    BufReader::new(reader)
+        .read_to_string(&mut text)
+        .map_err(|err| {
+            CustomError::error(
+                "Could not read file",
+                format!("Additional info: {err}"),
+                Context::none(),
+            )
+        })?;
+    Ok(text)
+}
+
+/// Build the search to resolve mass modifications.
+///
+/// This replaces all mass modifications with the closest Unimod (or custom) modification within
+/// the given absolute tolerance (in Dalton), and is intended for formats that only report rounded
+/// mass differences. Build this once per file, so the cache of the search is shared between all
+/// peptides.
+pub fn mass_modification_search(
+    custom_database: Option<&CustomDatabase>,
+    tolerance: f64,
+) -> PeptideModificationSearch {
+    let ontologies = if custom_database.is_some() {
+        vec![Ontology::Unimod, Ontology::Custom]
+    } else {
+        vec![Ontology::Unimod]
+    };
+    PeptideModificationSearch::in_ontologies(ontologies, custom_database.cloned())
+        .tolerance(Tolerance::Absolute(crate::system::da(tolerance)))
+        .force_closest(true)
+}
@@ -3,12 +3,18 @@ use std::path::Path;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    fasta::FastaData, novor::NovorData, opair::OpairData, peaks::PeaksData, MSFraggerData,
-    MaxQuantData, SageData,
+    fasta::FastaData, helper_functions::mass_modification_search, novor::NovorData,
+    opair::OpairData, peaks::PeaksData, ByonicData, CasanovoData, DiaNNData, InstaNovoData,
+    MSFraggerData, MZIdentMLData, MZTabData, MaxQuantData, OpairMatchKind, PGlycoData, PepNetData,
+    PepXMLData, SageData, SpectronautData,
 };
 use crate::{
-    error::CustomError, ontologies::CustomDatabase, peptide::SemiAmbiguous, system::usize::Charge,
-    system::Time, LinearPeptide,
+    error::CustomError,
+    ontologies::CustomDatabase,
+    peptide::SemiAmbiguous,
+    search::SearchData,
+    system::{isize, usize::Charge, Time},
+    LinearPeptide, PeptideModificationSearch,
 };
 
 /// A peptide that is identified by a de novo or database matching program
@@ -40,6 +46,28 @@ pub enum MetaData {
     Sage(SageData),
     /// MSFragger metadata
     MSFragger(MSFraggerData),
+    /// mzIdentML metadata
+    MZIdentML(MZIdentMLData),
+    /// pepXML metadata
+    PepXML(PepXMLData),
+    /// mzTab metadata
+    MZTab(MZTabData),
+    /// DIA-NN metadata
+    DiaNN(DiaNNData),
+    /// Spectronaut metadata
+    Spectronaut(SpectronautData),
+    /// Casanovo metadata
+    Casanovo(CasanovoData),
+    /// InstaNovo metadata
+    InstaNovo(InstaNovoData),
+    /// PepNet metadata
+    PepNet(PepNetData),
+    /// pGlyco metadata
+    PGlyco(PGlycoData),
+    /// Byonic metadata
+    Byonic(ByonicData),
+    /// Metadata from the built in database search, see [`crate::search`]
+    Search(SearchData),
 }
 
 impl IdentifiedPeptide {
@@ -50,9 +78,20 @@ impl IdentifiedPeptide {
             | MetaData::Novor(NovorData { peptide, .. })
             | MetaData::Opair(OpairData { peptide, .. })
             | MetaData::Sage(SageData { peptide, .. })
-            | MetaData::Fasta(FastaData { peptide, .. }) => Some(peptide),
+            | MetaData::MZIdentML(MZIdentMLData { peptide, .. })
+            | MetaData::PepXML(PepXMLData { peptide, .. })
+            | MetaData::MZTab(MZTabData { peptide, .. })
+            | MetaData::DiaNN(DiaNNData { peptide, .. })
+            | MetaData::Spectronaut(SpectronautData { peptide, .. })
+            | MetaData::Casanovo(CasanovoData { peptide, .. })
+            | MetaData::Fasta(FastaData { peptide, .. })
+            | MetaData::PGlyco(PGlycoData { peptide, .. })
+            | MetaData::Byonic(ByonicData { peptide, .. })
+            | MetaData::Search(SearchData { peptide, .. }) => Some(peptide),
             MetaData::MSFragger(MSFraggerData { peptide, .. })
-            | MetaData::MaxQuant(MaxQuantData { peptide, .. }) => peptide.as_ref(),
+            | MetaData::MaxQuant(MaxQuantData { peptide, .. })
+            | MetaData::InstaNovo(InstaNovoData { peptide, .. })
+            | MetaData::PepNet(PepNetData { peptide, .. }) => peptide.as_ref(),
             MetaData::None => None,
         }
     }
@@ -60,26 +99,64 @@ impl IdentifiedPeptide {
     /// Get the local confidence, it is the same lengths as the peptide with a local score in 0..=1
     pub fn local_confidence(&self) -> Option<&[f64]> {
         match &self.metadata {
-            MetaData::Peaks(PeaksData {
-                local_confidence, ..
-            }) => Some(local_confidence),
             MetaData::Novor(NovorData {
                 local_confidence, ..
+            })
+            | MetaData::Casanovo(CasanovoData {
+                local_confidence, ..
             }) => local_confidence.as_deref(),
+            MetaData::Peaks(PeaksData {
+                local_confidence, ..
+            })
+            | MetaData::InstaNovo(InstaNovoData {
+                local_confidence, ..
+            })
+            | MetaData::PepNet(PepNetData {
+                local_confidence, ..
+            }) => Some(local_confidence),
             _ => None,
         }
     }
 
-    /// The charge of the precursor, if known
-    pub const fn charge(&self) -> Option<Charge> {
+    /// The charge of the precursor, if known. For negative ion mode matches this is the absolute
+    /// charge, see [`Self::signed_charge`] for the charge with its sign.
+    pub fn charge(&self) -> Option<Charge> {
         match &self.metadata {
             MetaData::Peaks(PeaksData { z, .. })
             | MetaData::Novor(NovorData { z, .. })
             | MetaData::Opair(OpairData { z, .. })
             | MetaData::Sage(SageData { z, .. })
             | MetaData::MSFragger(MSFraggerData { z, .. })
-            | MetaData::MaxQuant(MaxQuantData { z, .. }) => Some(*z),
-            MetaData::Fasta(_) | MetaData::None => None,
+            | MetaData::PepXML(PepXMLData { z, .. })
+            | MetaData::DiaNN(DiaNNData { z, .. })
+            | MetaData::Spectronaut(SpectronautData { z, .. })
+            | MetaData::InstaNovo(InstaNovoData { z, .. })
+            | MetaData::MaxQuant(MaxQuantData { z, .. })
+            | MetaData::PGlyco(PGlycoData { z, .. })
+            | MetaData::Byonic(ByonicData { z, .. }) => Some(*z),
+            MetaData::Search(SearchData { z, .. })
+            | MetaData::MZIdentML(MZIdentMLData { z, .. }) => {
+                Some(Charge::new::<crate::system::e>(z.value.unsigned_abs()))
+            }
+            MetaData::MZTab(MZTabData { z, .. }) => {
+                z.map(|z| Charge::new::<crate::system::e>(z.value.unsigned_abs()))
+            }
+            MetaData::Casanovo(CasanovoData { z, .. }) => *z,
+            MetaData::PepNet(_) | MetaData::Fasta(_) | MetaData::None => None,
+        }
+    }
+
+    /// The charge of the precursor with its sign, if known. Only the formats that can store
+    /// negative charges give negative charges, all others are assumed to be positive.
+    pub fn signed_charge(&self) -> Option<isize::Charge> {
+        match &self.metadata {
+            MetaData::Search(SearchData { z, .. })
+            | MetaData::MZIdentML(MZIdentMLData { z, .. }) => Some(*z),
+            MetaData::MZTab(MZTabData { z, .. }) => *z,
+            _ => self
+                .charge()
+                .and_then(|c| isize::try_from(c.value).ok())
+                .map(isize::Charge::new::<crate::system::e>),
         }
     }
 
@@ -98,11 +175,21 @@ impl IdentifiedPeptide {
             MetaData::Peaks(PeaksData { rt, .. })
             | MetaData::Opair(OpairData { rt, .. })
             | MetaData::Sage(SageData { rt, .. })
-            | MetaData::MSFragger(MSFraggerData { rt, .. }) => Some(*rt),
-            MetaData::MaxQuant(MaxQuantData { rt, .. }) | MetaData::Novor(NovorData { rt, .. }) => {
-                *rt
+            | MetaData::MSFragger(MSFraggerData { rt, .. })
+            | MetaData::DiaNN(DiaNNData { rt, .. })
+            | MetaData::PGlyco(PGlycoData { rt, .. })
+            | MetaData::Byonic(ByonicData { rt, .. }) => Some(*rt),
+            MetaData::MaxQuant(MaxQuantData { rt, .. })
+            | MetaData::Novor(NovorData { rt, .. })
+            | MetaData::MZIdentML(MZIdentMLData { rt, .. })
+            | MetaData::PepXML(PepXMLData { rt, .. })
+            | MetaData::MZTab(MZTabData { rt, .. })
+            | MetaData::Spectronaut(SpectronautData { rt, .. })
+            | MetaData::Casanovo(CasanovoData { rt, .. })
+            | MetaData::Search(SearchData { rt, .. }) => *rt,
+            MetaData::InstaNovo(_) | MetaData::PepNet(_) | MetaData::Fasta(_) | MetaData::None => {
+                None
             }
-            MetaData::Fasta(_) | MetaData::None => None,
         }
     }
 
@@ -117,7 +204,35 @@ impl IdentifiedPeptide {
             }
             MetaData::MaxQuant(MaxQuantData { scan_number, .. }) => Some(scan_number.clone()),
             MetaData::MSFragger(MSFraggerData { spectrum, .. }) => Some(vec![spectrum.scan.0]),
-            MetaData::Sage(_) | MetaData::Fasta(_) | MetaData::None => None,
+            MetaData::MZIdentML(MZIdentMLData { scan_number, .. }) => {
+                (!scan_number.is_empty()).then(|| scan_number.clone())
+            }
+            MetaData::PepXML(PepXMLData {
+                start_scan,
+                end_scan,
+                ..
+            }) => Some((*start_scan..=*end_scan).collect()),
+            MetaData::InstaNovo(InstaNovoData { scan_number, .. }) => Some(vec![*scan_number]),
+            MetaData::PGlyco(PGlycoData { scan, .. })
+            | MetaData::Byonic(ByonicData { scan, .. }) => Some(vec![*scan]),
+            MetaData::MZTab(MZTabData { spectra_ref, .. })
+            | MetaData::Casanovo(CasanovoData { spectra_ref, .. }) => {
+                let scans: Vec<usize> = spectra_ref
+                    .iter()
+                    .flat_map(|(_, id)| id.split(' '))
+                    .filter_map(|part| part.strip_prefix("scan="))
+                    .filter_map(|n| n.parse().ok())
+                    .collect();
+                (!scans.is_empty()).then_some(scans)
+            }
+            MetaData::DiaNN(DiaNNData { scan, .. }) | MetaData::Search(SearchData { scan, .. }) => {
+                scan.map(|scan| vec![scan])
+            }
+            MetaData::Sage(_)
+            | MetaData::Spectronaut(_)
+            | MetaData::PepNet(_)
+            | MetaData::Fasta(_)
+            | MetaData::None => None,
         }
     }
 
@@ -125,12 +240,30 @@ impl IdentifiedPeptide {
     pub fn spectrum_native_ids(&self) -> Option<Vec<String>> {
         match &self.metadata {
             MetaData::Sage(SageData { native_id, .. }) => Some(vec![native_id.clone()]),
+            MetaData::MZIdentML(MZIdentMLData { spectrum_id, .. }) => {
+                Some(vec![spectrum_id.clone()])
+            }
+            MetaData::PepXML(PepXMLData { native_id, .. }) => {
+                native_id.as_ref().map(|id| vec![id.clone()])
+            }
+            MetaData::MZTab(MZTabData { spectra_ref, .. })
+            | MetaData::Casanovo(CasanovoData { spectra_ref, .. }) => (!spectra_ref.is_empty())
+                .then(|| spectra_ref.iter().map(|(_, id)| id.clone()).collect()),
+            MetaData::InstaNovo(InstaNovoData { spectrum_id, .. }) => {
+                spectrum_id.as_ref().map(|id| vec![id.clone()])
+            }
+            MetaData::PepNet(PepNetData { title, .. })
+            | MetaData::Search(SearchData { title, .. }) => Some(vec![title.clone()]),
             MetaData::MaxQuant(_)
             | MetaData::Opair(_)
             | MetaData::Novor(_)
             | MetaData::Peaks(_)
             | MetaData::Fasta(_)
             | MetaData::MSFragger(_)
+            | MetaData::DiaNN(_)
+            | MetaData::Spectronaut(_)
+            | MetaData::PGlyco(_)
+            | MetaData::Byonic(_)
             | MetaData::None => None,
         }
     }
@@ -138,12 +271,90 @@ impl IdentifiedPeptide {
     /// Get the file name for the raw file
     pub fn raw_file(&self) -> Option<&Path> {
         match &self.metadata {
-            MetaData::Peaks(PeaksData { raw_file, .. }) => raw_file.as_deref(),
             MetaData::Opair(OpairData { raw_file, .. })
             | MetaData::MaxQuant(MaxQuantData { raw_file, .. })
-            | MetaData::Sage(SageData { raw_file, .. }) => Some(raw_file),
+            | MetaData::Sage(SageData { raw_file, .. })
+            | MetaData::DiaNN(DiaNNData { raw_file, .. })
+            | MetaData::Spectronaut(SpectronautData { raw_file, .. })
+            | MetaData::InstaNovo(InstaNovoData { raw_file, .. })
+            | MetaData::PGlyco(PGlycoData { raw_file, .. }) => Some(raw_file),
             MetaData::MSFragger(MSFraggerData { spectrum, .. }) => Some(&spectrum.file),
-            MetaData::Novor(_) | MetaData::Fasta(_) | MetaData::None => None,
+            MetaData::Peaks(PeaksData { raw_file, .. })
+            | MetaData::MZIdentML(MZIdentMLData { raw_file, .. })
+            | MetaData::PepXML(PepXMLData { raw_file, .. })
+            | MetaData::Search(SearchData { raw_file, .. }) => raw_file.as_deref(),
+            MetaData::MZTab(MZTabData { spectra_ref, .. })
+            | MetaData::Casanovo(CasanovoData { spectra_ref, .. }) => {
+                spectra_ref.first().and_then(|(file, _)| file.as_deref())
+            }
+            MetaData::Novor(_)
+            | MetaData::PepNet(_)
+            | MetaData::Byonic(_)
+            | MetaData::Fasta(_)
+            | MetaData::None => None,
+        }
+    }
+
+    /// The q-value of the identification of this peptide (or precursor for DIA formats), if
+    /// known
+    pub const fn q_value(&self) -> Option<f64> {
+        match &self.metadata {
+            MetaData::Sage(SageData { spectrum_q, .. }) => Some(*spectrum_q),
+            MetaData::DiaNN(DiaNNData { q_value, .. })
+            | MetaData::Spectronaut(SpectronautData { q_value, .. }) => Some(*q_value),
+            MetaData::PGlyco(PGlycoData { total_fdr, .. }) => Some(*total_fdr),
+            _ => None,
+        }
+    }
+
+    /// If this is a decoy match, if known. For mzIdentML the peptide is a decoy if all proteins it
+    /// is found in are decoys.
+    pub fn is_decoy(&self) -> Option<bool> {
+        match &self.metadata {
+            MetaData::Opair(OpairData { kind, .. }) => Some(*kind == OpairMatchKind::Decoy),
+            MetaData::Sage(SageData { decoy, .. }) | MetaData::Search(SearchData { decoy, .. }) => {
+                Some(*decoy)
+            }
+            MetaData::MZIdentML(MZIdentMLData { proteins, .. }) => {
+                (!proteins.is_empty()).then(|| proteins.iter().all(|p| p.decoy))
+            }
+            MetaData::Spectronaut(SpectronautData { decoy, .. }) => *decoy,
+            MetaData::PGlyco(PGlycoData {
+                glycan_decoy,
+                peptide_decoy,
+                ..
+            }) => match (glycan_decoy, peptide_decoy) {
+                (None, None) => None,
+                (glycan, peptide) => {
+                    Some(glycan.unwrap_or_default() || peptide.unwrap_or_default())
+                }
+            },
+            _ => None,
+        }
+    }
+
+    /// If this match comes from a de novo program
+    pub const fn is_de_novo(&self) -> bool {
+        matches!(
+            self.metadata,
+            MetaData::Peaks(_)
+                | MetaData::Novor(_)
+                | MetaData::Casanovo(_)
+                | MetaData::InstaNovo(_)
+                | MetaData::PepNet(_)
+        )
+    }
+
+    /// The quantity of the precursor, if known
+    pub const fn precursor_quantity(&self) -> Option<f64> {
+        match &self.metadata {
+            MetaData::DiaNN(DiaNNData {
+                precursor_quantity, ..
+            }) => Some(*precursor_quantity),
+            MetaData::Spectronaut(SpectronautData {
+                precursor_quantity, ..
+            }) => *precursor_quantity,
+            _ => None,
         }
     }
 }
@@ -172,6 +383,18 @@ where
         format: &Self::Format,
         custom_database: Option<&CustomDatabase>,
     ) -> Result<Self, CustomError>;
+    /// The absolute tolerance (in Dalton) to resolve mass modifications with, for formats that
+    /// only report rounded mass differences. If set a search for the closest Unimod (or custom)
+    /// modification is built once per parsed file and every parsed peptide is passed to
+    /// [`Self::resolve_mass_modifications`].
+    fn mass_modification_tolerance() -> Option<f64> {
+        None
+    }
+    /// Replace the mass modifications in this peptide using the given search
+    #[must_use]
+    fn resolve_mass_modifications(self, _search: &mut PeptideModificationSearch) -> Self {
+        self
+    }
     /// Parse a source of multiple peptides automatically determining the format to use by the first item
     /// # Errors
     /// When the source is not a valid peptide
@@ -183,6 +406,20 @@ where
             iter: Box::new(iter),
             format: None,
             custom_database,
+            search: None,
+        }
+    }
+    /// Parse a source of multiple peptides using the given format for all items
+    fn parse_many_specific<'a, I: Iterator<Item = Result<Self::Source, CustomError>>>(
+        iter: I,
+        format: &Self::Format,
+        custom_database: Option<&'a CustomDatabase>,
+    ) -> IdentifiedPeptideIter<'a, Self, I> {
+        IdentifiedPeptideIter {
+            iter: Box::new(iter),
+            format: Some(format.clone()),
+            custom_database,
+            search: None,
         }
     }
     /// Parse a file with identified peptides.
@@ -210,6 +447,9 @@ pub struct IdentifiedPeptideIter<
     iter: Box<I>,
     format: Option<R::Format>,
     custom_database: Option<&'lifetime CustomDatabase>,
+    /// The search to resolve mass modifications, built on first use (see
+    /// [`IdentifiedPeptideSource::mass_modification_tolerance`])
+    search: Option<PeptideModificationSearch>,
 }
 
 impl<'lifetime, R: IdentifiedPeptideSource, I: Iterator<Item = Result<R::Source, CustomError>>>
@@ -219,7 +459,7 @@ where
 {
     type Item = Result<R, CustomError>;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(format) = &self.format {
+        let peptide = if let Some(format) = &self.format {
             self.iter
                 .next()
                 .map(|source| R::parse_specific(&source?, format, self.custom_database))
@@ -236,6 +476,16 @@ where
                 }
                 Some(Err(e)) => Some(Err(e)),
             }
+        };
+        match (peptide, R::mass_modification_tolerance()) {
+            (Some(Ok(peptide)), Some(tolerance)) => {
+                let custom_database = self.custom_database;
+                let search = self
+                    .search
+                    .get_or_insert_with(|| mass_modification_search(custom_database, tolerance));
+                Some(Ok(peptide.resolve_mass_modifications(search)))
+            }
+            (peptide, _) => peptide,
         }
     }
 }
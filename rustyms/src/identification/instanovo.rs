@@ -0,0 +1,146 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::CustomError,
+    helper_functions::InvertResult,
+    ontologies::CustomDatabase,
+    peptide::{SemiAmbiguous, SloppyParsingParameters},
+    system::{usize::Charge, MassOverCharge, Ratio},
+    LinearPeptide,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common_parser::{Location, OptionalLocation},
+    csv::{parse_csv, CsvLine},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+static NUMBER_ERROR: (&str, &str) = (
+    "Invalid InstaNovo line",
+    "This column is not a number but it is required to be a number in this InstaNovo format",
+);
+
+format_family!(
+    /// The format for any InstaNovo file
+    InstaNovoFormat,
+    /// The data from any InstaNovo file
+    InstaNovoData,
+    InstaNovoVersion, [&V1_0, &V0_1], b',';
+    required {
+        scan_number: usize, |location: Location, _| location.parse(NUMBER_ERROR);
+        mz: MassOverCharge, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(MassOverCharge::new::<crate::system::mz>);
+        z: Charge, |location: Location, _| location.parse::<usize>(NUMBER_ERROR).map(Charge::new::<crate::system::e>);
+        raw_file: PathBuf, |location: Location, _| Ok(Path::new(&location.get_string()).to_owned());
+        peptide: Option<LinearPeptide<SemiAmbiguous>>, |location: Location, custom_database: Option<&CustomDatabase>| location.or_empty().parse_with(|location| parse_peptide(&location, custom_database));
+        /// The log probability of the full peptide
+        log_probability: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        /// The probability for each amino acid 0.0..=1.0
+        local_confidence: Vec<f64>, |location: Location, _| location.trim()
+                .strip_enclosure('[', ']')
+                .or_empty()
+                .array(',')
+                .map(|l| l.parse::<f64>(NUMBER_ERROR).map(f64::exp))
+                .collect::<Result<Vec<_>, _>>();
+    }
+    optional {
+        spectrum_id: String, |location: Location, _| Ok(location.or_empty().get_string());
+        ppm_difference: Ratio, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|v| v.map(Ratio::new::<crate::system::ratio::ppm>));
+    }
+    // The masses are rounded to two decimals
+    mass_modification_tolerance: 0.02;
+);
+
+impl From<InstaNovoData> for IdentifiedPeptide {
+    fn from(value: InstaNovoData) -> Self {
+        Self {
+            score: Some(value.log_probability.exp().clamp(0.0, 1.0)),
+            metadata: MetaData::InstaNovo(value),
+        }
+    }
+}
+
+/// Parse an InstaNovo peptide.
+///
+/// Newer versions use ProForma with Unimod accessions while older versions use mass deltas in
+/// round brackets (`C(+57.02)`). The mass deltas are resolved to Unimod modifications when
+/// parsing multiple lines, with a search that is built once per file.
+/// # Errors
+/// If the peptide is not valid.
+fn parse_peptide(
+    location: &Location,
+    custom_database: Option<&CustomDatabase>,
+) -> Result<LinearPeptide<SemiAmbiguous>, CustomError> {
+    if location.as_str().contains("UNIMOD") {
+        LinearPeptide::pro_forma(location.as_str(), custom_database)
+            .map(LinearPeptide::into_semi_ambiguous)
+            .and_then(|p| {
+                p.ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid InstaNovo peptide",
+                        "The peptide is not a simple linear peptide",
+                        location.context(),
+                    )
+                })
+            })
+    } else {
+        LinearPeptide::sloppy_pro_forma(
+            location.full_line(),
+            location.location.clone(),
+            custom_database,
+            SloppyParsingParameters::default(),
+        )
+    }
+}
+
+/// All possible InstaNovo versions
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum InstaNovoVersion {
+    /// Version 0.1, with mass delta modifications
+    V0_1,
+    /// Version 1.0, with ProForma modifications
+    #[default]
+    V1_0,
+}
+
+impl std::fmt::Display for InstaNovoVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::V0_1 => "v0.1",
+                Self::V1_0 => "v1.0",
+            }
+        )
+    }
+}
+
+/// Version 0.1
+pub const V0_1: InstaNovoFormat = InstaNovoFormat {
+    version: InstaNovoVersion::V0_1,
+    scan_number: "scan_number",
+    mz: "precursor_mz",
+    z: "precursor_charge",
+    raw_file: "experiment_name",
+    peptide: "preds",
+    log_probability: "log_probs",
+    local_confidence: "token_log_probs",
+    spectrum_id: None,
+    ppm_difference: None,
+};
+
+/// Version 1.0
+pub const V1_0: InstaNovoFormat = InstaNovoFormat {
+    version: InstaNovoVersion::V1_0,
+    scan_number: "scan_number",
+    mz: "precursor_mz",
+    z: "precursor_charge",
+    raw_file: "experiment_name",
+    peptide: "predictions",
+    log_probability: "log_probabilities",
+    local_confidence: "token_log_probabilities",
+    spectrum_id: Some("spectrum_id"),
+    ppm_difference: Some("delta_mass_ppm"),
+};
@@ -0,0 +1,65 @@
+#![allow(clippy::missing_panics_doc)]
+use std::io::BufReader;
+
+use crate::system::{e, usize::Charge};
+
+use super::{
+    csv::parse_csv_raw, instanovo, IdentifiedPeptide, IdentifiedPeptideSource, InstaNovoData,
+};
+
+#[test]
+fn instanovo_v1_0() {
+    let reader = BufReader::new(DATA_V1_0.as_bytes());
+    let lines = parse_csv_raw(reader, b',', None).unwrap();
+    let peptides: Vec<InstaNovoData> = lines
+        .map(|line| InstaNovoData::parse_specific(&line.unwrap(), &instanovo::V1_0, None).unwrap())
+        .collect();
+    assert_eq!(peptides.len(), 3);
+    assert_eq!(peptides[0].peptide.as_ref().unwrap().to_string(), "LESLIEK");
+    assert_eq!(
+        peptides[1].peptide.as_ref().unwrap().to_string(),
+        "[U:Acetyl]-M[U:Oxidation]PEPC[U:Carbamidomethyl]K"
+    );
+    assert_eq!(peptides[2].peptide, None);
+    assert!(peptides[2].local_confidence.is_empty());
+
+    let identified: IdentifiedPeptide = peptides[0].clone().into();
+    assert!((identified.score.unwrap() - (-0.5_f64).exp()).abs() < 1e-9);
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(2)));
+    assert_eq!(identified.scan_indices(), Some(vec![1]));
+    let local_confidence = identified.local_confidence().unwrap();
+    assert_eq!(local_confidence.len(), 7);
+    assert!((local_confidence[0] - (-0.1_f64).exp()).abs() < 1e-9);
+}
+
+#[test]
+fn instanovo_v0_1() {
+    let reader = BufReader::new(DATA_V0_1.as_bytes());
+    let lines = parse_csv_raw(reader, b',', None).unwrap();
+    for line in lines.map(Result::unwrap) {
+        let (peptide, format) = InstaNovoData::parse(&line, None).unwrap();
+        assert_eq!(format, &instanovo::V0_1);
+        assert_eq!(
+            peptide.peptide.unwrap().to_string(),
+            "M[+15.99]PEPC[+57.02]N[+0.98]K"
+        );
+    }
+    let reader = BufReader::new(DATA_V0_1.as_bytes());
+    let lines = parse_csv_raw(reader, b',', None).unwrap();
+    for peptide in InstaNovoData::parse_many(lines, None) {
+        assert_eq!(
+            peptide.unwrap().peptide.unwrap().to_string(),
+            "M[U:Oxidation]PEPC[U:Carbamidomethyl]N[+0.98]K"
+        );
+    }
+}
+
+const DATA_V1_0: &str = r#"experiment_name,scan_number,spectrum_id,precursor_mz,precursor_charge,predictions,predictions_tokenised,log_probabilities,token_log_probabilities,delta_mass_ppm
+sample,1,sample:1,416.2425,2,LESLIEK,"L, E, S, L, I, E, K",-0.5,"[-0.1, -0.05, -0.02, -0.1, -0.1, -0.1, -0.03]",1.2
+sample,2,sample:2,398.6543,2,[UNIMOD:1]-M[UNIMOD:35]PEPC[UNIMOD:4]K,"[UNIMOD:1], M[UNIMOD:35], P, E, P, C[UNIMOD:4], K",-1.2,"[-0.3, -0.2, -0.1, -0.2, -0.2, -0.1]",-3.4
+sample,3,sample:3,512.1,3,,,-inf,[],
+"#;
+
+const DATA_V0_1: &str = r#"experiment_name,scan_number,precursor_mz,precursor_charge,preds,log_probs,token_log_probs
+sample,1,411.6,2,M(+15.99)PEPC(+57.02)N(+.98)K,-0.8,"[-0.1, -0.1, -0.1, -0.1, -0.1, -0.1, -0.2]"
+"#;
@@ -3,37 +3,86 @@
 #[macro_use]
 mod common_parser;
 
+mod byonic;
+mod casanovo;
+mod common_xml;
+mod diann;
+mod export;
 mod fasta;
 mod general;
 mod helper_functions;
 mod identified_peptide;
+mod instanovo;
 mod maxquant;
 mod msfragger;
+mod mzident;
+mod mztab;
 mod novor;
 mod opair;
 mod peaks;
+mod pepnet;
+mod pepxml;
+mod pglyco;
 mod sage;
+mod spectronaut;
+mod target_decoy;
 
 use crate::*;
+pub use byonic::*;
+pub use casanovo::*;
+pub use diann::*;
+pub use export::*;
 pub use fasta::*;
 pub use general::*;
 pub use identified_peptide::*;
+pub use instanovo::*;
 pub use maxquant::*;
 pub use msfragger::*;
+pub use mzident::*;
+pub use mztab::*;
 pub use novor::*;
 pub use opair::*;
 pub use peaks::*;
+pub use pepnet::*;
+pub use pepxml::*;
+pub use pglyco::*;
 pub use sage::*;
+pub use spectronaut::*;
+pub use target_decoy::*;
 
+#[cfg(test)]
+mod byonic_tests;
+#[cfg(test)]
+mod casanovo_tests;
+#[cfg(test)]
+mod diann_tests;
+#[cfg(test)]
+mod export_tests;
+#[cfg(test)]
+mod general_tests;
+#[cfg(test)]
+mod instanovo_tests;
 #[cfg(test)]
 mod maxquant_tests;
 #[cfg(test)]
 mod msfragger_tests;
 #[cfg(test)]
+mod mzident_tests;
+#[cfg(test)]
+mod mztab_tests;
+#[cfg(test)]
 mod novor_tests;
 #[cfg(test)]
 mod opair_tests;
 #[cfg(test)]
 mod peaks_tests;
 #[cfg(test)]
+mod pepnet_tests;
+#[cfg(test)]
+mod pepxml_tests;
+#[cfg(test)]
+mod pglyco_tests;
+#[cfg(test)]
 mod sage_tests;
+#[cfg(test)]
+mod spectronaut_tests;
@@ -3,11 +3,13 @@ use std::path::{Path, PathBuf};
 //Spectrum	Spectrum.File	Peptide	Modified sequence	Extended.Peptide	Prev.AA	Next.AA	Peptide.Length	Charge	Retention	Observed.Mass	Calibrated.Observed.Mass	Observed.M.Z	Calibrated.Observed.M.Z	Calculated.Peptide.Mass	Calculated.M.Z	Delta.Mass	Expectation	Hyperscore	Nextscore	PeptideProphet.Probability	Number.of.Enzymatic.Termini	Number.of.Missed.Cleavages	Protein.Start	Protein.End	Intensity	Assigned.Modifications	Observed.Modifications	Purity	Is.Unique	Protein	Protein.ID	Entry.Name	Gene	Protein.Description	Mapped.Genes	Mapped.Proteins	condition	group
 use crate::{
     error::{Context, CustomError},
+    glycan::{GlycanCompositionNotation, MonoSaccharide},
     helper_functions::{explain_number_error, InvertResult},
+    modification::{Modification, SimpleModification},
     ontologies::CustomDatabase,
     peptide::{SemiAmbiguous, SloppyParsingParameters},
     system::{usize::Charge, Mass, MassOverCharge, Time},
-    LinearPeptide,
+    Chemical, LinearPeptide,
 };
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -72,7 +74,12 @@ format_family!(
     optional {
         condition: String, |location: Location, _| Ok(Some(location.get_string()));
         group: String, |location: Location, _| Ok(Some(location.get_string()));
+        /// The glycan composition as assigned by MSFragger-Glyco, this is placed on the peptide
+        glycan: Vec<(MonoSaccharide, isize)>, |location: Location, _| location.or_empty().parse_with(|location| GlycanCompositionNotation::MSFragger.parse(location.as_str()).map_err(|err| err.with_context(location.context())));
+        glycan_score: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        glycan_q_value: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
     }
+    post_process: MSFraggerData::place_glycan;
 );
 
 impl From<MSFraggerData> for IdentifiedPeptide {
@@ -84,6 +91,56 @@ impl From<MSFraggerData> for IdentifiedPeptide {
     }
 }
 
+impl MSFraggerData {
+    /// Place the glycan on the peptide, on the site of the assigned modification with the mass
+    /// of the glycan. Any other modifications on that site (the glycan mass as written in the
+    /// modified peptide) are removed.
+    /// # Errors
+    /// If there is no assigned modification with the mass of the glycan.
+    fn place_glycan(
+        source: &CsvLine,
+        mut parsed: Self,
+        _custom_database: Option<&CustomDatabase>,
+    ) -> Result<Self, CustomError> {
+        if let (Some(peptide), Some(glycan)) = (&mut parsed.peptide, &parsed.glycan) {
+            if glycan.is_empty() {
+                return Ok(parsed);
+            }
+            let mass = SimpleModification::Glycan(glycan.clone())
+                .formula()
+                .monoisotopic_mass()
+                .value;
+            let site = parsed
+                .assigned_modifications
+                .split(',')
+                .filter_map(|modification| {
+                    let (position, mass) = modification.trim().split_once('(')?;
+                    let index = position
+                        .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+                        .parse::<usize>()
+                        .ok()?;
+                    let mass = mass.strip_suffix(')')?.parse::<f64>().ok()?;
+                    Some((index, mass))
+                })
+                .find(|(index, assigned)| {
+                    (assigned - mass).abs() < 0.01 && (1..=peptide.len()).contains(index)
+                })
+                .ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid MSFragger line",
+                        format!("The glycan ({mass:.4} Da) could not be placed as there is no assigned modification with this mass"),
+                        source.full_context(),
+                    )
+                })?
+                .0;
+            peptide.sequence_mut()[site - 1].modifications = vec![Modification::Simple(
+                SimpleModification::Glycan(glycan.clone()),
+            )];
+        }
+        Ok(parsed)
+    }
+}
+
 /// All possible MSFragger versions
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
 pub enum MSFraggerVersion {
@@ -142,6 +199,9 @@ pub const V21: MSFraggerFormat = MSFraggerFormat {
     mapped_proteins: "mapped proteins",
     condition: Some("condition"),
     group: Some("group"),
+    glycan: Some("total glycan composition"),
+    glycan_score: Some("glycan score"),
+    glycan_q_value: Some("glycan q-value"),
 };
 
 /// v22
@@ -178,6 +238,9 @@ pub const V22: MSFraggerFormat = MSFraggerFormat {
     mapped_proteins: "mapped proteins",
     condition: Some("condition"),
     group: Some("group"),
+    glycan: Some("total glycan composition"),
+    glycan_score: Some("glycan score"),
+    glycan_q_value: Some("glycan q-value"),
 };
 
 /// The scans identifier for a MSFragger identification
@@ -4,6 +4,7 @@ use std::io::BufReader;
 use super::IdentifiedPeptideSource;
 
 use super::{csv::parse_csv_raw, msfragger, IdentifiedPeptide, MSFraggerData};
+use crate::{glycan::GlycanCompositionNotation, modification::SimpleModification, Modification};
 
 #[test]
 fn msfragger_v21() {
@@ -53,6 +54,45 @@ fn msfragger_detect() {
     }
 }
 
+#[test]
+fn msfragger_glyco() {
+    let reader = BufReader::new(DATA_GLYCO.as_bytes());
+    let lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    let peptides: Vec<MSFraggerData> = lines
+        .map(|line| MSFraggerData::parse_specific(&line.unwrap(), &msfragger::V22, None).unwrap())
+        .collect();
+    let glycan = GlycanCompositionNotation::Byonic
+        .parse("HexNAc(4)Hex(3)Fuc(1)")
+        .unwrap();
+    assert_eq!(peptides[0].glycan.as_ref(), Some(&glycan));
+    assert_eq!(peptides[0].glycan_q_value, Some(0.0012));
+    let peptide = peptides[0].peptide.as_ref().unwrap();
+    assert_eq!(
+        peptide.sequence()[4].modifications,
+        vec![Modification::Simple(SimpleModification::Glycan(glycan))]
+    );
+    assert!(peptide
+        .sequence()
+        .iter()
+        .enumerate()
+        .all(|(index, element)| index == 4 || element.modifications.is_empty()));
+    assert_eq!(peptides[1].glycan, None);
+    assert_eq!(peptides[1].peptide, None);
+
+    let invalid = DATA_GLYCO.replace("5N(1444.5339)", "5N(1216.4229)");
+    let reader = BufReader::new(invalid.as_bytes());
+    let mut lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    assert!(
+        MSFraggerData::parse_specific(&lines.next().unwrap().unwrap(), &msfragger::V22, None)
+            .is_err()
+    );
+}
+
+const DATA_GLYCO: &str = r"Spectrum	Spectrum File	Peptide	Modified Peptide	Extended Peptide	Prev AA	Next AA	Peptide Length	Charge	Retention	Observed Mass	Calibrated Observed Mass	Observed M/Z	Calibrated Observed M/Z	Calculated Peptide Mass	Calculated M/Z	Delta Mass	Expectation	Hyperscore	Nextscore	Probability	Number of Enzymatic Termini	Number of Missed Cleavages	Protein Start	Protein End	Intensity	Assigned Modifications	Observed Modifications	Purity	Is Unique	Protein	Protein ID	Entry Name	Gene	Protein Description	Mapped Genes	Mapped Proteins	Total Glycan Composition	Glycan Score	Glycan q-value
+20230412_IgG_glyco_01.08123.08123.3	D:\glyco\IgG\interact.pep.xml	EEQYNSTYR	EEQYN[1559]STYR	K.EEQYNSTYR.V	K	V	9	3	1710.9843	2633.0315	2633.0301	878.6844	878.6840	2633.0296	878.6838	0.0005	0.00000104	31.2840	12.5100	0.9990	2	0	176	184	12345678	5N(1444.5339)	Glycan: HexNAc(4)Hex(3)Fuc(1)	0.00	false	sp|P01857|IGHG1_HUMAN	P01857	IGHG1_HUMAN	IGHG1	Immunoglobulin heavy constant gamma 1			HexNAc(4)Hex(3)Fuc(1) % 1444.5339	3.41	0.0012
+20230412_IgG_glyco_01.08200.08200.2	D:\glyco\IgG\interact.pep.xml	ALPAPIEK		R.ALPAPIEK.T	R	T	8	2	1722.0012	837.4934	837.4930	419.7540	419.7538	837.4963	419.7554	-0.0033	0.00120000	22.1040	8.0020	0.9870	2	0	236	243	2345678			0.00	false	sp|P01857|IGHG1_HUMAN	P01857	IGHG1_HUMAN	IGHG1	Immunoglobulin heavy constant gamma 1					
+";
+
 const DATA_V21_MANUAL: &str = r"Spectrum	Spectrum File	Peptide	Modified Peptide	Extended Peptide	Prev AA	Next AA	Peptide Length	Charge	Retention	Observed Mass	Calibrated Observed Mass	Observed M/Z	Calibrated Observed M/Z	Calculated Peptide Mass	Calculated M/Z	Delta Mass	Expectation	Hyperscore	Nextscore	PeptideProphet Probability	Number of Enzymatic Termini	Number of Missed Cleavages	Protein Start	Protein End	Intensity	Assigned Modifications	Observed Modifications	Purity	Is Unique	Protein	Protein ID	Entry Name	Gene	Protein Description	Mapped Genes	Mapped Proteins	condition	group
 20240621_EX2_UM2_6579035_SA_EXT00_SS25_1A_Alk14_R1.21346.21346.2	D:\06-June\6579035\SS25\MSFragger_4-24\1_alk14_1\interact.pep.xml	GAQLSGGR	n[621]GAQLSGGR	.GAQLSGGR.GAPEPAQT	M	G	8	2	1811.7999	1364.8169	1364.8121	683.4157	683.4133	1364.814	683.4143	-0.0018	1.449549e-05	24.446	10.854	1	2	0	2	9	478745568	N-term(DB14 (N-term))		0	true	sp|A0A0U1RRL7|MMPOS_HUMAN	A0A0U1RRL7	MMPOS_HUMAN	MMP24OS	Protein MMP24OS			1_alk14_1	1_alk14
 20240621_EX2_UM2_6579035_SA_EXT00_SS25_1B_Alk14_R2.20911.20911.2	D:\06-June\6579035\SS25\MSFragger_4-24\1_alk14_2\interact.pep.xml	GAQLSGGR	n[621]GAQLSGGR	.GAQLSGGR.GAPEPAQT	M	G	8	2	1812.5955	1364.8169	1364.8116	683.4157	683.4131	1364.814	683.4143	-0.0023	1.071478e-05	24.441	10.843	1	2	0	2	9	405120480	N-term(DB14 (N-term))		0	true	sp|A0A0U1RRL7|MMPOS_HUMAN	A0A0U1RRL7	MMPOS_HUMAN	MMP24OS	Protein MMP24OS			1_alk14_2	1_alk14
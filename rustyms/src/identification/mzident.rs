@@ -0,0 +1,467 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Context, CustomError},
+    modification::{Ontology, SimpleModification},
+    ontologies::CustomDatabase,
+    peptide::SemiAmbiguous,
+    system::{dalton, e, isize::Charge, mz, Mass, MassOverCharge, Time},
+    CheckedAminoAcid, LinearPeptide, SequenceElement, SequencePosition,
+};
+
+use super::{
+    common_xml::{descendants, optional_number, parse_number, required_attribute, LocalName},
+    helper_functions::{read_file, read_to_string},
+    IdentifiedPeptide, MetaData,
+};
+
+/// A single peptide spectrum match (a `SpectrumIdentificationItem`) from a mzIdentML file
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MZIdentMLData {
+    /// The id of the spectrum identification item
+    pub id: String,
+    /// The identified peptide
+    pub peptide: LinearPeptide<SemiAmbiguous>,
+    /// The precursor charge, negative for negative ion mode
+    pub z: Charge,
+    /// The rank of this identification for its spectrum
+    pub rank: usize,
+    /// If this identification passed the threshold set by the search engine
+    pub pass_threshold: bool,
+    /// The experimental precursor m/z
+    pub experimental_mz: MassOverCharge,
+    /// The calculated precursor m/z
+    pub calculated_mz: Option<MassOverCharge>,
+    /// The native id of the spectrum
+    pub spectrum_id: String,
+    /// The scan number(s), from a 'scan number(s)' cvParam or the native id
+    pub scan_number: Vec<usize>,
+    /// The spectrum title, if given
+    pub title: Option<String>,
+    /// The retention time, if given
+    pub rt: Option<Time>,
+    /// The location of the raw file
+    pub raw_file: Option<PathBuf>,
+    /// The search engine that generated this identification
+    pub search_engine: Option<String>,
+    /// All proteins this peptide is found in
+    pub proteins: Vec<MZIdentMLProteinEvidence>,
+    /// All numeric cvParams and userParams on this identification, mostly scores
+    pub scores: Vec<MZIdentMLScore>,
+}
+
+/// The evidence for a peptide being part of a protein
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct MZIdentMLProteinEvidence {
+    /// The protein accession
+    pub accession: String,
+    /// The protein description, if given
+    pub description: Option<String>,
+    /// If this protein is a decoy
+    pub decoy: bool,
+    /// The start position (1 based) of the peptide in the protein
+    pub start: Option<usize>,
+    /// The end position (1 based, inclusive) of the peptide in the protein
+    pub end: Option<usize>,
+    /// The residue before the peptide ('-' for the protein N terminus)
+    pub pre: Option<char>,
+    /// The residue after the peptide ('-' for the protein C terminus)
+    pub post: Option<char>,
+}
+
+/// A numeric parameter of an identification
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MZIdentMLScore {
+    /// The name of the parameter
+    pub name: String,
+    /// The accession, if this is a cvParam
+    pub accession: Option<String>,
+    /// The value
+    pub value: f64,
+}
+
+impl MZIdentMLData {
+    /// Parse a mzIdentML file, the file is decompressed if the extension is `.gz`.
+    /// # Errors
+    /// If the file could not be opened or is not a valid mzIdentML file.
+    pub fn parse_file(
+        path: impl AsRef<Path>,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        Self::parse_str(&read_file(path.as_ref())?, custom_database)
+    }
+
+    /// Parse a mzIdentML file from a raw reader.
+    /// # Errors
+    /// If the reader could not be read or does not contain a valid mzIdentML file.
+    pub fn parse_reader(
+        reader: impl Read,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        Self::parse_str(&read_to_string(reader)?, custom_database)
+    }
+
+    /// Parse the text of a mzIdentML file.
+    /// # Errors
+    /// If the text is not a valid mzIdentML file.
+    pub fn parse_str(
+        text: &str,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        let document = Document::parse(text).map_err(|err| {
+            CustomError::error(
+                "Invalid mzIdentML file",
+                format!("The file is not valid XML: {err}"),
+                Context::none(),
+            )
+        })?;
+        let root = document.root_element();
+        if root.tag_name().name() != "MzIdentML" {
+            return Err(CustomError::error(
+                "Invalid mzIdentML file",
+                "The root element is not 'MzIdentML'",
+                Context::show(root.tag_name().name()),
+            ));
+        }
+
+        let search_engine = descendants(root, "AnalysisSoftware")
+            .flat_map(|software| descendants(software, "SoftwareName"))
+            .flat_map(|name| name.children().filter(Node::is_element))
+            .find_map(|param| param.attribute("name").or_else(|| param.attribute("value")))
+            .map(ToString::to_string);
+
+        let search_modifications = descendants(root, "SearchModification")
+            .map(|node| {
+                Ok((
+                    parse_number::<f64>(node, "massDelta")?,
+                    node.children()
+                        .filter(|c| c.has_tag_name_local("cvParam"))
+                        .collect::<Vec<_>>(),
+                ))
+            })
+            .collect::<Result<Vec<_>, CustomError>>()?;
+
+        let proteins: HashMap<&str, (&str, Option<&str>)> = descendants(root, "DBSequence")
+            .map(|node| {
+                Ok((
+                    required_attribute(node, "id")?,
+                    (
+                        required_attribute(node, "accession")?,
+                        cv_param(node, "MS:1001088").and_then(|p| p.attribute("value")),
+                    ),
+                ))
+            })
+            .collect::<Result<_, CustomError>>()?;
+
+        let peptides: HashMap<&str, LinearPeptide<SemiAmbiguous>> = descendants(root, "Peptide")
+            .map(|node| {
+                Ok((
+                    required_attribute(node, "id")?,
+                    parse_peptide(node, &search_modifications, custom_database)?,
+                ))
+            })
+            .collect::<Result<_, CustomError>>()?;
+
+        let evidence: HashMap<&str, MZIdentMLProteinEvidence> =
+            descendants(root, "PeptideEvidence")
+                .map(|node| {
+                    let (accession, description) = node
+                        .attribute("dBSequence_ref")
+                        .and_then(|r| proteins.get(r))
+                        .copied()
+                        .unwrap_or_default();
+                    Ok((
+                        required_attribute(node, "id")?,
+                        MZIdentMLProteinEvidence {
+                            accession: accession.to_string(),
+                            description: description.map(ToString::to_string),
+                            decoy: node.attribute("isDecoy") == Some("true"),
+                            start: optional_number(node, "start")?,
+                            end: optional_number(node, "end")?,
+                            pre: node.attribute("pre").and_then(|v| v.chars().next()),
+                            post: node.attribute("post").and_then(|v| v.chars().next()),
+                        },
+                    ))
+                })
+                .collect::<Result<_, CustomError>>()?;
+
+        let spectra_data: HashMap<&str, &str> = descendants(root, "SpectraData")
+            .map(|node| {
+                Ok((
+                    required_attribute(node, "id")?,
+                    required_attribute(node, "location")?,
+                ))
+            })
+            .collect::<Result<_, CustomError>>()?;
+
+        let mut output = Vec::new();
+        for result in descendants(root, "SpectrumIdentificationResult") {
+            let spectrum_id = required_attribute(result, "spectrumID")?;
+            let raw_file = result
+                .attribute("spectraData_ref")
+                .and_then(|r| spectra_data.get(r))
+                .map(PathBuf::from);
+            let title = cv_param(result, "MS:1000796")
+                .and_then(|p| p.attribute("value"))
+                .map(ToString::to_string);
+            let rt = cv_param(result, "MS:1000894")
+                .or_else(|| cv_param(result, "MS:1000016"))
+                .map(parse_time)
+                .transpose()?;
+            let scan_number = cv_param(result, "MS:1001115")
+                .and_then(|p| p.attribute("value"))
+                .map_or_else(
+                    || scan_number_from_native_id(spectrum_id),
+                    |v| v.split(',').filter_map(|s| s.trim().parse().ok()).collect(),
+                );
+
+            for item in result
+                .children()
+                .filter(|c| c.has_tag_name_local("SpectrumIdentificationItem"))
+            {
+                let peptide_ref = required_attribute(item, "peptide_ref")?;
+                let peptide = peptides.get(peptide_ref).cloned().ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid mzIdentML file",
+                        "The referenced peptide does not exist",
+                        Context::show(peptide_ref),
+                    )
+                })?;
+                output.push(Self {
+                    id: required_attribute(item, "id")?.to_string(),
+                    peptide,
+                    z: Charge::new::<e>(parse_number(item, "chargeState")?),
+                    rank: parse_number(item, "rank")?,
+                    pass_threshold: item.attribute("passThreshold") == Some("true"),
+                    experimental_mz: MassOverCharge::new::<mz>(parse_number(
+                        item,
+                        "experimentalMassToCharge",
+                    )?),
+                    calculated_mz: optional_number(item, "calculatedMassToCharge")?
+                        .map(MassOverCharge::new::<mz>),
+                    spectrum_id: spectrum_id.to_string(),
+                    scan_number: scan_number.clone(),
+                    title: title.clone(),
+                    rt,
+                    raw_file: raw_file.clone(),
+                    search_engine: search_engine.clone(),
+                    proteins: item
+                        .children()
+                        .filter(|c| c.has_tag_name_local("PeptideEvidenceRef"))
+                        .filter_map(|c| c.attribute("peptideEvidence_ref"))
+                        .filter_map(|r| evidence.get(r).cloned())
+                        .collect(),
+                    scores: item
+                        .children()
+                        .filter(|c| {
+                            c.has_tag_name_local("cvParam") || c.has_tag_name_local("userParam")
+                        })
+                        .filter_map(|c| {
+                            Some(MZIdentMLScore {
+                                name: c.attribute("name")?.to_string(),
+                                accession: c.attribute("accession").map(ToString::to_string),
+                                value: c.attribute("value")?.parse().ok()?,
+                            })
+                        })
+                        .collect(),
+                });
+            }
+        }
+        Ok(output)
+    }
+
+    /// Get a normalised score (0..=1) for this identification, based on the first known score
+    /// that is present. Q-values and e-values are transformed with `1 - value` and PEAKS -10lgP
+    /// scores are transformed back into a probability.
+    pub fn normalised_score(&self) -> Option<f64> {
+        const INVERTED: &[&str] = &[
+            "MS:1002354", // PSM-level q-value
+            "MS:1001491", // percolator:Q value
+            "MS:1002055", // MS-GF:PepQValue
+            "MS:1002054", // MS-GF:QValue
+            "MS:1001868", // distinct peptide-level q-value
+            "MS:1002052", // MS-GF:SpecEValue
+            "MS:1002053", // MS-GF:EValue
+            "MS:1001172", // Mascot:expectation value
+            "MS:1001330", // X!Tandem:expect
+            "MS:1001328", // OMSSA:evalue
+        ];
+        let find = |accession: &str| {
+            self.scores
+                .iter()
+                .find(|s| s.accession.as_deref() == Some(accession))
+                .map(|s| s.value)
+        };
+        INVERTED
+            .iter()
+            .find_map(|accession| find(accession))
+            .map(|value| 1.0 - value.clamp(0.0, 1.0))
+            .or_else(|| {
+                // PEAKS:peptideScore, -10lgP
+                find("MS:1001950").map(|value| 1.0 - 10.0_f64.powf(-value / 10.0).min(1.0))
+            })
+    }
+}
+
+impl From<MZIdentMLData> for IdentifiedPeptide {
+    fn from(value: MZIdentMLData) -> Self {
+        Self {
+            score: value.normalised_score(),
+            metadata: MetaData::MZIdentML(value),
+        }
+    }
+}
+
+/// Get the direct child cvParam with the given accession
+fn cv_param<'a, 'input>(node: Node<'a, 'input>, accession: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|c| c.has_tag_name_local("cvParam") && c.attribute("accession") == Some(accession))
+}
+
+/// Parse a time cvParam, taking the unit into account (seconds if no unit is given)
+/// # Errors
+/// If the value is not a number.
+fn parse_time(param: Node) -> Result<Time, CustomError> {
+    let value: f64 = parse_number(param, "value")?;
+    Ok(
+        match (
+            param.attribute("unitAccession"),
+            param.attribute("unitName"),
+        ) {
+            (Some("UO:0000031"), _) | (_, Some("minute")) => {
+                Time::new::<crate::system::time::min>(value)
+            }
+            (Some("UO:0000032"), _) | (_, Some("hour")) => {
+                Time::new::<crate::system::time::h>(value)
+            }
+            _ => Time::new::<crate::system::time::s>(value),
+        },
+    )
+}
+
+/// Get the scan number from a native id (eg 'controllerType=0 controllerNumber=1 scan=123')
+fn scan_number_from_native_id(native_id: &str) -> Vec<usize> {
+    native_id
+        .split(' ')
+        .filter_map(|part| part.strip_prefix("scan="))
+        .filter_map(|n| n.parse().ok())
+        .collect()
+}
+
+/// Parse a `Peptide` element into a peptide
+/// # Errors
+/// If the sequence contains invalid amino acids or the modifications could not be recognised.
+fn parse_peptide(
+    node: Node,
+    search_modifications: &[(f64, Vec<Node>)],
+    custom_database: Option<&CustomDatabase>,
+) -> Result<LinearPeptide<SemiAmbiguous>, CustomError> {
+    let sequence = node
+        .children()
+        .find(|c| c.has_tag_name_local("PeptideSequence"))
+        .and_then(|c| c.text())
+        .unwrap_or_default()
+        .trim();
+    let mut peptide: LinearPeptide<SemiAmbiguous> = LinearPeptide::new(
+        sequence
+            .chars()
+            .map(|c| {
+                CheckedAminoAcid::try_from(c)
+                    .map(|aa| SequenceElement::new(aa, None))
+                    .map_err(|()| {
+                        CustomError::error(
+                            "Invalid mzIdentML peptide",
+                            format!("The character '{c}' is not a valid amino acid"),
+                            Context::show(sequence),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    );
+
+    for modification in node
+        .children()
+        .filter(|c| c.has_tag_name_local("Modification"))
+    {
+        let location: usize = optional_number(modification, "location")?.ok_or_else(|| {
+            CustomError::error(
+                "Invalid mzIdentML peptide",
+                "Modifications without a location are not supported",
+                Context::show(sequence),
+            )
+        })?;
+        let position = match location {
+            0 => SequencePosition::NTerm,
+            l if l > peptide.len() => SequencePosition::CTerm,
+            l => SequencePosition::Index(l - 1),
+        };
+        peptide.add_simple_modification(
+            position,
+            parse_modification(modification, search_modifications, custom_database)?,
+        );
+    }
+    Ok(peptide)
+}
+
+/// Parse a `Modification` element, using the cvParams if these can be recognised, otherwise use
+/// the cvParams of the search modification with the same mass, and as last resort use the mass.
+/// # Errors
+/// If the modification could not be recognised and has no mass.
+fn parse_modification(
+    node: Node,
+    search_modifications: &[(f64, Vec<Node>)],
+    custom_database: Option<&CustomDatabase>,
+) -> Result<SimpleModification, CustomError> {
+    let mass: Option<f64> = optional_number(node, "monoisotopicMassDelta")?;
+    node.children()
+        .filter(|c| c.has_tag_name_local("cvParam"))
+        .find_map(|param| modification_from_cv_param(param, custom_database))
+        .or_else(|| {
+            let mass = mass?;
+            search_modifications
+                .iter()
+                .filter(|(delta, _)| (delta - mass).abs() < 1e-4)
+                .flat_map(|(_, params)| params)
+                .find_map(|param| modification_from_cv_param(*param, custom_database))
+        })
+        .or_else(|| mass.map(|m| SimpleModification::Mass(Mass::new::<dalton>(m).into())))
+        .ok_or_else(|| {
+            CustomError::error(
+                "Invalid mzIdentML modification",
+                "The modification could not be recognised and has no mass",
+                Context::show(node.attribute("location").unwrap_or_default()),
+            )
+        })
+}
+
+/// Find the modification for a cvParam, supports Unimod, PSI-MOD, and XL-MOD
+fn modification_from_cv_param(
+    param: Node,
+    custom_database: Option<&CustomDatabase>,
+) -> Option<SimpleModification> {
+    let accession = param.attribute("accession")?;
+    let (ontology, id) = accession
+        .strip_prefix("UNIMOD:")
+        .map(|id| (Ontology::Unimod, id))
+        .or_else(|| {
+            accession
+                .strip_prefix("MOD:")
+                .map(|id| (Ontology::Psimod, id))
+        })
+        .or_else(|| {
+            accession
+                .strip_prefix("XLMOD:")
+                .map(|id| (Ontology::Xlmod, id))
+        })?;
+    id.parse()
+        .ok()
+        .and_then(|id| ontology.find_id(id, custom_database))
+        .or_else(|| ontology.find_name(param.attribute("name")?, custom_database))
+}
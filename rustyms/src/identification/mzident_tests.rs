@@ -0,0 +1,180 @@
+#![allow(clippy::missing_panics_doc)]
+use crate::system::{e, isize, time::s, usize::Charge};
+
+use super::{IdentifiedPeptide, MZIdentMLData};
+
+#[test]
+fn mzident() {
+    let peptides = MZIdentMLData::parse_str(DATA, None).unwrap();
+    assert_eq!(peptides.len(), 3);
+
+    let first = &peptides[0];
+    assert_eq!(
+        first.peptide.to_string(),
+        "[U:Acetyl]-PEPM[U:Oxidation]C[U:Carbamidomethyl]K"
+    );
+    assert_eq!(first.z, isize::Charge::new::<e>(2));
+    assert_eq!(first.rank, 1);
+    assert!(first.pass_threshold);
+    assert_eq!(first.scan_number, vec![1234]);
+    assert_eq!(first.title.as_deref(), Some("spectrum 1234"));
+    assert!((first.rt.unwrap().get::<s>() - 1800.0).abs() < 1e-6);
+    assert_eq!(first.search_engine.as_deref(), Some("MS-GF+"));
+    assert_eq!(first.proteins.len(), 2);
+    assert_eq!(first.proteins[0].accession, "sp|P12345|PROT_HUMAN");
+    assert_eq!(
+        first.proteins[0].description.as_deref(),
+        Some("Some protein")
+    );
+    assert_eq!(first.proteins[0].start, Some(10));
+    assert_eq!(first.proteins[0].pre, Some('K'));
+    assert!(first.proteins[1].decoy);
+    assert_eq!(first.scores.len(), 3);
+
+    // Resolved with the search modification, and as a plain mass
+    let second = &peptides[1];
+    assert_eq!(second.peptide.to_string(), "S[U:Phospho]AMPLER");
+    assert!(second.scan_number.is_empty());
+    let third = &peptides[2];
+    assert_eq!(third.rank, 2);
+    assert!(
+        (third.peptide.sequence()[1].modifications[0]
+            .formula()
+            .monoisotopic_mass()
+            .value
+            - 12.3456)
+            .abs()
+            < 1e-6
+    );
+
+    let identified: IdentifiedPeptide = first.clone().into();
+    assert!((identified.score.unwrap() - 0.999).abs() < 1e-6);
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(2)));
+    assert_eq!(identified.scan_indices(), Some(vec![1234]));
+    assert_eq!(
+        identified.spectrum_native_ids(),
+        Some(vec![
+            "controllerType=0 controllerNumber=1 scan=1234".to_string()
+        ])
+    );
+    assert_eq!(
+        identified.raw_file().unwrap().to_string_lossy(),
+        "file:///data/run01.mzML"
+    );
+    assert!(identified.peptide().is_some());
+}
+
+#[test]
+fn mzident_negative_charge() {
+    let data = DATA.replace(
+        r#"chargeState="2" id="SII_2_2""#,
+        r#"chargeState="-2" id="SII_2_2""#,
+    );
+    let peptides = MZIdentMLData::parse_str(&data, None).unwrap();
+    assert_eq!(peptides[2].z, isize::Charge::new::<e>(-2));
+    let identified: IdentifiedPeptide = peptides[2].clone().into();
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(2)));
+    assert_eq!(
+        identified.signed_charge(),
+        Some(isize::Charge::new::<e>(-2))
+    );
+}
+
+#[test]
+fn mzident_invalid() {
+    assert!(MZIdentMLData::parse_str("<NotMzIdentML/>", None).is_err());
+    assert!(MZIdentMLData::parse_str("<MzIdentML><Peptide", None).is_err());
+    assert!(MZIdentMLData::parse_str(
+        r#"<MzIdentML><SequenceCollection><Peptide id="p"><PeptideSequence>PEP1</PeptideSequence></Peptide></SequenceCollection></MzIdentML>"#,
+        None
+    )
+    .is_err());
+}
+
+const DATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MzIdentML id="" version="1.1.0" xmlns="http://psidev.info/psi/pi/mzIdentML/1.1">
+  <cvList>
+    <cv id="PSI-MS" uri="https://raw.githubusercontent.com/HUPO-PSI/psi-ms-CV/master/psi-ms.obo" fullName="PSI-MS"/>
+    <cv id="UNIMOD" uri="http://www.unimod.org/obo/unimod.obo" fullName="UNIMOD"/>
+    <cv id="UO" uri="https://raw.githubusercontent.com/bio-ontology-research-group/unit-ontology/master/unit.obo" fullName="UNIT-ONTOLOGY"/>
+  </cvList>
+  <AnalysisSoftwareList>
+    <AnalysisSoftware version="Release (v2019.07.03)" name="MS-GF+" id="ID_software">
+      <SoftwareName>
+        <cvParam cvRef="PSI-MS" accession="MS:1002048" name="MS-GF+"/>
+      </SoftwareName>
+    </AnalysisSoftware>
+  </AnalysisSoftwareList>
+  <SequenceCollection>
+    <DBSequence length="300" searchDatabase_ref="SearchDB_1" accession="sp|P12345|PROT_HUMAN" id="DBSeq1">
+      <cvParam cvRef="PSI-MS" accession="MS:1001088" name="protein description" value="Some protein"/>
+    </DBSequence>
+    <DBSequence length="300" searchDatabase_ref="SearchDB_1" accession="XXX_sp|P12345|PROT_HUMAN" id="DBSeq2"/>
+    <Peptide id="Pep1">
+      <PeptideSequence>PEPMCK</PeptideSequence>
+      <Modification monoisotopicMassDelta="42.010565" location="0">
+        <cvParam cvRef="UNIMOD" accession="UNIMOD:1" name="Acetyl"/>
+      </Modification>
+      <Modification monoisotopicMassDelta="15.994915" location="4">
+        <cvParam cvRef="UNIMOD" accession="UNIMOD:35" name="Oxidation"/>
+      </Modification>
+      <Modification monoisotopicMassDelta="57.021464" location="5">
+        <cvParam cvRef="UNIMOD" accession="UNIMOD:4" name="Carbamidomethyl"/>
+      </Modification>
+    </Peptide>
+    <Peptide id="Pep2">
+      <PeptideSequence>SAMPLER</PeptideSequence>
+      <Modification monoisotopicMassDelta="79.966331" location="1">
+        <cvParam cvRef="PSI-MS" accession="MS:1001460" name="unknown modification"/>
+      </Modification>
+    </Peptide>
+    <Peptide id="Pep3">
+      <PeptideSequence>SAMPLER</PeptideSequence>
+      <Modification monoisotopicMassDelta="12.3456" location="2">
+        <cvParam cvRef="PSI-MS" accession="MS:1001460" name="unknown modification"/>
+      </Modification>
+    </Peptide>
+    <PeptideEvidence isDecoy="false" post="A" pre="K" end="15" start="10" peptide_ref="Pep1" dBSequence_ref="DBSeq1" id="PepEv1"/>
+    <PeptideEvidence isDecoy="true" post="A" pre="K" end="15" start="10" peptide_ref="Pep1" dBSequence_ref="DBSeq2" id="PepEv2"/>
+    <PeptideEvidence isDecoy="false" peptide_ref="Pep2" dBSequence_ref="DBSeq1" id="PepEv3"/>
+    <PeptideEvidence isDecoy="false" peptide_ref="Pep3" dBSequence_ref="DBSeq1" id="PepEv4"/>
+  </SequenceCollection>
+  <AnalysisProtocolCollection>
+    <SpectrumIdentificationProtocol analysisSoftware_ref="ID_software" id="SearchProtocol_1">
+      <ModificationParams>
+        <SearchModification residues="S" massDelta="79.966331" fixedMod="false">
+          <cvParam cvRef="UNIMOD" accession="UNIMOD:21" name="Phospho"/>
+        </SearchModification>
+      </ModificationParams>
+    </SpectrumIdentificationProtocol>
+  </AnalysisProtocolCollection>
+  <DataCollection>
+    <Inputs>
+      <SpectraData location="file:///data/run01.mzML" id="SID_1"/>
+    </Inputs>
+    <AnalysisData>
+      <SpectrumIdentificationList id="SI_LIST_1">
+        <SpectrumIdentificationResult spectraData_ref="SID_1" spectrumID="controllerType=0 controllerNumber=1 scan=1234" id="SIR_1">
+          <SpectrumIdentificationItem passThreshold="true" rank="1" peptide_ref="Pep1" calculatedMassToCharge="395.1612" experimentalMassToCharge="395.1620" chargeState="2" id="SII_1_1">
+            <PeptideEvidenceRef peptideEvidence_ref="PepEv1"/>
+            <PeptideEvidenceRef peptideEvidence_ref="PepEv2"/>
+            <cvParam cvRef="PSI-MS" accession="MS:1002049" name="MS-GF:RawScore" value="120"/>
+            <cvParam cvRef="PSI-MS" accession="MS:1002052" name="MS-GF:SpecEValue" value="0.001"/>
+            <userParam name="IsotopeError" value="0"/>
+          </SpectrumIdentificationItem>
+          <cvParam cvRef="PSI-MS" accession="MS:1000796" name="spectrum title" value="spectrum 1234"/>
+          <cvParam cvRef="PSI-MS" accession="MS:1000016" name="scan start time" value="30" unitCvRef="UO" unitAccession="UO:0000031" unitName="minute"/>
+        </SpectrumIdentificationResult>
+        <SpectrumIdentificationResult spectraData_ref="SID_1" spectrumID="index=5" id="SIR_2">
+          <SpectrumIdentificationItem passThreshold="false" rank="1" peptide_ref="Pep2" experimentalMassToCharge="420.1" chargeState="2" id="SII_2_1">
+            <PeptideEvidenceRef peptideEvidence_ref="PepEv3"/>
+          </SpectrumIdentificationItem>
+          <SpectrumIdentificationItem passThreshold="false" rank="2" peptide_ref="Pep3" experimentalMassToCharge="420.1" chargeState="2" id="SII_2_2">
+            <PeptideEvidenceRef peptideEvidence_ref="PepEv4"/>
+          </SpectrumIdentificationItem>
+        </SpectrumIdentificationResult>
+      </SpectrumIdentificationList>
+    </AnalysisData>
+  </DataCollection>
+</MzIdentML>
+"#;
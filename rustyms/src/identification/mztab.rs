@@ -0,0 +1,759 @@
+use std::{
+    collections::HashMap,
+    io::{BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Context, CustomError},
+    modification::{Modification, Ontology, SimpleModification},
+    molecular_formula,
+    ontologies::CustomDatabase,
+    peptide::SemiAmbiguous,
+    system::{dalton, e, isize::Charge, mz, time::s, Mass, MassOverCharge, Time},
+    CheckedAminoAcid, Chemical, LinearPeptide, MolecularFormula, SequenceElement, SequencePosition,
+};
+
+use super::{
+    helper_functions::{read_file, read_to_string},
+    IdentifiedPeptide, MetaData,
+};
+
+/// A single PSM from the PSM section of a mzTab file
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MZTabData {
+    /// The identified peptide
+    pub peptide: LinearPeptide<SemiAmbiguous>,
+    /// The PSM id
+    pub psm_id: String,
+    /// The protein accession, if given
+    pub accession: Option<String>,
+    /// If the peptide is unique for this protein, if given
+    pub unique: Option<bool>,
+    /// The protein database, if given
+    pub database: Option<String>,
+    /// The search engine(s) used to identify this PSM
+    pub search_engine: Vec<String>,
+    /// The search engine scores, in the order of the `search_engine_score[n]` columns
+    pub search_engine_scores: Vec<Option<f64>>,
+    /// The first search engine score normalised to 0..=1, if the type of the score is known
+    pub normalised_score: Option<f64>,
+    /// The retention time, if given
+    pub rt: Option<Time>,
+    /// The precursor charge, if given, negative for negative ion mode
+    pub z: Option<Charge>,
+    /// The experimental precursor m/z, if given
+    pub experimental_mz: Option<MassOverCharge>,
+    /// The calculated precursor m/z, if given
+    pub calculated_mz: Option<MassOverCharge>,
+    /// The spectra for this PSM, with the location of the raw file (if known) and the spectrum id
+    pub spectra_ref: Vec<(Option<PathBuf>, String)>,
+    /// The residue before the peptide in the protein
+    pub pre: Option<char>,
+    /// The residue after the peptide in the protein
+    pub post: Option<char>,
+    /// The start position (1 based) in the protein
+    pub start: Option<usize>,
+    /// The end position (1 based, inclusive) in the protein
+    pub end: Option<usize>,
+}
+
+impl MZTabData {
+    /// Parse the PSM section of a mzTab file, the file is decompressed if the extension is `.gz`.
+    /// # Errors
+    /// If the file could not be opened or is not a valid mzTab file.
+    pub fn parse_file(
+        path: impl AsRef<Path>,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        Self::parse_str(&read_file(path.as_ref())?, custom_database)
+    }
+
+    /// Parse the PSM section of a mzTab file from a raw reader.
+    /// # Errors
+    /// If the reader could not be read or does not contain a valid mzTab file.
+    pub fn parse_reader(
+        reader: impl Read,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        Self::parse_str(&read_to_string(reader)?, custom_database)
+    }
+
+    /// Parse the PSM section of the text of a mzTab file. All other sections are ignored.
+    /// # Errors
+    /// If the text is not a valid mzTab file.
+    pub fn parse_str(
+        text: &str,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Vec<Self>, CustomError> {
+        let section = psm_section(text)?;
+        section
+            .rows
+            .iter()
+            .map(|(line_index, line, row)| {
+                Self::parse_row(row, &section.ms_runs, &section.score_names, custom_database)
+                    .map_err(|err| {
+                        CustomError::error(
+                            "Invalid mzTab PSM line",
+                            err,
+                            Context::full_line(*line_index, *line),
+                        )
+                    })
+            })
+            .collect()
+    }
+
+    /// Parse a single PSM row
+    /// # Errors
+    /// If any column contains an invalid value.
+    fn parse_row(
+        row: &HashMap<&str, &str>,
+        ms_runs: &HashMap<String, PathBuf>,
+        score_names: &HashMap<String, String>,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<Self, String> {
+        let column = |name: &str| row.get(name).copied().and_then(optional);
+        let number = |name: &str| {
+            column(name)
+                .map(|v| {
+                    v.split('|')
+                        .next()
+                        .unwrap_or_default()
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|_| format!("The column '{name}' is not a valid number"))
+                })
+                .transpose()
+        };
+        let sequence = column("sequence").ok_or("The sequence column is required")?;
+
+        let search_engine_scores: Vec<Option<f64>> = (1..)
+            .map(|i| format!("search_engine_score[{i}]"))
+            .take_while(|name| row.contains_key(name.as_str()))
+            .map(|name| number(&name))
+            .collect::<Result<_, _>>()?;
+        let normalised_score = search_engine_scores
+            .first()
+            .copied()
+            .flatten()
+            .and_then(|v| {
+                let name = score_names
+                    .get("[1]")
+                    .map(|n| n.to_ascii_lowercase())
+                    .unwrap_or_default();
+                if ["q-value", "e-value", "evalue", "expect"]
+                    .iter()
+                    .any(|n| name.contains(n))
+                {
+                    Some(1.0 - v.clamp(0.0, 1.0))
+                } else {
+                    (-1.0..=1.0).contains(&v).then_some(v)
+                }
+            });
+
+        let residue = |name: &str| column(name).and_then(|v| v.chars().next());
+        Ok(Self {
+            peptide: parse_peptide(
+                sequence,
+                column("modifications").unwrap_or_default(),
+                custom_database,
+            )?,
+            psm_id: column("PSM_ID").unwrap_or_default().to_string(),
+            accession: column("accession").map(ToString::to_string),
+            unique: column("unique").map(|v| v == "1"),
+            database: column("database").map(ToString::to_string),
+            search_engine: column("search_engine")
+                .map(|v| {
+                    v.split('|')
+                        .map(|p| parameter_name(p).to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            search_engine_scores,
+            normalised_score,
+            rt: number("retention_time")?.map(Time::new::<s>),
+            z: number("charge")?.map(|c| Charge::new::<e>(c as isize)),
+            experimental_mz: number("exp_mass_to_charge")?.map(MassOverCharge::new::<mz>),
+            calculated_mz: number("calc_mass_to_charge")?.map(MassOverCharge::new::<mz>),
+            spectra_ref: column("spectra_ref")
+                .map(|v| parse_spectra_ref(v, ms_runs))
+                .unwrap_or_default(),
+            pre: residue("pre"),
+            post: residue("post"),
+            start: number("start")?.map(|v| v as usize),
+            end: number("end")?.map(|v| v as usize),
+        })
+    }
+}
+
+impl From<MZTabData> for IdentifiedPeptide {
+    fn from(value: MZTabData) -> Self {
+        Self {
+            score: value.normalised_score,
+            metadata: MetaData::MZTab(value),
+        }
+    }
+}
+
+/// Write the given identified peptides to a mzTab 1.0 identification file, with a PSM and a PEP
+/// section. The file is compressed if the extension is `.gz`.
+/// # Errors
+/// If the file could not be created or written to.
+pub fn write_mztab<'a>(
+    path: impl AsRef<Path>,
+    peptides: impl IntoIterator<Item = &'a IdentifiedPeptide>,
+) -> Result<(), CustomError> {
+    let path = path.as_ref();
+    let file = std::fs::File::create(path).map_err(|err| {
+        CustomError::error(
+            "Could not create file",
+            format!("Additional info: {err}"),
+            Context::show(path.to_string_lossy()),
+        )
+    })?;
+    if crate::helper_functions::check_extension(path, "gz") {
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        write_mztab_raw(&mut encoder, peptides)?;
+        encoder.try_finish().map_err(|err| {
+            CustomError::error(
+                "Could not write mzTab file",
+                format!("Additional info: {err}"),
+                Context::show(path.to_string_lossy()),
+            )
+        })
+    } else {
+        write_mztab_raw(file, peptides)
+    }
+}
+
+/// Write the given identified peptides as a mzTab 1.0 identification file to a raw writer.
+///
+/// Peptides without a sequence are skipped. All modifications with a Unimod or PSI-MOD accession are
+/// written as such, formula modifications are written as `CHEMMOD` with their formula, and all
+/// other modifications are written as `CHEMMOD` with their monoisotopic mass. The PEP section
+/// contains one line per unique peptide and charge.
+/// # Errors
+/// If the writer could not be written to.
+pub fn write_mztab_raw<'a>(
+    writer: impl Write,
+    peptides: impl IntoIterator<Item = &'a IdentifiedPeptide>,
+) -> Result<(), CustomError> {
+    let peptides: Vec<(&IdentifiedPeptide, &LinearPeptide<SemiAmbiguous>)> = peptides
+        .into_iter()
+        .filter_map(|p| p.peptide().map(|peptide| (p, peptide)))
+        .collect();
+    let runs: Vec<Option<&Path>> = peptides
+        .iter()
+        .map(|(p, _)| p.raw_file())
+        .unique()
+        .collect();
+    let modifications: Vec<String> = peptides
+        .iter()
+        .flat_map(|(_, peptide)| peptide_modifications(peptide))
+        .map(|(_, modification)| modification)
+        .unique()
+        .collect();
+
+    let mut writer = BufWriter::new(writer);
+    let mut lines = vec![
+        "MTD\tmzTab-version\t1.0.0".to_string(),
+        "MTD\tmzTab-mode\tSummary".to_string(),
+        "MTD\tmzTab-type\tIdentification".to_string(),
+        "MTD\tdescription\tIdentified peptides".to_string(),
+    ];
+    for (index, run) in runs.iter().enumerate() {
+        lines.push(format!(
+            "MTD\tms_run[{}]-location\t{}",
+            index + 1,
+            run.map_or_else(|| "null".to_string(), location)
+        ));
+    }
+    lines.push("MTD\tpsm_search_engine_score[1]\t[, , score, ]".to_string());
+    lines.push("MTD\tpeptide_search_engine_score[1]\t[, , score, ]".to_string());
+    lines
+        .push("MTD\tfixed_mod[1]\t[MS, MS:1002453, No fixed modifications searched, ]".to_string());
+    if modifications.is_empty() {
+        lines.push(
+            "MTD\tvariable_mod[1]\t[MS, MS:1002454, No variable modifications searched, ]"
+                .to_string(),
+        );
+    }
+    for (index, modification) in modifications.iter().enumerate() {
+        lines.push(format!(
+            "MTD\tvariable_mod[{}]\t{}",
+            index + 1,
+            modification_parameter(modification)
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(
+        "PSH\tsequence\tPSM_ID\taccession\tunique\tdatabase\tdatabase_version\tsearch_engine\tsearch_engine_score[1]\tmodifications\tretention_time\tcharge\texp_mass_to_charge\tcalc_mass_to_charge\tspectra_ref\tpre\tpost\tstart\tend"
+            .to_string(),
+    );
+    let mut peptide_section: Vec<(Option<Charge>, Vec<usize>)> = Vec::new();
+    let mut peptide_lookup: HashMap<(String, Option<Charge>), usize> = HashMap::new();
+    for (index, (identified, peptide)) in peptides.iter().enumerate() {
+        let sequence = plain_sequence(peptide);
+        let modifications = modifications_column(peptide);
+        let run = runs
+            .iter()
+            .position(|r| *r == identified.raw_file())
+            .unwrap_or_default()
+            + 1;
+        let spectra_ref = spectra_ref(identified, run);
+        lines.push(format!(
+            "PSM\t{sequence}\t{}\tnull\tnull\tnull\tnull\t{}\t{}\t{modifications}\t{}\t{}\tnull\t{}\t{spectra_ref}\tnull\tnull\tnull\tnull",
+            index + 1,
+            search_engine(identified),
+            optional_value(identified.score),
+            optional_value(identified.retention_time().map(|rt| rt.get::<s>())),
+            optional_value(identified.signed_charge().map(|c| c.value)),
+            optional_value(calculated_mz(peptide, identified.signed_charge())),
+        ));
+        let charge = identified.signed_charge();
+        let group = *peptide_lookup
+            .entry((format!("{sequence}/{modifications}"), charge))
+            .or_insert_with(|| {
+                peptide_section.push((charge, Vec::new()));
+                peptide_section.len() - 1
+            });
+        peptide_section[group].1.push(index);
+    }
+
+    lines.push(String::new());
+    lines.push(
+        "PEH\tsequence\taccession\tunique\tdatabase\tdatabase_version\tsearch_engine\tbest_search_engine_score[1]\tmodifications\tretention_time\tretention_time_window\tcharge\tmass_to_charge\tspectra_ref"
+            .to_string(),
+    );
+    for (charge, indices) in peptide_section {
+        let best = indices
+            .iter()
+            .copied()
+            .max_by(|a, b| {
+                peptides[*a]
+                    .0
+                    .score
+                    .unwrap_or(f64::MIN)
+                    .total_cmp(&peptides[*b].0.score.unwrap_or(f64::MIN))
+            })
+            .unwrap_or(indices[0]);
+        let (identified, peptide) = peptides[best];
+        let rts: Vec<f64> = indices
+            .iter()
+            .filter_map(|i| peptides[*i].0.retention_time().map(|rt| rt.get::<s>()))
+            .collect();
+        let window = rts
+            .iter()
+            .copied()
+            .minmax()
+            .into_option()
+            .filter(|(min, max)| min < max)
+            .map_or_else(|| "null".to_string(), |(min, max)| format!("{min}|{max}"));
+        lines.push(format!(
+            "PEP\t{}\tnull\tnull\tnull\tnull\t{}\t{}\t{}\t{}\t{window}\t{}\t{}\t{}",
+            plain_sequence(peptide),
+            search_engine(identified),
+            optional_value(identified.score),
+            modifications_column(peptide),
+            optional_value(identified.retention_time().map(|rt| rt.get::<s>())),
+            optional_value(charge.map(|c| c.value)),
+            optional_value(calculated_mz(peptide, charge)),
+            indices
+                .iter()
+                .map(|i| {
+                    let run = runs
+                        .iter()
+                        .position(|r| *r == peptides[*i].0.raw_file())
+                        .unwrap_or_default()
+                        + 1;
+                    spectra_ref(peptides[*i].0, run)
+                })
+                .join("|"),
+        ));
+    }
+
+    for line in lines {
+        writeln!(writer, "{line}").map_err(|err| {
+            CustomError::error(
+                "Could not write mzTab file",
+                format!("Additional info: {err}"),
+                Context::none(),
+            )
+        })?;
+    }
+    writer.flush().map_err(|err| {
+        CustomError::error(
+            "Could not write mzTab file",
+            format!("Additional info: {err}"),
+            Context::none(),
+        )
+    })
+}
+
+/// The PSM section of a mzTab file, with the metadata needed to interpret the rows
+pub(super) struct PSMSection<'a> {
+    /// The locations of the MS runs, by their name (eg `ms_run[1]`)
+    pub ms_runs: HashMap<String, PathBuf>,
+    /// The names of the PSM search engine scores, by their index (eg `[1]`)
+    pub score_names: HashMap<String, String>,
+    /// The names of the software used to generate the file
+    pub software: Vec<String>,
+    /// All PSM rows as line index, full line, and the cells by column name
+    pub rows: Vec<(usize, &'a str, HashMap<&'a str, &'a str>)>,
+}
+
+/// Read the metadata and the PSM rows from the text of a mzTab file
+/// # Errors
+/// If a PSM line is found before the PSH header line.
+pub(super) fn psm_section(text: &str) -> Result<PSMSection<'_>, CustomError> {
+    let mut section = PSMSection {
+        ms_runs: HashMap::new(),
+        score_names: HashMap::new(),
+        software: Vec::new(),
+        rows: Vec::new(),
+    };
+    let mut header: Option<Vec<&str>> = None;
+
+    for (line_index, line) in text.lines().enumerate() {
+        let mut fields = line.split('\t');
+        match fields.next().map(str::trim) {
+            Some("MTD") => {
+                let key = fields.next().unwrap_or_default();
+                let value = fields.next().unwrap_or_default();
+                if let Some(run) = key.strip_suffix("-location") {
+                    if let Some(location) = optional(value) {
+                        section.ms_runs.insert(
+                            run.to_string(),
+                            PathBuf::from(location.strip_prefix("file://").unwrap_or(location)),
+                        );
+                    }
+                } else if let Some(index) = key.strip_prefix("psm_search_engine_score") {
+                    section
+                        .score_names
+                        .insert(index.to_string(), parameter_name(value).to_string());
+                } else if key.starts_with("software[") && key.ends_with(']') {
+                    section.software.push(parameter_name(value).to_string());
+                }
+            }
+            Some("PSH") => header = Some(fields.collect()),
+            Some("PSM") => {
+                let header = header.as_ref().ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid mzTab file",
+                        "A PSM line was found before the PSH header line",
+                        Context::full_line(line_index, line),
+                    )
+                })?;
+                section.rows.push((
+                    line_index,
+                    line,
+                    header.iter().copied().zip(fields).collect(),
+                ));
+            }
+            _ => (),
+        }
+    }
+    Ok(section)
+}
+
+/// Parse a `spectra_ref` cell into the raw file (if the MS run is known) and the spectrum id
+pub(super) fn parse_spectra_ref(
+    value: &str,
+    ms_runs: &HashMap<String, PathBuf>,
+) -> Vec<(Option<PathBuf>, String)> {
+    value
+        .split('|')
+        .map(|reference| {
+            reference.split_once(':').map_or_else(
+                || (None, reference.to_string()),
+                |(run, id)| (ms_runs.get(run).cloned(), id.to_string()),
+            )
+        })
+        .collect()
+}
+
+/// Get the value of a mzTab cell, returns None for `null` and empty cells
+pub(super) fn optional(value: &str) -> Option<&str> {
+    let value = value.trim();
+    (!value.is_empty() && !value.eq_ignore_ascii_case("null")).then_some(value)
+}
+
+/// Format an optional value, using `null` for missing values
+fn optional_value(value: Option<impl std::fmt::Display>) -> String {
+    value.map_or_else(|| "null".to_string(), |v| v.to_string())
+}
+
+/// Get the name from a parameter (`[label, accession, name, value]`), or the full text if this
+/// is not a valid parameter
+fn parameter_name(parameter: &str) -> &str {
+    parameter
+        .trim()
+        .strip_prefix('[')
+        .and_then(|p| p.strip_suffix(']'))
+        .and_then(|p| p.split(',').nth(2))
+        .map_or_else(|| parameter.trim(), str::trim)
+}
+
+/// Create a URI for a raw file location
+fn location(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    if path.contains("://") {
+        path.to_string()
+    } else {
+        format!("file://{path}")
+    }
+}
+
+/// The name of the search engine for an identified peptide, as a parameter
+fn search_engine(peptide: &IdentifiedPeptide) -> String {
+    let name = match &peptide.metadata {
+        MetaData::Peaks(_) => Some("PEAKS"),
+        MetaData::Novor(_) => Some("Novor"),
+        MetaData::Opair(_) => Some("OPair"),
+        MetaData::MaxQuant(_) => Some("MaxQuant"),
+        MetaData::Sage(_) => Some("Sage"),
+        MetaData::MSFragger(_) => Some("MSFragger"),
+        MetaData::DiaNN(_) => Some("DIA-NN"),
+        MetaData::Spectronaut(_) => Some("Spectronaut"),
+        MetaData::Casanovo(_) => Some("Casanovo"),
+        MetaData::InstaNovo(_) => Some("InstaNovo"),
+        MetaData::PepNet(_) => Some("PepNet"),
+        MetaData::PGlyco(_) => Some("pGlyco"),
+        MetaData::Byonic(_) => Some("Byonic"),
+        MetaData::Search(_) => Some("rustyms"),
+        MetaData::MZIdentML(data) => data.search_engine.as_deref(),
+        MetaData::PepXML(data) => data.search_engine.as_deref(),
+        MetaData::MZTab(data) => data.search_engine.first().map(String::as_str),
+        MetaData::Fasta(_) | MetaData::None => None,
+    };
+    name.map_or_else(|| "null".to_string(), |name| format!("[, , {name}, ]"))
+}
+
+/// The spectra reference for an identified peptide, using the native ids if known and
+/// otherwise the scan numbers
+fn spectra_ref(peptide: &IdentifiedPeptide, run: usize) -> String {
+    peptide
+        .spectrum_native_ids()
+        .or_else(|| {
+            peptide
+                .scan_indices()
+                .map(|scans| scans.iter().map(|scan| format!("scan={scan}")).collect())
+        })
+        .filter(|ids| !ids.is_empty())
+        .map_or_else(
+            || "null".to_string(),
+            |ids| ids.iter().map(|id| format!("ms_run[{run}]:{id}")).join("|"),
+        )
+}
+
+/// The sequence without any modifications
+fn plain_sequence(peptide: &LinearPeptide<SemiAmbiguous>) -> String {
+    peptide
+        .sequence()
+        .iter()
+        .map(|element| element.aminoacid.char())
+        .collect()
+}
+
+/// The calculated m/z for a peptide with the given charge, if the mass is defined. Negative
+/// charges are assumed to be lost protons.
+fn calculated_mz(peptide: &LinearPeptide<SemiAmbiguous>, charge: Option<Charge>) -> Option<f64> {
+    let charge = charge.filter(|c| c.value != 0)?.value;
+    let mass = peptide.formulas().first()?.monoisotopic_mass().value;
+    let proton = molecular_formula!(H 1 Electron -1)
+        .monoisotopic_mass()
+        .value;
+    Some((charge as f64).mul_add(proton, mass) / charge.unsigned_abs() as f64)
+}
+
+/// All modifications with their mzTab position (0 for the N terminus, length + 1 for the C
+/// terminus) and their mzTab notation
+fn peptide_modifications(peptide: &LinearPeptide<SemiAmbiguous>) -> Vec<(usize, String)> {
+    peptide
+        .get_n_term()
+        .map(|m| (0, modification_notation(m)))
+        .into_iter()
+        .chain(
+            peptide
+                .sequence()
+                .iter()
+                .enumerate()
+                .flat_map(|(index, element)| {
+                    element
+                        .modifications
+                        .iter()
+                        .map(move |m| (index + 1, modification_notation(m)))
+                }),
+        )
+        .chain(
+            peptide
+                .get_c_term()
+                .map(|m| (peptide.len() + 1, modification_notation(m))),
+        )
+        .collect()
+}
+
+/// The modifications column for a peptide
+fn modifications_column(peptide: &LinearPeptide<SemiAmbiguous>) -> String {
+    let modifications = peptide_modifications(peptide);
+    if modifications.is_empty() {
+        "null".to_string()
+    } else {
+        modifications
+            .iter()
+            .map(|(position, modification)| format!("{position}-{modification}"))
+            .join(",")
+    }
+}
+
+/// The mzTab notation for a modification, the accession for Unimod and PSI-MOD modifications, a
+/// `CHEMMOD` with the formula for formula modifications, and a `CHEMMOD` with the mass for all
+/// others
+fn modification_notation(modification: &Modification) -> String {
+    let simple = match modification {
+        Modification::Simple(simple) => simple,
+        Modification::CrossLink { linker, .. } => linker,
+    };
+    match simple {
+        SimpleModification::Database { id, .. }
+        | SimpleModification::Linker { id, .. }
+        | SimpleModification::Gno { id, .. }
+            if id.id.is_some() && id.ontology == Ontology::Unimod =>
+        {
+            format!("UNIMOD:{}", id.id.unwrap_or_default())
+        }
+        SimpleModification::Database { id, .. }
+        | SimpleModification::Linker { id, .. }
+        | SimpleModification::Gno { id, .. }
+            if id.id.is_some() && id.ontology == Ontology::Psimod =>
+        {
+            format!("MOD:{:05}", id.id.unwrap_or_default())
+        }
+        SimpleModification::Formula(formula) => format!("CHEMMOD:{}", formula.hill_notation()),
+        other => format!("CHEMMOD:{:+}", other.formula().monoisotopic_mass().value),
+    }
+}
+
+/// The parameter to list a modification in the metadata section
+fn modification_parameter(notation: &str) -> String {
+    [Ontology::Unimod, Ontology::Psimod]
+        .iter()
+        .find_map(|ontology| {
+            let id = notation
+                .strip_prefix(&format!("{}:", ontology.name()))?
+                .parse()
+                .ok()?;
+            let name = ontology
+                .find_id(id, None)
+                .map(|m| m.to_string())
+                .unwrap_or_default();
+            Some(format!(
+                "[{}, {notation}, {}, ]",
+                ontology.name(),
+                name.trim_start_matches(&format!("{}:", ontology.char()))
+            ))
+        })
+        .unwrap_or_else(|| format!("[, , {notation}, ]"))
+}
+
+/// Parse a mzTab sequence with its modifications column
+/// # Errors
+/// If the sequence contains invalid amino acids or a modification could not be recognised.
+fn parse_peptide(
+    sequence: &str,
+    modifications: &str,
+    custom_database: Option<&CustomDatabase>,
+) -> Result<LinearPeptide<SemiAmbiguous>, String> {
+    let mut peptide: LinearPeptide<SemiAmbiguous> = LinearPeptide::new(
+        sequence
+            .chars()
+            .map(|c| {
+                CheckedAminoAcid::try_from(c)
+                    .map(|aa| SequenceElement::new(aa, None))
+                    .map_err(|()| format!("The character '{c}' is not a valid amino acid"))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    );
+
+    for entry in split_outside_brackets(modifications) {
+        let entry = entry.trim();
+        // Neutral losses are given as a bare parameter, these are not modifications
+        if entry.is_empty() || entry.starts_with('[') {
+            continue;
+        }
+        let (positions, accession) = entry
+            .split_once('-')
+            .ok_or_else(|| format!("The modification '{entry}' does not have a position"))?;
+        if positions.contains('|') {
+            return Err(format!(
+                "The modification '{entry}' has ambiguous positions, which is not supported"
+            ));
+        }
+        // Remove any position reliability parameter
+        let position: usize = positions
+            .split('[')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .parse()
+            .map_err(|_| format!("The modification '{entry}' has an invalid position"))?;
+        let modification = parse_modification(accession.trim(), custom_database)
+            .ok_or_else(|| format!("The modification '{entry}' could not be recognised"))?;
+        let position = match position {
+            0 => SequencePosition::NTerm,
+            p if p > peptide.len() => SequencePosition::CTerm,
+            p => SequencePosition::Index(p - 1),
+        };
+        peptide.add_simple_modification(position, modification);
+    }
+    Ok(peptide)
+}
+
+/// Split a comma separated list, ignoring any commas inside of square brackets
+fn split_outside_brackets(text: &str) -> Vec<&str> {
+    let mut output = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                output.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    if start < text.len() {
+        output.push(&text[start..]);
+    }
+    output
+}
+
+/// Parse a modification accession, supports Unimod, PSI-MOD, and `CHEMMOD` with mass or formula
+fn parse_modification(
+    accession: &str,
+    custom_database: Option<&CustomDatabase>,
+) -> Option<SimpleModification> {
+    if let Some(chemmod) = accession.strip_prefix("CHEMMOD:") {
+        chemmod.parse::<f64>().map_or_else(
+            |_| {
+                MolecularFormula::from_pro_forma(chemmod, .., false, false)
+                    .ok()
+                    .map(SimpleModification::Formula)
+            },
+            |mass| Some(SimpleModification::Mass(Mass::new::<dalton>(mass).into())),
+        )
+    } else if let Some(id) = accession.strip_prefix("UNIMOD:") {
+        Ontology::Unimod.find_id(id.parse().ok()?, custom_database)
+    } else if let Some(id) = accession.strip_prefix("MOD:") {
+        Ontology::Psimod.find_id(id.parse().ok()?, custom_database)
+    } else {
+        None
+    }
+}
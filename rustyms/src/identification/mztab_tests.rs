@@ -0,0 +1,152 @@
+#![allow(clippy::missing_panics_doc)]
+use crate::system::{e, isize::Charge, mz, time::s};
+
+use super::{write_mztab, write_mztab_raw, IdentifiedPeptide, MZTabData};
+
+#[test]
+fn mztab() {
+    let peptides = MZTabData::parse_str(DATA, None).unwrap();
+    assert_eq!(peptides.len(), 3);
+
+    let first = &peptides[0];
+    assert_eq!(
+        first.peptide.to_string(),
+        "[U:Acetyl]-PEPM[U:Oxidation]C[U:Carbamidomethyl]K"
+    );
+    assert_eq!(first.psm_id, "1");
+    assert_eq!(first.accession.as_deref(), Some("P12345"));
+    assert_eq!(first.unique, Some(true));
+    assert_eq!(first.search_engine, vec!["MS-GF+"]);
+    assert_eq!(first.search_engine_scores, vec![Some(0.001), Some(120.0)]);
+    assert!((first.normalised_score.unwrap() - 0.999).abs() < 1e-9);
+    assert!((first.rt.unwrap().get::<s>() - 1800.5).abs() < 1e-9);
+    assert_eq!(first.z, Some(Charge::new::<e>(2)));
+    assert!((first.experimental_mz.unwrap().get::<mz>() - 395.162).abs() < 1e-9);
+    assert_eq!(first.pre, Some('K'));
+    assert_eq!(first.start, Some(10));
+
+    // Position reliabilities and neutral losses
+    let second = &peptides[1];
+    assert_eq!(second.peptide.to_string(), "S[U:Phospho]AMPLER");
+    assert_eq!(second.search_engine_scores, vec![Some(0.05), None]);
+    // CHEMMOD mass and formula
+    let third = &peptides[2];
+    assert_eq!(
+        third.peptide.to_string(),
+        "SA[+12.3456]MPLER-[Formula:H2O1]"
+    );
+    assert_eq!(third.z, None);
+
+    let identified: IdentifiedPeptide = first.clone().into();
+    assert_eq!(identified.scan_indices(), Some(vec![1234]));
+    assert_eq!(
+        identified.raw_file().unwrap().to_string_lossy(),
+        "/data/run01.mzML"
+    );
+    assert_eq!(
+        identified.spectrum_native_ids(),
+        Some(vec![
+            "controllerType=0 controllerNumber=1 scan=1234".to_string()
+        ])
+    );
+}
+
+#[test]
+fn mztab_round_trip() {
+    let peptides: Vec<IdentifiedPeptide> = MZTabData::parse_str(DATA, None)
+        .unwrap()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let mut buffer = Vec::new();
+    write_mztab_raw(&mut buffer, &peptides).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    assert!(text.contains("MTD\tms_run[1]-location\tfile:///data/run01.mzML"));
+    assert!(text.contains("MTD\tvariable_mod[1]\t[UNIMOD, UNIMOD:1, Acetyl, ]"));
+    assert_eq!(text.lines().filter(|l| l.starts_with("PEP\t")).count(), 3);
+
+    let read = MZTabData::parse_str(&text, None).unwrap();
+    assert_eq!(read.len(), peptides.len());
+    for (original, read) in peptides.iter().zip(read) {
+        let read: IdentifiedPeptide = read.into();
+        assert_eq!(original.peptide(), read.peptide());
+        assert_eq!(original.score, read.score);
+        assert_eq!(original.charge(), read.charge());
+        assert_eq!(original.retention_time(), read.retention_time());
+        assert_eq!(original.spectrum_native_ids(), read.spectrum_native_ids());
+        assert_eq!(original.raw_file(), read.raw_file());
+    }
+}
+
+#[test]
+fn mztab_negative_charge() {
+    let data = DATA.replace("\t1900\t2\t420.1\t", "\t1900\t-2\t420.1\t");
+    let peptides: Vec<IdentifiedPeptide> = MZTabData::parse_str(&data, None)
+        .unwrap()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    assert_eq!(peptides[1].signed_charge(), Some(Charge::new::<e>(-2)));
+    assert_eq!(peptides[1].charge().map(|c| c.value), Some(2));
+
+    let mut buffer = Vec::new();
+    write_mztab_raw(&mut buffer, &peptides).unwrap();
+    let text = String::from_utf8(buffer).unwrap();
+    let read = MZTabData::parse_str(&text, None).unwrap();
+    assert_eq!(read[1].z, Some(Charge::new::<e>(-2)));
+    // The negative charge is kept as a separate PEP line
+    let pep: Vec<&str> = text.lines().filter(|l| l.starts_with("PEP\t")).collect();
+    assert_eq!(pep.len(), 3);
+    assert!(pep.iter().any(|l| l.split('\t').nth(11) == Some("-2")));
+}
+
+#[test]
+fn mztab_gzip() {
+    let peptides: Vec<IdentifiedPeptide> = MZTabData::parse_str(DATA, None)
+        .unwrap()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let path = std::env::temp_dir().join(format!(
+        "rustyms_mztab_gzip_{}.mztab.gz",
+        std::process::id()
+    ));
+    write_mztab(&path, &peptides).unwrap();
+    let read = MZTabData::parse_file(&path, None);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read.unwrap().len(), peptides.len());
+}
+
+#[test]
+fn mztab_invalid() {
+    assert!(MZTabData::parse_str("PSM\tPEPTIDE", None).is_err());
+    assert!(MZTabData::parse_str("PSH\tsequence\nPSM\tPEP1", None).is_err());
+    assert!(MZTabData::parse_str(
+        "PSH\tsequence\tmodifications\nPSM\tPEPTIDE\t3|4-UNIMOD:21",
+        None
+    )
+    .is_err());
+    assert!(MZTabData::parse_str(
+        "PSH\tsequence\tmodifications\nPSM\tPEPTIDE\t3-UNKNOWN:21",
+        None
+    )
+    .is_err());
+}
+
+const DATA: &str = "MTD\tmzTab-version\t1.0.0
+MTD\tmzTab-mode\tSummary
+MTD\tmzTab-type\tIdentification
+MTD\tdescription\tTest file
+MTD\tms_run[1]-location\tfile:///data/run01.mzML
+MTD\tms_run[2]-location\tfile:///data/run02.mzML
+MTD\tpsm_search_engine_score[1]\t[MS, MS:1002052, MS-GF:SpecEValue, ]
+MTD\tpsm_search_engine_score[2]\t[MS, MS:1002049, MS-GF:RawScore, ]
+MTD\tfixed_mod[1]\t[UNIMOD, UNIMOD:4, Carbamidomethyl, ]
+MTD\tvariable_mod[1]\t[UNIMOD, UNIMOD:35, Oxidation, ]
+
+COM\tSome comment
+PSH\tsequence\tPSM_ID\taccession\tunique\tdatabase\tdatabase_version\tsearch_engine\tsearch_engine_score[1]\tsearch_engine_score[2]\tmodifications\tretention_time\tcharge\texp_mass_to_charge\tcalc_mass_to_charge\tspectra_ref\tpre\tpost\tstart\tend
+PSM\tPEPMCK\t1\tP12345\t1\tUniProt\t2024_01\t[MS, MS:1002048, MS-GF+, ]\t0.001\t120\t0-UNIMOD:1,4-UNIMOD:35,5-UNIMOD:4\t1800.5\t2\t395.162\t395.1612\tms_run[1]:controllerType=0 controllerNumber=1 scan=1234\tK\tA\t10\t15
+PSM\tSAMPLER\t2\tP12345\t0\tUniProt\t2024_01\t[MS, MS:1002048, MS-GF+, ]\t0.05\tnull\t1[MS, MS:1001876, modification probability, 0.8]-UNIMOD:21,[MS, MS:1001524, fragment neutral loss, 97.976896]\t1900\t2\t420.1\tnull\tms_run[2]:scan=1300\tR\t-\t20\t26
+PSM\tSAMPLER\t3\tnull\tnull\tnull\tnull\tnull\tnull\tnull\t2-CHEMMOD:+12.3456,8-CHEMMOD:H2O\tnull\tnull\tnull\tnull\tms_run[2]:scan=1301\tnull\tnull\tnull\tnull
+";
@@ -0,0 +1,88 @@
+use crate::{
+    error::CustomError,
+    ontologies::CustomDatabase,
+    peptide::{SemiAmbiguous, SloppyParsingParameters},
+    system::Ratio,
+    LinearPeptide,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common_parser::{Location, OptionalLocation},
+    csv::{parse_csv, CsvLine},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+static NUMBER_ERROR: (&str, &str) = (
+    "Invalid PepNet line",
+    "This column is not a number but it is required to be a number in this PepNet format",
+);
+
+format_family!(
+    /// The format for any PepNet file
+    PepNetFormat,
+    /// The data from any PepNet file
+    PepNetData,
+    PepNetVersion, [&PEPNET_V1_0], b'\t';
+    required {
+        /// The title of the spectrum in the MGF file
+        title: String, |location: Location, _| Ok(location.get_string());
+        peptide: Option<LinearPeptide<SemiAmbiguous>>, |location: Location, custom_database: Option<&CustomDatabase>| location.or_empty().parse_with(|location| LinearPeptide::sloppy_pro_forma(
+            location.full_line(),
+            location.location.clone(),
+            custom_database,
+            SloppyParsingParameters::default(),
+        ));
+        score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        ppm_difference: Ratio, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Ratio::new::<crate::system::ratio::ppm>);
+        /// The score for each amino acid 0.0..=1.0
+        local_confidence: Vec<f64>, |location: Location, _| location.trim()
+                .strip_enclosure('[', ']')
+                .or_empty()
+                .array(',')
+                .map(|l| l.parse::<f64>(NUMBER_ERROR))
+                .collect::<Result<Vec<_>, _>>();
+    }
+    optional { }
+    mass_modification_tolerance: 0.02;
+);
+
+impl From<PepNetData> for IdentifiedPeptide {
+    fn from(value: PepNetData) -> Self {
+        Self {
+            score: Some(value.score.clamp(-1.0, 1.0)),
+            metadata: MetaData::PepNet(value),
+        }
+    }
+}
+
+/// All possible PepNet versions
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+#[allow(non_camel_case_types)]
+pub enum PepNetVersion {
+    /// Version 1.0
+    #[default]
+    V1_0,
+}
+
+impl std::fmt::Display for PepNetVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::V1_0 => "v1.0",
+            }
+        )
+    }
+}
+
+/// Version 1.0
+pub const PEPNET_V1_0: PepNetFormat = PepNetFormat {
+    version: PepNetVersion::V1_0,
+    title: "title",
+    peptide: "denovo",
+    score: "score",
+    ppm_difference: "ppm difference",
+    local_confidence: "positional score",
+};
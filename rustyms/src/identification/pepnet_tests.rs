@@ -0,0 +1,35 @@
+#![allow(clippy::missing_panics_doc)]
+use std::io::BufReader;
+
+use super::{csv::parse_csv_raw, pepnet, IdentifiedPeptide, IdentifiedPeptideSource, PepNetData};
+
+#[test]
+fn pepnet() {
+    let reader = BufReader::new(DATA.as_bytes());
+    let lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    let peptides: Vec<PepNetData> =
+        PepNetData::parse_many_specific(lines, &pepnet::PEPNET_V1_0, None)
+            .collect::<Result<_, _>>()
+            .unwrap();
+    assert_eq!(peptides.len(), 2);
+    assert_eq!(
+        peptides[1].peptide.as_ref().unwrap().to_string(),
+        "M[U:Oxidation]PEPK"
+    );
+
+    let identified: IdentifiedPeptide = peptides[0].clone().into();
+    assert_eq!(identified.score, Some(0.9));
+    assert_eq!(
+        identified.local_confidence(),
+        Some([0.9, 0.8, 0.95, 0.9, 0.91, 0.92, 0.99].as_slice())
+    );
+    assert_eq!(
+        identified.spectrum_native_ids(),
+        Some(vec!["sample.1.1.2".to_string()])
+    );
+}
+
+const DATA: &str = "TITLE\tDENOVO\tScore\tPPM Difference\tPositional Score
+sample.1.1.2\tLESLIEK\t0.9\t1.5\t[0.9, 0.8, 0.95, 0.9, 0.91, 0.92, 0.99]
+sample.2.2.2\tM(+15.99)PEPK\t0.8\t-2.1\t[0.9, 0.8, 0.95, 0.9, 0.91]
+";
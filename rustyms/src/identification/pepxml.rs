@@ -0,0 +1,307 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Context, CustomError},
+    modification::SimpleModification,
+    molecular_formula,
+    ontologies::CustomDatabase,
+    peptide::SemiAmbiguous,
+    system::{dalton, e, usize::Charge, Mass, Time},
+    CheckedAminoAcid, LinearPeptide, MultiChemical, SequenceElement, SequencePosition,
+};
+
+use super::{
+    common_xml::{children, descendants, optional_number, parse_number, required_attribute},
+    helper_functions::{mass_modification_search, read_file, read_to_string},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+/// A single search hit from a pepXML file
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PepXMLData {
+    /// The identified peptide
+    pub peptide: LinearPeptide<SemiAmbiguous>,
+    /// The spectrum name
+    pub spectrum: String,
+    /// The native id of the spectrum, if given
+    pub native_id: Option<String>,
+    /// The first scan of the spectrum
+    pub start_scan: usize,
+    /// The last scan of the spectrum
+    pub end_scan: usize,
+    /// The assumed precursor charge
+    pub z: Charge,
+    /// The neutral mass of the precursor
+    pub precursor_mass: Mass,
+    /// The calculated neutral mass of the peptide, if given
+    pub calculated_mass: Option<Mass>,
+    /// The retention time, if given
+    pub rt: Option<Time>,
+    /// The rank of this hit for its spectrum
+    pub rank: usize,
+    /// The raw file, from the base name and raw data extension of the run
+    pub raw_file: Option<PathBuf>,
+    /// The search engine that generated this hit
+    pub search_engine: Option<String>,
+    /// The protein this peptide is found in
+    pub protein: Option<String>,
+    /// The description of the protein
+    pub protein_description: Option<String>,
+    /// Any other proteins this peptide is found in
+    pub alternative_proteins: Vec<String>,
+    /// The residue before the peptide in the protein
+    pub previous_aa: Option<char>,
+    /// The residue after the peptide in the protein
+    pub next_aa: Option<char>,
+    /// All search scores as given by the search engine
+    pub scores: Vec<(String, f64)>,
+    /// The `PeptideProphet` probability, if analysed
+    pub peptide_prophet_probability: Option<f64>,
+    /// The `iProphet` probability, if analysed
+    pub iprophet_probability: Option<f64>,
+}
+
+/// A search hit as read from a pepXML file, with the mass modifications already resolved
+#[derive(Clone, PartialEq, Debug)]
+pub struct PepXMLSearchHit(PepXMLData);
+
+/// The format of a pepXML file, there is only one
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub struct PepXMLFormat;
+
+impl IdentifiedPeptideSource for PepXMLData {
+    type Source = PepXMLSearchHit;
+    type Format = PepXMLFormat;
+
+    fn parse(
+        source: &Self::Source,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<(Self, &'static Self::Format), CustomError> {
+        Self::parse_specific(source, &PepXMLFormat, custom_database).map(|p| (p, &PepXMLFormat))
+    }
+
+    fn parse_specific(
+        source: &Self::Source,
+        _format: &Self::Format,
+        _custom_database: Option<&CustomDatabase>,
+    ) -> Result<Self, CustomError> {
+        Ok(source.0.clone())
+    }
+
+    fn parse_file(
+        path: impl AsRef<Path>,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<BoxedIdentifiedPeptideIter<'_, Self>, CustomError> {
+        Self::parse_str(&read_file(path.as_ref())?, custom_database)
+    }
+}
+
+impl PepXMLData {
+    /// Parse a pepXML file from a raw reader.
+    /// # Errors
+    /// If the reader could not be read or does not contain a valid pepXML file.
+    pub fn parse_reader(
+        reader: impl Read,
+        custom_database: Option<&CustomDatabase>,
+    ) -> Result<BoxedIdentifiedPeptideIter<'_, Self>, CustomError> {
+        Self::parse_str(&read_to_string(reader)?, custom_database)
+    }
+
+    /// Parse the text of a pepXML file. Errors in single search hits are returned by the
+    /// iterator.
+    /// # Errors
+    /// If the text is not a valid pepXML file.
+    pub fn parse_str<'a>(
+        text: &str,
+        custom_database: Option<&'a CustomDatabase>,
+    ) -> Result<BoxedIdentifiedPeptideIter<'a, Self>, CustomError> {
+        let document = Document::parse(text).map_err(|err| {
+            CustomError::error(
+                "Invalid pepXML file",
+                format!("The file is not valid XML: {err}"),
+                Context::none(),
+            )
+        })?;
+        let root = document.root_element();
+        if root.tag_name().name() != "msms_pipeline_analysis" {
+            return Err(CustomError::error(
+                "Invalid pepXML file",
+                "The root element is not 'msms_pipeline_analysis'",
+                Context::show(root.tag_name().name()),
+            ));
+        }
+
+        // The masses in pepXML are commonly rounded to four decimals
+        let mut search = mass_modification_search(custom_database, 0.005);
+        let mut hits = Vec::new();
+        for run in descendants(root, "msms_run_summary") {
+            let raw_file = run.attribute("base_name").map(|base| {
+                PathBuf::from(format!(
+                    "{base}{}",
+                    run.attribute("raw_data").unwrap_or_default()
+                ))
+            });
+            let search_engine = descendants(run, "search_summary")
+                .find_map(|s| s.attribute("search_engine"))
+                .map(ToString::to_string);
+            for query in children(run, "spectrum_query") {
+                for hit in children(query, "search_result").flat_map(|r| children(r, "search_hit"))
+                {
+                    hits.push(
+                        parse_search_hit(query, hit, raw_file.as_ref(), search_engine.as_ref())
+                            .map(|data| {
+                                PepXMLSearchHit(Self {
+                                    peptide: search.search(data.peptide),
+                                    ..data
+                                })
+                            }),
+                    );
+                }
+            }
+        }
+        Ok(Self::parse_many::<
+            Box<dyn Iterator<Item = Result<PepXMLSearchHit, CustomError>>>,
+        >(Box::new(hits.into_iter()), custom_database))
+    }
+}
+
+impl From<PepXMLData> for IdentifiedPeptide {
+    fn from(value: PepXMLData) -> Self {
+        Self {
+            score: value
+                .iprophet_probability
+                .or(value.peptide_prophet_probability),
+            metadata: MetaData::PepXML(value),
+        }
+    }
+}
+
+/// Parse a single `search_hit` in the context of its `spectrum_query`
+/// # Errors
+/// If any required attribute is missing or the peptide is invalid.
+fn parse_search_hit(
+    query: Node,
+    hit: Node,
+    raw_file: Option<&PathBuf>,
+    search_engine: Option<&String>,
+) -> Result<PepXMLData, CustomError> {
+    let probability = |analysis: &str, result: &'static str| {
+        children(hit, "analysis_result")
+            .filter(|a| a.attribute("analysis") == Some(analysis))
+            .flat_map(|a| children(a, result))
+            .map(|r| optional_number::<f64>(r, "probability"))
+            .next()
+            .transpose()
+            .map(Option::flatten)
+    };
+    let start_scan = parse_number(query, "start_scan")?;
+    Ok(PepXMLData {
+        peptide: parse_peptide(hit)?,
+        spectrum: required_attribute(query, "spectrum")?.to_string(),
+        native_id: query.attribute("spectrumNativeID").map(ToString::to_string),
+        start_scan,
+        end_scan: optional_number(query, "end_scan")?.unwrap_or(start_scan),
+        z: Charge::new::<e>(parse_number(query, "assumed_charge")?),
+        precursor_mass: Mass::new::<dalton>(parse_number(query, "precursor_neutral_mass")?),
+        calculated_mass: optional_number(hit, "calc_neutral_pep_mass")?.map(Mass::new::<dalton>),
+        rt: optional_number(query, "retention_time_sec")?.map(Time::new::<crate::system::time::s>),
+        rank: parse_number(hit, "hit_rank")?,
+        raw_file: raw_file.cloned(),
+        search_engine: search_engine.cloned(),
+        protein: hit.attribute("protein").map(ToString::to_string),
+        protein_description: hit.attribute("protein_descr").map(ToString::to_string),
+        alternative_proteins: children(hit, "alternative_protein")
+            .filter_map(|p| p.attribute("protein"))
+            .map(ToString::to_string)
+            .collect(),
+        previous_aa: hit
+            .attribute("peptide_prev_aa")
+            .and_then(|a| a.chars().next()),
+        next_aa: hit
+            .attribute("peptide_next_aa")
+            .and_then(|a| a.chars().next()),
+        scores: children(hit, "search_score")
+            .filter_map(|s| {
+                Some((
+                    s.attribute("name")?.to_string(),
+                    s.attribute("value")?.trim().parse().ok()?,
+                ))
+            })
+            .collect(),
+        peptide_prophet_probability: probability("peptideprophet", "peptideprophet_result")?,
+        iprophet_probability: probability("interprophet", "interprophet_result")?,
+    })
+}
+
+/// Parse the peptide of a `search_hit`, with all modifications from the `modification_info` as
+/// mass modifications.
+/// # Errors
+/// If the sequence contains invalid amino acids or a modification is placed outside the peptide.
+fn parse_peptide(hit: Node) -> Result<LinearPeptide<SemiAmbiguous>, CustomError> {
+    let sequence = required_attribute(hit, "peptide")?;
+    let mut peptide: LinearPeptide<SemiAmbiguous> = LinearPeptide::new(
+        sequence
+            .chars()
+            .map(|c| {
+                CheckedAminoAcid::try_from(c)
+                    .map(|aa| SequenceElement::new(aa, None))
+                    .map_err(|()| {
+                        CustomError::error(
+                            "Invalid pepXML peptide",
+                            format!("The character '{c}' is not a valid amino acid"),
+                            Context::show(sequence),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+    );
+    let mass = |delta: f64| SimpleModification::Mass(Mass::new::<dalton>(delta).into());
+
+    for info in children(hit, "modification_info") {
+        // The terminal masses include the mass of the terminal H and OH
+        if let Some(n_term) = optional_number::<f64>(info, "mod_nterm_mass")? {
+            let h = molecular_formula!(H 1).monoisotopic_mass().value;
+            peptide.add_simple_modification(SequencePosition::NTerm, mass(n_term - h));
+        }
+        if let Some(c_term) = optional_number::<f64>(info, "mod_cterm_mass")? {
+            let oh = molecular_formula!(O 1 H 1).monoisotopic_mass().value;
+            peptide.add_simple_modification(SequencePosition::CTerm, mass(c_term - oh));
+        }
+        for modification in children(info, "mod_aminoacid_mass") {
+            let position: usize = parse_number(modification, "position")?;
+            let element = position
+                .checked_sub(1)
+                .and_then(|index| peptide.sequence().get(index))
+                .ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid pepXML modification",
+                        "The modification is placed outside of the peptide",
+                        Context::show(format!("{sequence} at position {position}")),
+                    )
+                })?;
+            // Newer versions give the mass difference, older versions only the total mass
+            let delta = match (
+                optional_number::<f64>(modification, "static")?,
+                optional_number::<f64>(modification, "variable")?,
+            ) {
+                (None, None) => {
+                    parse_number::<f64>(modification, "mass")?
+                        - element
+                            .aminoacid
+                            .formulas_inner(SequencePosition::Index(position - 1), 0)
+                            .first()
+                            .map_or(0.0, |f| f.monoisotopic_mass().value)
+                }
+                (s, v) => s.unwrap_or_default() + v.unwrap_or_default(),
+            };
+            peptide.add_simple_modification(SequencePosition::Index(position - 1), mass(delta));
+        }
+    }
+    Ok(peptide)
+}
@@ -0,0 +1,115 @@
+#![allow(clippy::missing_panics_doc)]
+use crate::system::{e, time::s, usize::Charge};
+
+use super::{IdentifiedPeptide, PepXMLData};
+
+#[test]
+fn pepxml() {
+    let peptides = PepXMLData::parse_str(DATA, None)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(peptides.len(), 3);
+
+    let first = &peptides[0];
+    assert_eq!(
+        first.peptide.to_string(),
+        "[U:Acetyl]-PEPM[U:Oxidation]C[U:Carbamidomethyl]K"
+    );
+    assert_eq!(first.z, Charge::new::<e>(2));
+    assert_eq!(first.rank, 1);
+    assert_eq!(first.start_scan, 1234);
+    assert!((first.rt.unwrap().get::<s>() - 1800.5).abs() < 1e-6);
+    assert_eq!(first.search_engine.as_deref(), Some("Comet"));
+    assert_eq!(first.protein.as_deref(), Some("sp|P12345|PROT_HUMAN"));
+    assert_eq!(first.alternative_proteins, vec!["sp|P67890|OTHER_HUMAN"]);
+    assert_eq!(first.previous_aa, Some('K'));
+    assert_eq!(first.scores.len(), 2);
+    assert_eq!(first.peptide_prophet_probability, Some(0.98));
+    assert_eq!(first.iprophet_probability, Some(0.995));
+
+    // Only the total residue mass is given, and a mass without a Unimod match
+    let second = &peptides[1];
+    assert_eq!(second.peptide.to_string(), "S[U:Phospho]AMPLER");
+    assert_eq!(second.rank, 1);
+    let third = &peptides[2];
+    assert_eq!(third.rank, 2);
+    assert_eq!(third.peptide.to_string(), "SA[+12.3456]MPLER");
+
+    let identified: IdentifiedPeptide = first.clone().into();
+    assert_eq!(identified.score, Some(0.995));
+    assert_eq!(identified.scan_indices(), Some(vec![1234]));
+    assert_eq!(
+        identified.raw_file().unwrap().to_string_lossy(),
+        "/data/run01.mzML"
+    );
+    let identified: IdentifiedPeptide = second.clone().into();
+    assert_eq!(identified.score, Some(0.5));
+}
+
+#[test]
+fn pepxml_invalid() {
+    assert!(PepXMLData::parse_str("<MzIdentML/>", None).is_err());
+    assert!(PepXMLData::parse_str("<msms_pipeline_analysis>", None).is_err());
+    let mut peptides = PepXMLData::parse_str(
+        r#"<msms_pipeline_analysis><msms_run_summary><spectrum_query spectrum="a" start_scan="1" assumed_charge="2" precursor_neutral_mass="500"><search_result><search_hit hit_rank="1" peptide="PEP1"/></search_result></spectrum_query></msms_run_summary></msms_pipeline_analysis>"#,
+        None,
+    )
+    .unwrap();
+    assert!(peptides.next().unwrap().is_err());
+}
+
+const DATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<msms_pipeline_analysis date="2024-01-01T00:00:00" xmlns="http://regis-web.systemsbiology.net/pepXML" summary_xml="/data/run01.pep.xml">
+  <analysis_summary analysis="peptideprophet" time="2024-01-01T00:00:00"/>
+  <msms_run_summary base_name="/data/run01" raw_data_type="raw" raw_data=".mzML">
+    <sample_enzyme name="trypsin">
+      <specificity cut="KR" no_cut="P" sense="C"/>
+    </sample_enzyme>
+    <search_summary base_name="/data/run01" search_engine="Comet" search_engine_version="2019.01 rev. 5" precursor_mass_type="monoisotopic" fragment_mass_type="monoisotopic" search_id="1">
+      <aminoacid_modification aminoacid="C" massdiff="57.021464" mass="160.030649" variable="N"/>
+      <aminoacid_modification aminoacid="M" massdiff="15.994915" mass="147.035400" variable="Y"/>
+      <aminoacid_modification aminoacid="S" massdiff="79.966331" mass="166.998359" variable="Y"/>
+      <terminal_modification terminus="N" massdiff="42.010565" mass="43.018390" variable="Y" protein_terminus="N"/>
+    </search_summary>
+    <spectrum_query spectrum="run01.01234.01234.2" spectrumNativeID="controllerType=0 controllerNumber=1 scan=1234" start_scan="1234" end_scan="1234" precursor_neutral_mass="788.3086" assumed_charge="2" index="1" retention_time_sec="1800.5">
+      <search_result>
+        <search_hit hit_rank="1" peptide="PEPMCK" peptide_prev_aa="K" peptide_next_aa="A" protein="sp|P12345|PROT_HUMAN" num_tot_proteins="2" num_matched_ions="8" tot_num_ions="10" calc_neutral_pep_mass="788.3082" massdiff="0.0004" num_tol_term="2" num_missed_cleavages="0" num_matched_peptides="120">
+          <alternative_protein protein="sp|P67890|OTHER_HUMAN" num_tol_term="2"/>
+          <modification_info modified_peptide="n[43]PEPM[147]C[160]K" mod_nterm_mass="43.018390">
+            <mod_aminoacid_mass position="4" mass="147.035400" variable="15.994915"/>
+            <mod_aminoacid_mass position="5" mass="160.030649" static="57.021464"/>
+          </modification_info>
+          <search_score name="xcorr" value="2.543"/>
+          <search_score name="expect" value="1.2e-04"/>
+          <analysis_result analysis="peptideprophet">
+            <peptideprophet_result probability="0.98" all_ntt_prob="(0.0000,0.0000,0.9800)"/>
+          </analysis_result>
+          <analysis_result analysis="interprophet">
+            <interprophet_result probability="0.995" all_ntt_prob="(0,0,0.995)"/>
+          </analysis_result>
+        </search_hit>
+      </search_result>
+    </spectrum_query>
+    <spectrum_query spectrum="run01.01300.01300.2" start_scan="1300" end_scan="1300" precursor_neutral_mass="868.3612" assumed_charge="2" index="2">
+      <search_result>
+        <search_hit hit_rank="1" peptide="SAMPLER" peptide_prev_aa="R" peptide_next_aa="-" protein="sp|P12345|PROT_HUMAN" calc_neutral_pep_mass="868.3608" massdiff="0.0004">
+          <modification_info modified_peptide="S[167]AMPLER">
+            <mod_aminoacid_mass position="1" mass="166.9984"/>
+          </modification_info>
+          <search_score name="xcorr" value="1.2"/>
+          <analysis_result analysis="peptideprophet">
+            <peptideprophet_result probability="0.5"/>
+          </analysis_result>
+        </search_hit>
+        <search_hit hit_rank="2" peptide="SAMPLER" peptide_prev_aa="R" peptide_next_aa="-" protein="sp|P12345|PROT_HUMAN" calc_neutral_pep_mass="800.7608" massdiff="67.6004">
+          <modification_info modified_peptide="SA[83]MPLER">
+            <mod_aminoacid_mass position="2" mass="83.4167" variable="12.3456"/>
+          </modification_info>
+          <search_score name="xcorr" value="0.8"/>
+        </search_hit>
+      </search_result>
+    </spectrum_query>
+  </msms_run_summary>
+</msms_pipeline_analysis>
+"#;
@@ -0,0 +1,305 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::CustomError,
+    glycan::{GlycanCompositionNotation, GlycanStructure, MonoSaccharide},
+    helper_functions::InvertResult,
+    modification::{Modification, SimpleModification},
+    ontologies::CustomDatabase,
+    peptide::{SemiAmbiguous, SloppyParsingParameters},
+    system::{usize::Charge, Mass, MassOverCharge, Time},
+    AminoAcid, CheckedAminoAcid, LinearPeptide, SequencePosition,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common_parser::{Location, OptionalLocation},
+    csv::{parse_csv, CsvLine},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+static NUMBER_ERROR: (&str, &str) = (
+    "Invalid pGlyco line",
+    "This column is not a number but it is required to be a number in this pGlyco format",
+);
+
+format_family!(
+    /// The format for any pGlyco result file
+    PGlycoFormat,
+    /// The data from any pGlyco result file
+    PGlycoData,
+    PGlycoVersion, [&V3], b'\t';
+    required {
+        raw_file: PathBuf, |location: Location, _| Ok(Path::new(&location.get_string()).to_owned());
+        scan: usize, |location: Location, _| location.parse(NUMBER_ERROR);
+        rt: Time, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Time::new::<crate::system::time::s>);
+        precursor_mh: Mass, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(Mass::new::<crate::system::dalton>);
+        mz: MassOverCharge, |location: Location, _| location.parse::<f64>(NUMBER_ERROR).map(MassOverCharge::new::<crate::system::mz>);
+        z: Charge, |location: Location, _| location.parse::<usize>(NUMBER_ERROR).map(Charge::new::<crate::system::e>);
+        /// The peptide with all modifications and the glycan placed on the glycosylation site
+        peptide: LinearPeptide<SemiAmbiguous>, |location: Location, custom_database: Option<&CustomDatabase>| LinearPeptide::sloppy_pro_forma(
+            location.full_line(),
+            location.location.clone(),
+            custom_database,
+            SloppyParsingParameters::default(),
+        );
+        /// The modifications as reported by pGlyco, these are already placed on the peptide
+        modifications: Vec<(SequencePosition, SimpleModification)>, |location: Location, custom_database: Option<&CustomDatabase>| parse_modifications(location, custom_database);
+        glycan: Vec<(MonoSaccharide, isize)>, |location: Location, _| GlycanCompositionNotation::PGlyco.parse(location.as_str()).map_err(|err| err.with_context(location.context()));
+        /// The 1 based index of the glycosylation site on the peptide
+        glycan_site: usize, |location: Location, _| location.parse(NUMBER_ERROR);
+        total_score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        peptide_score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        glycan_score: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+        total_fdr: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+    }
+    optional {
+        rank: usize, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        /// The most plausible glycan structure, if present this is placed on the peptide
+        glycan_structure: GlycanStructure, |location: Location, _| location.or_empty().parse_with(parse_structure);
+        glycan_mass: Mass, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|m| m.map(Mass::new::<crate::system::dalton>));
+        peptide_mh: Mass, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|m| m.map(Mass::new::<crate::system::dalton>));
+        ppm: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        core_matched: usize, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        glycan_ion_ratio: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        peptide_ion_ratio: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        glycan_decoy: bool, |location: Location, _| location.or_empty().parse::<u8>(NUMBER_ERROR).map(|n| n.map(|n| n != 0));
+        peptide_decoy: bool, |location: Location, _| location.or_empty().parse::<u8>(NUMBER_ERROR).map(|n| n.map(|n| n != 0));
+        glycan_fdr: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        peptide_fdr: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        proteins: Vec<String>, |location: Location, _| Ok(Some(split_list(location.as_str())));
+        protein_sites: Vec<String>, |location: Location, _| Ok(Some(split_list(location.as_str())));
+        genes: Vec<String>, |location: Location, _| Ok(Some(split_list(location.as_str())));
+    }
+    post_process: PGlycoData::place_modifications;
+);
+
+impl From<PGlycoData> for IdentifiedPeptide {
+    fn from(value: PGlycoData) -> Self {
+        Self {
+            score: Some(1.0 - value.total_fdr),
+            metadata: MetaData::PGlyco(value),
+        }
+    }
+}
+
+impl PGlycoData {
+    /// Place the modifications and the glycan on the peptide, and translate the `J` used to mark
+    /// N-glycosylation sites.
+    /// # Errors
+    /// If any modification or the glycan site is outside of the peptide.
+    fn place_modifications(
+        source: &CsvLine,
+        mut parsed: Self,
+        _custom_database: Option<&CustomDatabase>,
+    ) -> Result<Self, CustomError> {
+        let length = parsed.peptide.len();
+        let out_of_range = |explanation: String| {
+            CustomError::error("Invalid pGlyco line", explanation, source.full_context())
+        };
+        for element in parsed.peptide.sequence_mut() {
+            if element.aminoacid.aminoacid() == AminoAcid::AmbiguousLeucine {
+                element.aminoacid = CheckedAminoAcid::new(AminoAcid::Asparagine);
+            }
+        }
+        for (position, modification) in &parsed.modifications {
+            if let SequencePosition::Index(index) = position {
+                if *index >= length {
+                    return Err(out_of_range(format!(
+                        "The modification {modification} is placed at position {} but the peptide is only {length} long",
+                        index + 1
+                    )));
+                }
+            }
+            parsed
+                .peptide
+                .add_simple_modification(*position, modification.clone());
+        }
+        if parsed.glycan_site == 0 || parsed.glycan_site > length {
+            return Err(out_of_range(format!(
+                "The glycan site {} is outside of the peptide, which is {length} long",
+                parsed.glycan_site
+            )));
+        }
+        parsed.peptide.add_simple_modification(
+            SequencePosition::Index(parsed.glycan_site - 1),
+            parsed.glycan_structure.clone().map_or_else(
+                || SimpleModification::Glycan(parsed.glycan.clone()),
+                SimpleModification::GlycanStructure,
+            ),
+        );
+        Ok(parsed)
+    }
+}
+
+/// Parse the pGlyco modifications, eg `3,Carbamidomethyl[C];7,Oxidation[M];`. The position is 1
+/// based and the amino acid is given in square brackets (or the terminus, eg `ProteinN-term`).
+/// # Errors
+/// If a modification is not in the expected format or is not a known modification.
+fn parse_modifications(
+    location: Location,
+    custom_database: Option<&CustomDatabase>,
+) -> Result<Vec<(SequencePosition, SimpleModification)>, CustomError> {
+    location
+        .array(';')
+        .filter(|part| !part.as_str().trim().is_empty())
+        .map(|part| {
+            let text = part.as_str();
+            let invalid = || {
+                CustomError::error(
+                    "Invalid pGlyco modification",
+                    "A modification should be written as 'position,Name[AminoAcid]'",
+                    part.context(),
+                )
+            };
+            let (position, name) = text.split_once(',').ok_or_else(invalid)?;
+            let name_start = part.location.start + position.len() + 1;
+            let name_end = name.find('[').map_or(part.location.end, |i| name_start + i);
+            let site = &part.full_line()[name_end..part.location.end];
+            let modification = Modification::sloppy_modification(
+                part.full_line(),
+                name_start..name_end,
+                None,
+                custom_database,
+            )?;
+            let position = if site.contains("N-term") {
+                SequencePosition::NTerm
+            } else if site.contains("C-term") {
+                SequencePosition::CTerm
+            } else {
+                match position.trim().parse::<usize>() {
+                    Ok(index) if index > 0 => SequencePosition::Index(index - 1),
+                    _ => return Err(invalid()),
+                }
+            };
+            Ok((position, modification))
+        })
+        .collect()
+}
+
+/// Parse a pGlyco glycan structure, eg `(N(F)(N(H(H)(H))))`. Every monosaccharide is enclosed in
+/// parentheses and followed by its branches.
+/// # Errors
+/// If the structure is not valid.
+#[allow(clippy::needless_pass_by_value)] // Needed to be used in `parse_with`
+fn parse_structure(location: Location) -> Result<GlycanStructure, CustomError> {
+    let text = location.as_str().trim();
+    let (structure, end) = parse_structure_internal(text, 0, &location)?;
+    if end == text.len() {
+        Ok(structure)
+    } else {
+        Err(CustomError::error(
+            "Invalid pGlyco glycan structure",
+            "The structure continues after the root monosaccharide",
+            location.context(),
+        ))
+    }
+}
+
+/// Parse a single monosaccharide with its branches starting at the given index, returns the
+/// index after the closing parenthesis.
+/// # Errors
+/// If the structure is not valid.
+fn parse_structure_internal(
+    text: &str,
+    index: usize,
+    location: &Location,
+) -> Result<(GlycanStructure, usize), CustomError> {
+    let invalid = |explanation: &str| {
+        CustomError::error(
+            "Invalid pGlyco glycan structure",
+            explanation,
+            location.context(),
+        )
+    };
+    if text.as_bytes().get(index) != Some(&b'(') {
+        return Err(invalid(
+            "Every monosaccharide should be enclosed in parentheses",
+        ));
+    }
+    let name = text
+        .get(index + 1..index + 2)
+        .ok_or_else(|| invalid("Missing monosaccharide"))?;
+    let sugar = match GlycanCompositionNotation::PGlyco
+        .parse(&format!("{name}1"))
+        .map_err(|err| err.with_context(location.context()))?
+        .as_slice()
+    {
+        [(sugar, 1)] => sugar.clone(),
+        _ => return Err(invalid("Invalid monosaccharide")),
+    };
+    let mut index = index + 2;
+    let mut branches = Vec::new();
+    while text.as_bytes().get(index) == Some(&b'(') {
+        let (branch, end) = parse_structure_internal(text, index, location)?;
+        branches.push(branch);
+        index = end;
+    }
+    if text.as_bytes().get(index) == Some(&b')') {
+        Ok((GlycanStructure::new(sugar, branches), index + 1))
+    } else {
+        Err(invalid("Missing closing parenthesis"))
+    }
+}
+
+/// Split a slash or semicolon separated list, ignoring empty elements
+fn split_list(text: &str) -> Vec<String> {
+    text.split(['/', ';'])
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.trim().to_string())
+        .collect()
+}
+
+/// All possible pGlyco versions
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum PGlycoVersion {
+    /// pGlyco3 FDR filtered glycopeptide results (`pGlycoDB-GP-FDR-Pro.txt`)
+    #[default]
+    V3,
+}
+
+impl std::fmt::Display for PGlycoVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::V3 => "v3",
+            }
+        )
+    }
+}
+
+/// pGlyco3 FDR filtered glycopeptide results (`pGlycoDB-GP-FDR-Pro.txt`)
+pub const V3: PGlycoFormat = PGlycoFormat {
+    version: PGlycoVersion::V3,
+    raw_file: "rawname",
+    scan: "scan",
+    rt: "rt",
+    precursor_mh: "precursormh",
+    mz: "precursormz",
+    z: "charge",
+    peptide: "peptide",
+    modifications: "mod",
+    glycan: "glycancomposition",
+    glycan_site: "glysite",
+    total_score: "totalscore",
+    peptide_score: "pepscore",
+    glycan_score: "glyscore",
+    total_fdr: "totalfdr",
+    rank: Some("rank"),
+    glycan_structure: Some("plausiblestruct"),
+    glycan_mass: Some("glymass"),
+    peptide_mh: Some("peptidemh"),
+    ppm: Some("ppm"),
+    core_matched: Some("corematched"),
+    glycan_ion_ratio: Some("glyionratio"),
+    peptide_ion_ratio: Some("pepionratio"),
+    glycan_decoy: Some("glydecoy"),
+    peptide_decoy: Some("pepdecoy"),
+    glycan_fdr: Some("glycanfdr"),
+    peptide_fdr: Some("peptidefdr"),
+    proteins: Some("proteins"),
+    protein_sites: Some("prosite"),
+    genes: Some("genes"),
+};
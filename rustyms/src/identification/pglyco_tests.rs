@@ -0,0 +1,98 @@
+#![allow(clippy::missing_panics_doc)]
+use std::io::BufReader;
+
+use crate::{
+    glycan::GlycanCompositionNotation,
+    helper_functions::temporary_file,
+    modification::SimpleModification,
+    system::{e, time::s, usize::Charge},
+    AminoAcid, Modification, SequencePosition,
+};
+
+use super::{
+    csv::parse_csv_raw, detect_identified_peptides_file_format, open_identified_peptides_file,
+    pglyco, IdentifiedPeptide, IdentifiedPeptideSource, KnownFileFormat, PGlycoData, PGlycoVersion,
+};
+
+#[test]
+fn pglyco() {
+    let reader = BufReader::new(DATA.as_bytes());
+    let lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    let peptides: Vec<PGlycoData> = lines
+        .map(|line| PGlycoData::parse_specific(&line.unwrap(), &pglyco::V3, None).unwrap())
+        .collect();
+    assert_eq!(peptides.len(), 2);
+
+    // The structure is placed on the peptide, on the N that was written as J
+    let peptide = &peptides[0].peptide;
+    assert_eq!(
+        peptide.sequence()[11].aminoacid.aminoacid(),
+        AminoAcid::Asparagine
+    );
+    assert!(matches!(
+        peptide.sequence()[11].modifications.as_slice(),
+        [Modification::Simple(SimpleModification::GlycanStructure(structure))]
+            if structure.composition() == GlycanCompositionNotation::PGlyco.parse("H5N2").unwrap()
+    ));
+    assert_eq!(peptides[0].modifications.len(), 2);
+    assert_eq!(peptides[0].modifications[0].0, SequencePosition::Index(1));
+    assert_eq!(peptide.sequence()[1].modifications.len(), 1);
+    assert_eq!(peptide.sequence()[4].modifications.len(), 1);
+
+    // Without a structure the composition is placed
+    let peptide = &peptides[1].peptide;
+    let glycan = GlycanCompositionNotation::PGlyco.parse("H3N4F1").unwrap();
+    assert_eq!(peptides[1].glycan, glycan);
+    assert_eq!(
+        peptide.sequence()[4].modifications,
+        vec![Modification::Simple(SimpleModification::Glycan(glycan))]
+    );
+    assert_eq!(
+        peptides[1].proteins,
+        Some(vec![
+            "sp|P01857|IGHG1_HUMAN".to_string(),
+            "sp|P01859|IGHG2_HUMAN".to_string()
+        ])
+    );
+
+    let identified: IdentifiedPeptide = peptides[1].clone().into();
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(3)));
+    assert!((identified.retention_time().unwrap().get::<s>() - 3354.0711).abs() < 1e-9);
+    assert_eq!(identified.q_value(), Some(0.0035));
+    assert_eq!(identified.is_decoy(), Some(false));
+    assert_eq!(identified.scan_indices(), Some(vec![13977]));
+}
+
+#[test]
+fn pglyco_detect() {
+    let path = temporary_file("txt", DATA);
+    let format = detect_identified_peptides_file_format(&path);
+    let peptides =
+        open_identified_peptides_file(&path, None).map(Iterator::collect::<Result<Vec<_>, _>>);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(format.unwrap(), KnownFileFormat::PGlyco(PGlycoVersion::V3));
+    assert_eq!(peptides.unwrap().unwrap().len(), 2);
+}
+
+#[test]
+fn pglyco_invalid() {
+    for (from, to) in [
+        ("\t12\t", "\t16\t"),
+        ("2,Carbamidomethyl[C];", "20,Carbamidomethyl[C];"),
+        ("(N(N(H(H)(H(H)(H)))))", "(N(N(H(H)(H(H)(H))))"),
+        ("H(5)N(2)", "H(5)Q(2)"),
+    ] {
+        let data = DATA.replace(from, to);
+        let reader = BufReader::new(data.as_bytes());
+        let mut lines = parse_csv_raw(reader, b'\t', None).unwrap();
+        assert!(
+            PGlycoData::parse_specific(&lines.next().unwrap().unwrap(), &pglyco::V3, None).is_err(),
+            "{to}"
+        );
+    }
+}
+
+const DATA: &str = r"GlySpec	PepSpec	RawName	Scan	RT	PrecursorMH	PrecursorMZ	Charge	Rank	Peptide	Mod	PeptideMH	GlycanComposition	PlausibleStruct	GlyID	GlyFrag	GlyMass	GlySite	TotalScore	PepScore	GlyScore	CoreMatched	CoreFuc	MassDeviation	PPM	GlyIonRatio	PepIonRatio	GlyDecoy	PepDecoy	GlycanFDR	PeptideFDR	TotalFDR	Proteins	ProSite	Genes
+20220916_HeLa_glyco_01.12401.12401.3.0.dta	20220916_HeLa_glyco_01.12401.12401.3.0.dta	20220916_HeLa_glyco_01	12401	3012.4521	2948.2310	983.4152	3	1	LCPDCPLLAPLJDSR	2,Carbamidomethyl[C];5,Carbamidomethyl[C];	1732.8352	H(5)N(2)	(N(N(H(H)(H(H)(H)))))	1520		1216.4229	12	21.0032	15.4010	32.1184	5	0	0.0021	0.7123	0.4521	0.3310	0	0	0.0001	0.0032	0.0012	sp|P13473|LAMP2_HUMAN	/356/	LAMP2
+20220916_HeLa_glyco_01.13977.13977.3.0.dta	20220916_HeLa_glyco_01.13977.13977.3.0.dta	20220916_HeLa_glyco_01	13977	3354.0711	2634.0336	878.6827	3	1	EEQYJSTYR		1189.5123	H(3)N(4)F(1)		2301		1444.5339	5	18.2201	12.0932	25.0012	5	1	-0.0011	-0.4175	0.3982	0.2711	0	0	0.0003	0.0020	0.0035	sp|P01857|IGHG1_HUMAN/sp|P01859|IGHG2_HUMAN	/180/176/	IGHG1/IGHG2
+";
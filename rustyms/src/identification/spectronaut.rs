@@ -0,0 +1,123 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    error::CustomError,
+    helper_functions::InvertResult,
+    ontologies::CustomDatabase,
+    peptide::{SemiAmbiguous, SloppyParsingParameters},
+    system::{usize::Charge, MassOverCharge, Time},
+    LinearPeptide,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    common_parser::{Location, OptionalLocation},
+    csv::{parse_csv, CsvLine},
+    BoxedIdentifiedPeptideIter, IdentifiedPeptide, IdentifiedPeptideSource, MetaData,
+};
+
+static NUMBER_ERROR: (&str, &str) = (
+    "Invalid Spectronaut line",
+    "This column is not a number but it is required to be a number in this Spectronaut format",
+);
+static BOOL_ERROR: (&str, &str) = (
+    "Invalid Spectronaut line",
+    "This column is not a boolean but it is required to be a boolean ('True' or 'False') in this Spectronaut format",
+);
+
+format_family!(
+    /// The format for any Spectronaut report file
+    SpectronautFormat,
+    /// The data from any Spectronaut report file
+    SpectronautData,
+    SpectronautVersion, [&LONG_FORMAT], b'\t';
+    required {
+        raw_file: PathBuf, |location: Location, _| Ok(Path::new(&location.get_string()).to_owned());
+        peptide: LinearPeptide<SemiAmbiguous>, |location: Location, custom_database: Option<&CustomDatabase>| LinearPeptide::sloppy_pro_forma(
+            location.full_line(),
+            location.location.clone(),
+            custom_database,
+            SloppyParsingParameters::default(),
+        );
+        z: Charge, |location: Location, _| location.parse::<usize>(NUMBER_ERROR).map(Charge::new::<crate::system::e>);
+        q_value: f64, |location: Location, _| location.parse(NUMBER_ERROR);
+    }
+    optional {
+        condition: String, |location: Location, _| Ok(Some(location.get_string()));
+        replicate: usize, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        protein_groups: String, |location: Location, _| Ok(Some(location.get_string()));
+        protein_accessions: Vec<String>, |location: Location, _| Ok(Some(location.get_string().split(';').filter(|s| !s.is_empty()).map(ToString::to_string).collect()));
+        genes: Vec<String>, |location: Location, _| Ok(Some(location.get_string().split(';').filter(|s| !s.is_empty()).map(ToString::to_string).collect()));
+        pg_q_value: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        pg_quantity: f64, |location: Location, _| location.or_empty().ignore("Filtered").parse(NUMBER_ERROR);
+        precursor_id: String, |location: Location, _| Ok(Some(location.get_string()));
+        cscore: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        decoy: bool, |location: Location, _| location.or_empty().parse_with(|location| match location.as_str().to_ascii_lowercase().as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(CustomError::error(BOOL_ERROR.0, BOOL_ERROR.1, location.context())),
+        });
+        rt: Time, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|rt| rt.map(Time::new::<crate::system::time::min>));
+        predicted_rt: Time, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|rt| rt.map(Time::new::<crate::system::time::min>));
+        irt_empirical: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        irt_predicted: f64, |location: Location, _| location.or_empty().parse(NUMBER_ERROR);
+        precursor_mz: MassOverCharge, |location: Location, _| location.or_empty().parse::<f64>(NUMBER_ERROR).map(|mz| mz.map(MassOverCharge::new::<crate::system::mz>));
+        precursor_quantity: f64, |location: Location, _| location.or_empty().ignore("Filtered").parse(NUMBER_ERROR);
+    }
+);
+
+impl From<SpectronautData> for IdentifiedPeptide {
+    fn from(value: SpectronautData) -> Self {
+        Self {
+            score: Some(1.0 - value.q_value),
+            metadata: MetaData::Spectronaut(value),
+        }
+    }
+}
+
+/// All possible Spectronaut versions
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum SpectronautVersion {
+    /// The long format report (one row per precursor per run), as generated by the default
+    /// report schemas
+    #[default]
+    LongFormat,
+}
+
+impl std::fmt::Display for SpectronautVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::LongFormat => "long format",
+            }
+        )
+    }
+}
+
+/// The long format report (one row per precursor per run), if the report also contains fragment
+/// level columns every precursor is repeated for every fragment
+pub const LONG_FORMAT: SpectronautFormat = SpectronautFormat {
+    version: SpectronautVersion::LongFormat,
+    raw_file: "r.filename",
+    peptide: "eg.modifiedsequence",
+    z: "fg.charge",
+    q_value: "eg.qvalue",
+    condition: Some("r.condition"),
+    replicate: Some("r.replicate"),
+    protein_groups: Some("pg.proteingroups"),
+    protein_accessions: Some("pg.proteinaccessions"),
+    genes: Some("pg.genes"),
+    pg_q_value: Some("pg.qvalue"),
+    pg_quantity: Some("pg.quantity"),
+    precursor_id: Some("eg.precursorid"),
+    cscore: Some("eg.cscore"),
+    decoy: Some("eg.isdecoy"),
+    rt: Some("eg.apexrt"),
+    predicted_rt: Some("eg.rtpredicted"),
+    irt_empirical: Some("eg.irtempirical"),
+    irt_predicted: Some("eg.irtpredicted"),
+    precursor_mz: Some("fg.precmz"),
+    precursor_quantity: Some("fg.quantity"),
+};
@@ -0,0 +1,64 @@
+#![allow(clippy::missing_panics_doc)]
+use std::io::BufReader;
+
+use crate::system::{e, time::min, usize::Charge};
+
+use super::{
+    csv::parse_csv_raw, spectronaut, IdentifiedPeptide, IdentifiedPeptideSource, SpectronautData,
+};
+
+#[test]
+fn spectronaut() {
+    let reader = BufReader::new(DATA.as_bytes());
+    let lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    let peptides: Vec<SpectronautData> = lines
+        .map(|line| {
+            SpectronautData::parse_specific(&line.unwrap(), &spectronaut::LONG_FORMAT, None)
+                .unwrap()
+        })
+        .collect();
+    assert_eq!(peptides.len(), 3);
+
+    assert_eq!(peptides[0].peptide.to_string(), "AEVAHRFK");
+    assert_eq!(
+        peptides[1].peptide.to_string(),
+        "[U:Acetyl]-M[U:Oxidation]DEKLLPK"
+    );
+    assert_eq!(peptides[1].precursor_quantity, None);
+    assert_eq!(peptides[2].peptide.to_string(), "GPS[U:Phospho]VFPLAPSSK");
+    assert_eq!(
+        peptides[2].protein_accessions,
+        Some(vec!["P01857".to_string(), "P01859".to_string()])
+    );
+    assert!(peptides[2].pg_quantity.unwrap().is_nan());
+    assert_eq!(peptides[2].decoy, Some(false));
+    assert_eq!(peptides[2].rt, None);
+
+    let identified: IdentifiedPeptide = peptides[0].clone().into();
+    assert_eq!(identified.charge(), Some(Charge::new::<e>(2)));
+    assert!((identified.retention_time().unwrap().get::<min>() - 24.51).abs() < 1e-9);
+    assert_eq!(identified.q_value(), Some(0.0002));
+    assert_eq!(identified.precursor_quantity(), Some(34567.8));
+    assert_eq!(
+        identified.raw_file().unwrap().to_string_lossy(),
+        "20240101_HeLa_01"
+    );
+}
+
+#[test]
+fn spectronaut_invalid() {
+    let data = DATA.replace("False", "Maybe");
+    let reader = BufReader::new(data.as_bytes());
+    let mut lines = parse_csv_raw(reader, b'\t', None).unwrap();
+    assert!(SpectronautData::parse_specific(
+        &lines.next().unwrap().unwrap(),
+        &spectronaut::LONG_FORMAT,
+        None
+    )
+    .is_err());
+}
+
+const DATA: &str = r"R.Condition	R.FileName	R.Replicate	PG.ProteinAccessions	PG.ProteinGroups	PG.Genes	PG.Qvalue	PG.Quantity	PEP.StrippedSequence	EG.ModifiedSequence	EG.PrecursorId	EG.Qvalue	EG.Cscore	EG.IsDecoy	EG.ApexRT	EG.RTPredicted	EG.iRTEmpirical	EG.iRTPredicted	FG.Charge	FG.PrecMz	FG.Quantity
+Control	20240101_HeLa_01	1	P02768	P02768	ALB	0.0001	120345.6	AEVAHRFK	_AEVAHRFK_	_AEVAHRFK_.2	0.0002	3.21	False	24.51	24.55	12.3	12.5	2	480.7644	34567.8
+Control	20240101_HeLa_01	1	P02768	P02768	ALB	0.0001	120345.6	MDEKLLPK	_[Acetyl (Protein N-term)]M[Oxidation (M)]DEKLLPK_	_[Acetyl (Protein N-term)]M[Oxidation (M)]DEKLLPK_.3	0.0035	2.01	False	31.02	30.8	20.1	20.0	3	337.5058	Filtered
+Treated	20240101_HeLa_02	2	P01857;P01859	P01857;P01859	IGHG1;IGHG2	0.0004	NaN	GPSVFPLAPSSK	_GPS[Phospho (STY)]VFPLAPSSK_	_GPS[Phospho (STY)]VFPLAPSSK_.2	0.001	2.55	False			55.2	55.0	2	633.8047	8912.3";
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use super::IdentifiedPeptide;
+
+/// A scored match that can be used in target decoy competition, see [`target_decoy_competition`]
+pub trait TargetDecoyMatch {
+    /// The score of this match, higher is better. Matches without a score are ignored.
+    fn score(&self) -> Option<f64>;
+    /// If this is a decoy match. Matches with unknown decoy status are ignored.
+    fn is_decoy(&self) -> Option<bool>;
+    /// If this is a de novo match, the FDR for de novo and database matches is estimated
+    /// separately.
+    fn is_de_novo(&self) -> bool {
+        false
+    }
+    /// An identifier for the spectrum of this match. Of all matches for the same spectrum only the
+    /// best scoring one is retained. If this is `None` the match does not compete with any other
+    /// match.
+    fn spectrum(&self) -> Option<String> {
+        None
+    }
+}
+
+/// The result of target decoy competition for a single match
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TargetDecoyResult {
+    /// The q-value, the lowest FDR at which this match is accepted
+    pub q_value: f64,
+    /// The posterior error probability, estimated from the ratio of decoys to targets among the
+    /// matches with a similar score
+    pub pep: f64,
+}
+
+/// Run target decoy competition on the given matches and estimate the q-value and posterior error
+/// probability for all winning matches.
+///
+/// The returned list has the same order as the given
+/// matches, with `None` for all matches that are ignored (see [`TargetDecoyMatch`]) or lost the
+/// competition for their spectrum.
+///
+/// # Estimation
+/// The de novo and database matches are handled separately. The FDR at a score threshold is the
+/// number of decoys divided by the number of targets scoring at least that threshold, and the
+/// q-value is the lowest FDR of any threshold that accepts the match. The posterior error
+/// probability is the ratio of decoys to targets in a window around the match (1% of the matches,
+/// with at least 10 matches on each side), made monotone so that a lower score never gets a lower
+/// probability. Both are capped at 1.0.
+pub fn target_decoy_competition<T: TargetDecoyMatch>(
+    matches: &[T],
+) -> Vec<Option<TargetDecoyResult>> {
+    let mut winners = Vec::new();
+    let mut spectra: HashMap<(bool, String), (usize, f64)> = HashMap::new();
+    for (index, m) in matches.iter().enumerate() {
+        let (Some(score), Some(_)) = (m.score(), m.is_decoy()) else {
+            continue;
+        };
+        if score.is_nan() {
+            continue;
+        }
+        if let Some(spectrum) = m.spectrum() {
+            let best = spectra
+                .entry((m.is_de_novo(), spectrum))
+                .or_insert((index, score));
+            if score > best.1 {
+                *best = (index, score);
+            }
+        } else {
+            winners.push(index);
+        }
+    }
+    winners.extend(spectra.into_values().map(|(index, _)| index));
+
+    let mut results = vec![None; matches.len()];
+    for de_novo in [false, true] {
+        // Sort on score, with decoys before targets on ties to be conservative
+        let group = winners
+            .iter()
+            .filter(|index| matches[**index].is_de_novo() == de_novo)
+            .map(|index| {
+                (
+                    *index,
+                    matches[*index].score().unwrap_or_default(),
+                    matches[*index].is_decoy().unwrap_or_default(),
+                )
+            })
+            .sorted_by(|a, b| b.1.total_cmp(&a.1).then(b.2.cmp(&a.2)))
+            .collect_vec();
+        let ratio =
+            |decoys: usize, targets: usize| (decoys as f64 / targets.max(1) as f64).min(1.0);
+
+        // FDR, equal scores get the FDR of the last match with that score
+        let mut fdr = vec![0.0; group.len()];
+        let (mut decoys, mut targets) = (0, 0);
+        for (index, (_, score, decoy)) in group.iter().enumerate() {
+            if *decoy {
+                decoys += 1;
+            } else {
+                targets += 1;
+            }
+            fdr[index] = ratio(decoys, targets);
+            for previous in (0..index).rev() {
+                if group[previous].1.total_cmp(score).is_ne() {
+                    break;
+                }
+                fdr[previous] = fdr[index];
+            }
+        }
+        let mut q_values = fdr;
+        for index in (0..q_values.len().saturating_sub(1)).rev() {
+            q_values[index] = q_values[index].min(q_values[index + 1]);
+        }
+
+        // PEP from the local decoy density
+        let window = (group.len() / 100).max(10);
+        let mut pep = 0.0_f64;
+        for (index, (original, _, _)) in group.iter().enumerate() {
+            let local = &group[index.saturating_sub(window)..(index + window + 1).min(group.len())];
+            let decoys = local.iter().filter(|(_, _, decoy)| *decoy).count();
+            pep = pep.max(ratio(decoys, local.len() - decoys));
+            results[*original] = Some(TargetDecoyResult {
+                q_value: q_values[index],
+                pep,
+            });
+        }
+    }
+    results
+}
+
+impl TargetDecoyMatch for IdentifiedPeptide {
+    fn score(&self) -> Option<f64> {
+        self.score
+    }
+
+    fn is_decoy(&self) -> Option<bool> {
+        self.is_decoy()
+    }
+
+    fn is_de_novo(&self) -> bool {
+        self.is_de_novo()
+    }
+
+    fn spectrum(&self) -> Option<String> {
+        let spectrum = self
+            .spectrum_native_ids()
+            .map(|ids| ids.join(";"))
+            .or_else(|| self.scan_indices().map(|scans| scans.iter().join(";")))?;
+        Some(format!(
+            "{}:{spectrum}",
+            self.raw_file()
+                .map(|file| file.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ))
+    }
+}
+
+impl TargetDecoyMatch for (f64, bool) {
+    fn score(&self) -> Option<f64> {
+        Some(self.0)
+    }
+
+    fn is_decoy(&self) -> Option<bool> {
+        Some(self.1)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn q_values() {
+        let matches = [
+            (10.0, false),
+            (9.0, false),
+            (8.0, true),
+            (7.0, false),
+            (6.0, false),
+            (5.0, true),
+            (4.0, true),
+            (f64::NAN, false),
+        ];
+        let results = target_decoy_competition(&matches);
+        let q_values = results.iter().map(|r| r.map(|r| r.q_value)).collect_vec();
+        assert_eq!(
+            q_values,
+            vec![
+                Some(0.0),
+                Some(0.0),
+                Some(0.25),
+                Some(0.25),
+                Some(0.25),
+                Some(0.5),
+                Some(0.75),
+                None
+            ]
+        );
+        assert!(results
+            .iter()
+            .flatten()
+            .tuple_windows()
+            .all(|(a, b)| a.pep <= b.pep));
+    }
+
+    struct Psm(f64, bool, bool, &'static str);
+
+    impl TargetDecoyMatch for Psm {
+        fn score(&self) -> Option<f64> {
+            Some(self.0)
+        }
+        fn is_decoy(&self) -> Option<bool> {
+            Some(self.1)
+        }
+        fn is_de_novo(&self) -> bool {
+            self.2
+        }
+        fn spectrum(&self) -> Option<String> {
+            Some(self.3.to_string())
+        }
+    }
+
+    #[test]
+    fn competition() {
+        let matches = [
+            Psm(10.0, false, false, "1"),
+            Psm(11.0, true, false, "1"),
+            Psm(9.0, false, false, "2"),
+            Psm(8.0, true, false, "2"),
+            Psm(20.0, false, true, "1"),
+            Psm(1.0, true, true, "3"),
+        ];
+        let results = target_decoy_competition(&matches);
+        assert!(results[0].is_none());
+        assert_eq!(results[1].map(|r| r.q_value), Some(1.0));
+        assert_eq!(results[2].map(|r| r.q_value), Some(1.0));
+        assert!(results[3].is_none());
+        assert_eq!(results[4].map(|r| r.q_value), Some(0.0));
+        assert_eq!(results[5].map(|r| r.q_value), Some(1.0));
+    }
+}
@@ -30,21 +30,6 @@ pub fn building_blocks(
     BuildingBlocks,
     TerminalBuildingBlocks,
 ) {
-    /// Enforce the placement rules of predefined modifications.
-    fn can_be_placed(
-        modification: &SimpleModification,
-        seq: &SequenceElement<SemiAmbiguous>,
-        position: SequencePosition,
-    ) -> bool {
-        if let SimpleModification::Database { specificities, .. } = modification {
-            specificities.is_empty()
-                || specificities
-                    .iter()
-                    .any(|(rules, _, _)| PlacementRule::any_possible(rules, seq, position))
-        } else {
-            true
-        }
-    }
     fn n_term_options(amino_acids: &[AminoAcid], rule: &PlacementRule) -> Vec<AminoAcid> {
         match rule {
             PlacementRule::AminoAcid(aa, Position::AnyNTerm | Position::ProteinNTerm) => {
@@ -148,20 +133,11 @@ pub fn building_blocks(
                     fixed
                         .iter()
                         .filter(|&m| {
-                            m.1.as_ref().map_or_else(
-                                || {
-                                    can_be_placed(
-                                        &m.0,
-                                        &SequenceElement::new(aa.into(), None),
-                                        position,
-                                    )
-                                },
-                                |rule| {
-                                    rule.is_possible(
-                                        &SequenceElement::new(aa.into(), None),
-                                        position,
-                                    )
-                                },
+                            can_be_placed(
+                                &m.0,
+                                m.1.as_ref(),
+                                &SequenceElement::<SemiAmbiguous>::new(aa.into(), None),
+                                position,
                             )
                         })
                         .map(|m| {
@@ -180,12 +156,7 @@ pub fn building_blocks(
                 options.extend(
                     variable
                         .iter()
-                        .filter(|&m| {
-                            m.1.as_ref().map_or_else(
-                                || can_be_placed(&m.0, &seq, position),
-                                |rule| rule.is_possible(&seq, position),
-                            )
-                        })
+                        .filter(|&m| can_be_placed(&m.0, m.1.as_ref(), &seq, position))
                         .map(|m| {
                             let mut modifications = seq.modifications.clone();
                             modifications.push(Modification::Simple(m.0.clone()));
@@ -216,6 +187,44 @@ pub fn building_blocks(
     )
 }
 
+/// Enforce the placement rules of modifications.
+///
+/// If a placement rule is given only that rule is used, otherwise the placement rules of the
+/// modification itself are used. Modifications without any placement rules can be placed on the
+/// side chain of any amino acid. On the termini only rules that are specific for that terminus
+/// are accepted, so a side chain modification is never placed on a terminus.
+pub fn can_be_placed<T>(
+    modification: &SimpleModification,
+    rule: Option<&PlacementRule>,
+    seq: &SequenceElement<T>,
+    position: SequencePosition,
+) -> bool {
+    let side_chain = matches!(position, SequencePosition::Index(_));
+    let allowed = |rule: &PlacementRule| {
+        rule.is_possible(seq, position)
+            && (side_chain
+                || !matches!(
+                    rule,
+                    PlacementRule::Anywhere
+                        | PlacementRule::AminoAcid(_, Position::Anywhere)
+                        | PlacementRule::PsiModification(_, Position::Anywhere)
+                ))
+    };
+    rule.map_or_else(
+        || {
+            if let SimpleModification::Database { specificities, .. } = modification {
+                (side_chain && specificities.is_empty())
+                    || specificities
+                        .iter()
+                        .any(|(rules, _, _)| rules.iter().any(allowed))
+            } else {
+                side_chain
+            }
+        },
+        allowed,
+    )
+}
+
 /// Find the isobaric sets for the given mass with the given modifications and ppm error.
 /// The modifications are placed on any location they are allowed based on the given placement
 /// rules, so using any modifications which provide those is advised. If the provided [`LinearPeptide`]
@@ -494,6 +503,7 @@ impl Iterator for IsobaricSetIterator {
 mod tests {
 
     use super::*;
+    use crate::{modification::Ontology, system::da};
     #[test]
     fn simple_isobaric_sets() {
         let pep = LinearPeptide::pro_forma("AG", None)
@@ -523,4 +533,71 @@ mod tests {
             ]
         );
     }
+    #[test]
+    fn placement_rules() {
+        let oxidation = Ontology::Unimod.find_id(35, None).unwrap();
+        let acetyl = Ontology::Unimod.find_id(1, None).unwrap();
+        let mass = SimpleModification::Mass(da(5.0).into());
+        let met = SequenceElement::<SemiAmbiguous>::new(AminoAcid::Methionine.into(), None);
+        let lys = SequenceElement::<SemiAmbiguous>::new(AminoAcid::Lysine.into(), None);
+        let side_chain = SequencePosition::Index(0);
+        // Side chain rules are not used on the termini
+        assert!(can_be_placed(&oxidation, None, &met, side_chain));
+        assert!(!can_be_placed(
+            &oxidation,
+            None,
+            &met,
+            SequencePosition::NTerm
+        ));
+        assert!(!can_be_placed(
+            &oxidation,
+            None,
+            &met,
+            SequencePosition::CTerm
+        ));
+        // Terminal rules are only used on the termini
+        assert!(can_be_placed(&acetyl, None, &lys, side_chain));
+        assert!(can_be_placed(&acetyl, None, &met, SequencePosition::NTerm));
+        assert!(!can_be_placed(&acetyl, None, &met, side_chain));
+        // Modifications without rules are only placed on side chains
+        assert!(can_be_placed(&mass, None, &met, side_chain));
+        assert!(!can_be_placed(&mass, None, &met, SequencePosition::NTerm));
+        assert!(!can_be_placed(&mass, None, &met, SequencePosition::CTerm));
+        let anywhere = PlacementRule::Anywhere;
+        assert!(can_be_placed(&mass, Some(&anywhere), &met, side_chain));
+        assert!(!can_be_placed(
+            &mass,
+            Some(&anywhere),
+            &met,
+            SequencePosition::NTerm
+        ));
+        let terminal = PlacementRule::Terminal(Position::AnyNTerm);
+        assert!(can_be_placed(
+            &mass,
+            Some(&terminal),
+            &met,
+            SequencePosition::NTerm
+        ));
+        assert!(!can_be_placed(&mass, Some(&terminal), &met, side_chain));
+    }
+
+    #[test]
+    fn building_blocks_placement() {
+        let mass = SimpleModification::Mass(da(5.0).into());
+        let (n_term, center, c_term) = building_blocks(
+            &[AminoAcid::Glycine, AminoAcid::Alanine],
+            &[],
+            &[(mass, None)],
+        );
+        assert!(n_term.is_empty());
+        assert!(c_term.is_empty());
+        assert_eq!(center.len(), 4);
+        assert_eq!(
+            center
+                .iter()
+                .filter(|(element, _)| !element.modifications.is_empty())
+                .count(),
+            2
+        );
+    }
 }
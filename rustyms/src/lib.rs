@@ -38,6 +38,7 @@ pub mod csv;
 pub mod aminoacid_properties;
 mod aminoacids;
 mod checked_aminoacid;
+mod decoy;
 mod element;
 pub mod error;
 pub mod fragment;
@@ -52,7 +53,7 @@ pub mod modification;
 mod molecular_charge;
 #[path = "shared/multi.rs"]
 mod multi;
-mod mzpaf;
+pub mod mzpaf;
 mod neutral_loss;
 pub mod ontologies;
 mod peptide;
@@ -62,6 +63,9 @@ mod protease;
 /// Only available with features `rand`.
 mod rand;
 pub mod rawfile;
+#[cfg(feature = "identification")]
+/// Only available with feature `identification`.
+pub mod search;
 mod sequence_element;
 #[path = "shared/sequence_position.rs"]
 mod sequence_position;
@@ -69,6 +73,7 @@ pub mod spectrum;
 pub mod system;
 mod tolerance;
 
+pub use crate::decoy::*;
 pub use crate::element::*;
 pub use crate::formula::*;
 pub use crate::isobaric_sets::{building_blocks, find_isobaric_sets};
@@ -108,7 +113,7 @@ mod test {
             .into_linear()
             .unwrap();
         let fragments = peptide.generate_theoretical_fragments(
-            system::usize::Charge::new::<system::e>(1),
+            system::isize::Charge::new::<system::e>(1),
             &Model::all(),
         );
         println!("{}", fragments.len());
@@ -121,8 +126,66 @@ mod test {
         let spectrum = rawfile::mgf::open("data/example.mgf").unwrap();
         let peptide = CompoundPeptidoform::pro_forma("WFWF", None).unwrap();
         let fragments = peptide
-            .generate_theoretical_fragments(system::usize::Charge::new::<system::e>(1), &model);
+            .generate_theoretical_fragments(system::isize::Charge::new::<system::e>(1), &model);
         let annotated = spectrum[0].annotate(peptide, &fragments, &model, MassMode::Monoisotopic);
         println!("{annotated:?}");
     }
+
+    #[test]
+    #[cfg(feature = "isotopes")]
+    fn isotope_matching() {
+        let peptide = CompoundPeptidoform::pro_forma("WFWF", None).unwrap();
+        let fragments = peptide.generate_theoretical_fragments(
+            system::isize::Charge::new::<system::e>(1),
+            &Model::none(),
+        );
+        let precursor = fragments[0].mz(MassMode::Monoisotopic).value;
+        let spectrum = |intensities: &[f64]| {
+            let mut spectrum = RawSpectrum::default();
+            spectrum.extend(intensities.iter().enumerate().map(|(offset, intensity)| {
+                spectrum::RawPeak {
+                    mz: system::MassOverCharge::new::<system::mz>(
+                        precursor + offset as f64 * 1.003_354_835,
+                    ),
+                    intensity: (*intensity).into(),
+                }
+            }));
+            spectrum
+        };
+        let annotate = |spectrum: &RawSpectrum, model: &Model| {
+            spectrum.annotate(peptide.clone(), &fragments, model, MassMode::Monoisotopic)
+        };
+
+        // Without isotope annotation only the monoisotopic peak is annotated
+        let expected = spectrum(&[100.0, 43.0, 10.0]);
+        let annotated = annotate(&expected, &Model::none());
+        assert_eq!(annotated[0].annotation.len(), 1);
+        assert!(annotated[1].isotope_annotation.is_empty());
+
+        // With isotope annotation the M+1 and M+2 peaks are annotated
+        let model =
+            Model::none().isotopes(model::IsotopeModel::ALLOW.minimal_similarity(Some(0.9)));
+        let annotated = annotate(&expected, &model);
+        assert_eq!(annotated[1].isotope_annotation.len(), 1);
+        assert_eq!(annotated[1].isotope_annotation[0].1, 1);
+        assert_eq!(annotated[2].isotope_annotation[0].1, 2);
+        let (scores, _) = annotated.scores(&fragments, &model, MassMode::Monoisotopic);
+        match scores.score {
+            spectrum::Score::UniqueFormulas {
+                intensity,
+                isotope_intensity,
+                ..
+            } => {
+                assert!((intensity.fraction() - 1.0).abs() < 1e-9);
+                assert!((isotope_intensity.fraction() - 53.0 / 153.0).abs() < 1e-9);
+            }
+            spectrum::Score::Position { .. } => panic!("Expected a unique formulas score"),
+        }
+
+        // An envelope that does not resemble the predicted envelope is not annotated
+        let annotated = annotate(&spectrum(&[10.0, 100.0, 100.0]), &model);
+        assert_eq!(annotated[0].annotation.len(), 1);
+        assert!(annotated[1].isotope_annotation.is_empty());
+        assert!(annotated[2].isotope_annotation.is_empty());
+    }
 }
@@ -5,7 +5,7 @@ use std::ops::RangeInclusive;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    fragment::PeptidePosition,
+    fragment::{FragmentKind, PeptidePosition},
     system::{e, f64::MassOverCharge, isize::Charge, mz},
     NeutralLoss, Tolerance,
 };
@@ -13,7 +13,8 @@ use crate::{
 /// Control what charges are allowed for an ion series. Defined as an inclusive range.
 /// Any charge above the precursor charge will result in the quotient time the precursor
 /// charge carriers + all options for the remainder within the limits of the precursor
-/// charge carriers.
+/// charge carriers. For negatively charged precursors the range is applied to the absolute
+/// charge, so [`ChargePoint::Absolute(1)`] results in a charge of -1.
 #[non_exhaustive]
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ChargeRange {
@@ -26,17 +27,33 @@ pub struct ChargeRange {
 impl ChargeRange {
     /// Get all possible charges for the given precursor charge.
     pub fn charges(&self, precursor: Charge) -> RangeInclusive<Charge> {
-        Charge::new::<e>(self.start.to_absolute(precursor).value.max(1))
-            ..=self.end.to_absolute(precursor)
+        let (sign, range) = self.absolute_charges(precursor);
+        if sign < 0 {
+            Charge::new::<e>(-range.end())..=Charge::new::<e>(-range.start())
+        } else {
+            Charge::new::<e>(*range.start())..=Charge::new::<e>(*range.end())
+        }
     }
 
-    /// Get all possible charges for the given precursor charge.
+    /// Get all possible charges for the given precursor charge. Ordered from the lowest to the
+    /// highest absolute charge.
     pub fn charges_iter(
         &self,
         precursor: Charge,
     ) -> impl DoubleEndedIterator<Item = Charge> + Clone {
-        (self.start.to_absolute(precursor).value.max(1)..=self.end.to_absolute(precursor).value)
-            .map(Charge::new::<e>)
+        let (sign, range) = self.absolute_charges(precursor);
+        range.map(move |c| Charge::new::<e>(sign * c))
+    }
+
+    /// Get the sign of the precursor charge (neutral is seen as positive) and the range of
+    /// absolute charges.
+    fn absolute_charges(&self, precursor: Charge) -> (isize, RangeInclusive<isize>) {
+        let sign = if precursor.value < 0 { -1 } else { 1 };
+        let absolute = Charge::new::<e>(precursor.value.abs());
+        (
+            sign,
+            self.start.to_absolute(absolute).value.max(1)..=self.end.to_absolute(absolute).value,
+        )
     }
 
     /// Solely single charged
@@ -54,6 +71,11 @@ impl ChargeRange {
         start: ChargePoint::Absolute(1),
         end: ChargePoint::Relative(0),
     };
+    /// Range from 1 to one below the precursor, as for charge reduced precursors
+    pub const ONE_TO_PRECURSOR_MINUS_ONE: Self = Self {
+        start: ChargePoint::Absolute(1),
+        end: ChargePoint::Relative(-1),
+    };
 }
 
 /// A reference point for charge range definition.
@@ -97,8 +119,12 @@ pub struct Model {
     pub y: PrimaryIonSeries,
     /// z series ions
     pub z: PrimaryIonSeries,
+    /// internal fragment ions (two backbone cleavages)
+    pub internal: InternalIonSeries,
     /// precursor ions
     pub precursor: (Vec<NeutralLoss>, ChargeRange),
+    /// charge reduced precursor ions (electron captured but not dissociated)
+    pub charge_reduced_precursor: ChargeReducedPrecursorModel,
     /// immonium ions
     pub immonium: (bool, ChargeRange),
     /// m ions, loss of the amino acid side chain from the precursor (follows precursor charge)
@@ -109,6 +135,9 @@ pub struct Model {
     pub modification_specific_diagnostic_ions: (bool, ChargeRange),
     /// Glycan fragmentation
     pub glycan: GlycanModel,
+    /// Annotation of the isotope peaks of fragments
+    #[cfg(feature = "isotopes")]
+    pub isotopes: IsotopeModel,
     /// Allow any MS cleavable cross-link to be cleaved
     pub allow_cross_link_cleavage: bool,
     /// The matching tolerance
@@ -126,6 +155,11 @@ pub struct PrimaryIonSeries {
     pub neutral_losses: Vec<NeutralLoss>,
     /// The allowed charges
     pub charge_range: ChargeRange,
+    /// The hydrogen shifted variants of this series that are generated, given as the number of
+    /// hydrogens added (positive) or removed (negative), so `[0, 1]` generates z and z· (z+1).
+    /// If this is `None` the z series generates z and z· and all other series only generate the
+    /// unshifted ion.
+    pub variants: Option<Vec<i8>>,
 }
 
 impl PrimaryIonSeries {
@@ -150,14 +184,94 @@ impl PrimaryIonSeries {
             ..self
         }
     }
+    /// Replace the hydrogen shifted variants, see [`Self::variants`]
+    #[must_use]
+    pub fn variants(self, variants: Vec<i8>) -> Self {
+        Self {
+            variants: Some(variants),
+            ..self
+        }
+    }
 }
 
 impl std::default::Default for PrimaryIonSeries {
+    /// Generates all positions, without neutral losses, with all charges up to the precursor
+    /// charge, and the default hydrogen shifted variants (see [`Self::variants`])
     fn default() -> Self {
         Self {
             location: Location::All,
             neutral_losses: Vec::new(),
             charge_range: ChargeRange::ONE_TO_PRECURSOR,
+            variants: None,
+        }
+    }
+}
+
+/// The settings for internal fragments, resulting from two backbone cleavages
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct InternalIonSeries {
+    /// The combinations of ion types for the C terminal side cleavage (a/b/c) and the N terminal
+    /// side cleavage (x/y/z) that are generated, the classic internal fragment is `(b, y)`
+    pub ion_types: Vec<(FragmentKind, FragmentKind)>,
+    /// The allowed number of residues in the internal fragments
+    pub length: RangeInclusive<usize>,
+    /// The allowed neutral losses
+    pub neutral_losses: Vec<NeutralLoss>,
+    /// The allowed charges
+    pub charge_range: ChargeRange,
+}
+
+impl InternalIonSeries {
+    /// Replace the ion types, see [`Self::ion_types`]
+    #[must_use]
+    pub fn ion_types(self, ion_types: Vec<(FragmentKind, FragmentKind)>) -> Self {
+        Self { ion_types, ..self }
+    }
+    /// Replace the allowed length
+    #[must_use]
+    pub fn length(self, length: RangeInclusive<usize>) -> Self {
+        Self { length, ..self }
+    }
+    /// Replace the neutral losses
+    #[must_use]
+    pub fn neutral_losses(self, neutral_losses: Vec<NeutralLoss>) -> Self {
+        Self {
+            neutral_losses,
+            ..self
+        }
+    }
+    /// Replace the charge range
+    #[must_use]
+    pub fn charge_range(self, charge_range: ChargeRange) -> Self {
+        Self {
+            charge_range,
+            ..self
+        }
+    }
+    /// Generate all combinations of a/b/c with x/y/z internal fragments
+    pub fn all() -> Self {
+        Self {
+            ion_types: [FragmentKind::a, FragmentKind::b, FragmentKind::c]
+                .into_iter()
+                .flat_map(|n_kind| {
+                    [FragmentKind::x, FragmentKind::y, FragmentKind::z]
+                        .into_iter()
+                        .map(move |c_kind| (n_kind, c_kind))
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+}
+
+impl std::default::Default for InternalIonSeries {
+    /// Does not generate any internal fragments, set [`Self::ion_types`] to enable them
+    fn default() -> Self {
+        Self {
+            ion_types: Vec::new(),
+            length: 1..=usize::MAX,
+            neutral_losses: Vec::new(),
+            charge_range: ChargeRange::ONE_TO_PRECURSOR,
         }
     }
 }
@@ -176,6 +290,14 @@ pub struct GlycanModel {
     pub oxonium_charge_range: ChargeRange,
     /// The allowed charges for other glycan fragments (Y)
     pub other_charge_range: ChargeRange,
+    /// Allows glycosidic C and Z fragments from glycans with defined structures
+    pub allow_c_z: bool,
+    /// The cross-ring cleavages that generate A and X fragments from glycans with defined
+    /// structures, given as the two broken ring bonds in Domon–Costello numbering (eg `(0, 2)`
+    /// for 0,2A and 0,2X). See [`Self::CROSS_RING_CLEAVAGES`] for all possible pyranose
+    /// cleavages. These are only generated for monosaccharides with a known ring layout and
+    /// known linkage positions for all branches.
+    pub cross_ring: Vec<(u8, u8)>,
 }
 
 impl GlycanModel {
@@ -219,6 +341,28 @@ impl GlycanModel {
             ..self
         }
     }
+    /// Sets the status of glycosidic C and Z fragments
+    #[must_use]
+    pub fn allow_c_z(self, allow_c_z: bool) -> Self {
+        Self { allow_c_z, ..self }
+    }
+    /// Replace the cross-ring cleavages, see [`Self::cross_ring`]
+    #[must_use]
+    pub fn cross_ring(self, cross_ring: Vec<(u8, u8)>) -> Self {
+        Self { cross_ring, ..self }
+    }
+    /// All cross-ring cleavages possible in a pyranose ring
+    pub const CROSS_RING_CLEAVAGES: &'static [(u8, u8)] = &[
+        (0, 2),
+        (0, 3),
+        (0, 4),
+        (1, 3),
+        (1, 4),
+        (1, 5),
+        (2, 4),
+        (2, 5),
+        (3, 5),
+    ];
     /// Default set for models that allow glycan fragmentation
     pub const ALLOW: Self = Self {
         allow_structural: true,
@@ -226,6 +370,8 @@ impl GlycanModel {
         neutral_losses: Vec::new(),
         oxonium_charge_range: ChargeRange::ONE,
         other_charge_range: ChargeRange::ONE_TO_PRECURSOR,
+        allow_c_z: false,
+        cross_ring: Vec::new(),
     };
     /// Default set for models that disallow glycan fragmentation
     pub const DISALLOW: Self = Self {
@@ -234,6 +380,128 @@ impl GlycanModel {
         neutral_losses: Vec::new(),
         oxonium_charge_range: ChargeRange::ONE,
         other_charge_range: ChargeRange::ONE_TO_PRECURSOR,
+        allow_c_z: false,
+        cross_ring: Vec::new(),
+    };
+}
+
+/// The settings for charge reduced precursors.
+///
+/// These are precursors that captured one or more electrons without dissociating (eg [M+3H]2+•
+/// from [M+3H]3+) as seen in ETD/ECD spectra. These keep all charge carriers of the precursor,
+/// the captured electrons reduce the charge. For negatively charged precursors this generates the
+/// electron detached species instead. None of the preset models generate these, turn them on
+/// with [`Model::charge_reduced_precursor`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct ChargeReducedPrecursorModel {
+    /// If charge reduced precursors should be generated
+    pub allow: bool,
+    /// The allowed neutral losses
+    pub neutral_losses: Vec<NeutralLoss>,
+    /// If the loss of the side chain of any amino acid in the peptide should be generated
+    pub side_chain_losses: bool,
+    /// The allowed charges, any charge at or above the precursor charge is ignored
+    pub charge_range: ChargeRange,
+}
+
+impl ChargeReducedPrecursorModel {
+    /// Sets if charge reduced precursors should be generated
+    #[must_use]
+    pub fn allow(self, allow: bool) -> Self {
+        Self { allow, ..self }
+    }
+    /// Replace the neutral losses
+    #[must_use]
+    pub fn neutral_losses(self, neutral_losses: Vec<NeutralLoss>) -> Self {
+        Self {
+            neutral_losses,
+            ..self
+        }
+    }
+    /// Sets if the side chain losses should be generated
+    #[must_use]
+    pub fn side_chain_losses(self, side_chain_losses: bool) -> Self {
+        Self {
+            side_chain_losses,
+            ..self
+        }
+    }
+    /// Replace the charge range
+    #[must_use]
+    pub fn charge_range(self, charge_range: ChargeRange) -> Self {
+        Self {
+            charge_range,
+            ..self
+        }
+    }
+    /// Generate the charge reduced precursors for all charges below the precursor charge
+    pub const ALLOW: Self = Self {
+        allow: true,
+        neutral_losses: Vec::new(),
+        side_chain_losses: false,
+        charge_range: ChargeRange::ONE_TO_PRECURSOR_MINUS_ONE,
+    };
+    /// Do not generate charge reduced precursors
+    pub const DISALLOW: Self = Self {
+        allow: false,
+        neutral_losses: Vec::new(),
+        side_chain_losses: false,
+        charge_range: ChargeRange::ONE_TO_PRECURSOR_MINUS_ONE,
+    };
+}
+
+/// The settings for the annotation of the isotope envelopes of fragments.
+///
+/// When enabled the isotope peaks (M+1, M+2, etc) following an annotated monoisotopic peak are
+/// annotated as well. Isotope peaks are only annotated when annotating with
+/// [`MassMode::Monoisotopic`](crate::MassMode::Monoisotopic).
+///
+/// Only available with crate feature 'isotopes'.
+#[cfg(feature = "isotopes")]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct IsotopeModel {
+    /// If the isotope peaks of annotated fragments should be annotated
+    pub annotate: bool,
+    /// The minimal theoretical abundance of an isotope, as fraction of the whole distribution, for
+    /// it to be searched for
+    pub threshold: f64,
+    /// If set, the isotope peaks are only annotated if the cosine similarity between the observed
+    /// and the predicted envelope is at least this value (0.0..=1.0)
+    pub minimal_similarity: Option<f64>,
+}
+
+#[cfg(feature = "isotopes")]
+impl IsotopeModel {
+    /// Sets if the isotope peaks should be annotated
+    #[must_use]
+    pub const fn annotate(self, annotate: bool) -> Self {
+        Self { annotate, ..self }
+    }
+    /// Set the minimal theoretical abundance, see [`Self::threshold`]
+    #[must_use]
+    pub const fn threshold(self, threshold: f64) -> Self {
+        Self { threshold, ..self }
+    }
+    /// Set the minimal similarity between the observed and predicted envelope, see [`Self::minimal_similarity`]
+    #[must_use]
+    pub const fn minimal_similarity(self, minimal_similarity: Option<f64>) -> Self {
+        Self {
+            minimal_similarity,
+            ..self
+        }
+    }
+    /// Annotate all isotopes with a theoretical abundance of at least 1%, without requiring the
+    /// envelope to be similar to the predicted envelope
+    pub const ALLOW: Self = Self {
+        annotate: true,
+        threshold: 0.01,
+        minimal_similarity: None,
+    };
+    /// Only annotate the monoisotopic peaks
+    pub const DISALLOW: Self = Self {
+        annotate: false,
+        threshold: 0.01,
+        minimal_similarity: None,
     };
 }
 
@@ -243,23 +511,23 @@ impl GlycanModel {
 #[non_exhaustive]
 pub struct PossibleIons<'a> {
     /// a series ions
-    pub a: (bool, &'a [NeutralLoss], ChargeRange),
+    pub a: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// b series ions
-    pub b: (bool, &'a [NeutralLoss], ChargeRange),
+    pub b: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// c series ions
-    pub c: (bool, &'a [NeutralLoss], ChargeRange),
+    pub c: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// d series ions (side chain fragmentation from a)
-    pub d: (bool, &'a [NeutralLoss], ChargeRange),
+    pub d: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// v series ions (full side chain broken off)
-    pub v: (bool, &'a [NeutralLoss], ChargeRange),
+    pub v: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// w series ions (side chain fragmentation from z)
-    pub w: (bool, &'a [NeutralLoss], ChargeRange),
+    pub w: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// x series ions
-    pub x: (bool, &'a [NeutralLoss], ChargeRange),
+    pub x: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// y series ions
-    pub y: (bool, &'a [NeutralLoss], ChargeRange),
+    pub y: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// z series ions
-    pub z: (bool, &'a [NeutralLoss], ChargeRange),
+    pub z: (bool, &'a [NeutralLoss], ChargeRange, &'a [i8]),
     /// precursor ions
     pub precursor: (&'a [NeutralLoss], ChargeRange),
     /// immonium
@@ -269,15 +537,15 @@ pub struct PossibleIons<'a> {
 impl<'a> PossibleIons<'a> {
     /// Give an upper bound for the number of theoretical fragment for these possible ions
     pub fn size_upper_bound(&self) -> usize {
-        usize::from(self.a.0) * (self.a.1.len() + 1)
-            + usize::from(self.b.0) * (self.b.1.len() + 1)
-            + usize::from(self.c.0) * (self.c.1.len() + 1)
-            + usize::from(self.d.0) * 2 * (self.d.1.len() + 1)
-            + usize::from(self.v.0) * (self.v.1.len() + 1)
-            + usize::from(self.w.0) * 2 * (self.w.1.len() + 1)
-            + usize::from(self.x.0) * (self.x.1.len() + 1)
-            + usize::from(self.y.0) * (self.y.1.len() + 1)
-            + usize::from(self.z.0) * 2 * (self.z.1.len() + 1)
+        usize::from(self.a.0) * (self.a.1.len() + 1) * self.a.3.len()
+            + usize::from(self.b.0) * (self.b.1.len() + 1) * self.b.3.len()
+            + usize::from(self.c.0) * (self.c.1.len() + 1) * self.c.3.len()
+            + usize::from(self.d.0) * 2 * (self.d.1.len() + 1) * self.d.3.len()
+            + usize::from(self.v.0) * (self.v.1.len() + 1) * self.v.3.len()
+            + usize::from(self.w.0) * 2 * (self.w.1.len() + 1) * self.w.3.len()
+            + usize::from(self.x.0) * (self.x.1.len() + 1) * self.x.3.len()
+            + usize::from(self.y.0) * (self.y.1.len() + 1) * self.y.3.len()
+            + usize::from(self.z.0) * (self.z.1.len() + 1) * self.z.3.len()
             + self.precursor.0.len()
             + 1
     }
@@ -330,11 +598,22 @@ impl Model {
     pub fn z(self, z: PrimaryIonSeries) -> Self {
         Self { z, ..self }
     }
+    /// Set internal
+    #[must_use]
+    pub fn internal(self, internal: InternalIonSeries) -> Self {
+        Self { internal, ..self }
+    }
     /// Set glycan
     #[must_use]
     pub fn glycan(self, glycan: GlycanModel) -> Self {
         Self { glycan, ..self }
     }
+    /// Set isotopes
+    #[cfg(feature = "isotopes")]
+    #[must_use]
+    pub fn isotopes(self, isotopes: IsotopeModel) -> Self {
+        Self { isotopes, ..self }
+    }
     /// Overwrite the precursor neutral losses
     #[must_use]
     pub fn precursor(self, neutral_loss: Vec<NeutralLoss>, charges: ChargeRange) -> Self {
@@ -343,6 +622,17 @@ impl Model {
             ..self
         }
     }
+    /// Set the charge reduced precursor settings
+    #[must_use]
+    pub fn charge_reduced_precursor(
+        self,
+        charge_reduced_precursor: ChargeReducedPrecursorModel,
+    ) -> Self {
+        Self {
+            charge_reduced_precursor,
+            ..self
+        }
+    }
     /// Set immonium
     #[must_use]
     pub fn immonium(self, state: (bool, ChargeRange)) -> Self {
@@ -404,46 +694,55 @@ impl Model {
                 self.a.location.possible(position),
                 self.a.neutral_losses.as_slice(),
                 self.a.charge_range,
+                self.a.variants.as_deref().unwrap_or(&[0]),
             ),
             b: (
                 self.b.location.possible(position),
                 self.b.neutral_losses.as_slice(),
                 self.b.charge_range,
+                self.b.variants.as_deref().unwrap_or(&[0]),
             ),
             c: (
                 self.c.location.possible(position),
                 self.c.neutral_losses.as_slice(),
                 self.c.charge_range,
+                self.c.variants.as_deref().unwrap_or(&[0]),
             ),
             d: (
                 self.d.location.possible(position),
                 self.d.neutral_losses.as_slice(),
                 self.d.charge_range,
+                self.d.variants.as_deref().unwrap_or(&[0]),
             ),
             v: (
                 self.v.location.possible(c_position),
                 self.v.neutral_losses.as_slice(),
                 self.v.charge_range,
+                self.v.variants.as_deref().unwrap_or(&[0]),
             ),
             w: (
                 self.w.location.possible(c_position),
                 self.w.neutral_losses.as_slice(),
                 self.w.charge_range,
+                self.w.variants.as_deref().unwrap_or(&[0]),
             ),
             x: (
                 self.x.location.possible(c_position),
                 self.x.neutral_losses.as_slice(),
                 self.x.charge_range,
+                self.x.variants.as_deref().unwrap_or(&[0]),
             ),
             y: (
                 self.y.location.possible(c_position),
                 self.y.neutral_losses.as_slice(),
                 self.y.charge_range,
+                self.y.variants.as_deref().unwrap_or(&[0]),
             ),
             z: (
                 self.z.location.possible(c_position),
                 self.z.neutral_losses.as_slice(),
                 self.z.charge_range,
+                self.z.variants.as_deref().unwrap_or(&[0, 1]),
             ),
             precursor: (self.precursor.0.as_slice(), self.precursor.1),
             immonium: self.immonium,
@@ -451,6 +750,12 @@ impl Model {
     }
 
     /// Generate all possible fragments
+    ///
+    /// To keep the number of fragments manageable the internal fragments are limited to b/y and
+    /// a/y internal fragments of at most four residues, use [`InternalIonSeries::all`] to generate
+    /// all internal fragments. For the same reason the glycan C/Z and cross-ring A/X fragments are
+    /// not generated, see [`GlycanModel::allow_c_z`] and [`GlycanModel::cross_ring`]. Charge
+    /// reduced precursors are not generated either, see [`Self::charge_reduced_precursor`].
     pub fn all() -> Self {
         Self {
             a: PrimaryIonSeries::default()
@@ -471,10 +776,18 @@ impl Model {
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             z: PrimaryIonSeries::default()
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
+            internal: InternalIonSeries::default()
+                .ion_types(vec![
+                    (FragmentKind::b, FragmentKind::y),
+                    (FragmentKind::a, FragmentKind::y),
+                ])
+                .length(1..=4)
+                .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             precursor: (
                 vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))],
                 ChargeRange::PRECURSOR,
             ),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
             immonium: (true, ChargeRange::ONE),
             m: true,
             modification_specific_neutral_losses: true,
@@ -482,6 +795,8 @@ impl Model {
             glycan: GlycanModel::ALLOW
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             allow_cross_link_cleavage: true,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
             tolerance: Tolerance::new_ppm(20.0),
             mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
         }
@@ -499,13 +814,17 @@ impl Model {
             x: PrimaryIonSeries::default().location(Location::None),
             y: PrimaryIonSeries::default().location(Location::None),
             z: PrimaryIonSeries::default().location(Location::None),
+            internal: InternalIonSeries::default(),
             precursor: (vec![], ChargeRange::PRECURSOR),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
             immonium: (false, ChargeRange::ONE),
             m: false,
             modification_specific_neutral_losses: false,
             modification_specific_diagnostic_ions: (false, ChargeRange::ONE),
             glycan: GlycanModel::DISALLOW,
             allow_cross_link_cleavage: false,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
             tolerance: Tolerance::new_ppm(20.0),
             mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
         }
@@ -528,6 +847,7 @@ impl Model {
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             z: PrimaryIonSeries::default()
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
+            internal: InternalIonSeries::default(),
             precursor: (
                 vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))],
                 ChargeRange {
@@ -535,6 +855,7 @@ impl Model {
                     end: ChargePoint::Relative(0),
                 },
             ),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
             immonium: (false, ChargeRange::ONE),
             m: false,
             modification_specific_neutral_losses: true,
@@ -542,6 +863,8 @@ impl Model {
             glycan: GlycanModel::ALLOW
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             allow_cross_link_cleavage: true,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
             tolerance: Tolerance::new_ppm(20.0),
             mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
         }
@@ -565,16 +888,20 @@ impl Model {
             y: PrimaryIonSeries::default()
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             z: PrimaryIonSeries::default().location(Location::None),
+            internal: InternalIonSeries::default(),
             precursor: (
                 vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))],
                 ChargeRange::PRECURSOR,
             ),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
             immonium: (false, ChargeRange::ONE),
             m: false,
             modification_specific_neutral_losses: true,
             modification_specific_diagnostic_ions: (true, ChargeRange::ONE),
             glycan: GlycanModel::DISALLOW,
             allow_cross_link_cleavage: true,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
             tolerance: Tolerance::new_ppm(20.0),
             mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
         }
@@ -595,6 +922,7 @@ impl Model {
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
             z: PrimaryIonSeries::default()
                 .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
+            internal: InternalIonSeries::default(),
             precursor: (
                 vec![
                     NeutralLoss::Loss(molecular_formula!(H 2 O 1)),
@@ -608,16 +936,107 @@ impl Model {
                     end: ChargePoint::Relative(0),
                 },
             ),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
+            immonium: (false, ChargeRange::ONE),
+            m: false,
+            modification_specific_neutral_losses: true,
+            modification_specific_diagnostic_ions: (true, ChargeRange::ONE),
+            glycan: GlycanModel::DISALLOW,
+            allow_cross_link_cleavage: true,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
+            tolerance: Tolerance::new_ppm(20.0),
+            mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
+        }
+    }
+
+    /// Ultraviolet photodissociation (UVPD), with the hydrogen shifted a+1, x+1, y-1, y-2, and
+    /// z+1 ions as well as the satellite d and w ions
+    pub fn uvpd() -> Self {
+        Self {
+            a: PrimaryIonSeries::default().variants(vec![0, 1]),
+            b: PrimaryIonSeries::default()
+                .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
+            c: PrimaryIonSeries::default(),
+            d: PrimaryIonSeries::default(),
+            v: PrimaryIonSeries::default().location(Location::None),
+            w: PrimaryIonSeries::default(),
+            x: PrimaryIonSeries::default().variants(vec![0, 1]),
+            y: PrimaryIonSeries::default()
+                .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))])
+                .variants(vec![0, -1, -2]),
+            z: PrimaryIonSeries::default(),
+            internal: InternalIonSeries::default(),
+            precursor: (
+                vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))],
+                ChargeRange::PRECURSOR,
+            ),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
+            immonium: (true, ChargeRange::ONE),
+            m: false,
+            modification_specific_neutral_losses: true,
+            modification_specific_diagnostic_ions: (true, ChargeRange::ONE),
+            glycan: GlycanModel::DISALLOW,
+            allow_cross_link_cleavage: true,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
+            tolerance: Tolerance::new_ppm(20.0),
+            mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
+        }
+    }
+
+    /// Electron capture dissociation (ECD), with the c-1 and z+2 ions resulting from hydrogen
+    /// migration between the c and z· ions
+    pub fn ecd() -> Self {
+        Self {
+            a: PrimaryIonSeries::default().location(Location::None),
+            b: PrimaryIonSeries::default().location(Location::None),
+            c: PrimaryIonSeries::default().variants(vec![0, -1]),
+            d: PrimaryIonSeries::default().location(Location::None),
+            v: PrimaryIonSeries::default().location(Location::None),
+            w: PrimaryIonSeries::default().variants(vec![0, 1]),
+            x: PrimaryIonSeries::default().location(Location::None),
+            y: PrimaryIonSeries::default().location(Location::None),
+            z: PrimaryIonSeries::default().variants(vec![1, 2]),
+            internal: InternalIonSeries::default(),
+            precursor: (
+                vec![
+                    NeutralLoss::Loss(molecular_formula!(H 2 O 1)),
+                    NeutralLoss::Loss(molecular_formula!(H 3 N 1)),
+                ],
+                ChargeRange {
+                    start: ChargePoint::Relative(-2),
+                    end: ChargePoint::Relative(0),
+                },
+            ),
+            charge_reduced_precursor: ChargeReducedPrecursorModel::DISALLOW,
             immonium: (false, ChargeRange::ONE),
             m: false,
             modification_specific_neutral_losses: true,
             modification_specific_diagnostic_ions: (true, ChargeRange::ONE),
             glycan: GlycanModel::DISALLOW,
             allow_cross_link_cleavage: true,
+            #[cfg(feature = "isotopes")]
+            isotopes: IsotopeModel::DISALLOW,
             tolerance: Tolerance::new_ppm(20.0),
             mz_range: MassOverCharge::new::<mz>(0.0)..=MassOverCharge::new::<mz>(f64::MAX),
         }
     }
+
+    /// Electron activated dissociation (EAD), on top of the ECD ions this also generates the a+1,
+    /// b, y, and d ions formed at higher electron energies
+    pub fn ead() -> Self {
+        Self {
+            a: PrimaryIonSeries::default().variants(vec![0, 1]),
+            b: PrimaryIonSeries::default()
+                .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
+            d: PrimaryIonSeries::default(),
+            y: PrimaryIonSeries::default()
+                .neutral_losses(vec![NeutralLoss::Loss(molecular_formula!(H 2 O 1))]),
+            immonium: (true, ChargeRange::ONE),
+            ..Self::ecd()
+        }
+    }
 }
 
 /// A location, or range of locations where an ion can be generated
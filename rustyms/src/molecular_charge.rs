@@ -66,7 +66,7 @@ pub struct MolecularCharge {
 }
 
 impl MolecularCharge {
-    /// Create a default charge state with only protons
+    /// Create a default charge state with only protons, a negative charge results in the loss of protons
     #[allow(clippy::missing_panics_doc)] // Cannot panic
     pub fn proton(charge: isize) -> Self {
         Self {
@@ -85,47 +85,40 @@ impl MolecularCharge {
         }
     }
 
+    /// Create a default negative charge state with only deprotonations, the charge is the
+    /// number of protons removed
+    pub fn deprotonation(charge: isize) -> Self {
+        Self::proton(-charge)
+    }
+
     /// Get all options resulting in this exact charge
     /// # Panics
-    /// If the charge is not at least 1.
+    /// If the charge is zero or does not have the same sign as the charge of these charge carriers.
     pub fn options(&self, charge: Charge) -> Vec<Self> {
-        assert!(charge.value > 0);
         let own_charge = self.charge();
-        let remainder = charge.value.rem_euclid(own_charge.value);
-        let quotient = charge.value.div_euclid(own_charge.value).max(0);
-
-        let mut too_low_options: Vec<Vec<(isize, MolecularFormula)>> = Vec::new();
+        assert!(charge.value != 0 && charge.value.signum() == own_charge.value.signum());
+        // Work with absolute charges to handle positive and negative charges the same way
+        let sign = own_charge.value.signum();
+        let remainder = (charge.value * sign).rem_euclid(own_charge.value * sign);
+        let quotient = (charge.value * sign).div_euclid(own_charge.value * sign);
+
+        let mut too_low_options: Vec<(Vec<(isize, MolecularFormula)>, isize)> =
+            vec![(Vec::new(), 0)];
         let mut options = Vec::new();
-        for carrier in &self.charge_carriers {
+        for (amount, carrier) in &self.charge_carriers {
+            let unit = amount.signum() * carrier.charge().value * sign;
             let mut new_too_low_options = Vec::new();
-            if too_low_options.is_empty() {
-                for n in 0..=carrier.0 {
-                    let charge = n * carrier.1.charge();
-                    match charge.value.cmp(&remainder) {
-                        Ordering::Less => new_too_low_options.push(vec![(n, carrier.1.clone())]),
-                        Ordering::Equal => options.push(vec![(n, carrier.1.clone())]),
+            for (option, option_charge) in &too_low_options {
+                for n in 0..=amount.abs() {
+                    let mut new = option.clone();
+                    new.push((n * amount.signum(), carrier.clone()));
+                    let charge = option_charge + n * unit;
+                    match charge.cmp(&remainder) {
+                        Ordering::Less => new_too_low_options.push((new, charge)),
+                        Ordering::Equal => options.push(new),
                         Ordering::Greater => (),
                     }
                 }
-            } else {
-                for n in 0..=carrier.0 {
-                    for o in &too_low_options {
-                        let mut new = o.clone();
-                        new.push((n, carrier.1.clone()));
-                        let full_charge = new
-                            .iter()
-                            .fold(Charge::default(), |acc, (amount, formula)| {
-                                acc + *amount * formula.charge()
-                            });
-
-                        let charge = n * carrier.1.charge() + full_charge;
-                        match charge.value.cmp(&remainder) {
-                            Ordering::Less => new_too_low_options.push(new),
-                            Ordering::Equal => options.push(new),
-                            Ordering::Greater => (),
-                        }
-                    }
-                }
             }
             too_low_options = new_too_low_options;
         }
@@ -240,4 +233,15 @@ mod tests {
         assert_eq!(options.len(), 1);
         assert_eq!(options[0].formula(), molecular_formula!(H 1 Electron -1));
     }
+
+    #[test]
+    fn negative_charge_options() {
+        let mc = MolecularCharge::deprotonation(3);
+        let options = mc.options(crate::system::isize::Charge::new::<crate::system::e>(-2));
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].formula(), molecular_formula!(H -2 Electron 2));
+        let options = mc.options(crate::system::isize::Charge::new::<crate::system::e>(-4));
+        assert_eq!(options.len(), 1);
+        assert_eq!(options[0].formula(), molecular_formula!(H -4 Electron 4));
+    }
 }
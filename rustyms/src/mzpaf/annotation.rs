@@ -0,0 +1,330 @@
+use std::{
+    fmt::{Display, Write},
+    num::NonZeroU16,
+    sync::OnceLock,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    modification::{Ontology, SimpleModification},
+    system::{mz, ratio::ppm, MassOverCharge},
+    AminoAcid, CompoundPeptidoform, Element, MolecularCharge, MolecularFormula, NeutralLoss,
+    Tolerance,
+};
+
+/// A single mzPAF peak annotation.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PeakAnnotation {
+    /// If this is an auxiliary annotation ('&'), meaning that it is not the main explanation for this peak
+    pub auxiliary: bool,
+    /// The analyte this annotation belongs to ('1@'), numbered from one
+    pub analyte_number: Option<usize>,
+    /// The ion
+    pub ion: IonType,
+    /// All neutral losses and gains
+    pub neutral_losses: Vec<NeutralLoss>,
+    /// All isotopes, with the number of isotopic steps for each ('+2i', '+i13C')
+    pub isotopes: Vec<(i32, Isotope)>,
+    /// The charge carriers, if no adduct was defined this is only protons
+    pub charge: MolecularCharge,
+    /// The mass deviation of the peak compared to this annotation
+    pub deviation: Option<Tolerance<MassOverCharge>>,
+    /// The confidence in this annotation
+    pub confidence: Option<f64>,
+}
+
+impl PeakAnnotation {
+    /// Create a new annotation for the given ion, singly charged with a proton and without any
+    /// other properties.
+    pub fn new(ion: IonType) -> Self {
+        Self {
+            auxiliary: false,
+            analyte_number: None,
+            ion,
+            neutral_losses: Vec::new(),
+            isotopes: Vec::new(),
+            charge: MolecularCharge::proton(1),
+            deviation: None,
+            confidence: None,
+        }
+    }
+}
+
+/// The ion of an mzPAF annotation.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum IonType {
+    /// An unknown ion ('?'), with an optional ordinal to distinguish different unknown ions ('?17')
+    Unknown(Option<usize>),
+    /// A main series ion ('b2'), with the series (a/b/c/x/y/z), the ordinal, and optionally the
+    /// analyte it originates from ('b2{PEPTIDE}')
+    MainSeries(char, usize, Option<CompoundPeptidoform>),
+    /// An immonium ion ('IY'), with the amino acid and optionally its modification ('IC[Carbamidomethyl]')
+    Immonium(AminoAcid, Option<SimpleModification>),
+    /// An internal fragment ('m3:6'), with the first and last residue of the fragment (one based)
+    Internal(usize, usize),
+    /// A named compound ('_{Urocanic Acid}')
+    Named(String),
+    /// The precursor ('p')
+    Precursor,
+    /// A reporter ion ('r[TMT127N]'), with its name and neutral formula
+    Reporter(String, MolecularFormula),
+    /// A molecule defined by its formula ('f{C16H22O}')
+    Formula(MolecularFormula),
+    /// A molecule defined in SMILES ('s{CN=C=O}'), with the SMILES and the resulting formula
+    Smiles(String, MolecularFormula),
+}
+
+/// An isotope step for an mzPAF annotation.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Isotope {
+    /// An unspecified isotope, assumed to be a carbon 13 ('+i')
+    General,
+    /// A specified isotope ('+i15N')
+    Specific(Element, NonZeroU16),
+    /// An averaged isotope step, 1.00235 Da as for averagine ('+iA')
+    Average,
+}
+
+impl Isotope {
+    /// The change in formula for a single step of this isotope
+    #[allow(clippy::missing_panics_doc)] // The isotopes are validated when constructed from mzPAF
+    pub fn formula(self) -> MolecularFormula {
+        match self {
+            Self::General => molecular_formula!([13 C 1] C -1),
+            Self::Specific(element, isotope) => {
+                MolecularFormula::new(&[(element, Some(isotope), 1), (element, None, -1)], &[])
+                    .unwrap_or_default()
+            }
+            Self::Average => MolecularFormula::with_additional_mass(1.002_35),
+        }
+    }
+}
+
+impl Display for PeakAnnotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.auxiliary {
+            write!(f, "&")?;
+        }
+        if let Some(number) = self.analyte_number {
+            write!(f, "{number}@")?;
+        }
+        write!(f, "{}", self.ion)?;
+        for loss in &self.neutral_losses {
+            let (sign, formula) = match loss {
+                NeutralLoss::Gain(formula) => ('+', formula),
+                NeutralLoss::Loss(formula) => ('-', formula),
+            };
+            if let Some((name, _)) = mzpaf_named_molecules()
+                .iter()
+                .find(|(_, named)| named == formula)
+            {
+                write!(f, "{sign}[{name}]")?;
+            } else {
+                write!(f, "{sign}{}", mzpaf_formula(formula))?;
+            }
+        }
+        for (amount, isotope) in &self.isotopes {
+            write!(f, "{}", if *amount < 0 { '-' } else { '+' })?;
+            if amount.abs() != 1 {
+                write!(f, "{}", amount.abs())?;
+            }
+            match isotope {
+                Isotope::General => write!(f, "i")?,
+                Isotope::Specific(element, isotope) => write!(f, "i{isotope}{element}")?,
+                Isotope::Average => write!(f, "iA")?,
+            }
+        }
+        let charge = self.charge.charge().value;
+        let proton = MolecularCharge::proton(1).charge_carriers[0].1.clone();
+        if !(self
+            .charge
+            .charge_carriers
+            .iter()
+            .all(|(_, c)| *c == proton)
+            && charge != 0)
+        {
+            write!(f, "[M")?;
+            for (amount, carrier) in &self.charge.charge_carriers {
+                write!(f, "{}", if *amount < 0 { '-' } else { '+' })?;
+                if amount.abs() != 1 {
+                    write!(f, "{}", amount.abs())?;
+                }
+                let neutral = carrier + &molecular_formula!(Electron carrier.charge().value as i32);
+                write!(f, "{}", mzpaf_formula(&neutral))?;
+            }
+            write!(f, "]")?;
+        }
+        if charge != 1 {
+            write!(f, "^{charge}")?;
+        }
+        match self.deviation {
+            Some(Tolerance::Absolute(deviation)) => write!(f, "/{}", deviation.get::<mz>())?,
+            Some(Tolerance::Relative(deviation)) => {
+                // Round to remove floating point artefacts from the conversion to and from ppm
+                let value = (deviation.into_inner().get::<ppm>() * 1e9).round() / 1e9;
+                write!(f, "/{value}ppm")?;
+            }
+            None => (),
+        }
+        if let Some(confidence) = self.confidence {
+            write!(f, "*{confidence}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for IonType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unknown(None) => write!(f, "?"),
+            Self::Unknown(Some(ordinal)) => write!(f, "?{ordinal}"),
+            Self::MainSeries(series, ordinal, analyte) => {
+                write!(f, "{series}{ordinal}")?;
+                if let Some(analyte) = analyte {
+                    write!(f, "{{{analyte}}}")?;
+                }
+                Ok(())
+            }
+            Self::Immonium(aa, modification) => {
+                write!(f, "I{}", aa.char())?;
+                match modification {
+                    Some(SimpleModification::Database { id, .. })
+                        if id.ontology == Ontology::Unimod =>
+                    {
+                        write!(f, "[{}]", id.name)
+                    }
+                    Some(modification) => write!(f, "[{modification}]"),
+                    None => Ok(()),
+                }
+            }
+            Self::Internal(start, end) => write!(f, "m{start}:{end}"),
+            Self::Named(name) => write!(f, "_{{{name}}}"),
+            Self::Precursor => write!(f, "p"),
+            Self::Reporter(name, _) => write!(f, "r[{name}]"),
+            Self::Formula(formula) => write!(f, "f{{{}}}", mzpaf_formula(formula)),
+            Self::Smiles(smiles, _) => write!(f, "s{{{smiles}}}"),
+        }
+    }
+}
+
+/// Write a formula in the mzPAF style: Hill ordering, element counts of one are left out,
+/// isotopes written as `[13C2]`. Electrons and additional masses cannot be represented and are
+/// not written.
+pub(super) fn mzpaf_formula(formula: &MolecularFormula) -> String {
+    let mut elements = formula
+        .elements()
+        .iter()
+        .filter(|(e, _, n)| *e != Element::Electron && *n != 0)
+        .collect::<Vec<_>>();
+    let hill_rank = |e: &&(Element, Option<NonZeroU16>, i32)| match e {
+        (Element::C, None, _) => 0,
+        (Element::H, None, _) if formula.elements().iter().any(|e| e.0 == Element::C) => 1,
+        _ => 2,
+    };
+    elements.sort_by_key(hill_rank);
+    let mut output = String::new();
+    for (element, isotope, amount) in elements {
+        if let Some(isotope) = isotope {
+            write!(&mut output, "[{isotope}{element}{amount}]").unwrap();
+        } else if *amount == 1 {
+            output.push_str(&element.to_string());
+        } else {
+            write!(&mut output, "{element}{amount}").unwrap();
+        }
+    }
+    output
+}
+
+/// The named molecules as defined by mzPAF, used as neutral losses ('-[Hex]') and reporter
+/// ions ('r[TMT127N]'). The formulas are for the neutral molecules.
+pub(super) fn mzpaf_named_molecules() -> &'static [(&'static str, MolecularFormula)] {
+    MZPAF_NAMED_MOLECULES_CELL.get_or_init(|| {
+        vec![
+            ("Hex", molecular_formula!(C 6 H 10 O 5)),
+            ("HexNAc", molecular_formula!(C 8 H 13 N 1 O 5)),
+            ("dHex", molecular_formula!(C 6 H 10 O 4)),
+            ("NeuAc", molecular_formula!(C 11 H 17 N 1 O 8)),
+            ("NeuGc", molecular_formula!(C 11 H 17 N 1 O 9)),
+            ("TMT126", molecular_formula!(C 8 N 1 H 15)),
+            ("TMT127N", molecular_formula!(C 8 [15 N 1] H 15)),
+            ("TMT127C", molecular_formula!(C 7 [13 C 1] N 1 H 15)),
+            ("TMT128N", molecular_formula!(C 7 [13 C 1] [15 N 1] H 15)),
+            ("TMT128C", molecular_formula!(C 6 [13 C 2] N 1 H 15)),
+            ("TMT129N", molecular_formula!(C 6 [13 C 2] [15 N 1] H 15)),
+            ("TMT129C", molecular_formula!(C 5 [13 C 3] N 1 H 15)),
+            ("TMT130N", molecular_formula!(C 5 [13 C 3] [15 N 1] H 15)),
+            ("TMT130C", molecular_formula!(C 4 [13 C 4] N 1 H 15)),
+            ("TMT131N", molecular_formula!(C 4 [13 C 4] [15 N 1] H 15)),
+            ("TMT131C", molecular_formula!(C 3 [13 C 5] N 1 H 15)),
+            ("TMT132N", molecular_formula!(C 3 [13 C 5] [15 N 1] H 15)),
+            ("TMT132C", molecular_formula!(C 2 [13 C 6] N 1 H 15)),
+            ("TMT133N", molecular_formula!(C 2 [13 C 6] [15 N 1] H 15)),
+            ("TMT133C", molecular_formula!(C 1 [13 C 7] N 1 H 15)),
+            ("TMT134N", molecular_formula!(C 1 [13 C 7] [15 N 1] H 15)),
+            ("TMT134C", molecular_formula!(C 0 [13 C 8] N 1 H 15)),
+            ("TMT135N", molecular_formula!(C 0 [13 C 8] [15 N 1] H 15)),
+            ("TMTzero", molecular_formula!(C 12 H 20 N 2 O 2)),
+            ("TMTpro_zero", molecular_formula!(C 15 H 25 N 3 O 3)),
+            ("TMT2plex", molecular_formula!(C 11 [ 13 C 1] H 20 N 2 O 2)),
+            (
+                "TMT6plex",
+                molecular_formula!(C 8 [13 C 5] H 20 N 1 [ 15 N 1] O 2),
+            ),
+            (
+                "TMTpro",
+                molecular_formula!(C 8 [13 C 7] H 25 [15 N 2] N 1 O 3),
+            ),
+            ("iTRAQ113", molecular_formula!(C 6 N 2 H 12)),
+            ("iTRAQ114", molecular_formula!(C 5 [13 C 1] N 2 H 12)),
+            (
+                "iTRAQ115",
+                molecular_formula!(C 5 [13 C 1] N 1 [15 N 1] H 12),
+            ),
+            (
+                "iTRAQ116",
+                molecular_formula!(C 4 [13 C 2] N 1 [15 N 1] H 12),
+            ),
+            (
+                "iTRAQ117",
+                molecular_formula!(C 3 [13 C 3] N 1 [15 N 1] H 12),
+            ),
+            ("iTRAQ118", molecular_formula!(C 3 [13 C 3] [15 N 2] H 12)),
+            ("iTRAQ119", molecular_formula!(C 4 [13 C 2] [15 N 2] H 12)),
+            ("iTRAQ121", molecular_formula!([13 C 6] [15 N 2] H 12)),
+            (
+                "iTRAQ4plex",
+                molecular_formula!(C 4 [13 C 3] H 12 N 1 [15 N 1] O 1),
+            ),
+            (
+                "iTRAQ8plex",
+                molecular_formula!(C 7 [13 C 7] H 24 N 3 [15 N 1] O 3),
+            ),
+            ("TMT126-ETD", molecular_formula!(C 7 N 1 H 15)),
+            ("TMT127N-ETD", molecular_formula!(C 7 [15 N 1] H 15)),
+            ("TMT127C-ETD", molecular_formula!(C 6 [13 C 1] N 1 H 15)),
+            (
+                "TMT128N-ETD",
+                molecular_formula!(C 6 [13 C 1] [15 N 1] H 15),
+            ),
+            ("TMT128C-ETD", molecular_formula!(C 5 [13 C 2] N 1 H 15)),
+            (
+                "TMT129N-ETD",
+                molecular_formula!(C 5 [13 C 2] [15 N 1] H 15),
+            ),
+            ("TMT129C-ETD", molecular_formula!(C 4 [13 C 3] N 1 H 15)),
+            (
+                "TMT130N-ETD",
+                molecular_formula!(C 4 [13 C 3] [15 N 1] H 15),
+            ),
+            ("TMT130C-ETD", molecular_formula!(C 3 [13 C 4] N 1 H 15)),
+            (
+                "TMT131N-ETD",
+                molecular_formula!(C 3 [13 C 4] [15 N 1] H 15),
+            ),
+            ("TMT131C-ETD", molecular_formula!(C 2 [13 C 5] N 1 H 15)),
+        ]
+    })
+}
+
+static MZPAF_NAMED_MOLECULES_CELL: OnceLock<Vec<(&str, MolecularFormula)>> = OnceLock::new();
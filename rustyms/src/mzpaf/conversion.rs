@@ -0,0 +1,440 @@
+use std::cmp::Ordering;
+
+use itertools::Itertools;
+
+use crate::{
+    error::{Context, CustomError},
+    fragment::{
+        internal_fragment_difference, DiagnosticPosition, FragmentKind, FragmentType,
+        PeptidePosition,
+    },
+    model::{ChargeRange, PrimaryIonSeries},
+    modification::Modification,
+    spectrum::AnnotatedPeak,
+    system::{e, isize::Charge, mz, MassOverCharge},
+    Chemical, CompoundPeptidoform, Fragment, MassMode, Model, MolecularCharge, MolecularFormula,
+    Multi, MultiChemical, NeutralLoss, Peptidoform, SequencePosition, Tolerance,
+};
+
+use super::{annotation::mzpaf_named_molecules, IonType, PeakAnnotation};
+
+impl PeakAnnotation {
+    /// Get the fragments described by this annotation for the given analyte. The analyte number
+    /// is used as (one based) index into the peptidoforms of the compound peptidoform, if not set
+    /// the first peptidoform is used. Main series ions with an inline analyte (`b2{PEPTIDE}`) use
+    /// that analyte instead. If the annotation could apply to more than one peptide (for
+    /// cross-linked peptidoforms) or the composition is ambiguous a fragment is returned for each
+    /// option.
+    ///
+    /// The neutral losses are combined into a single neutral loss on the fragment, isotopes are
+    /// applied to the formula of the fragment. The deviation and confidence are not represented
+    /// in a [`Fragment`]. Unknown ions ('?') are returned as fragments without any composition
+    /// other than the charge carriers.
+    /// # Errors
+    /// If the analyte number does not exist, the charge is zero, the annotation is a named
+    /// compound ('_{...}'), or the annotation does not fit on the analyte (eg 'b12' on a peptide
+    /// of length 5).
+    pub fn to_fragments(
+        &self,
+        analyte: &CompoundPeptidoform,
+    ) -> Result<Vec<Fragment>, CustomError> {
+        let peptidoform_index = self.analyte_number.map_or(0, |n| n.saturating_sub(1));
+        let peptidoform = analyte.peptidoforms().get(peptidoform_index).ok_or_else(|| {
+            CustomError::error(
+                "Invalid mzPAF analyte number",
+                format!(
+                    "The analyte number {} is not present in the peptide, which has {} peptidoform(s)",
+                    peptidoform_index + 1,
+                    analyte.peptidoforms().len()
+                ),
+                Context::show(self),
+            )
+        })?;
+        let charge = self.charge.charge();
+        if charge.value == 0 {
+            return Err(CustomError::error(
+                "Invalid mzPAF charge",
+                "Only charged annotations can be converted into fragments",
+                Context::show(self),
+            ));
+        }
+        let shift = self.shift();
+        let neutral_loss = self.combined_neutral_loss();
+        let fragment =
+            |formula: MolecularFormula, peptide_index: usize, ion: FragmentType| Fragment {
+                formula: formula + &shift,
+                charge,
+                ion,
+                peptidoform_index,
+                peptide_index,
+                neutral_loss: neutral_loss.clone(),
+                charge_carriers: None,
+            };
+
+        let fragments = match &self.ion {
+            IonType::MainSeries(series, ordinal, inline) => {
+                let peptidoform = inline
+                    .as_ref()
+                    .and_then(|p| p.peptidoforms().first())
+                    .unwrap_or(peptidoform);
+                main_series(peptidoform, *series, *ordinal)
+                    .into_iter()
+                    .map(|(formula, peptide_index, ion)| fragment(formula, peptide_index, ion))
+                    .collect_vec()
+            }
+            IonType::Precursor => peptidoform
+                .formulas()
+                .iter()
+                .map(|formula| fragment(formula.clone(), 0, FragmentType::precursor))
+                .collect_vec(),
+            IonType::Immonium(aa, modification) => {
+                let position = peptidoform
+                    .peptides()
+                    .iter()
+                    .enumerate()
+                    .find_map(|(peptide_index, peptide)| {
+                        peptide
+                            .sequence()
+                            .iter()
+                            .position(|s| {
+                                s.aminoacid.aminoacid() == *aa
+                                    && modification.as_ref().map_or(true, |m| {
+                                        s.modifications.contains(&Modification::Simple(m.clone()))
+                                    })
+                            })
+                            .map(|index| {
+                                (
+                                    peptide_index,
+                                    PeptidePosition::n(
+                                        SequencePosition::Index(index),
+                                        peptide.len(),
+                                    ),
+                                    peptide.get_global(),
+                                )
+                            })
+                    });
+                let modification = modification
+                    .as_ref()
+                    .map(Chemical::formula)
+                    .unwrap_or_default();
+                aa.formulas()
+                    .iter()
+                    .map(|formula| {
+                        let formula = formula + &modification - molecular_formula!(C 1 O 1);
+                        position.map_or_else(
+                            || fragment(formula.clone(), 0, FragmentType::immonium(PeptidePosition::default(), *aa)),
+                            |(peptide_index, position, global)| {
+                                fragment(
+                                    formula
+                                        .with_global_isotope_modifications(global)
+                                        .unwrap_or_else(|| formula.clone()),
+                                    peptide_index,
+                                    FragmentType::immonium(position, *aa),
+                                )
+                            },
+                        )
+                    })
+                    .collect_vec()
+            }
+            IonType::Internal(start, end) => {
+                let mut fragments = Vec::new();
+                for (peptide_index, peptide) in peptidoform.peptides().iter().enumerate() {
+                    if *start == 0 || *end > peptide.len() {
+                        continue;
+                    }
+                    let formulas = (*start - 1..*end).fold(Multi::default(), |acc, index| {
+                        acc * peptide.sequence()[index]
+                            .formulas_all(
+                                peptidoform.peptides(),
+                                &[peptide_index],
+                                &mut Vec::new(),
+                                false,
+                                SequencePosition::Index(index),
+                                peptide_index,
+                            )
+                            .0
+                    });
+                    let ion = FragmentType::internal(
+                        (FragmentKind::b, FragmentKind::y),
+                        PeptidePosition::n(SequencePosition::Index(start - 1), peptide.len()),
+                        PeptidePosition::n(SequencePosition::Index(end - 1), peptide.len()),
+                    );
+                    fragments.extend(formulas.iter().map(|formula| {
+                        fragment(
+                            formula
+                                .with_global_isotope_modifications(peptide.get_global())
+                                .unwrap_or_else(|| formula.clone()),
+                            peptide_index,
+                            ion.clone(),
+                        )
+                    }));
+                }
+                fragments
+            }
+            IonType::Named(name) => {
+                return Err(CustomError::error(
+                    "Invalid mzPAF annotation",
+                    format!("The named compound '{name}' has no known composition so cannot be converted into a fragment"),
+                    Context::show(self),
+                ))
+            }
+            IonType::Reporter(_, formula) => vec![fragment(
+                formula.clone(),
+                0,
+                FragmentType::diagnostic(DiagnosticPosition::Reporter),
+            )],
+            IonType::Formula(formula) | IonType::Smiles(_, formula) => {
+                vec![fragment(formula.clone(), 0, FragmentType::unknown(None))]
+            }
+            IonType::Unknown(ordinal) => vec![fragment(
+                MolecularFormula::default(),
+                0,
+                FragmentType::unknown(*ordinal),
+            )],
+        };
+
+        if fragments.is_empty() {
+            Err(CustomError::error(
+                "Invalid mzPAF annotation",
+                "This annotation does not fit on the given peptide",
+                Context::show(self),
+            ))
+        } else {
+            Ok(fragments)
+        }
+    }
+
+    /// The change in formula caused by the charge carriers, neutral losses, and isotopes
+    fn shift(&self) -> MolecularFormula {
+        self.charge.formula()
+            + self
+                .neutral_losses
+                .iter()
+                .fold(MolecularFormula::default(), |acc, loss| acc + loss)
+            + self
+                .isotopes
+                .iter()
+                .map(|(amount, isotope)| isotope.formula() * *amount)
+                .sum::<MolecularFormula>()
+    }
+
+    /// All neutral losses combined into a single neutral loss (if there are any)
+    fn combined_neutral_loss(&self) -> Option<NeutralLoss> {
+        match self.neutral_losses.as_slice() {
+            [] => None,
+            [loss] => Some(loss.clone()),
+            losses => {
+                let total = losses
+                    .iter()
+                    .fold(MolecularFormula::default(), |acc, loss| acc + loss);
+                if total.monoisotopic_mass().value < 0.0 {
+                    Some(NeutralLoss::Loss(-total))
+                } else {
+                    Some(NeutralLoss::Gain(total))
+                }
+            }
+        }
+    }
+}
+
+/// Get the neutral formula (without charge carriers) for the given main series ion on all
+/// peptides in this peptidoform, with the peptide index and fragment type.
+fn main_series(
+    peptidoform: &Peptidoform,
+    series: char,
+    ordinal: usize,
+) -> Vec<(MolecularFormula, usize, FragmentType)> {
+    let ions = PrimaryIonSeries::default().charge_range(ChargeRange::ONE);
+    let model = match series {
+        'a' => Model::none().a(ions),
+        'b' => Model::none().b(ions),
+        'c' => Model::none().c(ions),
+        'x' => Model::none().x(ions),
+        'y' => Model::none().y(ions),
+        'z' => Model::none().z(ions),
+        _ => return Vec::new(),
+    };
+    let proton = MolecularCharge::proton(1).formula();
+    let label = series.to_string();
+    peptidoform
+        .generate_theoretical_fragments(Charge::new::<e>(1), &model)
+        .into_iter()
+        .filter(|f| {
+            f.neutral_loss.is_none()
+                && f.ion.label() == label
+                && f.ion.position().is_some_and(|p| p.series_number == ordinal)
+        })
+        .map(|f| {
+            let carriers = f.charge_carriers.as_ref().unwrap_or(&proton);
+            (&f.formula - carriers, f.peptide_index, f.ion)
+        })
+        .collect()
+}
+
+impl From<&Fragment> for PeakAnnotation {
+    /// Create an mzPAF annotation for this fragment. The charge carriers of the fragment are
+    /// kept if they are a number of times the same singly charged ion ('[M+2Na]^2'), any other
+    /// charge carriers are written as protons with the difference as neutral losses. If the
+    /// charge carriers are not known they are assumed to be protons, or for negative charges the
+    /// loss of protons. Ions that cannot be represented in mzPAF directly (eg d/v/w ions or
+    /// glycan fragments) are represented with their formula ('f{...}'). Hydrogen shifted ions
+    /// (eg z·) are represented as the base ion with an added or lost hydrogen. The peptide index
+    /// of the fragment cannot be represented.
+    fn from(fragment: &Fragment) -> Self {
+        let carriers = fragment
+            .charge_carriers
+            .clone()
+            .unwrap_or_else(|| MolecularCharge::proton(fragment.charge.value).formula());
+        // An adduct type directly after an immonium ion would be read as its modification
+        let (charge, carrier_difference) = adduct_type(
+            &carriers,
+            fragment.charge.value,
+            !matches!(fragment.ion, FragmentType::immonium(..)),
+        );
+        // Neutral losses can contain negative element counts, which cannot be written in mzPAF
+        let mut neutral_losses = fragment
+            .neutral_loss
+            .iter()
+            .flat_map(|loss| split_difference(&(MolecularFormula::default() + loss)))
+            .chain(split_difference(&carrier_difference))
+            .collect_vec();
+        let neutral = &fragment.formula
+            - &carriers
+            - fragment
+                .neutral_loss
+                .as_ref()
+                .map_or_else(MolecularFormula::default, |loss| {
+                    MolecularFormula::default() + loss
+                });
+        let ion = match &fragment.ion {
+            FragmentType::a(p, shift) => main_series_ion('a', p, *shift, &mut neutral_losses),
+            FragmentType::b(p, shift) => main_series_ion('b', p, *shift, &mut neutral_losses),
+            FragmentType::c(p, shift) => main_series_ion('c', p, *shift, &mut neutral_losses),
+            FragmentType::x(p, shift) => main_series_ion('x', p, *shift, &mut neutral_losses),
+            FragmentType::y(p, shift) => main_series_ion('y', p, *shift, &mut neutral_losses),
+            FragmentType::z(p, shift) => main_series_ion('z', p, *shift, &mut neutral_losses),
+            FragmentType::immonium(_, aa) => {
+                let base = aa.formulas().first().cloned().unwrap_or_default()
+                    - molecular_formula!(C 1 O 1);
+                neutral_losses.extend(split_difference(&(neutral - base)));
+                IonType::Immonium(*aa, None)
+            }
+            FragmentType::internal((n, c), start, end) => {
+                neutral_losses.extend(split_difference(&internal_fragment_difference(*n, *c)));
+                IonType::Internal(start.series_number, end.series_number)
+            }
+            FragmentType::diagnostic(DiagnosticPosition::Reporter) => mzpaf_named_molecules()
+                .iter()
+                .find(|(_, formula)| *formula == neutral)
+                .map_or_else(
+                    || IonType::Formula(neutral.clone()),
+                    |(name, formula)| IonType::Reporter((*name).to_string(), formula.clone()),
+                ),
+            FragmentType::unknown(ordinal) if neutral.is_empty() => IonType::Unknown(*ordinal),
+            FragmentType::precursor => IonType::Precursor,
+            _ => IonType::Formula(neutral),
+        };
+        Self {
+            analyte_number: (fragment.peptidoform_index != 0)
+                .then_some(fragment.peptidoform_index + 1),
+            neutral_losses,
+            charge,
+            ..Self::new(ion)
+        }
+    }
+}
+
+/// Get the mzPAF adduct type for the given charge carriers.
+///
+/// If the carriers are not a number of times the same singly charged ion, or adducts are not
+/// allowed, they are represented as protons and the difference with the actual carriers is
+/// returned as well.
+fn adduct_type(
+    carriers: &MolecularFormula,
+    charge: isize,
+    allow_adduct: bool,
+) -> (MolecularCharge, MolecularFormula) {
+    let protons = MolecularCharge::proton(charge);
+    if allow_adduct && charge > 0 && *carriers != protons.formula() {
+        let times = i32::try_from(charge).unwrap_or(i32::MAX);
+        let neutral = carriers + &molecular_formula!(Electron times);
+        let mut single = MolecularFormula::default();
+        if neutral.elements().iter().all(|(element, isotope, amount)| {
+            *amount > 0 && amount % times == 0 && single.add((*element, *isotope, amount / times))
+        }) {
+            return (
+                MolecularCharge::new(&[(charge, single + molecular_formula!(Electron - 1))]),
+                MolecularFormula::default(),
+            );
+        }
+    }
+    let difference = carriers - &protons.formula();
+    (protons, difference)
+}
+
+/// Create a main series ion, a hydrogen shift is represented as a hydrogen gain or loss which is
+/// placed before any other neutral losses.
+fn main_series_ion(
+    series: char,
+    position: &PeptidePosition,
+    shift: i8,
+    neutral_losses: &mut Vec<NeutralLoss>,
+) -> IonType {
+    let hydrogens = molecular_formula!(H 1) * i32::from(shift.unsigned_abs());
+    match shift.cmp(&0) {
+        Ordering::Greater => neutral_losses.insert(0, NeutralLoss::Gain(hydrogens)),
+        Ordering::Less => neutral_losses.insert(0, NeutralLoss::Loss(hydrogens)),
+        Ordering::Equal => (),
+    }
+    IonType::MainSeries(series, position.series_number, None)
+}
+
+/// Split a difference in formula into a gain for all positive elements and a loss for all
+/// negative elements.
+fn split_difference(difference: &MolecularFormula) -> Vec<NeutralLoss> {
+    let mut gain = MolecularFormula::default();
+    let mut loss = MolecularFormula::default();
+    for (element, isotope, amount) in difference.elements() {
+        if *amount > 0 {
+            let _ = gain.add((*element, *isotope, *amount));
+        } else {
+            let _ = loss.add((*element, *isotope, -*amount));
+        }
+    }
+    [NeutralLoss::Gain(gain), NeutralLoss::Loss(loss)]
+        .into_iter()
+        .filter(|l| !l.is_empty())
+        .collect()
+}
+
+impl Fragment {
+    /// Get the mzPAF annotation for this fragment, see [`PeakAnnotation`] for more details on how
+    /// fragments are represented in mzPAF.
+    pub fn to_mzpaf(&self) -> String {
+        PeakAnnotation::from(self).to_string()
+    }
+}
+
+impl AnnotatedPeak {
+    /// Get the mzPAF annotation for this peak, with all annotations separated by commas and the
+    /// deviation from the theoretical m/z (in the given mass mode) for each annotation. An
+    /// unannotated peak is annotated as unknown ('?').
+    pub fn to_mzpaf(&self, mode: MassMode) -> String {
+        if self.annotation.is_empty() {
+            return "?".to_string();
+        }
+        self.annotation
+            .iter()
+            .map(|fragment| {
+                let deviation = (self.experimental_mz - fragment.mz(mode)).get::<mz>();
+                PeakAnnotation {
+                    deviation: Some(Tolerance::new_absolute(MassOverCharge::new::<mz>(
+                        (deviation * 1e5).round() / 1e5,
+                    ))),
+                    ..PeakAnnotation::from(fragment)
+                }
+                .to_string()
+            })
+            .join(",")
+    }
+}
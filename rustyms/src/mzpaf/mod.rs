@@ -0,0 +1,23 @@
+//! Read and write peak annotations in the [mzPAF](https://www.psidev.info/mzPAF) format.
+//!
+//! ```
+//! # use rustyms::{mzpaf::*, *};
+//! let annotations = parse_mzpaf("b2-H2O/3.4ppm,y3^2/-0.001").unwrap();
+//! assert_eq!(annotations.len(), 2);
+//! assert_eq!(annotations[0].to_string(), "b2-H2O/3.4ppm");
+//!
+//! let peptide = CompoundPeptidoform::pro_forma("PEPTIDE", None).unwrap();
+//! let fragments = annotations[1].to_fragments(&peptide).unwrap();
+//! assert_eq!(fragments[0].to_mzpaf(), "y3^2");
+//! ```
+
+mod annotation;
+mod conversion;
+mod parse;
+mod smiles;
+
+#[cfg(test)]
+mod tests;
+
+pub use annotation::*;
+pub use parse::parse_mzpaf;
@@ -0,0 +1,903 @@
+use std::{num::NonZeroU16, ops::Range};
+
+use crate::{
+    error::{Context, CustomError},
+    helper_functions::{end_of_enclosure, explain_number_error},
+    modification::SimpleModification,
+    system::{mz, MassOverCharge},
+    AminoAcid, CompoundPeptidoform, Element, MolecularCharge, MolecularFormula, NeutralLoss,
+    Tolerance,
+};
+
+use super::{
+    annotation::mzpaf_named_molecules, smiles::parse_smiles, IonType, Isotope, PeakAnnotation,
+};
+
+/// Parse a mzPAF peak annotation line (can contain multiple annotations).
+/// # Errors
+/// When the annotation does not follow the format.
+pub fn parse_mzpaf(line: &str) -> Result<Vec<PeakAnnotation>, CustomError> {
+    let end = line.trim_end().len();
+    let mut index = skip_whitespace(line, 0, end);
+    if index == end {
+        return Err(CustomError::error(
+            "Invalid mzPAF annotation",
+            "An mzPAF annotation cannot be empty, use '?' for an unknown annotation",
+            Context::full_line(0, line),
+        ));
+    }
+    let mut annotations = Vec::new();
+    loop {
+        let (next, annotation) = parse_annotation(line, index..end)?;
+        annotations.push(annotation);
+        index = skip_whitespace(line, next, end);
+        if index == end {
+            break;
+        }
+        if line.as_bytes()[index] == b',' {
+            index = skip_whitespace(line, index + 1, end);
+        } else {
+            return Err(CustomError::error(
+                "Invalid mzPAF annotation delimiter",
+                "Different mzPAF annotations should be separated with commas ','.",
+                Context::line(None, line, index, 1),
+            ));
+        }
+    }
+    Ok(annotations)
+}
+
+/// Parse a single mzPAF peak annotation, returns the index of the first byte after the annotation.
+/// # Errors
+/// When the annotation does not follow the format.
+fn parse_annotation(
+    line: &str,
+    range: Range<usize>,
+) -> Result<(usize, PeakAnnotation), CustomError> {
+    let end = range.end;
+    let mut index = range.start;
+    let auxiliary = peek(line, index, end) == Some(b'&');
+    if auxiliary {
+        index += 1;
+    }
+    let (index, analyte_number) = parse_analyte_number(line, index, end)?;
+    let (index, ion) = parse_ion(line, index, end)?;
+    let (index, neutral_losses, isotopes) = parse_neutral_losses(line, index, end)?;
+    let (index, adduct) = parse_adduct_type(line, index, end)?;
+    let (charge_index, charge) = parse_charge(line, index, end)?;
+    let (index, deviation) = parse_deviation(line, charge_index, end)?;
+    let (index, confidence) = parse_confidence(line, index, end)?;
+    let charge = match (adduct, charge) {
+        (Some(adduct), Some(charge)) if adduct.charge().value != charge => {
+            return Err(CustomError::error(
+                "Invalid mzPAF annotation",
+                "The defined charge should be identical to the total charge as defined in the adduct ions",
+                Context::line_range(None, line, range.start..charge_index),
+            ));
+        }
+        (Some(adduct), _) => adduct,
+        (None, charge) => MolecularCharge::proton(charge.unwrap_or(1)),
+    };
+    Ok((
+        index,
+        PeakAnnotation {
+            auxiliary,
+            analyte_number,
+            ion,
+            neutral_losses,
+            isotopes,
+            charge,
+            deviation,
+            confidence,
+        },
+    ))
+}
+
+/// Get the byte at the given index if it is still inside the range.
+fn peek(line: &str, index: usize, end: usize) -> Option<u8> {
+    (index < end).then(|| line.as_bytes()[index])
+}
+
+/// Skip any whitespace and return the index of the next non whitespace byte.
+fn skip_whitespace(line: &str, index: usize, end: usize) -> usize {
+    index
+        + line.as_bytes()[index..end]
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count()
+}
+
+/// Get the number of consecutive ASCII digits starting at the index.
+fn digits(line: &str, index: usize, end: usize) -> usize {
+    line.as_bytes()[index.min(end)..end]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count()
+}
+
+/// Parse an unsigned number of the given length.
+/// # Errors
+/// If the text is not a valid number.
+fn parse_number(
+    line: &str,
+    index: usize,
+    len: usize,
+    title: &str,
+    name: &str,
+) -> Result<usize, CustomError> {
+    line[index..index + len].parse::<usize>().map_err(|err| {
+        CustomError::error(
+            title,
+            format!("The {name} {}", explain_number_error(&err)),
+            Context::line(None, line, index, len),
+        )
+    })
+}
+
+/// Parse a floating point number starting at the index, returns the length and the number.
+/// # Errors
+/// If the text is not a valid number.
+fn parse_float(
+    line: &str,
+    index: usize,
+    end: usize,
+    title: &str,
+    name: &str,
+) -> Result<(usize, f64), CustomError> {
+    let len = line.as_bytes()[index..end]
+        .iter()
+        .take_while(|b| b.is_ascii_digit() || matches!(b, b'+' | b'-' | b'.' | b'e' | b'E'))
+        .count();
+    line[index..index + len]
+        .parse::<f64>()
+        .map(|number| (len, number))
+        .map_err(|_| {
+            CustomError::error(
+                title,
+                format!("The {name} should be a valid number"),
+                Context::line(None, line, index, len.max(1)),
+            )
+        })
+}
+
+/// Find the closing symbol for the enclosure that is opened at the given index.
+/// # Errors
+/// If the enclosure is not closed before the end.
+fn enclosure(
+    line: &str,
+    index: usize,
+    end: usize,
+    open: u8,
+    close: u8,
+    title: &str,
+) -> Result<usize, CustomError> {
+    end_of_enclosure(line, index + 1, open, close)
+        .filter(|close| *close < end)
+        .ok_or_else(|| {
+            CustomError::error(
+                title,
+                format!(
+                    "No closing '{}' found for this opening '{}'",
+                    char::from(close),
+                    char::from(open)
+                ),
+                Context::line(None, line, index, 1),
+            )
+        })
+}
+
+/// Find the end of a formula, formulas consist of elements with their counts and isotopes
+/// enclosed in square brackets (`[13C2]`).
+fn formula_end(line: &str, index: usize, end: usize) -> usize {
+    let bytes = line.as_bytes();
+    let mut index = index;
+    while index < end {
+        if bytes[index].is_ascii_alphanumeric() {
+            index += 1;
+        } else if bytes[index] == b'['
+            && peek(line, index + 1, end).is_some_and(|b| b.is_ascii_digit())
+        {
+            match end_of_enclosure(line, index + 1, b'[', b']') {
+                Some(close) if close < end => index = close + 1,
+                _ => break,
+            }
+        } else {
+            break;
+        }
+    }
+    index
+}
+
+/// Parse a mzPAF chemical formula: 'C2H3NO' or '[13C2]C-2H2N'.
+///
+/// Unlike ProForma formulas these are case sensitive, so 'CO' is carbon monoxide and not cobalt.
+/// # Errors
+/// If the formula is not valid.
+fn parse_formula(line: &str, range: Range<usize>) -> Result<MolecularFormula, CustomError> {
+    let mut formula = MolecularFormula::default();
+    let mut index = range.start;
+    if range.is_empty() {
+        return Err(CustomError::error(
+            "Invalid mzPAF formula",
+            "A formula cannot be empty",
+            Context::line(None, line, range.start, 1),
+        ));
+    }
+    while index < range.end {
+        let start = index;
+        let (isotope, element, amount) = if line.as_bytes()[index] == b'[' {
+            let close = line[index..range.end]
+                .find(']')
+                .map(|i| i + index)
+                .ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid mzPAF formula",
+                        "An isotope was opened but never closed",
+                        Context::line(None, line, index, 1),
+                    )
+                })?;
+            let len = digits(line, index + 1, close);
+            let isotope = line[index + 1..index + 1 + len]
+                .parse::<NonZeroU16>()
+                .map_err(|err| {
+                    CustomError::error(
+                        "Invalid mzPAF formula",
+                        format!("The isotope number {}", explain_number_error(&err)),
+                        Context::line(None, line, index + 1, len.max(1)),
+                    )
+                })?;
+            let (element_len, element, amount) =
+                parse_element_and_count(line, index + 1 + len, close)?;
+            if index + 1 + len + element_len != close {
+                return Err(CustomError::error(
+                    "Invalid mzPAF formula",
+                    "An isotope should only contain the isotope number, element, and count",
+                    Context::line(None, line, index, close - index + 1),
+                ));
+            }
+            index = close + 1;
+            (Some(isotope), element, amount)
+        } else {
+            let (len, element, amount) = parse_element_and_count(line, index, range.end)?;
+            index += len;
+            (None, element, amount)
+        };
+        if !formula.add((element, isotope, amount)) {
+            return Err(CustomError::error(
+                "Invalid mzPAF formula",
+                "This element or isotope does not have a defined mass",
+                Context::line(None, line, start, index - start),
+            ));
+        }
+    }
+    Ok(formula)
+}
+
+/// Parse a single element with an optional count 'C', 'H2', 'N-1'.
+///
+/// The element has to start with an uppercase letter optionally followed by a lowercase letter.
+/// Returns the length, the element, and the count.
+/// # Errors
+/// If this is not a valid element or count.
+fn parse_element_and_count(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Element, i32), CustomError> {
+    let bytes = line.as_bytes();
+    if index >= end || !bytes[index].is_ascii_uppercase() {
+        return Err(CustomError::error(
+            "Invalid mzPAF formula",
+            "Expected an element, which starts with an uppercase letter",
+            Context::line(None, line, index, 1),
+        ));
+    }
+    let len = if peek(line, index + 1, end).is_some_and(|b| b.is_ascii_lowercase()) {
+        2
+    } else {
+        1
+    };
+    let element = Element::try_from(&line[index..index + len])
+        .ok()
+        .filter(|e| *e != Element::Electron)
+        .ok_or_else(|| {
+            CustomError::error(
+                "Invalid mzPAF formula",
+                "Not a valid element",
+                Context::line(None, line, index, len),
+            )
+        })?;
+    let sign = usize::from(peek(line, index + len, end) == Some(b'-'));
+    let number = digits(line, index + len + sign, end);
+    if number == 0 {
+        if sign == 1 {
+            return Err(CustomError::error(
+                "Invalid mzPAF formula",
+                "A negative count should be followed by a number",
+                Context::line(None, line, index + len, 1),
+            ));
+        }
+        return Ok((len, element, 1));
+    }
+    let amount = line[index + len..index + len + sign + number]
+        .parse::<i32>()
+        .map_err(|err| {
+            CustomError::error(
+                "Invalid mzPAF formula",
+                format!("The element count {}", explain_number_error(&err)),
+                Context::line(None, line, index + len, sign + number),
+            )
+        })?;
+    Ok((len + sign + number, element, amount))
+}
+
+/// Parse a mzPAF analyte number. '1@...'
+/// # Errors
+/// When the analyte number is not formatted correctly.
+fn parse_analyte_number(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Option<usize>), CustomError> {
+    let len = digits(line, index, end);
+    if len == 0 {
+        return Ok((index, None));
+    }
+    if peek(line, index + len, end) != Some(b'@') {
+        return Err(CustomError::error(
+            "Invalid mzPAF analyte number",
+            "The analyte number should be followed by an at sign '@'",
+            Context::line(None, line, index + len, 1),
+        ));
+    }
+    Ok((
+        index + len + 1,
+        Some(parse_number(
+            line,
+            index,
+            len,
+            "Invalid mzPAF analyte number",
+            "analyte number",
+        )?),
+    ))
+}
+
+/// Parse a mzPAF ion.
+/// # Errors
+/// When the ion is not formatted correctly.
+fn parse_ion(line: &str, index: usize, end: usize) -> Result<(usize, IonType), CustomError> {
+    match peek(line, index, end) {
+        Some(b'?') => {
+            let len = digits(line, index + 1, end);
+            if len == 0 {
+                Ok((index + 1, IonType::Unknown(None)))
+            } else {
+                Ok((
+                    index + 1 + len,
+                    IonType::Unknown(Some(parse_number(
+                        line,
+                        index + 1,
+                        len,
+                        "Invalid mzPAF unknown ion ordinal",
+                        "ordinal number",
+                    )?)),
+                ))
+            }
+        }
+        Some(series @ (b'a' | b'b' | b'c' | b'x' | b'y' | b'z')) => {
+            let len = digits(line, index + 1, end);
+            if len == 0 {
+                return Err(CustomError::error(
+                    "Invalid mzPAF main series ion ordinal",
+                    "For a main series ion the ordinal should be provided, like 'a12'",
+                    Context::line(None, line, index, 1),
+                ));
+            }
+            let ordinal = parse_number(
+                line,
+                index + 1,
+                len,
+                "Invalid mzPAF main series ion ordinal",
+                "ordinal number",
+            )?;
+            let next = index + 1 + len;
+            if peek(line, next, end) == Some(b'{') {
+                let close = enclosure(
+                    line,
+                    next,
+                    end,
+                    b'{',
+                    b'}',
+                    "Invalid mzPAF main series ion analyte",
+                )?;
+                let analyte = CompoundPeptidoform::pro_forma(&line[next + 1..close], None)?;
+                Ok((
+                    close + 1,
+                    IonType::MainSeries(char::from(series), ordinal, Some(analyte)),
+                ))
+            } else {
+                Ok((next, IonType::MainSeries(char::from(series), ordinal, None)))
+            }
+        }
+        Some(b'I') => {
+            let amino_acid = line[index + 1..end].chars().next().ok_or_else(|| {
+                CustomError::error(
+                    "Invalid mzPAF immonium ion",
+                    "The source amino acid for this immonium ion should be present like 'IA'",
+                    Context::line(None, line, index, 1),
+                )
+            })?;
+            let amino_acid = AminoAcid::try_from(amino_acid).map_err(|()| {
+                CustomError::error(
+                    "Invalid mzPAF immonium ion",
+                    "The provided amino acid is not a known amino acid",
+                    Context::line(None, line, index + 1, amino_acid.len_utf8()),
+                )
+            })?;
+            let next = index + 2;
+            if peek(line, next, end) == Some(b'[') {
+                let close = enclosure(line, next, end, b'[', b']', "Invalid mzPAF immonium ion")?;
+                let modification = SimpleModification::try_from(
+                    line,
+                    next + 1..close,
+                    &mut Vec::new(),
+                    &mut Vec::new(),
+                    None,
+                )?
+                .defined()
+                .ok_or_else(|| {
+                    CustomError::error(
+                        "Invalid mzPAF immonium ion",
+                        "The modification on an immonium ion should be a defined modification, not an ambiguous or cross-linked modification",
+                        Context::line_range(None, line, next + 1..close),
+                    )
+                })?;
+                Ok((close + 1, IonType::Immonium(amino_acid, Some(modification))))
+            } else {
+                Ok((next, IonType::Immonium(amino_acid, None)))
+            }
+        }
+        Some(b'm') => {
+            let first_len = digits(line, index + 1, end);
+            if first_len == 0 {
+                return Err(CustomError::error(
+                    "Invalid mzPAF internal ion first ordinal",
+                    "The first ordinal for an internal ion should be present, like 'm4:6'",
+                    Context::line(None, line, index, 1),
+                ));
+            }
+            let separator = index + 1 + first_len;
+            if peek(line, separator, end) != Some(b':') {
+                return Err(CustomError::error(
+                    "Invalid mzPAF internal ion ordinal separator",
+                    "The internal ion ordinal separator should be a colon ':', like 'm4:6'",
+                    Context::line(None, line, separator, 1),
+                ));
+            }
+            let second_len = digits(line, separator + 1, end);
+            if second_len == 0 {
+                return Err(CustomError::error(
+                    "Invalid mzPAF internal ion second ordinal",
+                    "The second ordinal for an internal ion should be present, like 'm4:6'",
+                    Context::line(None, line, separator, 1),
+                ));
+            }
+            let first = parse_number(
+                line,
+                index + 1,
+                first_len,
+                "Invalid mzPAF internal ion first ordinal",
+                "ordinal number",
+            )?;
+            let second = parse_number(
+                line,
+                separator + 1,
+                second_len,
+                "Invalid mzPAF internal ion second ordinal",
+                "ordinal number",
+            )?;
+            if first > second {
+                return Err(CustomError::error(
+                    "Invalid mzPAF internal ion",
+                    "The first ordinal of an internal ion cannot be bigger than the second ordinal",
+                    Context::line(None, line, index, 2 + first_len + second_len),
+                ));
+            }
+            Ok((separator + 1 + second_len, IonType::Internal(first, second)))
+        }
+        Some(b'_') => {
+            // TODO: Potentially recognise the following as known contaminants:
+            // 0@_{y1(R)}
+            // 0@_{a2(LP)}
+            // 0@_{b2(LP)}
+            if peek(line, index + 1, end) != Some(b'{') {
+                return Err(CustomError::error(
+                    "Invalid mzPAF named compound",
+                    "A named compound must be named with curly braces '{}' after the '_'",
+                    Context::line(None, line, index, 1),
+                ));
+            }
+            let close = enclosure(
+                line,
+                index + 1,
+                end,
+                b'{',
+                b'}',
+                "Invalid mzPAF named compound",
+            )?;
+            Ok((
+                close + 1,
+                IonType::Named(line[index + 2..close].to_string()),
+            ))
+        }
+        Some(b'p') => Ok((index + 1, IonType::Precursor)),
+        Some(b'r') => {
+            if peek(line, index + 1, end) != Some(b'[') {
+                return Err(CustomError::error(
+                    "Invalid mzPAF reporter ion",
+                    "A reporter ion must be named with square braces '[]' after the 'r'",
+                    Context::line(None, line, index, 1),
+                ));
+            }
+            let close = enclosure(
+                line,
+                index + 1,
+                end,
+                b'[',
+                b']',
+                "Invalid mzPAF reporter ion",
+            )?;
+            let name = &line[index + 2..close];
+            mzpaf_named_molecules()
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map_or_else(
+                    || {
+                        Err(CustomError::error(
+                            "Unknown mzPAF named reporter ion",
+                            "Unknown name",
+                            Context::line(None, line, index + 2, name.len()),
+                        ))
+                    },
+                    |(name, formula)| {
+                        Ok((
+                            close + 1,
+                            IonType::Reporter((*name).to_string(), formula.clone()),
+                        ))
+                    },
+                )
+        }
+        Some(b'f') => {
+            if peek(line, index + 1, end) != Some(b'{') {
+                return Err(CustomError::error(
+                    "Invalid mzPAF formula",
+                    "A formula must have the formula defined with curly braces '{}' after the 'f'",
+                    Context::line(None, line, index, 1),
+                ));
+            }
+            let close = enclosure(line, index + 1, end, b'{', b'}', "Invalid mzPAF formula")?;
+            let formula = parse_formula(line, index + 2..close)?;
+            Ok((close + 1, IonType::Formula(formula)))
+        }
+        Some(b's') => {
+            if peek(line, index + 1, end) != Some(b'{') {
+                return Err(CustomError::error(
+                    "Invalid mzPAF SMILES",
+                    "A SMILES must have the SMILES defined with curly braces '{}' after the 's'",
+                    Context::line(None, line, index, 1),
+                ));
+            }
+            let close = enclosure(line, index + 1, end, b'{', b'}', "Invalid mzPAF SMILES")?;
+            let formula = parse_smiles(line, index + 2..close)?;
+            Ok((
+                close + 1,
+                IonType::Smiles(line[index + 2..close].to_string(), formula),
+            ))
+        }
+        Some(_) => Err(CustomError::error(
+            "Invalid mzPAF ion",
+            "An ion cannot start with this character",
+            Context::line(
+                None,
+                line,
+                index,
+                line[index..].chars().next().map_or(1, char::len_utf8),
+            ),
+        )),
+        None => Err(CustomError::error(
+            "Invalid mzPAF ion",
+            "An ion cannot be an empty string",
+            Context::line(None, line, index, 1),
+        )),
+    }
+}
+
+/// Parse all mzPAF neutral losses ('-H2O', '+2NH3', '-[Hex]') and isotopes ('+i', '+2i13C', '-iA').
+/// # Errors
+/// When the neutral losses or isotopes are not formatted correctly.
+#[allow(clippy::type_complexity)]
+fn parse_neutral_losses(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Vec<NeutralLoss>, Vec<(i32, Isotope)>), CustomError> {
+    let mut index = index;
+    let mut neutral_losses = Vec::new();
+    let mut isotopes = Vec::new();
+    while let Some(sign @ (b'-' | b'+')) = peek(line, index, end) {
+        let start = index;
+        index += 1;
+        let len = digits(line, index, end);
+        let amount = if len == 0 {
+            1
+        } else {
+            i32::try_from(parse_number(
+                line,
+                index,
+                len,
+                "Invalid mzPAF neutral loss",
+                "amount",
+            )?)
+            .map_err(|_| {
+                CustomError::error(
+                    "Invalid mzPAF neutral loss",
+                    "The amount is too big to fit in the internal representation",
+                    Context::line(None, line, index, len),
+                )
+            })?
+        };
+        index += len;
+        if peek(line, index, end) == Some(b'i') {
+            index += 1;
+            let isotope = if peek(line, index, end) == Some(b'A') {
+                index += 1;
+                Isotope::Average
+            } else {
+                let isotope_len = digits(line, index, end);
+                if isotope_len == 0 {
+                    Isotope::General
+                } else {
+                    let element_start = index + isotope_len;
+                    let mut element_len = usize::from(
+                        peek(line, element_start, end).is_some_and(|b| b.is_ascii_uppercase()),
+                    );
+                    if element_len == 1
+                        && peek(line, element_start + 1, end)
+                            .is_some_and(|b| b.is_ascii_lowercase())
+                        && Element::try_from(&line[element_start..element_start + 2]).is_ok()
+                    {
+                        element_len = 2;
+                    }
+                    let element = (element_len > 0)
+                        .then(|| {
+                            Element::try_from(&line[element_start..element_start + element_len])
+                                .ok()
+                        })
+                        .flatten()
+                        .ok_or_else(|| {
+                            CustomError::error(
+                                "Invalid mzPAF isotope",
+                                "An isotope should be followed by a valid element, like '+i13C'",
+                                Context::line(None, line, element_start, element_len.max(1)),
+                            )
+                        })?;
+                    let number =
+                        line[index..element_start]
+                            .parse::<NonZeroU16>()
+                            .map_err(|err| {
+                                CustomError::error(
+                                    "Invalid mzPAF isotope",
+                                    format!("The isotope number {}", explain_number_error(&err)),
+                                    Context::line(None, line, index, isotope_len),
+                                )
+                            })?;
+                    if element == Element::Electron || !element.is_valid(Some(number)) {
+                        return Err(CustomError::error(
+                            "Invalid mzPAF isotope",
+                            format!("The isotope {number}{element} does not exist"),
+                            Context::line(None, line, index, isotope_len + element_len),
+                        ));
+                    }
+                    index = element_start + element_len;
+                    Isotope::Specific(element, number)
+                }
+            };
+            isotopes.push((if sign == b'-' { -amount } else { amount }, isotope));
+        } else if peek(line, index, end) == Some(b'[') && len == 0 {
+            let close = enclosure(line, index, end, b'[', b']', "Invalid mzPAF neutral loss")?;
+            let name = &line[index + 1..close];
+            let formula = mzpaf_named_molecules()
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, formula)| formula.clone())
+                .ok_or_else(|| {
+                    CustomError::error(
+                        "Unknown mzPAF named neutral loss",
+                        "Unknown name",
+                        Context::line(None, line, index + 1, name.len()),
+                    )
+                })?;
+            index = close + 1;
+            neutral_losses.push(if sign == b'-' {
+                NeutralLoss::Loss(formula)
+            } else {
+                NeutralLoss::Gain(formula)
+            });
+        } else {
+            let formula_end = formula_end(line, index, end);
+            if formula_end == index {
+                return Err(CustomError::error(
+                    "Invalid mzPAF neutral loss",
+                    "A neutral loss or gain should be followed by a formula, a named molecule in square brackets, or an isotope",
+                    Context::line(None, line, start, 1),
+                ));
+            }
+            let formula = parse_formula(line, index..formula_end)? * amount;
+            index = formula_end;
+            neutral_losses.push(if sign == b'-' {
+                NeutralLoss::Loss(formula)
+            } else {
+                NeutralLoss::Gain(formula)
+            });
+        }
+    }
+    Ok((index, neutral_losses, isotopes))
+}
+
+/// Parse a mzPAF adduct type ('[M+Na]', '[M+H+NH4]').
+/// # Errors
+/// When the adduct type is not formatted correctly.
+fn parse_adduct_type(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Option<MolecularCharge>), CustomError> {
+    if peek(line, index, end) != Some(b'[') {
+        return Ok((index, None));
+    }
+    let close = enclosure(line, index, end, b'[', b']', "Invalid mzPAF adduct type")?;
+    if peek(line, index + 1, close) != Some(b'M') {
+        return Err(CustomError::error(
+            "Invalid mzPAF adduct type",
+            "The adduct type should start with 'M', as in '[M+nA]'",
+            Context::line(None, line, index + 1, 1),
+        ));
+    }
+    let mut carriers = Vec::new();
+    let mut offset = index + 2;
+    while let Some(sign @ (b'-' | b'+')) = peek(line, offset, close) {
+        let len = digits(line, offset + 1, close);
+        let amount = if len == 0 {
+            1
+        } else {
+            isize::try_from(parse_number(
+                line,
+                offset + 1,
+                len,
+                "Invalid mzPAF adduct type",
+                "adduct amount",
+            )?)
+            .map_err(|_| {
+                CustomError::error(
+                    "Invalid mzPAF adduct type",
+                    "The adduct amount is too big to fit in the internal representation",
+                    Context::line(None, line, offset + 1, len),
+                )
+            })?
+        };
+        let formula_start = offset + 1 + len;
+        let formula_end = formula_end(line, formula_start, close);
+        if formula_end == formula_start {
+            return Err(CustomError::error(
+                "Invalid mzPAF adduct type",
+                "An adduct should have a formula, as in '[M+Na]'",
+                Context::line(None, line, offset, 1),
+            ));
+        }
+        let formula =
+            parse_formula(line, formula_start..formula_end)? + molecular_formula!(Electron - 1);
+        carriers.push((if sign == b'-' { -amount } else { amount }, formula));
+        offset = formula_end;
+    }
+    if offset != close {
+        return Err(CustomError::error(
+            "Invalid mzPAF adduct type",
+            "The adduct type should only contain adducts, as in '[M+H+Na]'",
+            Context::line(None, line, offset, close - offset),
+        ));
+    }
+    Ok((close + 1, Some(MolecularCharge::new(&carriers))))
+}
+
+/// Parse a mzPAF charge ('^2').
+/// # Errors
+/// When the charge is not formatted correctly.
+fn parse_charge(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Option<isize>), CustomError> {
+    if peek(line, index, end) != Some(b'^') {
+        return Ok((index, None));
+    }
+    let negative = peek(line, index + 1, end) == Some(b'-');
+    let sign = usize::from(matches!(peek(line, index + 1, end), Some(b'-' | b'+')));
+    let len = digits(line, index + 1 + sign, end);
+    if len == 0 {
+        return Err(CustomError::error(
+            "Invalid mzPAF charge",
+            "The number after the charge symbol should be present, eg '^2'.",
+            Context::line(None, line, index, 1),
+        ));
+    }
+    let charge = parse_number(
+        line,
+        index + 1 + sign,
+        len,
+        "Invalid mzPAF charge",
+        "charge number",
+    )
+    .and_then(|c| {
+        isize::try_from(c).map_err(|_| {
+            CustomError::error(
+                "Invalid mzPAF charge",
+                "The charge number is too big to fit in the internal representation",
+                Context::line(None, line, index + 1 + sign, len),
+            )
+        })
+    })?;
+    Ok((
+        index + 1 + sign + len,
+        Some(if negative { -charge } else { charge }),
+    ))
+}
+
+/// Parse a mzPAF deviation, either a ppm or mz deviation.
+/// # Errors
+/// When the deviation is not '<number>' or '<number>ppm'.
+fn parse_deviation(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Option<Tolerance<MassOverCharge>>), CustomError> {
+    if peek(line, index, end) != Some(b'/') {
+        return Ok((index, None));
+    }
+    let (len, deviation) =
+        parse_float(line, index + 1, end, "Invalid mzPAF deviation", "deviation")?;
+    let next = index + 1 + len;
+    if line[next..end]
+        .get(..3)
+        .is_some_and(|unit| unit.eq_ignore_ascii_case("ppm"))
+    {
+        Ok((next + 3, Some(Tolerance::new_ppm(deviation))))
+    } else {
+        Ok((
+            next,
+            Some(Tolerance::new_absolute(MassOverCharge::new::<mz>(
+                deviation,
+            ))),
+        ))
+    }
+}
+
+/// Parse a mzPAF confidence.
+/// # Errors
+/// When the confidence is not '*<number>'.
+fn parse_confidence(
+    line: &str,
+    index: usize,
+    end: usize,
+) -> Result<(usize, Option<f64>), CustomError> {
+    if peek(line, index, end) != Some(b'*') {
+        return Ok((index, None));
+    }
+    let (len, confidence) = parse_float(
+        line,
+        index + 1,
+        end,
+        "Invalid mzPAF confidence",
+        "confidence",
+    )?;
+    Ok((index + 1 + len, Some(confidence)))
+}
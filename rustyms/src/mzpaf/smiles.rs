@@ -0,0 +1,384 @@
+use std::{collections::HashMap, num::NonZeroU16, ops::Range};
+
+use crate::{
+    error::{Context, CustomError},
+    Element, MolecularFormula,
+};
+
+/// A single atom in a SMILES string.
+struct Atom {
+    element: Element,
+    isotope: Option<NonZeroU16>,
+    aromatic: bool,
+    /// The explicit number of hydrogens for bracket atoms, `None` for atoms from the organic subset
+    hydrogens: Option<i32>,
+    charge: i32,
+    /// The total bond order to this atom
+    bonds: u8,
+}
+
+impl Atom {
+    /// The number of implicit hydrogens for atoms from the organic subset, based on the lowest
+    /// normal valence that fits the bonds to this atom.
+    fn implicit_hydrogens(&self) -> i32 {
+        if let Some(hydrogens) = self.hydrogens {
+            return hydrogens;
+        }
+        let valences: &[u8] = match self.element {
+            Element::B => &[3],
+            Element::C => &[4],
+            Element::N | Element::P if self.aromatic => &[3],
+            Element::N | Element::P => &[3, 5],
+            Element::O => &[2],
+            Element::S if self.aromatic => &[2],
+            Element::S => &[2, 4, 6],
+            _ => &[1],
+        };
+        // Aromatic B, C, N, and P contribute one electron to the pi system, O and S two (which
+        // are not bound to any neighbour).
+        let bonds = self.bonds
+            + u8::from(
+                self.aromatic
+                    && matches!(
+                        self.element,
+                        Element::B | Element::C | Element::N | Element::P
+                    ),
+            );
+        valences
+            .iter()
+            .find(|v| **v >= bonds)
+            .map_or(0, |v| i32::from(v - bonds))
+    }
+}
+
+/// Parse a SMILES string into the molecular formula of the molecule. This supports the full
+/// syntax of atoms, bonds, branches, ring closures, and disconnected structures, but ignores
+/// stereochemistry.
+/// # Errors
+/// If the SMILES is not valid.
+pub(super) fn parse_smiles(
+    line: &str,
+    range: Range<usize>,
+) -> Result<MolecularFormula, CustomError> {
+    let bytes = line.as_bytes();
+    let mut atoms: Vec<Atom> = Vec::new();
+    let mut previous: Option<usize> = None;
+    let mut branches = Vec::new();
+    let mut rings: HashMap<usize, (usize, Option<u8>, usize)> = HashMap::new();
+    let mut bond: Option<u8> = None;
+    let mut index = range.start;
+
+    let error = |explanation: &str, index: usize, length: usize| {
+        CustomError::error(
+            "Invalid SMILES",
+            explanation,
+            Context::line(None, line, index, length),
+        )
+    };
+
+    while index < range.end {
+        match bytes[index] {
+            b'-' | b'/' | b'\\' | b':' => {
+                bond = Some(1);
+                index += 1;
+            }
+            b'=' => {
+                bond = Some(2);
+                index += 1;
+            }
+            b'#' => {
+                bond = Some(3);
+                index += 1;
+            }
+            b'$' => {
+                bond = Some(4);
+                index += 1;
+            }
+            b'.' => {
+                previous = None;
+                bond = None;
+                index += 1;
+            }
+            b'(' => {
+                branches.push(
+                    previous
+                        .ok_or_else(|| error("A branch cannot start before any atom", index, 1))?,
+                );
+                index += 1;
+            }
+            b')' => {
+                previous = Some(
+                    branches
+                        .pop()
+                        .ok_or_else(|| error("A branch was closed but never opened", index, 1))?,
+                );
+                index += 1;
+            }
+            b'0'..=b'9' | b'%' => {
+                let (number, length) = if bytes[index] == b'%' {
+                    if index + 2 >= range.end
+                        || !bytes[index + 1].is_ascii_digit()
+                        || !bytes[index + 2].is_ascii_digit()
+                    {
+                        return Err(error(
+                            "A ring closure with '%' should be followed by two digits",
+                            index,
+                            1,
+                        ));
+                    }
+                    (
+                        usize::from(bytes[index + 1] - b'0') * 10
+                            + usize::from(bytes[index + 2] - b'0'),
+                        3,
+                    )
+                } else {
+                    (usize::from(bytes[index] - b'0'), 1)
+                };
+                let current = previous.ok_or_else(|| {
+                    error(
+                        "A ring closure cannot be placed before any atom",
+                        index,
+                        length,
+                    )
+                })?;
+                if let Some((other, other_bond, _)) = rings.remove(&number) {
+                    let order = bond.or(other_bond).unwrap_or(1);
+                    atoms[current].bonds += order;
+                    atoms[other].bonds += order;
+                } else {
+                    rings.insert(number, (current, bond, index));
+                }
+                bond = None;
+                index += length;
+            }
+            b'[' => {
+                let close = line[index..range.end]
+                    .find(']')
+                    .map(|i| i + index)
+                    .ok_or_else(|| error("A bracket atom was opened but never closed", index, 1))?;
+                let atom = parse_bracket_atom(line, index + 1..close)?;
+                add_atom(&mut atoms, &mut previous, &mut bond, atom);
+                index = close + 1;
+            }
+            b'*' => {
+                return Err(error(
+                    "Wildcard atoms have no defined composition",
+                    index,
+                    1,
+                ))
+            }
+            _ => {
+                let (element, aromatic, length) = match &bytes[index..range.end] {
+                    [b'C', b'l', ..] => (Element::Cl, false, 2),
+                    [b'B', b'r', ..] => (Element::Br, false, 2),
+                    [b'B', ..] => (Element::B, false, 1),
+                    [b'C', ..] => (Element::C, false, 1),
+                    [b'N', ..] => (Element::N, false, 1),
+                    [b'O', ..] => (Element::O, false, 1),
+                    [b'P', ..] => (Element::P, false, 1),
+                    [b'S', ..] => (Element::S, false, 1),
+                    [b'F', ..] => (Element::F, false, 1),
+                    [b'I', ..] => (Element::I, false, 1),
+                    [b'b', ..] => (Element::B, true, 1),
+                    [b'c', ..] => (Element::C, true, 1),
+                    [b'n', ..] => (Element::N, true, 1),
+                    [b'o', ..] => (Element::O, true, 1),
+                    [b'p', ..] => (Element::P, true, 1),
+                    [b's', ..] => (Element::S, true, 1),
+                    _ => {
+                        return Err(error(
+                            "Not a valid atom or symbol in SMILES",
+                            index,
+                            line[index..].chars().next().map_or(1, char::len_utf8),
+                        ))
+                    }
+                };
+                let atom = Atom {
+                    element,
+                    isotope: None,
+                    aromatic,
+                    hydrogens: None,
+                    charge: 0,
+                    bonds: 0,
+                };
+                add_atom(&mut atoms, &mut previous, &mut bond, atom);
+                index += length;
+            }
+        }
+    }
+    if !branches.is_empty() {
+        return Err(error(
+            "Not all branches were closed",
+            range.start,
+            range.len(),
+        ));
+    }
+    if let Some((_, _, index)) = rings.values().next() {
+        return Err(error("This ring is never closed", *index, 1));
+    }
+
+    let mut formula = MolecularFormula::default();
+    for atom in &atoms {
+        let hydrogens = atom.implicit_hydrogens();
+        if !formula.add((atom.element, atom.isotope, 1))
+            || (hydrogens != 0 && !formula.add((Element::H, None, hydrogens)))
+            || (atom.charge != 0 && !formula.add((Element::Electron, None, -atom.charge)))
+        {
+            return Err(error(
+                "The formula resulting from this SMILES is not valid",
+                range.start,
+                range.len(),
+            ));
+        }
+    }
+    Ok(formula)
+}
+
+/// Add an atom to the structure and bond it to the previous atom if there is one.
+fn add_atom(
+    atoms: &mut Vec<Atom>,
+    previous: &mut Option<usize>,
+    bond: &mut Option<u8>,
+    atom: Atom,
+) {
+    let index = atoms.len();
+    atoms.push(atom);
+    if let Some(previous) = *previous {
+        let order = bond.unwrap_or(1);
+        atoms[previous].bonds += order;
+        atoms[index].bonds += order;
+    }
+    *previous = Some(index);
+    *bond = None;
+}
+
+/// Parse a bracket atom (without the brackets), eg `13CH4`, `nH`, `NH4+`, `Fe+2`, `C@@H`.
+/// # Errors
+/// If the bracket atom is not valid.
+/// # Panics
+/// If the element was checked to be valid but could not be parsed.
+fn parse_bracket_atom(line: &str, range: Range<usize>) -> Result<Atom, CustomError> {
+    let bytes = line.as_bytes();
+    let mut index = range.start;
+    let error = |explanation: &str, index: usize, length: usize| {
+        CustomError::error(
+            "Invalid SMILES bracket atom",
+            explanation,
+            Context::line(None, line, index, length),
+        )
+    };
+    let digits = |index: usize| {
+        bytes[index..range.end]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+
+    // Isotope
+    let isotope_length = digits(index);
+    let isotope = if isotope_length > 0 {
+        Some(
+            line[index..index + isotope_length]
+                .parse::<NonZeroU16>()
+                .map_err(|_| error("Invalid isotope number", index, isotope_length))?,
+        )
+    } else {
+        None
+    };
+    index += isotope_length;
+
+    // Element
+    let (element, aromatic, length) = if index + 1 < range.end
+        && bytes[index].is_ascii_uppercase()
+        && bytes[index + 1].is_ascii_lowercase()
+        && Element::try_from(&line[index..index + 2]).is_ok()
+    {
+        (
+            Element::try_from(&line[index..index + 2]).unwrap(),
+            false,
+            2,
+        )
+    } else if index + 1 < range.end && &bytes[index..index + 2] == b"se" {
+        (Element::Se, true, 2)
+    } else if index + 1 < range.end && &bytes[index..index + 2] == b"as" {
+        (Element::As, true, 2)
+    } else if index < range.end && bytes[index].is_ascii_alphabetic() {
+        let element = Element::try_from(&line[index..=index])
+            .map_err(|()| error("Not a valid element", index, 1))?;
+        (element, bytes[index].is_ascii_lowercase(), 1)
+    } else {
+        return Err(error("A bracket atom should contain an element", index, 1));
+    };
+    if element == Element::Electron || !element.is_valid(isotope) {
+        return Err(error(
+            "Not a valid element or isotope",
+            range.start,
+            isotope_length + length,
+        ));
+    }
+    index += length;
+
+    // Chirality
+    while index < range.end && bytes[index] == b'@' {
+        index += 1;
+    }
+    if index + 1 < range.end && bytes[index].is_ascii_uppercase() && bytes[index] != b'H' {
+        // Extended chirality classes like @TH1, @SP2, @OH12
+        index += 2 + digits(index + 2);
+    }
+
+    // Hydrogens
+    let mut hydrogens = 0;
+    if index < range.end && bytes[index] == b'H' {
+        let length = digits(index + 1);
+        hydrogens = if length == 0 {
+            1
+        } else {
+            line[index + 1..index + 1 + length]
+                .parse::<i32>()
+                .map_err(|_| error("Invalid hydrogen count", index + 1, length))?
+        };
+        index += 1 + length;
+    }
+
+    // Charge
+    let mut charge = 0;
+    if index < range.end && (bytes[index] == b'+' || bytes[index] == b'-') {
+        let sign = if bytes[index] == b'+' { 1 } else { -1 };
+        let repeated = bytes[index..range.end]
+            .iter()
+            .take_while(|b| **b == bytes[index])
+            .count();
+        let length = digits(index + repeated);
+        charge = if length == 0 {
+            sign * i32::try_from(repeated).unwrap_or(i32::MAX)
+        } else {
+            sign * line[index + repeated..index + repeated + length]
+                .parse::<i32>()
+                .map_err(|_| error("Invalid charge", index + repeated, length))?
+        };
+        index += repeated + length;
+    }
+
+    // Atom class
+    if index < range.end && bytes[index] == b':' {
+        index += 1 + digits(index + 1);
+    }
+
+    if index != range.end {
+        return Err(error(
+            "Invalid trailing characters in bracket atom",
+            index,
+            range.end - index,
+        ));
+    }
+
+    Ok(Atom {
+        element,
+        isotope,
+        aromatic,
+        hydrogens: Some(hydrogens),
+        charge,
+        bonds: 0,
+    })
+}
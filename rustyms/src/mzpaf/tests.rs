@@ -0,0 +1,201 @@
+#![allow(clippy::missing_panics_doc)]
+use crate::{
+    model::*,
+    system::{e, isize::Charge, mz},
+    *,
+};
+
+use super::*;
+
+#[test]
+fn parse_and_write() {
+    for (annotation, expected) in [
+        ("b2-H2O/3.4ppm", "b2-H2O/3.4ppm"),
+        ("b4-H2O^2/3.4ppm*0.3", "b4-H2O^2/3.4ppm*0.3"),
+        ("&1@y7/-0.002", "&1@y7/-0.002"),
+        ("p^2", "p^2"),
+        ("y3^-2", "y3^-2"),
+        ("IY", "IY"),
+        ("IC[Carbamidomethyl]", "IC[Carbamidomethyl]"),
+        ("m3:6", "m3:6"),
+        ("_{Urocanic Acid}", "_{Urocanic Acid}"),
+        ("r[TMT127N]", "r[TMT127N]"),
+        ("r[tmt127n]", "r[TMT127N]"),
+        ("f{C16H22O}", "f{C16H22O}"),
+        ("s{CN=C=O}[M+H]/-0.55ppm", "s{CN=C=O}/-0.55ppm"),
+        ("?", "?"),
+        ("?17", "?17"),
+        ("b2+i", "b2+i"),
+        ("y4+2i13C^2", "y4+2i13C^2"),
+        ("b2+iA", "b2+iA"),
+        ("p-[Hex]", "p-[Hex]"),
+        ("b3-2H2O", "b3-H4O2"),
+        ("b3-NH3-H2O", "b3-H3N-H2O"),
+        ("y3[M+Na]", "y3[M+Na]"),
+        ("p[M+H+Na]^2", "p[M+H+Na]^2"),
+        ("y2{PEPTIDE}", "y2{PEPTIDE}"),
+    ] {
+        let parsed = parse_mzpaf(annotation)
+            .unwrap_or_else(|err| panic!("Could not parse '{annotation}': {err}"));
+        assert_eq!(parsed.len(), 1, "{annotation}");
+        assert_eq!(parsed[0].to_string(), expected, "{annotation}");
+        let reparsed = parse_mzpaf(expected).unwrap();
+        assert_eq!(reparsed, parsed, "{annotation}");
+    }
+}
+
+#[test]
+fn parse_multiple() {
+    let parsed = parse_mzpaf("b2-H2O/3.4ppm, y3^2/-0.001,&2@p").unwrap();
+    assert_eq!(parsed.len(), 3);
+    assert_eq!(parsed[0].ion, IonType::MainSeries('b', 2, None));
+    assert_eq!(parsed[1].charge.charge().value, 2);
+    assert!(parsed[2].auxiliary);
+    assert_eq!(parsed[2].analyte_number, Some(2));
+}
+
+#[test]
+fn parse_invalid() {
+    for annotation in [
+        "",
+        "b",
+        "b2-",
+        "b2^",
+        "m6:3",
+        "r[Unknown]",
+        "f{C16H22O",
+        "s{C1CC}",
+        "s{C(C}",
+        "y3[M+H]^2",
+        "b2/3.4ppm*",
+        "b2,",
+        "q2",
+    ] {
+        assert!(
+            parse_mzpaf(annotation).is_err(),
+            "'{annotation}' should not be valid"
+        );
+    }
+}
+
+#[test]
+fn smiles() {
+    for (smiles, formula) in [
+        ("CN=C=O", molecular_formula!(C 2 H 3 N 1 O 1)),
+        ("c1ccccc1", molecular_formula!(C 6 H 6)),
+        ("C(=O)O", molecular_formula!(C 1 H 2 O 2)),
+        ("[NH4+]", molecular_formula!(N 1 H 4 Electron -1)),
+        ("c1ccncc1", molecular_formula!(C 5 H 5 N 1)),
+        ("CC(=O)Oc1ccccc1C(=O)O", molecular_formula!(C 9 H 8 O 4)),
+        ("[13CH4]", molecular_formula!([13 C 1] H 4)),
+        ("C1CC1.O", molecular_formula!(C 3 H 8 O 1)),
+    ] {
+        let parsed = parse_mzpaf(&format!("s{{{smiles}}}")).unwrap();
+        let IonType::Smiles(_, parsed) = &parsed[0].ion else {
+            panic!("Not parsed as a SMILES ion")
+        };
+        assert_eq!(*parsed, formula, "{smiles}");
+    }
+}
+
+#[test]
+fn to_fragments() {
+    let peptide = CompoundPeptidoform::pro_forma("PEPTIDE", None).unwrap();
+    let model = Model::none()
+        .b(PrimaryIonSeries::default())
+        .y(PrimaryIonSeries::default());
+    let theoretical = peptide.generate_theoretical_fragments(Charge::new::<e>(2), &model);
+    for (annotation, expected) in [("b2", "b2"), ("y3^2", "y3"), ("b4^2", "b4")] {
+        let parsed = parse_mzpaf(annotation).unwrap();
+        let fragments = parsed[0].to_fragments(&peptide).unwrap();
+        assert_eq!(fragments.len(), 1);
+        let fragment = &fragments[0];
+        let found = theoretical
+            .iter()
+            .find(|f| {
+                f.ion.to_string() == expected
+                    && f.charge == fragment.charge
+                    && f.neutral_loss.is_none()
+            })
+            .unwrap();
+        assert!(
+            (found.mz(MassMode::Monoisotopic) - fragment.mz(MassMode::Monoisotopic))
+                .get::<mz>()
+                .abs()
+                < 1e-6,
+            "{annotation}"
+        );
+    }
+    assert!(parse_mzpaf("b12").unwrap()[0]
+        .to_fragments(&peptide)
+        .is_err());
+    assert!(parse_mzpaf("2@b2").unwrap()[0]
+        .to_fragments(&peptide)
+        .is_err());
+    assert!(parse_mzpaf("_{Urocanic Acid}").unwrap()[0]
+        .to_fragments(&peptide)
+        .is_err());
+}
+
+#[test]
+fn round_trip() {
+    let peptide = CompoundPeptidoform::pro_forma("PEPTC[Carbamidomethyl]IDEK", None).unwrap();
+    for charge in [2, -2] {
+        let fragments =
+            peptide.generate_theoretical_fragments(Charge::new::<e>(charge), &Model::all());
+        assert!(!fragments.is_empty());
+        for fragment in &fragments {
+            let annotation = fragment.to_mzpaf();
+            let parsed = parse_mzpaf(&annotation)
+                .unwrap_or_else(|err| panic!("Could not parse '{annotation}' ({fragment}): {err}"));
+            assert_eq!(parsed.len(), 1);
+            let back = parsed[0].to_fragments(&peptide).unwrap_or_else(|err| {
+                panic!("Could not convert '{annotation}' ({fragment}): {err}")
+            });
+            assert!(
+                back.iter().any(|b| (b.mz(MassMode::Monoisotopic)
+                    - fragment.mz(MassMode::Monoisotopic))
+                .get::<mz>()
+                .abs()
+                    < 1e-6),
+                "{annotation} ({fragment}) {} != {}",
+                fragment.mz(MassMode::Monoisotopic).value,
+                back[0].mz(MassMode::Monoisotopic).value
+            );
+        }
+    }
+}
+
+#[test]
+fn round_trip_charge_carriers() {
+    for (sequence, expected) in [
+        ("PEPTC[Carbamidomethyl]IDEK/2[+2Na+]", "b2[M+Na]"),
+        ("PEPTC[Carbamidomethyl]IDEK/2[+Na+,+H+]", "b2[M+Na]"),
+    ] {
+        let peptide = CompoundPeptidoform::pro_forma(sequence, None).unwrap();
+        let fragments = peptide.generate_theoretical_fragments(Charge::new::<e>(2), &Model::all());
+        assert!(!fragments.is_empty());
+        assert!(
+            fragments.iter().any(|f| f.to_mzpaf() == expected),
+            "{sequence} has no '{expected}'"
+        );
+        for fragment in &fragments {
+            let annotation = fragment.to_mzpaf();
+            let parsed = parse_mzpaf(&annotation)
+                .unwrap_or_else(|err| panic!("Could not parse '{annotation}' ({fragment}): {err}"));
+            let back = parsed[0].to_fragments(&peptide).unwrap_or_else(|err| {
+                panic!("Could not convert '{annotation}' ({fragment}): {err}")
+            });
+            assert!(
+                back.iter().any(|b| (b.mz(MassMode::Monoisotopic)
+                    - fragment.mz(MassMode::Monoisotopic))
+                .get::<mz>()
+                .abs()
+                    < 1e-6),
+                "{sequence} {annotation} ({fragment}) {} != {}",
+                fragment.mz(MassMode::Monoisotopic).value,
+                back[0].mz(MassMode::Monoisotopic).value
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@ use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    peptide::Linked, system::usize::Charge, Fragment, LinearPeptide, Model, MolecularFormula,
+    peptide::Linked, system::isize::Charge, Fragment, LinearPeptide, Model, MolecularFormula,
     Multi, Peptidoform,
 };
 
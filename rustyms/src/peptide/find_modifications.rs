@@ -235,6 +235,10 @@ impl PeptideModificationSearch {
     }
 
     /// Search for modifications that can be replaced by named modifications in this peptide.
+    ///
+    /// Modifications for which no replacement is found are kept as is, this includes terminal
+    /// modifications. If a terminal modification is kept, side chain modifications on the
+    /// terminal amino acid are not redefined as terminal modifications.
     #[allow(clippy::similar_names)]
     pub fn search<Complexity>(
         &mut self,
@@ -302,21 +306,23 @@ impl PeptideModificationSearch {
         }
 
         // Start with N and C terminal mods
-        let mut n_term = peptide.get_n_term().cloned().and_then(|m| {
+        let mut n_term = peptide.get_n_term().cloned().map(|m| {
             find_replacement_modification(
                 self,
                 Position::AnyNTerm,
                 peptide.sequence().first().map(|p| p.aminoacid.aminoacid()),
                 &m,
             )
+            .unwrap_or(m)
         });
-        let mut c_term = peptide.get_c_term().cloned().and_then(|m| {
+        let mut c_term = peptide.get_c_term().cloned().map(|m| {
             find_replacement_modification(
                 self,
                 Position::AnyCTerm,
                 peptide.sequence().last().map(|p| p.aminoacid.aminoacid()),
                 &m,
             )
+            .unwrap_or(m)
         });
         let len = peptide.len();
 
@@ -510,4 +516,11 @@ fn test_replacement() {
     let peptide = LinearPeptide::pro_forma("M[Formula:O1]KSLM[+15.9949]E", None).unwrap();
     let expected = LinearPeptide::pro_forma("M[Oxidation]KSLM[Oxidation]E", None).unwrap();
     assert_eq!(search.search(peptide), expected);
+    // Terminal modifications without a replacement are kept
+    let peptide = LinearPeptide::pro_forma("[+1000.0]-SLM[+15.9949]E-[+2000.0]", None).unwrap();
+    let expected = LinearPeptide::pro_forma("[+1000.0]-SLM[Oxidation]E-[+2000.0]", None).unwrap();
+    assert_eq!(search.search(peptide), expected);
+    let peptide = LinearPeptide::pro_forma("[+1000.0]-Q[-17.02655]NKE", None).unwrap();
+    let expected = LinearPeptide::pro_forma("[+1000.0]-Q[-17.02655]NKE", None).unwrap();
+    assert_eq!(search.search(peptide), expected);
 }
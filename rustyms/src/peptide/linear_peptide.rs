@@ -2,7 +2,9 @@
 
 use crate::{
     checked_aminoacid::CheckedAminoAcid,
-    fragment::{DiagnosticPosition, Fragment, FragmentType, PeptidePosition},
+    fragment::{
+        internal_fragment_difference, DiagnosticPosition, Fragment, FragmentType, PeptidePosition,
+    },
     glycan::MonoSaccharide,
     helper_functions::RangeExtension,
     modification::{
@@ -12,8 +14,8 @@ use crate::{
     molecular_charge::{CachedCharge, MolecularCharge},
     peptide::*,
     placement_rule::PlacementRule,
-    system::usize::Charge,
-    AmbiguousLabel, Chemical, DiagnosticIon, Element, Model, MolecularFormula, Multi,
+    system::isize::Charge,
+    AmbiguousLabel, Chemical, DiagnosticIon, Digestion, Element, Model, MolecularFormula, Multi,
     MultiChemical, NeutralLoss, Protease, SequenceElement, SequencePosition,
 };
 use itertools::Itertools;
@@ -684,10 +686,85 @@ impl<Complexity> LinearPeptide<Complexity> {
         (formulas * all_ambiguous_options, seen)
     }
 
+    /// Generate the internal fragments (two backbone cleavages) as defined by the model. These
+    /// never contain a terminal residue. Fragments are not extended over residues with a
+    /// cross-link, as these would not result in a single linear fragment.
+    fn generate_internal_fragments(
+        &self,
+        model: &Model,
+        peptidoform_index: usize,
+        peptide_index: usize,
+        all_peptides: &[LinearPeptide<Linked>],
+        charge_carriers: &mut CachedCharge,
+    ) -> Vec<Fragment> {
+        let mut output = Vec::new();
+        if model.internal.ion_types.is_empty() || self.len() < 3 {
+            return output;
+        }
+        let residues = (1..self.len() - 1)
+            .map(|sequence_index| {
+                let (formulas, seen) = self.sequence[sequence_index].formulas_all(
+                    all_peptides,
+                    &[peptide_index],
+                    &mut Vec::new(),
+                    model.allow_cross_link_cleavage,
+                    SequencePosition::Index(sequence_index),
+                    peptide_index,
+                );
+                seen.is_empty().then_some(formulas)
+            })
+            .collect_vec();
+        let differences = model
+            .internal
+            .ion_types
+            .iter()
+            .map(|(n_kind, c_kind)| {
+                (
+                    *n_kind,
+                    *c_kind,
+                    internal_fragment_difference(*n_kind, *c_kind).into(),
+                )
+            })
+            .collect_vec();
+        for start in 1..self.len() - 1 {
+            let mut formulas = Multi::default();
+            for end in start..self.len() - 1 {
+                let length = end - start + 1;
+                let Some(residue) = &residues[end - 1] else {
+                    break;
+                };
+                if length > *model.internal.length.end() {
+                    break;
+                }
+                formulas *= residue;
+                if length < *model.internal.length.start() {
+                    continue;
+                }
+                for (n_kind, c_kind, difference) in &differences {
+                    output.extend(Fragment::generate_all(
+                        &formulas,
+                        peptidoform_index,
+                        peptide_index,
+                        &FragmentType::internal(
+                            (*n_kind, *c_kind),
+                            PeptidePosition::n(SequencePosition::Index(start), self.len()),
+                            PeptidePosition::n(SequencePosition::Index(end), self.len()),
+                        ),
+                        difference,
+                        &model.internal.neutral_losses,
+                        charge_carriers,
+                        model.internal.charge_range,
+                    ));
+                }
+            }
+        }
+        output
+    }
+
     /// Generate the theoretical fragments for this peptide, with the given maximal charge of the fragments, and the given model.
-    /// With the global isotope modifications applied.
+    /// With the global isotope modifications applied. A negative maximal charge generates negatively charged fragments.
     /// # Panics
-    /// Panics if the `max_charge` is bigger than [`isize::MAX`].
+    /// If the global isotope modifications are invalid.
     pub(crate) fn generate_theoretical_fragments_inner(
         &self,
         max_charge: Charge,
@@ -696,10 +773,7 @@ impl<Complexity> LinearPeptide<Complexity> {
         peptide_index: usize,
         all_peptides: &[LinearPeptide<Linked>],
     ) -> Vec<Fragment> {
-        let default_charge = MolecularCharge::proton(
-            isize::try_from(max_charge.value)
-                .expect("Charge of the precursor cannot be higher then isize::MAX"),
-        );
+        let default_charge = MolecularCharge::proton(max_charge.value);
         let mut charge_carriers: CachedCharge = self
             .charge_carriers
             .as_ref()
@@ -823,6 +897,13 @@ impl<Complexity> LinearPeptide<Complexity> {
                 );
             }
         }
+        output.extend(self.generate_internal_fragments(
+            model,
+            peptidoform_index,
+            peptide_index,
+            all_peptides,
+            &mut charge_carriers,
+        ));
         for fragment in &mut output {
             fragment.formula = fragment
                 .formula
@@ -860,6 +941,60 @@ impl<Complexity> LinearPeptide<Complexity> {
             model.precursor.1,
         ));
 
+        // Generate charge reduced precursors
+        if model.charge_reduced_precursor.allow && charge_carriers.charge().value != 0 {
+            let mut neutral_losses = model.charge_reduced_precursor.neutral_losses.clone();
+            if model.modification_specific_neutral_losses {
+                neutral_losses.extend(
+                    self.potential_neutral_losses(.., all_peptides, peptide_index, &mut Vec::new())
+                        .into_iter()
+                        .map(|(n, _, _)| n),
+                );
+            }
+            if model.charge_reduced_precursor.side_chain_losses {
+                neutral_losses.extend(self.sequence.iter().enumerate().flat_map(
+                    |(sequence_index, position)| {
+                        position
+                            .aminoacid
+                            .formulas_inner(SequencePosition::Index(sequence_index), peptide_index)
+                            .iter()
+                            .map(|aa| NeutralLoss::Loss(aa - &molecular_formula!(C 2 H 2 N 1 O 1)))
+                            .collect_vec()
+                    },
+                ));
+            }
+            neutral_losses.sort();
+            neutral_losses.dedup();
+
+            let precursor_charge = charge_carriers.charge();
+            let precursor_carriers = charge_carriers.options(precursor_charge).to_vec();
+            for charge in model
+                .charge_reduced_precursor
+                .charge_range
+                .charges_iter(precursor_charge)
+                .filter(|c| c.value.abs() < precursor_charge.value.abs())
+            {
+                // The captured electrons, negative for detached electrons
+                let electrons = precursor_charge.value - charge.value;
+                for carriers in &precursor_carriers {
+                    let charge_formula =
+                        carriers.formula() + molecular_formula!(Electron 1) * electrons as i32;
+                    for formula in full_precursor.iter() {
+                        output.extend(
+                            Fragment::new(
+                                formula + &charge_formula,
+                                charge,
+                                peptidoform_index,
+                                peptide_index,
+                                FragmentType::ChargeReducedPrecursor(electrons.unsigned_abs()),
+                            )
+                            .with_neutral_losses(&neutral_losses),
+                        );
+                    }
+                }
+            }
+        }
+
         // Add glycan fragmentation to all peptide fragments
         // Assuming that only one glycan can ever fragment at the same time,
         // and that no peptide fragmentation occurs during glycan fragmentation
@@ -907,6 +1042,7 @@ impl<Complexity> LinearPeptide<Complexity> {
                         peptidoform_index,
                         peptide_index,
                         neutral_loss: None,
+                        charge_carriers: None,
                     }
                     .with_charge_range(
                         &mut charge_carriers,
@@ -1235,18 +1371,30 @@ impl<Complexity: AtMax<Linear>> LinearPeptide<Complexity> {
 
     /// Digest this sequence with the given protease and the given maximal number of missed cleavages.
     pub fn digest(&self, protease: &Protease, max_missed_cleavages: usize) -> Vec<Self> {
+        self.digest_with(&Digestion::new(protease.clone(), max_missed_cleavages))
+    }
+
+    /// Digest this sequence with the given digestion settings. The peptides are returned sorted
+    /// on their start position and then their end position.
+    pub fn digest_with(&self, digestion: &Digestion) -> Vec<Self> {
         let mut sites = vec![0];
-        sites.extend_from_slice(&protease.match_locations(&self.sequence));
+        sites.extend_from_slice(&digestion.protease.match_locations(&self.sequence));
         sites.push(self.len());
+        sites.dedup();
 
-        let mut result = Vec::new();
-
-        for (index, start) in sites.iter().enumerate() {
-            for end in sites.iter().skip(index).take(max_missed_cleavages + 1) {
-                result.push(self.sub_peptide((*start)..*end));
-            }
-        }
-        result
+        digestion
+            .ranges(&sites, self.len())
+            .into_iter()
+            .map(|(start, end)| self.sub_peptide(start..end))
+            .filter(|peptide| {
+                digestion.mass.as_ref().map_or(true, |range| {
+                    peptide
+                        .formulas()
+                        .iter()
+                        .any(|f| range.contains(&f.monoisotopic_mass()))
+                })
+            })
+            .collect()
     }
 
     /// Get the N terminal modification as a simple modification
@@ -1268,10 +1416,8 @@ impl<Complexity: AtMax<Linear>> LinearPeptide<Complexity> {
     }
 
     /// Generate the theoretical fragments for this peptide, with the given maximal charge of the fragments, and the given model.
-    /// With the global isotope modifications applied.
-    ///
-    /// # Panics
-    /// If `max_charge` outside the range `1..=u64::MAX`.
+    /// With the global isotope modifications applied. A negative maximal charge generates negatively charged
+    /// fragments (deprotonated), if the peptide has charge carriers defined these are used instead.
     pub fn generate_theoretical_fragments(
         &self,
         max_charge: Charge,
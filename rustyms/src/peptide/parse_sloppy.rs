@@ -143,6 +143,8 @@ impl Modification {
                     Some(("u", tail)) => Ontology::Unimod.find_name(tail, None),
                     Some(("m", tail)) => Ontology::Psimod.find_name(tail, None),
                     Some(("c", tail)) => Ontology::Custom.find_name(tail, custom_database),
+                    // DIA-NN style `UniMod:35`
+                    Some(("unimod", tail)) => tail.trim().parse().ok().and_then(|id| Ontology::Unimod.find_id(id, None)),
                     _ => None
                 }
             })
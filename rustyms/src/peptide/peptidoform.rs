@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     modification::{CrossLinkName, CrossLinkSide, RulePossible, SimpleModification},
     peptide::Linked,
-    system::usize::Charge,
+    system::isize::Charge,
     Fragment, LinearPeptide, Model, MolecularCharge, MolecularFormula, Multi, SequencePosition,
 };
 /// A single peptidoform, can contain multiple linear peptides
@@ -8,7 +8,7 @@ use crate::{
         GlobalModification,
     },
     placement_rule::{self, PlacementRule},
-    system::{da, usize::Charge},
+    system::{da, isize::Charge},
     AminoAcid, CompoundPeptidoform, Element, LinearPeptide, Model, MolecularCharge, MultiChemical,
     Peptidoform,
 };
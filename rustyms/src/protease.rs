@@ -1,65 +1,454 @@
+use std::{ops::RangeInclusive, str::FromStr};
+
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use crate::{AminoAcid, SequenceElement};
 
 /// A protease defined by it ability to cut at any site identified by the right amino acids at the n and c terminal.
-/// Each position is identified by an option, a none means that there is no specificity at this position. If there is
-/// a specificity at a certain position any amino acid that is contained in the set is allowed (see
+/// Each position is identified by a [`ResidueSpecificity`], which can allow any amino acid, only
+/// the amino acids in a set, or any amino acid except the amino acids in a set (see
 /// [`crate::CheckedAminoAcid::canonical_identical`]).
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
 pub struct Protease {
     /// The amino acids n terminal of the cut site.
-    pub n_term: Vec<Option<Vec<AminoAcid>>>,
+    pub n_term: Vec<ResidueSpecificity>,
     /// The amino acids c terminal of the cut site.
-    pub c_term: Vec<Option<Vec<AminoAcid>>>,
+    pub c_term: Vec<ResidueSpecificity>,
+}
+
+/// The specificity of a protease for a single position around the cut site
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum ResidueSpecificity {
+    /// Any amino acid is allowed
+    Any,
+    /// Only these amino acids are allowed
+    Only(Vec<AminoAcid>),
+    /// All amino acids except these are allowed
+    Except(Vec<AminoAcid>),
+}
+
+impl ResidueSpecificity {
+    /// Check if this position allows the given amino acid
+    pub fn allows(&self, aminoacid: AminoAcid) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Only(set) => set.iter().any(|a| a.canonical_identical(aminoacid)),
+            Self::Except(set) => !set.iter().any(|a| a.canonical_identical(aminoacid)),
+        }
+    }
 }
 
 impl Protease {
     /// Define a simple protease that cuts exactly between the specified sequences.
     pub fn new(n_term: &[AminoAcid], c_term: &[AminoAcid]) -> Self {
         Self {
-            n_term: n_term.iter().map(|aa| Some(vec![*aa])).collect_vec(),
-            c_term: c_term.iter().map(|aa| Some(vec![*aa])).collect_vec(),
+            n_term: n_term
+                .iter()
+                .map(|aa| ResidueSpecificity::Only(vec![*aa]))
+                .collect_vec(),
+            c_term: c_term
+                .iter()
+                .map(|aa| ResidueSpecificity::Only(vec![*aa]))
+                .collect_vec(),
         }
     }
 
     /// Define a protease that cuts on the n terminal side of the provided amino acids.
+    ///
+    /// Behaviour change: previous versions cut on the c terminal side of the provided amino
+    /// acids instead, any code that relied on that should use [`Self::c_terminal_of`].
     pub fn n_terminal_of(residues: &[AminoAcid]) -> Self {
         Self {
-            n_term: vec![Some(residues.to_vec())],
-            c_term: Vec::new(),
+            n_term: Vec::new(),
+            c_term: vec![ResidueSpecificity::Only(residues.to_vec())],
         }
     }
 
     /// Define a protease that cuts on the c terminal side of the provided amino acids.
+    ///
+    /// Behaviour change: previous versions cut on the n terminal side of the provided amino
+    /// acids instead, any code that relied on that should use [`Self::n_terminal_of`].
     pub fn c_terminal_of(residues: &[AminoAcid]) -> Self {
         Self {
-            c_term: vec![Some(residues.to_vec())],
-            n_term: Vec::new(),
+            n_term: vec![ResidueSpecificity::Only(residues.to_vec())],
+            c_term: Vec::new(),
         }
     }
 
+    /// Define a protease that cuts on the n terminal side of the provided amino acids, unless the
+    /// preceding amino acid is one of the excluded amino acids.
+    pub fn n_terminal_of_except(residues: &[AminoAcid], not_after: &[AminoAcid]) -> Self {
+        Self {
+            n_term: vec![ResidueSpecificity::Except(not_after.to_vec())],
+            c_term: vec![ResidueSpecificity::Only(residues.to_vec())],
+        }
+    }
+
+    /// Define a protease that cuts on the c terminal side of the provided amino acids, unless the
+    /// following amino acid is one of the excluded amino acids.
+    pub fn c_terminal_of_except(residues: &[AminoAcid], not_before: &[AminoAcid]) -> Self {
+        Self {
+            n_term: vec![ResidueSpecificity::Only(residues.to_vec())],
+            c_term: vec![ResidueSpecificity::Except(not_before.to_vec())],
+        }
+    }
+
+    /// Trypsin, cuts after K and R but not before P
+    pub fn trypsin() -> Self {
+        Self::c_terminal_of_except(
+            &[AminoAcid::Lysine, AminoAcid::Arginine],
+            &[AminoAcid::Proline],
+        )
+    }
+
+    /// Trypsin/P, cuts after K and R
+    pub fn trypsin_p() -> Self {
+        Self::c_terminal_of(&[AminoAcid::Lysine, AminoAcid::Arginine])
+    }
+
+    /// Lys-C, cuts after K but not before P
+    pub fn lys_c() -> Self {
+        Self::c_terminal_of_except(&[AminoAcid::Lysine], &[AminoAcid::Proline])
+    }
+
+    /// Lys-N, cuts before K
+    pub fn lys_n() -> Self {
+        Self::n_terminal_of(&[AminoAcid::Lysine])
+    }
+
+    /// Arg-C, cuts after R but not before P
+    pub fn arg_c() -> Self {
+        Self::c_terminal_of_except(&[AminoAcid::Arginine], &[AminoAcid::Proline])
+    }
+
+    /// Asp-N, cuts before D
+    pub fn asp_n() -> Self {
+        Self::n_terminal_of(&[AminoAcid::AsparticAcid])
+    }
+
+    /// Glu-C, cuts after D and E but not before P
+    pub fn glu_c() -> Self {
+        Self::c_terminal_of_except(
+            &[AminoAcid::AsparticAcid, AminoAcid::GlutamicAcid],
+            &[AminoAcid::Proline],
+        )
+    }
+
+    /// Chymotrypsin, cuts after F, W, Y, and L but not before P. This is the broad rule used by
+    /// search engines like Comet, the high specificity rule only cuts after F, W, and Y.
+    pub fn chymotrypsin() -> Self {
+        Self::c_terminal_of_except(
+            &[
+                AminoAcid::Phenylalanine,
+                AminoAcid::Tryptophan,
+                AminoAcid::Tyrosine,
+                AminoAcid::Leucine,
+            ],
+            &[AminoAcid::Proline],
+        )
+    }
+
+    /// Pepsin A, cuts after F and L
+    pub fn pepsin() -> Self {
+        Self::c_terminal_of(&[AminoAcid::Phenylalanine, AminoAcid::Leucine])
+    }
+
+    /// Proteinase K, cuts after A, F, I, L, V, W, and Y
+    pub fn proteinase_k() -> Self {
+        Self::c_terminal_of(&[
+            AminoAcid::Alanine,
+            AminoAcid::Phenylalanine,
+            AminoAcid::Isoleucine,
+            AminoAcid::Leucine,
+            AminoAcid::Valine,
+            AminoAcid::Tryptophan,
+            AminoAcid::Tyrosine,
+        ])
+    }
+
+    /// Cyanogen bromide, cuts after M
+    pub fn cnbr() -> Self {
+        Self::c_terminal_of(&[AminoAcid::Methionine])
+    }
+
+    /// All named proteases, with their canonical names
+    pub fn catalogue() -> Vec<(&'static str, Self)> {
+        vec![
+            ("Trypsin", Self::trypsin()),
+            ("Trypsin/P", Self::trypsin_p()),
+            ("Lys-C", Self::lys_c()),
+            ("Lys-N", Self::lys_n()),
+            ("Arg-C", Self::arg_c()),
+            ("Asp-N", Self::asp_n()),
+            ("Glu-C", Self::glu_c()),
+            ("Chymotrypsin", Self::chymotrypsin()),
+            ("Pepsin A", Self::pepsin()),
+            ("Proteinase K", Self::proteinase_k()),
+            ("CNBr", Self::cnbr()),
+        ]
+    }
+
+    /// Get a protease from the catalogue by name, see [`Self::catalogue`]. The name is matched
+    /// case insensitive and ignoring spaces, dashes, and underscores, so `LysC`, `lys-c`, and
+    /// `Lys_C` all give Lys-C.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let normalise = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+        let name = normalise(name);
+        Self::catalogue()
+            .into_iter()
+            .find(|(option, _)| normalise(option) == name)
+            .map(|(_, protease)| protease)
+    }
+
     /// All locations in the given sequence where this protease could cut
     pub fn match_locations<T>(&self, sequence: &[SequenceElement<T>]) -> Vec<usize> {
-        (self.n_term.len()..sequence.len() - self.c_term.len())
+        if sequence.len() < self.n_term.len() + self.c_term.len() {
+            return Vec::new();
+        }
+        (self.n_term.len()..=sequence.len() - self.c_term.len())
             .filter(|i| self.matches_at(&sequence[i - self.n_term.len()..i + self.c_term.len()]))
             .collect_vec()
     }
 
     fn matches_at<T>(&self, slice: &[SequenceElement<T>]) -> bool {
         debug_assert!(slice.len() == self.n_term.len() + self.c_term.len());
-        'positions: for (actual, pattern) in slice
+        slice
             .iter()
             .zip(self.n_term.iter().chain(self.c_term.iter()))
-        {
-            if let Some(pattern) = pattern {
-                for option in pattern {
-                    if option.canonical_identical(actual.aminoacid.aminoacid()) {
-                        continue 'positions;
-                    }
+            .all(|(actual, pattern)| pattern.allows(actual.aminoacid.aminoacid()))
+    }
+}
+
+impl FromStr for Protease {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_name(s).ok_or(())
+    }
+}
+
+/// How specific the protease has to be for both termini of the digested peptides
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum DigestionSpecificity {
+    /// Both termini have to be a cleavage site (or a protein terminus)
+    #[default]
+    Full,
+    /// At least one terminus has to be a cleavage site (or a protein terminus)
+    Semi,
+    /// The termini can be anywhere, the protease and missed cleavages are ignored
+    None,
+}
+
+/// The settings for digesting a protein into peptides
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Digestion {
+    /// The protease
+    pub protease: Protease,
+    /// The maximal number of missed cleavages, the number of cleavage sites within a peptide
+    pub max_missed_cleavages: usize,
+    /// The specificity of the digestion
+    pub specificity: DigestionSpecificity,
+    /// The allowed lengths of the peptides
+    pub length: RangeInclusive<usize>,
+    /// The allowed monoisotopic masses of the peptides, if set
+    pub mass: Option<RangeInclusive<crate::system::Mass>>,
+}
+
+impl Digestion {
+    /// Fully specific digestion with the given protease and maximal number of missed cleavages,
+    /// without any filters on the length or mass of the peptides.
+    pub const fn new(protease: Protease, max_missed_cleavages: usize) -> Self {
+        Self {
+            protease,
+            max_missed_cleavages,
+            specificity: DigestionSpecificity::Full,
+            length: 1..=usize::MAX,
+            mass: None,
+        }
+    }
+
+    /// Set the specificity
+    #[must_use]
+    pub fn specificity(self, specificity: DigestionSpecificity) -> Self {
+        Self {
+            specificity,
+            ..self
+        }
+    }
+
+    /// Set the allowed peptide lengths
+    #[must_use]
+    pub fn length(self, length: RangeInclusive<usize>) -> Self {
+        Self { length, ..self }
+    }
+
+    /// Set the allowed peptide monoisotopic masses
+    #[must_use]
+    pub fn mass(self, mass: RangeInclusive<crate::system::Mass>) -> Self {
+        Self {
+            mass: Some(mass),
+            ..self
+        }
+    }
+
+    /// Get all peptide ranges in a sequence of the given length with the given cleavage sites.
+    /// The sites have to be sorted and include the start and end of the sequence. The mass
+    /// filter is not applied.
+    pub(crate) fn ranges(&self, sites: &[usize], len: usize) -> Vec<(usize, usize)> {
+        let missed = |start: usize, end: usize| {
+            sites
+                .partition_point(|s| *s < end)
+                .saturating_sub(sites.partition_point(|s| *s <= start))
+        };
+        let length = |start: usize, end: usize| self.length.contains(&(end - start));
+        let max_length = (*self.length.end()).min(len);
+        let mut ranges = match self.specificity {
+            DigestionSpecificity::Full => sites
+                .iter()
+                .enumerate()
+                .flat_map(|(index, start)| {
+                    sites
+                        .iter()
+                        .skip(index + 1)
+                        .take(self.max_missed_cleavages + 1)
+                        .map(|end| (*start, *end))
+                })
+                .collect_vec(),
+            DigestionSpecificity::Semi => {
+                let mut ranges = Vec::new();
+                for site in sites {
+                    ranges.extend(
+                        (site + 1..=len.min(site + max_length))
+                            .take_while(|end| missed(*site, *end) <= self.max_missed_cleavages)
+                            .map(|end| (*site, end)),
+                    );
+                    ranges.extend(
+                        (site.saturating_sub(max_length)..*site)
+                            .rev()
+                            .take_while(|start| missed(*start, *site) <= self.max_missed_cleavages)
+                            .map(|start| (start, *site)),
+                    );
                 }
-                return false;
+                ranges.sort_unstable();
+                ranges.dedup();
+                ranges
             }
+            DigestionSpecificity::None => (0..len)
+                .flat_map(|start| {
+                    (start + 1..=len.min(start + max_length)).map(move |end| (start, end))
+                })
+                .collect_vec(),
+        };
+        ranges.retain(|(start, end)| length(*start, *end));
+        ranges
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use crate::{system::da, CompoundPeptidoform, LinearPeptide, SemiAmbiguous};
+
+    use super::*;
+
+    fn peptide(sequence: &str) -> LinearPeptide<SemiAmbiguous> {
+        CompoundPeptidoform::pro_forma(sequence, None)
+            .unwrap()
+            .singular()
+            .unwrap()
+            .singular()
+            .unwrap()
+            .into_semi_ambiguous()
+            .unwrap()
+    }
+
+    fn digest(sequence: &str, digestion: &Digestion) -> Vec<String> {
+        peptide(sequence)
+            .digest_with(digestion)
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn trypsin() {
+        assert_eq!(
+            digest("AKPARGK", &Digestion::new(Protease::trypsin(), 0)),
+            vec!["AKPAR", "GK"]
+        );
+        assert_eq!(
+            digest("AKPARGK", &Digestion::new(Protease::trypsin_p(), 0)),
+            vec!["AK", "PAR", "GK"]
+        );
+        assert_eq!(
+            digest("AKPARGK", &Digestion::new(Protease::trypsin(), 1)),
+            vec!["AKPAR", "AKPARGK", "GK"]
+        );
+        assert_eq!(
+            digest("DAKDE", &Digestion::new(Protease::asp_n(), 0)),
+            vec!["DAK", "DE"]
+        );
+    }
+
+    #[test]
+    fn digest_missed_cleavages() {
+        let protease = Protease::new(&[AminoAcid::Lysine], &[]);
+        assert_eq!(
+            digest("PEPKTIDEKAR", &Digestion::new(protease.clone(), 0)),
+            vec!["PEPK", "TIDEK", "AR"]
+        );
+        assert_eq!(
+            digest("PEPKTIDEKAR", &Digestion::new(protease, 1)),
+            vec!["PEPK", "PEPKTIDEK", "TIDEK", "TIDEKAR", "AR"]
+        );
+    }
+
+    #[test]
+    fn cut_side() {
+        assert_eq!(
+            digest(
+                "AKPEKG",
+                &Digestion::new(Protease::c_terminal_of(&[AminoAcid::Lysine]), 0)
+            ),
+            vec!["AK", "PEK", "G"]
+        );
+        assert_eq!(
+            digest(
+                "AKPEKG",
+                &Digestion::new(Protease::n_terminal_of(&[AminoAcid::Lysine]), 0)
+            ),
+            vec!["A", "KPE", "KG"]
+        );
+    }
+
+    #[test]
+    fn specificity() {
+        let semi = Digestion::new(Protease::trypsin(), 0).specificity(DigestionSpecificity::Semi);
+        assert_eq!(digest("AKGR", &semi), vec!["A", "AK", "K", "G", "GR", "R"]);
+        let none = Digestion::new(Protease::trypsin(), 0).specificity(DigestionSpecificity::None);
+        assert_eq!(digest("AKGR", &none).len(), 10);
+        assert_eq!(
+            digest("AKGR", &none.clone().length(3..=3)),
+            vec!["AKG", "KGR"]
+        );
+        assert_eq!(
+            digest("AKGR", &none.mass(da(200.0)..=da(250.0))),
+            vec!["AK", "KG", "GR"]
+        );
+    }
+
+    #[test]
+    fn catalogue() {
+        for (name, protease) in Protease::catalogue() {
+            assert_eq!(Protease::from_name(name), Some(protease));
         }
-        true
+        assert_eq!(Protease::from_name("lysc"), Some(Protease::lys_c()));
+        assert_eq!("Trypsin/P".parse(), Ok(Protease::trypsin_p()));
+        assert_eq!(Protease::from_name("unknown"), None);
     }
 }
@@ -1,8 +1,9 @@
-//! Handle MGF reader reading
+//! Handle MGF reading and writing
 use std::{
     fs::File,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
     path::Path,
+    sync::OnceLock,
 };
 
 use ordered_float::OrderedFloat;
@@ -16,13 +17,13 @@ use crate::{
     system::{
         charge::e,
         f64::{Mass, MassOverCharge, Time},
+        isize::Charge,
         mass::dalton,
         mass_over_charge::mz,
         time::s,
-        usize::Charge,
     },
 };
-use flate2::bufread::GzDecoder;
+use flate2::{bufread::GzDecoder, write::GzEncoder, Compression};
 
 /// Open a MGF file and return the contained spectra.
 ///
@@ -33,19 +34,7 @@ use flate2::bufread::GzDecoder;
 /// * When any expected number in the file is not a number
 /// * When there is only one column (separated by space or tab) on a data row
 pub fn open(path: impl AsRef<Path>) -> Result<Vec<RawSpectrum>, CustomError> {
-    let path = path.as_ref();
-    let file = File::open(path).map_err(|err| {
-        CustomError::error(
-            "Could not open file",
-            format!("Additional info: {err}"),
-            Context::show(path.display()),
-        )
-    })?;
-    if check_extension(path, "gz") {
-        open_raw(GzDecoder::new(BufReader::new(file)))
-    } else {
-        open_raw(file)
-    }
+    MgfReader::open(path)?.collect()
 }
 
 /// Open a MGF file and return the contained spectra. Open it from a raw buffered reader.
@@ -56,140 +45,530 @@ pub fn open(path: impl AsRef<Path>) -> Result<Vec<RawSpectrum>, CustomError> {
 /// * Any line in the file could not be read
 /// * When any expected number in the file is not a number
 /// * When there is only one column (separated by space or tab) on a data row
-#[allow(clippy::missing_panics_doc)]
-pub fn open_raw<T: std::io::Read>(reader: T) -> Result<Vec<RawSpectrum>, CustomError> {
-    let reader = BufReader::new(reader);
-    let mut current = RawSpectrum::default();
-    let mut output = Vec::new();
-    for (line_index, line) in reader.lines().enumerate() {
-        let line = line.map_err(|err| {
+pub fn open_raw<T: Read>(reader: T) -> Result<Vec<RawSpectrum>, CustomError> {
+    MgfReader::new(reader).collect()
+}
+
+/// A streaming MGF reader, returning the spectra one at a time.
+///
+/// If a spectrum contains an error the error is returned and the rest of that spectrum is
+/// skipped, so reading can continue with the next spectrum.
+pub struct MgfReader<T: Read> {
+    reader: BufReader<T>,
+    line_index: usize,
+    buffer: String,
+    done: bool,
+}
+
+impl MgfReader<Box<dyn Read>> {
+    /// Open a MGF file for streaming, the file is decompressed if the extension is `.gz`.
+    ///
+    /// # Errors
+    /// If the file could not be opened.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CustomError> {
+        let path = path.as_ref();
+        let file = open_file(path)?;
+        if check_extension(path, "gz") {
+            Ok(Self::new(Box::new(GzDecoder::new(BufReader::new(file)))))
+        } else {
+            Ok(Self::new(Box::new(file)))
+        }
+    }
+}
+
+impl<T: Read> MgfReader<T> {
+    /// Create a streaming MGF reader from a raw reader.
+    pub fn new(reader: T) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            line_index: 0,
+            buffer: String::new(),
+            done: false,
+        }
+    }
+
+    /// Read the next line into the buffer without the line ending. Returns the number of bytes
+    /// read, which is 0 at the end of the file.
+    /// # Errors
+    /// If the line could not be read.
+    fn read_line(&mut self) -> Result<usize, CustomError> {
+        self.buffer.clear();
+        let read = self.reader.read_line(&mut self.buffer).map_err(|err| {
             CustomError::error(
                 "Could not read mgf file",
                 format!("Error while reading line: {err}"),
-                Context::show(format!("Line number {}", line_index + 1)),
+                Context::show(format!("Line number {}", self.line_index + 1)),
             )
         })?;
-        let base_error = CustomError::error(
-            "Could not read mgf file",
-            "..",
-            Context::full_line(line_index, line.clone()),
-        );
-        match line.as_str() {
-            "BEGIN IONS" | "" => (),
-            "END IONS" => {
-                output.push(current);
-                current = RawSpectrum::default();
+        let trimmed = self.buffer.trim_end_matches(['\n', '\r']).len();
+        self.buffer.truncate(trimmed);
+        self.line_index += 1;
+        Ok(read)
+    }
+}
+
+impl<T: Read> Iterator for MgfReader<T> {
+    type Item = Result<RawSpectrum, CustomError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut current = RawSpectrum::default();
+        let mut error = None;
+        loop {
+            match self.read_line() {
+                Ok(0) => {
+                    self.done = true;
+                    return error.map(Err);
+                }
+                Ok(_) => (),
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+            if error.is_some() {
+                if self.buffer == "END IONS" {
+                    return error.map(Err);
+                }
+            } else {
+                match parse_line(&self.buffer, self.line_index - 1, &mut current) {
+                    Ok(true) => return Some(Ok(current)),
+                    Ok(false) => (),
+                    Err(err) => error = Some(err),
+                }
+            }
+        }
+    }
+}
+
+/// An entry in the index of a MGF file
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MgfIndexEntry {
+    /// The byte offset of the 'BEGIN IONS' line of this spectrum
+    pub offset: u64,
+    /// The line index (0 based) of the 'BEGIN IONS' line of this spectrum
+    pub line_index: usize,
+    /// The title of this spectrum (empty if no title is given)
+    pub title: String,
+    /// The raw file scan number as parsed from the title
+    pub raw_scan_number: Option<usize>,
+}
+
+/// A MGF reader with a byte offset index, to look up spectra without rereading the whole file.
+///
+/// Building the index reads through the file once, but does not parse the peaks. Because random
+/// access requires seeking in the file gzipped files are not supported.
+pub struct IndexedMgfReader<T: Read + Seek> {
+    reader: MgfReader<T>,
+    index: Vec<MgfIndexEntry>,
+}
+
+impl IndexedMgfReader<File> {
+    /// Open a MGF file and build the index.
+    ///
+    /// # Errors
+    /// If the file could not be opened or read, or if the file is gzipped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, CustomError> {
+        let path = path.as_ref();
+        if check_extension(path, "gz") {
+            return Err(CustomError::error(
+                "Could not index mgf file",
+                "Gzipped files do not support random access, use the streaming reader instead",
+                Context::show(path.display()),
+            ));
+        }
+        Self::new(open_file(path)?)
+    }
+}
+
+impl<T: Read + Seek> IndexedMgfReader<T> {
+    /// Build the index for the given reader, starting from the current position of the reader.
+    ///
+    /// # Errors
+    /// If the reader could not be read.
+    pub fn new(reader: T) -> Result<Self, CustomError> {
+        let mut reader = MgfReader::new(reader);
+        let mut index: Vec<MgfIndexEntry> = Vec::new();
+        let mut offset = reader.reader.stream_position().map_err(|err| {
+            CustomError::error(
+                "Could not index mgf file",
+                format!("Could not get the position in the file: {err}"),
+                Context::none(),
+            )
+        })?;
+        loop {
+            let read = reader.read_line()?;
+            if read == 0 {
+                break;
+            }
+            if reader.buffer == "BEGIN IONS" {
+                index.push(MgfIndexEntry {
+                    offset,
+                    line_index: reader.line_index - 1,
+                    title: String::new(),
+                    raw_scan_number: None,
+                });
+            } else if let (Some(title), Some(entry)) =
+                (reader.buffer.strip_prefix("TITLE="), index.last_mut())
+            {
+                let mut spectrum = RawSpectrum::default();
+                parse_title(title, &mut spectrum);
+                entry.title = spectrum.title;
+                entry.raw_scan_number = spectrum.raw_scan_number;
             }
-            t if t.contains('=') => {
-                // THe previous line made sure it will always contain an equals sign
-                let (key, value) = t.split_once('=').unwrap();
-                match key {
-                    "PEPMASS" => match value.split_once(' ') {
-                        None => {
-                            current.mass =
-                                Some(Mass::new::<dalton>(value.parse().map_err(|_| {
-                                    base_error.with_long_description(format!(
-                                        "Not a number {key} for PEPMASS"
-                                    ))
-                                })?));
-                        }
-                        Some((mass, intensity)) => {
-                            current.mass =
-                                Some(Mass::new::<dalton>(mass.parse().map_err(|_| {
-                                    base_error.with_long_description(format!(
-                                        "Not a number {key} for PEPMASS"
-                                    ))
-                                })?));
-                            current.intensity = Some(intensity.parse().map_err(|_| {
-                                base_error.with_long_description(format!(
-                                    "Not a number {key} for PEPMASS"
-                                ))
-                            })?);
-                        }
-                    },
-                    "CHARGE" => {
-                        current.charge = Some(parse_charge(value).map_err(|()| {
+            offset += read as u64;
+        }
+        Ok(Self { reader, index })
+    }
+
+    /// The index of this file, with all spectra in the order of the file
+    pub fn index(&self) -> &[MgfIndexEntry] {
+        &self.index
+    }
+
+    /// The number of spectra in this file
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Check if this file contains no spectra
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Read the spectrum at the given position in the file, returns None if the index is out of
+    /// bounds.
+    pub fn get(&mut self, index: usize) -> Option<Result<RawSpectrum, CustomError>> {
+        let entry = self.index.get(index)?;
+        Some(self.read_at(entry.offset, entry.line_index))
+    }
+
+    /// Read the first spectrum with the given title, returns None if no spectrum has this title.
+    pub fn get_by_title(&mut self, title: &str) -> Option<Result<RawSpectrum, CustomError>> {
+        let entry = self.index.iter().find(|entry| entry.title == title)?;
+        Some(self.read_at(entry.offset, entry.line_index))
+    }
+
+    /// Read the first spectrum with the given raw scan number (as parsed from the title),
+    /// returns None if no spectrum has this scan number.
+    pub fn get_by_scan_number(
+        &mut self,
+        scan_number: usize,
+    ) -> Option<Result<RawSpectrum, CustomError>> {
+        let entry = self
+            .index
+            .iter()
+            .find(|entry| entry.raw_scan_number == Some(scan_number))?;
+        Some(self.read_at(entry.offset, entry.line_index))
+    }
+
+    /// Read the spectrum that starts at the given byte offset, the line index is used to give the
+    /// correct line numbers in errors.
+    /// # Errors
+    /// If the reader could not seek to this location, or the spectrum is invalid.
+    fn read_at(&mut self, offset: u64, line_index: usize) -> Result<RawSpectrum, CustomError> {
+        self.reader
+            .reader
+            .seek(SeekFrom::Start(offset))
+            .map_err(|err| {
+                CustomError::error(
+                    "Could not read mgf file",
+                    format!("Could not seek to the spectrum: {err}"),
+                    Context::none(),
+                )
+            })?;
+        self.reader.line_index = line_index;
+        self.reader.done = false;
+        self.reader.next().unwrap_or_else(|| {
+            Err(CustomError::error(
+                "Could not read mgf file",
+                "The spectrum at this location is not complete",
+                Context::none(),
+            ))
+        })
+    }
+}
+
+/// Open a file
+/// # Errors
+/// If the file could not be opened.
+fn open_file(path: &Path) -> Result<File, CustomError> {
+    File::open(path).map_err(|err| {
+        CustomError::error(
+            "Could not open file",
+            format!("Additional info: {err}"),
+            Context::show(path.display()),
+        )
+    })
+}
+
+/// Parse a single line of a MGF file into the current spectrum, returns true if this line ends
+/// the spectrum.
+/// # Errors
+/// If the line is not valid.
+#[allow(clippy::missing_panics_doc)]
+fn parse_line(
+    line: &str,
+    line_index: usize,
+    current: &mut RawSpectrum,
+) -> Result<bool, CustomError> {
+    let base_error = CustomError::error(
+        "Could not read mgf file",
+        "..",
+        Context::full_line(line_index, line),
+    );
+    match line {
+        "BEGIN IONS" | "" => (),
+        "END IONS" => return Ok(true),
+        t if t.contains('=') => {
+            // THe previous line made sure it will always contain an equals sign
+            let (key, value) = t.split_once('=').unwrap();
+            match key {
+                "PEPMASS" => match value.split_once(' ') {
+                    None => {
+                        current.mass = Some(Mass::new::<dalton>(value.parse().map_err(|_| {
                             base_error
-                                .with_long_description(format!("Not a number {key} for CHARGE"))
-                        })?);
-                    }
-                    "RT" => {
-                        current.rt = Some(Time::new::<s>(value.parse().map_err(|_| {
-                            base_error.with_long_description(format!("Not a number {key} for RT"))
+                                .with_long_description(format!("Not a number {key} for PEPMASS"))
                         })?));
                     }
-                    "RTINSECONDS" => {
-                        current.rt = Some(Time::new::<s>(value.parse().map_err(|_| {
-                            base_error.with_long_description(format!("Not a number {key} for RT"))
+                    Some((mass, intensity)) => {
+                        current.mass = Some(Mass::new::<dalton>(mass.parse().map_err(|_| {
+                            base_error
+                                .with_long_description(format!("Not a number {key} for PEPMASS"))
                         })?));
-                    }
-                    "TITLE" => parse_title(value, &mut current),
-                    "SEQUENCE" => current.sequence = Some(value.to_owned()),
-                    "NUM_SCANS" => {
-                        current.num_scans = value.parse().map_err(|_| {
+                        current.intensity = Some(intensity.parse().map_err(|_| {
                             base_error
-                                .with_long_description(format!("Not a number {key} for NUM_SCANS"))
-                        })?;
+                                .with_long_description(format!("Not a number {key} for PEPMASS"))
+                        })?);
                     }
-                    _ => (),
+                },
+                "CHARGE" => {
+                    current.charge = Some(parse_charge(value).map_err(|()| {
+                        base_error.with_long_description(format!("Not a number {key} for CHARGE"))
+                    })?);
                 }
-            }
-            t if t.contains(' ') || t.contains('\t') => {
-                let split = if t.contains(' ') {
-                    t.split(' ').collect::<Vec<_>>()
-                } else {
-                    t.split('\t').collect::<Vec<_>>()
-                };
-                let mut peak = RawPeak {
-                    mz: MassOverCharge::zero(),
-                    intensity: OrderedFloat(0.0),
-                };
-                if split.len() < 2 {
-                    return Err(base_error.with_long_description("Not enough columns"));
+                "RT" => {
+                    current.rt = Some(Time::new::<s>(value.parse().map_err(|_| {
+                        base_error.with_long_description(format!("Not a number {key} for RT"))
+                    })?));
                 }
-                peak.mz = MassOverCharge::new::<mz>(split[0].parse().map_err(|_| {
-                    base_error.with_long_description(format!("Not a number {} for MZ", split[0]))
-                })?);
-                peak.intensity = split[1].parse().map_err(|_| {
-                    base_error
-                        .with_long_description(format!("Not a number {} for INTENSITY", split[1]))
-                })?;
-                if split.len() >= 3 {
-                    _ = parse_charge(split[2]).map_err(|()| {
+                "RTINSECONDS" => {
+                    current.rt = Some(Time::new::<s>(value.parse().map_err(|_| {
+                        base_error.with_long_description(format!("Not a number {key} for RT"))
+                    })?));
+                }
+                "TITLE" => parse_title(value, current),
+                "SEQUENCE" => current.sequence = Some(value.to_owned()),
+                "NUM_SCANS" => {
+                    current.num_scans = value.parse().map_err(|_| {
                         base_error
-                            .with_long_description(format!("Not a number {} for CHARGE", split[2]))
+                            .with_long_description(format!("Not a number {key} for NUM_SCANS"))
                     })?;
                 }
-                current.add_peak(peak);
+                _ => (),
             }
-            _ => {}
         }
+        t if t.contains(' ') || t.contains('\t') => {
+            let split = if t.contains(' ') {
+                t.split(' ').collect::<Vec<_>>()
+            } else {
+                t.split('\t').collect::<Vec<_>>()
+            };
+            let mut peak = RawPeak {
+                mz: MassOverCharge::zero(),
+                intensity: OrderedFloat(0.0),
+            };
+            if split.len() < 2 {
+                return Err(base_error.with_long_description("Not enough columns"));
+            }
+            peak.mz = MassOverCharge::new::<mz>(split[0].parse().map_err(|_| {
+                base_error.with_long_description(format!("Not a number {} for MZ", split[0]))
+            })?);
+            peak.intensity = split[1].parse().map_err(|_| {
+                base_error.with_long_description(format!("Not a number {} for INTENSITY", split[1]))
+            })?;
+            if split.len() >= 3 {
+                _ = parse_charge(split[2]).map_err(|()| {
+                    base_error
+                        .with_long_description(format!("Not a number {} for CHARGE", split[2]))
+                })?;
+            }
+            current.add_peak(peak);
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// Write the given spectra to a MGF file, the file is gzipped if the extension is `.gz`.
+///
+/// # Errors
+/// It returns an error when the file could not be created or written to.
+pub fn write<'a>(
+    path: impl AsRef<Path>,
+    spectra: impl IntoIterator<Item = &'a RawSpectrum>,
+) -> Result<(), CustomError> {
+    let path = path.as_ref();
+    let file = File::create(path).map_err(|err| {
+        CustomError::error(
+            "Could not create file",
+            format!("Additional info: {err}"),
+            Context::show(path.display()),
+        )
+    })?;
+    if check_extension(path, "gz") {
+        let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+        write_raw(&mut encoder, spectra)?;
+        encoder.try_finish().map_err(|err| write_error(&err))
+    } else {
+        write_raw(BufWriter::new(file), spectra)
+    }
+}
+
+/// Write the given spectra in MGF format to a raw writer.
+///
+/// All fields that are read by [`open_raw`] are written, so reading the written spectra results
+/// in the same spectra. If a spectrum has no title but does have a raw file name, a title is
+/// generated that contains all title components (raw file, scan number, index, and the native ID
+/// headers).
+///
+/// Writing a read file does not give the exact same bytes, the output is normalised:
+/// * Only the fields read by [`open_raw`] are written, in the order `TITLE`, `PEPMASS`, `CHARGE`,
+///   `RTINSECONDS`, `NUM_SCANS`, `SEQUENCE`, with `RT` written as `RTINSECONDS` and a zero
+///   `NUM_SCANS` left out
+/// * Charges are written with the sign after the number (`2+`, `2-`)
+/// * All numbers are written in the shortest form that reads back to the same value
+/// * Peaks are sorted on m/z and the peak charge column is left out
+/// * Empty lines are left out, except for a single empty line after each `END IONS`
+///
+/// # Errors
+/// It returns an error when the writer could not be written to.
+pub fn write_raw<'a, W: Write>(
+    writer: W,
+    spectra: impl IntoIterator<Item = &'a RawSpectrum>,
+) -> Result<(), CustomError> {
+    let mut writer = writer;
+    for spectrum in spectra {
+        write_spectrum(&mut writer, spectrum).map_err(|err| write_error(&err))?;
+    }
+    writer.flush().map_err(|err| write_error(&err))
+}
+
+/// Write a single spectrum
+/// # Errors
+/// If the writer could not be written to.
+fn write_spectrum(writer: &mut impl Write, spectrum: &RawSpectrum) -> std::io::Result<()> {
+    writeln!(writer, "BEGIN IONS")?;
+    let title = title(spectrum);
+    if !title.is_empty() {
+        writeln!(writer, "TITLE={title}")?;
+    }
+    if let Some(mass) = spectrum.mass {
+        if let Some(intensity) = spectrum.intensity {
+            writeln!(writer, "PEPMASS={} {intensity}", mass.get::<dalton>())?;
+        } else {
+            writeln!(writer, "PEPMASS={}", mass.get::<dalton>())?;
+        }
+    }
+    if let Some(charge) = spectrum.charge {
+        writeln!(
+            writer,
+            "CHARGE={}{}",
+            charge.value.unsigned_abs(),
+            if charge.value < 0 { '-' } else { '+' }
+        )?;
+    }
+    if let Some(rt) = spectrum.rt {
+        writeln!(writer, "RTINSECONDS={}", rt.get::<s>())?;
+    }
+    if spectrum.num_scans != 0 {
+        writeln!(writer, "NUM_SCANS={}", spectrum.num_scans)?;
+    }
+    if let Some(sequence) = &spectrum.sequence {
+        writeln!(writer, "SEQUENCE={sequence}")?;
+    }
+    for peak in spectrum.spectrum() {
+        writeln!(writer, "{} {}", peak.mz.get::<mz>(), peak.intensity)?;
+    }
+    writeln!(writer, "END IONS")?;
+    writeln!(writer)
+}
+
+/// Get the title for this spectrum, if the title is not set it is generated from the title
+/// components so that [`parse_title`] recovers these.
+fn title(spectrum: &RawSpectrum) -> String {
+    if !spectrum.title.is_empty() {
+        return spectrum.title.clone();
     }
-    Ok(output)
+    let (Some(raw_file), Some(scan)) = (&spectrum.raw_file, spectrum.raw_scan_number) else {
+        return String::new();
+    };
+    spectrum.raw_index.map_or_else(
+        || {
+            let headers = [
+                ("sample", spectrum.sample),
+                ("period", spectrum.period),
+                ("cycle", spectrum.cycle),
+                ("experiment", spectrum.experiment),
+                ("controllerType", spectrum.controller_type),
+                ("controllerNumber", spectrum.controller_number),
+            ]
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|v| format!("{name}={v}")))
+            .chain(std::iter::once(format!("scan={scan}")))
+            .collect::<Vec<_>>()
+            .join(" ");
+            format!("{raw_file}.{scan}.{scan}. File:\"{raw_file}\", NativeID:\"{headers}\"")
+        },
+        |index| format!("{raw_file}.ScanId;v=1;d1={scan}.{scan}._INDEX{index}"),
+    )
 }
 
+/// Create the error for a failed write
+fn write_error(err: &std::io::Error) -> CustomError {
+    CustomError::error(
+        "Could not write mgf file",
+        format!("Additional info: {err}"),
+        Context::none(),
+    )
+}
+
+/// Parse a charge, the sign can be given before or after the number ('2+', '2-', '-2')
 /// # Errors
-/// When the charge could not be properly parsed. For example if it has a negative charge.
+/// When the charge is not a valid (signed) number, for example if it is empty or does not fit.
 fn parse_charge(input: &str) -> Result<Charge, ()> {
-    if input.ends_with('+') {
+    if let Some(number) = input.strip_suffix('+') {
+        Ok(Charge::new::<e>(
+            number
+                .parse::<usize>()
+                .map_err(|_| ())?
+                .try_into()
+                .map_err(|_| ())?,
+        ))
+    } else if let Some(number) = input.strip_suffix('-') {
         Ok(Charge::new::<e>(
-            input.trim_end_matches('+').parse().map_err(|_| ())?,
+            -isize::try_from(number.parse::<usize>().map_err(|_| ())?).map_err(|_| ())?,
         ))
-    } else if input.ends_with('-') {
-        Err(())
     } else {
         Ok(Charge::new::<e>(input.parse().map_err(|_| ())?))
     }
 }
 
+/// The title formats that are recognised, compiled once because this is parsed for every spectrum
+static TITLE_FORMATS: OnceLock<(Regex, Regex)> = OnceLock::new();
+
 #[allow(clippy::missing_panics_doc)]
 fn parse_title(title: &str, spectrum: &mut RawSpectrum) {
-    // basic structure: <name>.<scan>.<scan>.<experiment?>? File:"<name>", NativeID:"(<header>) +"
-    let ms_convert_format: Regex =
-        Regex::new(r#"(.+)\.(\d+)\.\d+\.\d* File:".*", NativeID:"(.+)""#).unwrap();
-    // other structure: <name>.ScanId;v=<num>;d1=<scan>.<scan>.<experiment?>_INDEX<index>
-    let other_format: Regex =
-        Regex::new(r"(.+)\.ScanId;v=\d+;d1=(\d+)\.\d+\.\d*_INDEX(\d+)").unwrap();
+    let (ms_convert_format, other_format) = TITLE_FORMATS.get_or_init(|| {
+        (
+            // basic structure: <name>.<scan>.<scan>.<experiment?>? File:"<name>", NativeID:"(<header>) +"
+            Regex::new(r#"(.+)\.(\d+)\.\d+\.\d* File:".*", NativeID:"(.+)""#).unwrap(),
+            // other structure: <name>.ScanId;v=<num>;d1=<scan>.<scan>.<experiment?>_INDEX<index>
+            Regex::new(r"(.+)\.ScanId;v=\d+;d1=(\d+)\.\d+\.\d*_INDEX(\d+)").unwrap(),
+        )
+    });
 
     spectrum.title = title.to_string();
     if let Some(ms_convert) = ms_convert_format.captures(title) {
@@ -227,6 +606,164 @@ mod tests {
         assert!(spectra[0][0].mz < spectra[0][1].mz);
     }
 
+    #[test]
+    fn write_round_trip() {
+        let data = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/data/";
+        for file in [
+            "annotated_example.mgf",
+            "chimeric_example.mgf",
+            "example.mgf",
+            "glycan.mgf",
+            "inter_link.mgf",
+            "intra_link.mgf",
+            "real_example.mgf",
+            "stuff.mgf",
+        ] {
+            // The input files are not normalised, so compare against the expected output
+            let input = std::fs::read(data.clone() + file).unwrap();
+            let expected = std::fs::read(data.clone() + "written/" + file).unwrap();
+            let spectra = open_raw(input.as_slice()).unwrap();
+            let mut buffer = Vec::new();
+            write_raw(&mut buffer, &spectra).unwrap();
+            assert_eq!(
+                String::from_utf8(buffer.clone()).unwrap(),
+                String::from_utf8(expected.clone()).unwrap(),
+                "{file}"
+            );
+            let read = open_raw(buffer.as_slice()).unwrap();
+            assert_eq!(spectra, read, "{file}");
+            let mut rewritten = Vec::new();
+            write_raw(&mut rewritten, &read).unwrap();
+            assert_eq!(buffer, rewritten, "{file}");
+
+            // The expected output is normalised, so writing it should give the exact same bytes
+            let mut buffer = Vec::new();
+            write_raw(&mut buffer, &open_raw(expected.as_slice()).unwrap()).unwrap();
+            assert_eq!(buffer, expected, "written/{file}");
+        }
+    }
+
+    #[test]
+    fn write_generated_title() {
+        let mut spectrum = RawSpectrum::default();
+        spectrum.raw_file = Some("run 01".to_string());
+        spectrum.raw_scan_number = Some(42);
+        spectrum.cycle = Some(3);
+        spectrum.experiment = Some(2);
+        let mut buffer = Vec::new();
+        write_raw(&mut buffer, [&spectrum]).unwrap();
+        let read = open_raw(buffer.as_slice()).unwrap();
+        spectrum.title.clone_from(&read[0].title);
+        assert_eq!(read, vec![spectrum.clone()]);
+
+        spectrum.title = String::new();
+        spectrum.raw_index = Some(7);
+        spectrum.cycle = None;
+        spectrum.experiment = None;
+        let mut buffer = Vec::new();
+        write_raw(&mut buffer, [&spectrum]).unwrap();
+        let read = open_raw(buffer.as_slice()).unwrap();
+        spectrum.title.clone_from(&read[0].title);
+        assert_eq!(read, vec![spectrum]);
+    }
+
+    #[test]
+    fn streaming() {
+        let path = std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/data/real_example.mgf";
+        let spectra = open(&path).unwrap();
+        let streamed = MgfReader::open(&path)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(spectra, streamed);
+
+        let gzipped = crate::helper_functions::temporary_path("mgf.gz");
+        write(&gzipped, &spectra).unwrap();
+        let streamed = MgfReader::open(&gzipped)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        std::fs::remove_file(&gzipped).unwrap();
+        assert_eq!(spectra, streamed);
+    }
+
+    #[test]
+    fn negative_charge() {
+        let file = "BEGIN IONS\nTITLE=first\nCHARGE=2-\n100 1\nEND IONS\nBEGIN IONS\nTITLE=second\nCHARGE=-3\n100 1\nEND IONS\n";
+        let spectra = open_raw(file.as_bytes()).unwrap();
+        assert_eq!(spectra[0].charge, Some(Charge::new::<e>(-2)));
+        assert_eq!(spectra[1].charge, Some(Charge::new::<e>(-3)));
+        let mut buffer = Vec::new();
+        write_raw(&mut buffer, &spectra).unwrap();
+        assert!(String::from_utf8_lossy(&buffer).contains("CHARGE=2-"));
+        assert_eq!(open_raw(buffer.as_slice()).unwrap(), spectra);
+    }
+
+    #[test]
+    fn streaming_recovers_from_errors() {
+        let file = "BEGIN IONS\nTITLE=first\nCHARGE=x\n100 1\nEND IONS\nBEGIN IONS\nTITLE=second\n100 1\nEND IONS\n";
+        let spectra = MgfReader::new(file.as_bytes()).collect::<Vec<_>>();
+        assert_eq!(spectra.len(), 2);
+        assert!(spectra[0].is_err());
+        assert_eq!(spectra[1].as_ref().unwrap().title, "second");
+    }
+
+    #[test]
+    fn indexed() {
+        let mut spectra = Vec::new();
+        for scan in 1..=5 {
+            let mut spectrum = RawSpectrum::default();
+            spectrum.raw_file = Some("run".to_string());
+            spectrum.raw_scan_number = Some(scan * 10);
+            spectrum.add_peak(RawPeak {
+                mz: MassOverCharge::new::<mz>(100.0 + scan as f64),
+                intensity: OrderedFloat(1.0),
+            });
+            spectra.push(spectrum);
+        }
+        let mut buffer = Vec::new();
+        write_raw(&mut buffer, &spectra).unwrap();
+        let expected = open_raw(buffer.as_slice()).unwrap();
+
+        let mut indexed = IndexedMgfReader::new(std::io::Cursor::new(buffer)).unwrap();
+        assert_eq!(indexed.len(), 5);
+        assert_eq!(indexed.index()[2].raw_scan_number, Some(30));
+        assert_eq!(indexed.get(3).unwrap().unwrap(), expected[3]);
+        assert_eq!(
+            indexed.get_by_scan_number(20).unwrap().unwrap(),
+            expected[1]
+        );
+        assert_eq!(indexed.get(0).unwrap().unwrap(), expected[0]);
+        let title = expected[4].title.clone();
+        assert_eq!(indexed.get_by_title(&title).unwrap().unwrap(), expected[4]);
+        assert!(indexed.get(5).is_none());
+        assert!(indexed.get_by_scan_number(11).is_none());
+
+        let mut indexed = IndexedMgfReader::open(
+            std::env::var("CARGO_MANIFEST_DIR").unwrap() + "/data/real_example.mgf",
+        )
+        .unwrap();
+        assert_eq!(indexed.len(), 1);
+        assert_eq!(
+            indexed.index()[0].title,
+            r"C:\Users\peng0013\Downloads\2232_23614_AspN.raw"
+        );
+        assert!(indexed.get(0).unwrap().is_ok());
+    }
+
+    #[test]
+    fn indexed_error_line() {
+        let file = "BEGIN IONS\nTITLE=first\n100 1\nEND IONS\nBEGIN IONS\nTITLE=second\nCHARGE=x\n100 1\nEND IONS\n";
+        let mut indexed = IndexedMgfReader::new(std::io::Cursor::new(file.as_bytes())).unwrap();
+        assert_eq!(indexed.index()[1].line_index, 4);
+        let error = indexed.get(1).unwrap().unwrap_err();
+        assert_eq!(
+            error.context(),
+            &Context::full_line(6, "CHARGE=x"),
+            "{error}"
+        );
+    }
+
     #[test]
     fn test_titles() {
         assert_eq!(
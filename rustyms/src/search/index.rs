@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    identification::FastaData, isobaric_sets::can_be_placed, system::Mass, LinearPeptide,
+    SemiAmbiguous, SequencePosition, Tolerance,
+};
+
+use super::SearchParameters;
+
+/// A single peptide in a [`PeptideIndex`]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct IndexedPeptide {
+    /// The peptide, with all fixed and variable modifications placed
+    pub peptide: LinearPeptide<SemiAmbiguous>,
+    /// The mass of the peptide (in the mass mode of the search parameters)
+    pub mass: Mass,
+    /// The ids of all proteins that contain this peptide
+    pub proteins: Vec<String>,
+    /// If this peptide is only found in decoy proteins
+    pub decoy: bool,
+}
+
+/// A digested protein database, sorted on mass to quickly find all candidate peptides for a
+/// precursor
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct PeptideIndex {
+    peptides: Vec<IndexedPeptide>,
+}
+
+impl PeptideIndex {
+    /// Digest all proteins with the protease from the parameters and place all fixed and
+    /// variable modifications on the resulting peptides. If decoys are requested the decoy
+    /// proteins are digested as well, named with the decoy prefix. A peptide that is
+    /// found in multiple proteins is only indexed once, and is only a decoy if it is not found in
+    /// any target protein. If a peptide has multiple possible formulas (e.g. because of ambiguous
+    /// amino acids) it is indexed once for every distinct formula.
+    pub fn new(database: &[FastaData], parameters: &SearchParameters) -> Self {
+        let decoys = parameters.decoys.iter().flat_map(|strategy| {
+            database.iter().map(|protein| {
+                let decoy = protein.decoy(strategy, &parameters.decoy_prefix);
+                (decoy.id, decoy.peptide, true)
+            })
+        });
+        let mut digested: HashMap<LinearPeptide<SemiAmbiguous>, (Vec<String>, bool)> =
+            HashMap::new();
+        for (id, protein, decoy) in database
+            .iter()
+            .map(|protein| (protein.id.clone(), protein.peptide.clone(), false))
+            .chain(decoys)
+        {
+            for peptide in protein.digest_with(&parameters.digestion) {
+                let entry = digested
+                    .entry(peptide)
+                    .or_insert_with(|| (Vec::new(), true));
+                if !entry.0.contains(&id) {
+                    entry.0.push(id.clone());
+                }
+                entry.1 &= decoy;
+            }
+        }
+
+        let mut peptides = digested
+            .into_iter()
+            .flat_map(|(peptide, (proteins, decoy))| {
+                modified_peptides(peptide, parameters)
+                    .into_iter()
+                    .flat_map(|peptide| {
+                        peptide
+                            .formulas()
+                            .iter()
+                            .unique()
+                            .map(|f| f.mass(parameters.mass_mode))
+                            .collect_vec()
+                            .into_iter()
+                            .map(move |mass| (peptide.clone(), mass))
+                    })
+                    .map(|(peptide, mass)| IndexedPeptide {
+                        peptide,
+                        mass,
+                        proteins: proteins.clone(),
+                        decoy,
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        peptides.sort_unstable_by(|a, b| {
+            a.mass
+                .value
+                .total_cmp(&b.mass.value)
+                .then_with(|| a.peptide.cmp(&b.peptide))
+        });
+        Self { peptides }
+    }
+
+    /// Get all peptides with a mass within the tolerance of the given mass
+    pub fn candidates(&self, mass: Mass, tolerance: Tolerance<Mass>) -> &[IndexedPeptide] {
+        let (low, high) = tolerance.bounds(mass);
+        let start = self.peptides.partition_point(|p| p.mass < low);
+        let end = self.peptides.partition_point(|p| p.mass <= high);
+        &self.peptides[start..end.max(start)]
+    }
+
+    /// All peptides in this index, sorted on mass
+    pub fn peptides(&self) -> &[IndexedPeptide] {
+        &self.peptides
+    }
+
+    /// The number of peptides in this index
+    pub fn len(&self) -> usize {
+        self.peptides.len()
+    }
+
+    /// Check if this index contains no peptides
+    pub fn is_empty(&self) -> bool {
+        self.peptides.is_empty()
+    }
+}
+
+/// Place all fixed modifications and all combinations of variable modifications on a peptide.
+///
+/// The placement follows the same rules as [`crate::building_blocks`], with at most the maximal
+/// number of variable modifications and at most a single modification per position, so variable
+/// modifications are never placed on positions that already carry a fixed modification.
+fn modified_peptides(
+    mut peptide: LinearPeptide<SemiAmbiguous>,
+    parameters: &SearchParameters,
+) -> Vec<LinearPeptide<SemiAmbiguous>> {
+    if peptide.is_empty() {
+        return vec![peptide];
+    }
+    let positions = std::iter::once(SequencePosition::NTerm)
+        .chain((0..peptide.len()).map(SequencePosition::Index))
+        .chain(std::iter::once(SequencePosition::CTerm))
+        .collect_vec();
+    let element = |peptide: &LinearPeptide<SemiAmbiguous>, position| match position {
+        SequencePosition::NTerm => 0,
+        SequencePosition::Index(index) => index,
+        SequencePosition::CTerm => peptide.len() - 1,
+    };
+    let free = |peptide: &LinearPeptide<SemiAmbiguous>, position| match position {
+        SequencePosition::NTerm => peptide.get_n_term().is_none(),
+        SequencePosition::CTerm => peptide.get_c_term().is_none(),
+        SequencePosition::Index(index) => peptide.sequence()[index].modifications.is_empty(),
+    };
+
+    for position in &positions {
+        for (modification, rule) in &parameters.fixed_modifications {
+            if free(&peptide, *position)
+                && can_be_placed(
+                    modification,
+                    rule.as_ref(),
+                    &peptide.sequence()[element(&peptide, *position)],
+                    *position,
+                )
+            {
+                peptide.add_simple_modification(*position, modification.clone());
+            }
+        }
+    }
+
+    // All positions that can take a variable modification with all modifications for each position
+    let sites = positions
+        .iter()
+        .filter(|position| free(&peptide, **position))
+        .map(|position| {
+            (
+                *position,
+                parameters
+                    .variable_modifications
+                    .iter()
+                    .filter(|(modification, rule)| {
+                        can_be_placed(
+                            modification,
+                            rule.as_ref(),
+                            &peptide.sequence()[element(&peptide, *position)],
+                            *position,
+                        )
+                    })
+                    .map(|(modification, _)| modification)
+                    .collect_vec(),
+            )
+        })
+        .filter(|(_, modifications)| !modifications.is_empty())
+        .collect_vec();
+
+    let mut result = vec![peptide.clone()];
+    for number in 1..=parameters.max_variable_modifications.min(sites.len()) {
+        for combination in sites.iter().combinations(number) {
+            for modifications in combination
+                .iter()
+                .map(|(_, modifications)| modifications.iter())
+                .multi_cartesian_product()
+            {
+                let mut modified = peptide.clone();
+                for ((position, _), modification) in combination.iter().zip(modifications) {
+                    modified.add_simple_modification(*position, (*modification).clone());
+                }
+                result.push(modified);
+            }
+        }
+    }
+    result
+}
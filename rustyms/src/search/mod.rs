@@ -0,0 +1,423 @@
+//! A simple peptide database search engine, to identify spectra by matching them against all
+//! peptides from a digested protein database
+
+mod index;
+
+use std::{ops::RangeInclusive, path::PathBuf};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+pub use index::*;
+
+use crate::{
+    identification::{IdentifiedPeptide, MetaData, TargetDecoyMatch},
+    modification::SimpleModification,
+    placement_rule::PlacementRule,
+    spectrum::MatchedIons,
+    system::{e, isize, Mass, Time},
+    AnnotatableSpectrum, DecoyStrategy, Digestion, LinearPeptide, MassMode, Model, Protease,
+    RawSpectrum, SemiAmbiguous, Tolerance,
+};
+
+/// The parameters for a database search
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SearchParameters {
+    /// The digestion of the proteins into peptides
+    pub digestion: Digestion,
+    /// The fixed modifications, placed on all locations allowed by the placement rule, or if no
+    /// rule is given on all locations allowed by the modification itself (see
+    /// [`crate::building_blocks`])
+    pub fixed_modifications: Vec<(SimpleModification, Option<PlacementRule>)>,
+    /// The variable modifications, placed with the same rules as the fixed modifications
+    pub variable_modifications: Vec<(SimpleModification, Option<PlacementRule>)>,
+    /// The maximal number of variable modifications on a single peptide
+    pub max_variable_modifications: usize,
+    /// The tolerance for the precursor mass
+    pub precursor_tolerance: Tolerance<Mass>,
+    /// The precursor charges to try if a spectrum has no charge
+    pub precursor_charges: RangeInclusive<isize>,
+    /// The fragmentation model, also defines the fragment tolerance
+    pub model: Model,
+    /// The mass mode used for precursors and fragments
+    pub mass_mode: MassMode,
+    /// The strategy to generate decoy proteins, if decoys are needed
+    pub decoys: Option<DecoyStrategy>,
+    /// The prefix for the ids of decoy proteins
+    pub decoy_prefix: String,
+    /// The maximal number of matches reported per spectrum
+    pub report: usize,
+}
+
+impl Default for SearchParameters {
+    /// Trypsin without modifications, 10 ppm precursor tolerance, the CID/HCD model, and with
+    /// reversed decoys.
+    fn default() -> Self {
+        Self {
+            digestion: Digestion::new(Protease::trypsin(), 2).length(7..=40),
+            fixed_modifications: Vec::new(),
+            variable_modifications: Vec::new(),
+            max_variable_modifications: 3,
+            precursor_tolerance: Tolerance::new_ppm(10.0),
+            precursor_charges: 2..=4,
+            model: Model::cid_hcd(),
+            mass_mode: MassMode::Monoisotopic,
+            decoys: Some(DecoyStrategy::Reverse),
+            decoy_prefix: "rev_".to_string(),
+            report: 1,
+        }
+    }
+}
+
+impl PeptideIndex {
+    /// Search a single spectrum against this index. The precursor is taken from the spectrum
+    /// as m/z (as the PEPMASS in MGF files) with the charge of the spectrum, or with all precursor
+    /// charges from the parameters if the charge is unknown. Every candidate within the precursor
+    /// tolerance is annotated and scored with the hyperscore (see
+    /// [`crate::AnnotatedSpectrum::hyperscore`]). The best matches are returned ranked from best
+    /// to worst, if the spectrum has no precursor nothing is returned.
+    pub fn search(&self, spectrum: &RawSpectrum, parameters: &SearchParameters) -> Vec<SearchData> {
+        let Some(precursor) = spectrum.mass else {
+            return Vec::new();
+        };
+        let proton = molecular_formula!(H 1 Electron -1).mass(parameters.mass_mode);
+        let charges = spectrum.charge.map_or_else(
+            || {
+                parameters
+                    .precursor_charges
+                    .clone()
+                    .filter(|c| *c != 0)
+                    .collect_vec()
+            },
+            |c| vec![c.value],
+        );
+
+        let mut hits = Vec::new();
+        for charge in charges {
+            let mass = precursor * charge.unsigned_abs() as f64 - proton * charge as f64;
+            for candidate in self.candidates(mass, parameters.precursor_tolerance) {
+                let fragments = candidate.peptide.generate_theoretical_fragments(
+                    isize::Charge::new::<e>(charge),
+                    &parameters.model,
+                );
+                let annotated = spectrum.annotate(
+                    candidate.peptide.clone().into(),
+                    &fragments,
+                    &parameters.model,
+                    parameters.mass_mode,
+                );
+                hits.push((
+                    annotated.hyperscore(),
+                    annotated.matched_ions(parameters.mass_mode),
+                    charge,
+                    mass,
+                    candidate,
+                ));
+            }
+        }
+        hits.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let scores = hits.iter().map(|hit| hit.0).collect_vec();
+        hits.into_iter()
+            .take(parameters.report)
+            .enumerate()
+            .map(
+                |(rank, (hyperscore, matched_ions, charge, mass, candidate))| SearchData {
+                    peptide: candidate.peptide.clone(),
+                    proteins: candidate.proteins.clone(),
+                    decoy: candidate.decoy,
+                    rank: rank + 1,
+                    hyperscore,
+                    delta_score: hyperscore - scores.get(rank + 1).copied().unwrap_or_default(),
+                    matched_ions,
+                    z: isize::Charge::new::<e>(charge),
+                    mass,
+                    theoretical_mass: candidate.mass,
+                    title: spectrum.title.clone(),
+                    rt: spectrum.rt,
+                    raw_file: spectrum.raw_file.as_ref().map(PathBuf::from),
+                    scan: spectrum.raw_scan_number,
+                },
+            )
+            .collect()
+    }
+}
+
+/// A single peptide spectrum match from a database search
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SearchData {
+    /// The matched peptide
+    pub peptide: LinearPeptide<SemiAmbiguous>,
+    /// The ids of all proteins that contain this peptide
+    pub proteins: Vec<String>,
+    /// If this peptide is only found in decoy proteins
+    pub decoy: bool,
+    /// The rank of this match for its spectrum, starting at 1
+    pub rank: usize,
+    /// The hyperscore of this match
+    pub hyperscore: f64,
+    /// The difference in hyperscore with the next ranked match
+    pub delta_score: f64,
+    /// The matched ions and their mass errors
+    pub matched_ions: MatchedIons,
+    /// The precursor charge, negative for negative ion mode spectra
+    pub z: isize::Charge,
+    /// The experimental neutral mass of the precursor
+    pub mass: Mass,
+    /// The theoretical neutral mass of the peptide
+    pub theoretical_mass: Mass,
+    /// The title of the spectrum
+    pub title: String,
+    /// The retention time of the spectrum
+    pub rt: Option<Time>,
+    /// The raw file of the spectrum
+    pub raw_file: Option<PathBuf>,
+    /// The scan number of the spectrum
+    pub scan: Option<usize>,
+}
+
+impl From<SearchData> for IdentifiedPeptide {
+    /// The score is the hyperscore mapped monotonically onto `0.0..1.0` as
+    /// `1 - exp(-hyperscore / 10)`, so a hyperscore of 10 gives 0.63 and a hyperscore of 30 gives
+    /// 0.95. The hyperscore itself is retained in the metadata.
+    fn from(value: SearchData) -> Self {
+        Self {
+            score: Some(1.0 - (-value.hyperscore / 10.0).exp()),
+            metadata: MetaData::Search(value),
+        }
+    }
+}
+
+impl TargetDecoyMatch for SearchData {
+    fn score(&self) -> Option<f64> {
+        Some(self.hyperscore)
+    }
+
+    fn is_decoy(&self) -> Option<bool> {
+        Some(self.decoy)
+    }
+
+    fn spectrum(&self) -> Option<String> {
+        Some(format!(
+            "{}:{}:{}",
+            self.raw_file
+                .as_ref()
+                .map(|file| file.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            self.scan.map(|scan| scan.to_string()).unwrap_or_default(),
+            self.title
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        identification::FastaData,
+        model::PrimaryIonSeries,
+        modification::Ontology,
+        placement_rule::Position,
+        spectrum::{PeakSpectrum, RawPeak},
+        system::dalton,
+        AminoAcid, CompoundPeptidoform,
+    };
+
+    use super::*;
+
+    fn protein(id: &str, sequence: &str) -> FastaData {
+        FastaData {
+            id: id.to_string(),
+            full_header: id.to_string(),
+            peptide: CompoundPeptidoform::pro_forma(sequence, None)
+                .unwrap()
+                .singular()
+                .unwrap()
+                .singular()
+                .unwrap()
+                .into_semi_ambiguous()
+                .unwrap(),
+        }
+    }
+
+    fn spectrum(peptide: &str, charge: isize) -> RawSpectrum {
+        let peptide = CompoundPeptidoform::pro_forma(peptide, None).unwrap();
+        let model = Model::none()
+            .b(PrimaryIonSeries::default())
+            .y(PrimaryIonSeries::default());
+        let mut spectrum = RawSpectrum::default();
+        for fragment in peptide.generate_theoretical_fragments(isize::Charge::new::<e>(1), &model) {
+            spectrum.add_peak(RawPeak {
+                mz: fragment.mz(MassMode::Monoisotopic),
+                intensity: OrderedFloat(100.0),
+            });
+        }
+        let proton = molecular_formula!(H 1 Electron -1).monoisotopic_mass();
+        spectrum.mass = Some(Mass::new::<dalton>(
+            (peptide.formulas()[0].monoisotopic_mass() + proton * charge as f64).value
+                / charge.unsigned_abs() as f64,
+        ));
+        spectrum.charge = Some(isize::Charge::new::<e>(charge));
+        spectrum
+    }
+
+    #[test]
+    fn digest_and_modify() {
+        let parameters = SearchParameters {
+            digestion: Digestion::new(Protease::trypsin_p(), 0).length(4..=20),
+            fixed_modifications: vec![(
+                Ontology::Unimod.find_id(4, None).unwrap(),
+                Some(PlacementRule::AminoAcid(
+                    vec![AminoAcid::Cysteine],
+                    Position::Anywhere,
+                )),
+            )],
+            variable_modifications: vec![(
+                Ontology::Unimod.find_id(35, None).unwrap(),
+                Some(PlacementRule::AminoAcid(
+                    vec![AminoAcid::Methionine],
+                    Position::Anywhere,
+                )),
+            )],
+            ..SearchParameters::default()
+        };
+        let index = PeptideIndex::new(
+            &[
+                protein("A", "MAGCKPEPTIDEKAAK"),
+                protein("B", "PEPTIDEKLLR"),
+            ],
+            &parameters,
+        );
+        let peptides = index
+            .peptides()
+            .iter()
+            .filter(|p| !p.decoy)
+            .map(|p| p.peptide.to_string())
+            .collect_vec();
+        assert!(peptides.contains(&"MAGC[U:Carbamidomethyl]K".to_string()));
+        assert!(peptides.contains(&"M[U:Oxidation]AGC[U:Carbamidomethyl]K".to_string()));
+        assert!(!peptides.contains(&"MAGCK".to_string()));
+        assert!(!peptides.contains(&"AAK".to_string()));
+        let shared = index
+            .peptides()
+            .iter()
+            .find(|p| p.peptide.to_string() == "PEPTIDEK")
+            .unwrap();
+        assert_eq!(shared.proteins, vec!["A".to_string(), "B".to_string()]);
+        assert!(index.peptides().iter().any(|p| p.decoy));
+        assert!(index.peptides().windows(2).all(|w| w[0].mass <= w[1].mass));
+    }
+
+    #[test]
+    fn variable_modification_placement() {
+        let parameters = SearchParameters {
+            digestion: Digestion::new(Protease::trypsin_p(), 0).length(2..=20),
+            fixed_modifications: vec![(
+                Ontology::Unimod.find_id(4, None).unwrap(),
+                Some(PlacementRule::AminoAcid(
+                    vec![AminoAcid::Cysteine],
+                    Position::Anywhere,
+                )),
+            )],
+            variable_modifications: vec![
+                (
+                    Ontology::Unimod.find_id(35, None).unwrap(),
+                    Some(PlacementRule::AminoAcid(
+                        vec![AminoAcid::Methionine],
+                        Position::Anywhere,
+                    )),
+                ),
+                (
+                    SimpleModification::Mass(Mass::new::<dalton>(5.0).into()),
+                    Some(PlacementRule::AminoAcid(
+                        vec![AminoAcid::Cysteine, AminoAcid::Methionine],
+                        Position::Anywhere,
+                    )),
+                ),
+            ],
+            decoys: None,
+            ..SearchParameters::default()
+        };
+        let index = PeptideIndex::new(&[protein("A", "AGCKMMK")], &parameters);
+        let with_sequence = |sequence: &str| {
+            index
+                .peptides()
+                .iter()
+                .filter(|p| {
+                    p.peptide
+                        .sequence()
+                        .iter()
+                        .map(|s| s.aminoacid.char())
+                        .collect::<String>()
+                        == sequence
+                })
+                .map(|p| p.peptide.to_string())
+                .collect_vec()
+        };
+        // No variable modification on top of the fixed modification
+        assert_eq!(with_sequence("AGCK"), vec!["AGC[U:Carbamidomethyl]K"]);
+        // Both modifications on both positions, with at most one modification per position
+        assert_eq!(with_sequence("MMK").len(), 9);
+    }
+
+    #[test]
+    fn search() {
+        let parameters = SearchParameters {
+            digestion: Digestion::new(Protease::trypsin(), 2).length(4..=20),
+            model: Model::none()
+                .b(PrimaryIonSeries::default())
+                .y(PrimaryIonSeries::default()),
+            report: 5,
+            ..SearchParameters::default()
+        };
+        let index = PeptideIndex::new(
+            &[
+                protein("A", "MAGCKPEPTIDEKAAK"),
+                protein("B", "PEPTIDEKLLR"),
+                protein("C", "EDITPEPKWWWR"),
+            ],
+            &parameters,
+        );
+        let results = index.search(&spectrum("PEPTIDEK", 2), &parameters);
+        assert!(!results.is_empty());
+        assert_eq!(results[0].peptide.to_string(), "PEPTIDEK");
+        assert_eq!(results[0].rank, 1);
+        assert!(!results[0].decoy);
+        assert_eq!(results[0].z.value, 2);
+        assert!(results[0].delta_score >= 0.0);
+        assert!(results
+            .windows(2)
+            .all(|w| w[0].hyperscore >= w[1].hyperscore));
+
+        let identified: IdentifiedPeptide = results[0].clone().into();
+        assert_eq!(
+            identified.peptide().map(ToString::to_string),
+            Some("PEPTIDEK".to_string())
+        );
+        assert_eq!(identified.charge().map(|c| c.value), Some(2));
+        assert!(
+            (identified.score.unwrap() - (1.0 - (-results[0].hyperscore / 10.0).exp())).abs()
+                < 1e-12
+        );
+
+        let mut negative = results[0].clone();
+        negative.z = isize::Charge::new::<e>(-2);
+        let identified: IdentifiedPeptide = negative.into();
+        assert_eq!(identified.charge().map(|c| c.value), Some(2));
+        assert_eq!(identified.signed_charge().map(|c| c.value), Some(-2));
+
+        let mut other_file = results[0].clone();
+        other_file.raw_file = Some(PathBuf::from("other.raw"));
+        let mut other_scan = results[0].clone();
+        other_scan.scan = Some(42);
+        assert_ne!(results[0].spectrum(), other_file.spectrum());
+        assert_ne!(results[0].spectrum(), other_scan.spectrum());
+        assert_ne!(other_file.spectrum(), other_scan.spectrum());
+
+        let mut no_precursor = spectrum("PEPTIDEK", 2);
+        no_precursor.mass = None;
+        assert!(index.search(&no_precursor, &parameters).is_empty());
+    }
+}
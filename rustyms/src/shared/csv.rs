@@ -179,11 +179,7 @@ pub fn parse_csv_raw<T: std::io::Read>(
     let column_headers = if let Some(header) = provided_header {
         header
     } else {
-        let (_, column_headers) = lines.next().ok_or_else(|| {
-            CustomError::error("Could parse csv file", "The file is empty", Context::None)
-        })?;
-        let header_line = column_headers
-            .map_err(|err| CustomError::error("Could not read header line", err, Context::None))?;
+        let header_line = header_line(&mut lines)?;
         csv_separate(&header_line, separator)?
             .into_iter()
             .map(|r| header_line[r].to_lowercase())
@@ -197,6 +193,33 @@ pub fn parse_csv_raw<T: std::io::Read>(
     })
 }
 
+/// Get the header line, this is the first line that is not blank, with any leading byte order mark
+/// removed.
+/// # Errors
+/// If there is no such line or if a line could not be read.
+pub fn header_line(
+    lines: &mut impl Iterator<Item = (usize, std::io::Result<String>)>,
+) -> Result<String, CustomError> {
+    for (line_index, line) in lines {
+        let line = line.map_err(|err| {
+            CustomError::error(
+                "Could not read header line",
+                err,
+                Context::full_line(line_index, "(failed)"),
+            )
+        })?;
+        let line = line.trim_start_matches('\u{feff}');
+        if !line.trim().is_empty() {
+            return Ok(line.to_string());
+        }
+    }
+    Err(CustomError::error(
+        "Could parse csv file",
+        "The file is empty",
+        Context::None,
+    ))
+}
+
 /// An iterator returning CSV lines
 pub struct CsvLineIter<T: std::io::Read> {
     lines: std::iter::Enumerate<std::io::Lines<BufReader<T>>>,
@@ -204,6 +227,14 @@ pub struct CsvLineIter<T: std::io::Read> {
     separator: u8,
 }
 
+impl<T: std::io::Read> CsvLineIter<T> {
+    /// Get the (lowercase) column headers
+    #[allow(dead_code)]
+    pub fn header(&self) -> &[String] {
+        &self.header
+    }
+}
+
 impl<T: std::io::Read> Iterator for CsvLineIter<T> {
     type Item = Result<CsvLine, CustomError>;
     fn next(&mut self) -> Option<Self::Item> {
@@ -7,13 +7,57 @@ use std::ops::Range;
 pub struct GlycanStructure {
     pub(super) sugar: MonoSaccharide,
     pub(super) branches: Vec<GlycanStructure>,
+    /// The linkage of this monosaccharide to its parent, or for the root the anomeric state and
+    /// position of the reducing end
+    pub(super) linkage: GlycanLinkage,
+}
+
+/// The linkage of a monosaccharide to its parent, all parts are optional as these are often not
+/// known
+#[allow(dead_code)]
+#[derive(
+    Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default, Serialize, Deserialize,
+)]
+pub struct GlycanLinkage {
+    /// The anomeric state of the monosaccharide
+    pub anomer: Option<AnomericState>,
+    /// The position on this monosaccharide (the anomeric carbon, 1 for most monosaccharides, 2
+    /// for sialic acids)
+    pub position: Option<u8>,
+    /// The position on the parent monosaccharide
+    pub parent_position: Option<u8>,
+}
+
+/// The anomeric state of a monosaccharide
+#[allow(dead_code)]
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Serialize, Deserialize)]
+pub enum AnomericState {
+    /// α
+    Alpha,
+    /// β
+    Beta,
 }
 
 impl GlycanStructure {
     /// Create a new glycan structure
     #[allow(dead_code)]
     pub const fn new(sugar: MonoSaccharide, branches: Vec<Self>) -> Self {
-        Self { sugar, branches }
+        Self {
+            sugar,
+            branches,
+            linkage: GlycanLinkage {
+                anomer: None,
+                position: None,
+                parent_position: None,
+            },
+        }
+    }
+
+    /// Set the linkage of this monosaccharide to its parent
+    #[must_use]
+    #[allow(dead_code)]
+    pub fn with_linkage(self, linkage: GlycanLinkage) -> Self {
+        Self { linkage, ..self }
     }
 
     /// Parse a short IUPAC glycan structure
@@ -27,10 +71,7 @@ impl GlycanStructure {
         line_index: usize,
     ) -> Result<Self, CustomError> {
         let mut offset = range.start;
-        let mut branch = Self {
-            sugar: MonoSaccharide::new(BaseSugar::Decose, &[]),
-            branches: Vec::new(),
-        }; // Starting sugar, will be removed
+        let mut branch = Self::new(MonoSaccharide::new(BaseSugar::Decose, &[]), Vec::new()); // Starting sugar, will be removed
         let mut last_branch: &mut Self = &mut branch;
         let bytes = line.as_bytes();
 
@@ -54,13 +95,12 @@ impl GlycanStructure {
             let (sugar, new_offset) = MonoSaccharide::from_short_iupac(line, offset, line_index)?;
             offset = new_offset;
 
-            last_branch.branches.push(Self {
-                sugar: sugar.clone(),
-                branches: Vec::new(),
-            });
+            last_branch.branches.push(Self::new(sugar, Vec::new()));
             last_branch = last_branch.branches.last_mut().unwrap();
 
-            offset = Self::ignore_linking_information(bytes, offset, &range);
+            let (linkage, new_offset) = Self::parse_linkage(bytes, offset, &range);
+            last_branch.linkage = linkage;
+            offset = new_offset;
         }
         branch
             .branches
@@ -78,19 +118,45 @@ impl GlycanStructure {
             .map(Self::reroot)
     }
 
+    /// Parse the linkage information following a monosaccharide, eg `(b1-4)`. Any part that
+    /// cannot be understood is left unknown.
     /// # Panics
     /// It panics if a brace was not closed that was not close to the end of the input (more then 10 bytes from the end).
-    fn ignore_linking_information(bytes: &[u8], mut offset: usize, range: &Range<usize>) -> usize {
-        if offset < bytes.len() && bytes[offset] == b'(' {
-            if let Some(end) = next_char(bytes, offset + 1, b')') {
-                offset = end + 1; // just ignore all linking stuff I do not care
-            } else {
-                // This only happens for incomplete branches where the last parts of the branch are unknown.
-                assert!(range.end - offset < 10); // make sure it is the last part
-                offset = range.end; // assume it is the last not closed brace
-            }
+    fn parse_linkage(bytes: &[u8], offset: usize, range: &Range<usize>) -> (GlycanLinkage, usize) {
+        if offset >= bytes.len() || bytes[offset] != b'(' {
+            return (GlycanLinkage::default(), offset);
         }
-        offset
+        let end = next_char(bytes, offset + 1, b')').unwrap_or_else(|| {
+            // This only happens for incomplete branches where the last parts of the branch are unknown.
+            assert!(range.end - offset < 10); // make sure it is the last part
+            range.end // assume it is the last not closed brace
+        });
+        let inner = &bytes[offset + 1..end.min(range.end)];
+        let number = |text: &[u8]| {
+            std::str::from_utf8(text)
+                .ok()
+                .and_then(|text| text.parse::<u8>().ok())
+        };
+        let (anomer, rest) = match inner.first() {
+            Some(b'a') => (Some(AnomericState::Alpha), &inner[1..]),
+            Some(b'b') => (Some(AnomericState::Beta), &inner[1..]),
+            Some(b'?') => (None, &inner[1..]),
+            _ => (None, inner),
+        };
+        let (position, parent_position) = rest
+            .iter()
+            .position(|c| *c == b'-')
+            .map_or_else(|| (number(rest), None), |dash| {
+                (number(&rest[..dash]), number(&rest[dash + 1..]))
+            });
+        (
+            GlycanLinkage {
+                anomer,
+                position,
+                parent_position,
+            },
+            (end + 1).min(range.end),
+        )
     }
 
     /// Inverts the tree, gets a tree where the an outer branch is chosen as root.
@@ -103,10 +169,7 @@ impl GlycanStructure {
 
         while let Some(mut old) = old_structure.take() {
             // Define new sugar
-            let mut new_sugar = Self {
-                sugar: old.sugar,
-                branches: Vec::new(),
-            };
+            let mut new_sugar = Self::new(old.sugar, Vec::new()).with_linkage(old.linkage);
             // If there is already some info in the new structure add that as a branch
             if let Some(new_structure) = new_structure {
                 for branch in new_structure {
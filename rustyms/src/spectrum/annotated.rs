@@ -9,7 +9,7 @@ use crate::{
     fragment::Fragment,
     system::{
         f64::{Mass, MassOverCharge, Time},
-        usize::Charge,
+        isize::Charge,
     },
     CompoundPeptidoform,
 };
@@ -27,7 +27,8 @@ pub struct AnnotatedSpectrum {
     pub rt: Option<Time>,
     /// The found precursor charge
     pub charge: Option<Charge>,
-    /// The found precursor mass
+    /// The found precursor m/z (as the PEPMASS in MGF files), stored as a mass because the
+    /// charge is not always known
     pub mass: Option<Mass>,
     /// The peptide with which this spectrum was annotated
     pub peptide: CompoundPeptidoform,
@@ -115,14 +116,19 @@ impl PeakSpectrum for AnnotatedSpectrum {
 /// An annotated peak
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AnnotatedPeak {
-    /// The experimental mz
+    /// The experimental mz, for deconvoluted spectra this is the mz of the singly protonated
+    /// species
     pub experimental_mz: MassOverCharge,
     /// The experimental intensity
     pub intensity: OrderedFloat<f64>,
+    /// The charge of this peak, if known (as for deconvoluted spectra)
+    pub charge: Option<Charge>,
     /// The annotation, if present
-    pub annotation: Vec<Fragment>, // Could become Vec<(Fragment, Vec<MatchedIsotopeDistribution>)> when isotope matching is finally in place
-    /// Any annotation as isotope from a given fragment
-    pub isotope_annotation: Vec<(usize, usize)>,
+    pub annotation: Vec<Fragment>,
+    /// Any annotation as isotope peak of a given fragment, with the isotope offset in whole
+    /// daltons from the monoisotopic peak (so 1 for M+1). Only filled when isotopes are annotated,
+    /// see `IsotopeModel` (crate feature 'isotopes').
+    pub isotope_annotation: Vec<(Fragment, usize)>,
 }
 
 impl AnnotatedPeak {
@@ -131,6 +137,7 @@ impl AnnotatedPeak {
         Self {
             experimental_mz: peak.mz,
             intensity: peak.intensity,
+            charge: None,
             annotation: vec![annotation],
             isotope_annotation: Vec::new(),
         }
@@ -141,6 +148,7 @@ impl AnnotatedPeak {
         Self {
             experimental_mz: peak.mz,
             intensity: peak.intensity,
+            charge: None,
             annotation: Vec::new(),
             isotope_annotation: Vec::new(),
         }
@@ -171,6 +179,7 @@ impl PartialEq for AnnotatedPeak {
             == Ordering::Equal
             && self.intensity.total_cmp(&other.intensity) == Ordering::Equal
             && self.annotation == other.annotation
+            && self.isotope_annotation == other.isotope_annotation
     }
 }
 
@@ -0,0 +1,162 @@
+//! Matching fragments to the peaks of deconvoluted spectra, which are defined by their neutral
+//! mass and charge instead of their mz
+
+use crate::{Fragment, MassMode};
+
+/// A single peak in a deconvoluted spectrum
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(not(feature = "mzdata"), allow(dead_code))] // Only used for mzdata spectra
+pub(super) struct DeconvolutedPeak {
+    /// The index of this peak in the spectrum
+    pub index: usize,
+    /// The neutral mass in dalton
+    pub neutral_mass: f64,
+    /// The charge, 0 if the charge is not known
+    pub charge: isize,
+}
+
+/// Find the peak that matches the given fragment best.
+///
+/// Only peaks with the same charge as the fragment, or with an unknown charge, are considered,
+/// and of these the peak closest to the neutral mass of the fragment (see
+/// [`Fragment::neutral_mass`]) is returned. The tolerance is not checked so the peaks should
+/// already be within the tolerance.
+#[cfg_attr(not(feature = "mzdata"), allow(dead_code))] // Only used for mzdata spectra
+pub(super) fn closest_peak(
+    peaks: impl IntoIterator<Item = DeconvolutedPeak>,
+    fragment: &Fragment,
+    mode: MassMode,
+) -> Option<usize> {
+    let neutral_mass = fragment.neutral_mass(mode).value;
+    peaks
+        .into_iter()
+        .filter(|p| p.charge == 0 || p.charge == fragment.charge.value)
+        .min_by(|a, b| {
+            (a.neutral_mass - neutral_mass)
+                .abs()
+                .total_cmp(&(b.neutral_mass - neutral_mass).abs())
+        })
+        .map(|p| p.index)
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        fragment::FragmentType,
+        molecular_charge::MolecularCharge,
+        spectrum::{AnnotatedPeak, RawPeak},
+        system::{e, isize::Charge, mz, MassOverCharge},
+        Chemical,
+    };
+
+    use super::*;
+
+    fn fragment(charge_carriers: &MolecularCharge) -> Fragment {
+        Fragment {
+            formula: molecular_formula!(C 6 H 12 O 6) + charge_carriers.formula(),
+            charge: charge_carriers.formula().charge(),
+            ion: FragmentType::precursor,
+            peptidoform_index: 0,
+            peptide_index: 0,
+            neutral_loss: None,
+            charge_carriers: Some(charge_carriers.formula()),
+        }
+    }
+
+    fn peak(index: usize, neutral_mass: f64, charge: isize) -> DeconvolutedPeak {
+        DeconvolutedPeak {
+            index,
+            neutral_mass,
+            charge,
+        }
+    }
+
+    #[test]
+    fn neutral_mass() {
+        let neutral = molecular_formula!(C 6 H 12 O 6).monoisotopic_mass().value;
+        for carriers in [
+            MolecularCharge::proton(2),
+            MolecularCharge::deprotonation(1),
+            MolecularCharge::new(&[(1, molecular_formula!(Na 1 Electron -1))]),
+        ] {
+            let fragment = fragment(&carriers);
+            assert!((fragment.neutral_mass(MassMode::Monoisotopic).value - neutral).abs() < 1e-9);
+        }
+        // Without known charge carriers protons are assumed
+        let unknown = Fragment {
+            charge_carriers: None,
+            ..fragment(&MolecularCharge::proton(2))
+        };
+        assert!((unknown.neutral_mass(MassMode::Monoisotopic).value - neutral).abs() < 1e-9);
+    }
+
+    #[test]
+    fn closest() {
+        let sodium = fragment(&MolecularCharge::new(&[(
+            1,
+            molecular_formula!(Na 1 Electron -1),
+        )]));
+        let neutral = molecular_formula!(C 6 H 12 O 6).monoisotopic_mass().value;
+        // The closest peak with the right charge wins
+        assert_eq!(
+            closest_peak(
+                [
+                    peak(0, neutral - 0.001, 1),
+                    peak(1, neutral + 0.0001, 2),
+                    peak(2, neutral + 0.0005, 1),
+                ],
+                &sodium,
+                MassMode::Monoisotopic
+            ),
+            Some(2)
+        );
+        // Peaks with an unknown charge match any fragment
+        assert_eq!(
+            closest_peak(
+                [peak(0, neutral + 0.001, 1), peak(1, neutral, 0)],
+                &sodium,
+                MassMode::Monoisotopic
+            ),
+            Some(1)
+        );
+        // The sodium is removed from the mass, not a proton
+        let proton = molecular_formula!(H 1 Electron -1)
+            .monoisotopic_mass()
+            .value;
+        let as_protonated = sodium.mz(MassMode::Monoisotopic).value - proton;
+        assert_eq!(
+            closest_peak(
+                [peak(0, as_protonated, 1), peak(1, neutral, 1)],
+                &sodium,
+                MassMode::Monoisotopic
+            ),
+            Some(1)
+        );
+        assert_eq!(
+            closest_peak([peak(0, neutral, 2)], &sodium, MassMode::Monoisotopic),
+            None
+        );
+    }
+
+    #[test]
+    fn charge_consistent_annotation() {
+        let protonated = fragment(&MolecularCharge::proton(1));
+        let doubly = fragment(&MolecularCharge::proton(2));
+        let mut annotated = AnnotatedPeak::background(&RawPeak {
+            mz: MassOverCharge::new::<mz>(100.0),
+            intensity: OrderedFloat(1.0),
+        });
+        annotated.annotation = vec![protonated, doubly.clone()];
+        assert_eq!(annotated.charge_consistent_annotation().count(), 2);
+        annotated.charge = Some(Charge::new::<e>(2));
+        assert_eq!(
+            annotated.charge_consistent_annotation().collect::<Vec<_>>(),
+            vec![&doubly]
+        );
+        annotated.charge = Some(Charge::new::<e>(3));
+        assert_eq!(annotated.charge_consistent_annotation().count(), 0);
+    }
+}
@@ -20,8 +20,23 @@ pub trait AnnotatableSpectrum {
     /// spectrum vector for closest peak (if there is any).
     fn search(&self, query: MassOverCharge, tolerance: Self::Tolerance) -> Option<usize>;
 
+    /// Search for the peak matching the given fragment within the tolerance. Has to return the
+    /// index in the annotated spectrum vector for the closest peak (if there is any). By default
+    /// this searches for the mz of the fragment, spectra that know the charge of their peaks (like
+    /// deconvoluted spectra) can override this to only match fragments with the same charge.
+    fn search_fragment(
+        &self,
+        fragment: &Fragment,
+        mode: MassMode,
+        tolerance: Self::Tolerance,
+    ) -> Option<usize> {
+        Self::search(self, fragment.mz(mode), tolerance)
+    }
+
     /// Annotate this spectrum with the given peptidoform and given fragments see
-    /// [`crate::CompoundPeptidoform::generate_theoretical_fragments`].
+    /// [`crate::CompoundPeptidoform::generate_theoretical_fragments`]. If the isotope model is
+    /// enabled (see [`crate::model::IsotopeModel`]) the isotope peaks of all found fragments are
+    /// annotated as well, except for peaks with a known charge as these are already deconvoluted.
     fn annotate(
         &self,
         peptide: CompoundPeptidoform,
@@ -40,11 +55,91 @@ pub trait AnnotatableSpectrum {
             }
 
             // Get the index of the element closest to this value
-            if let Some(index) = Self::search(self, mz, tolerance) {
+            if let Some(index) = Self::search_fragment(self, fragment, mode, tolerance) {
                 annotated.spectrum[index].annotation.push(fragment.clone());
+
+                #[cfg(feature = "isotopes")]
+                if model.isotopes.annotate
+                    && mode == MassMode::Monoisotopic
+                    && annotated.spectrum[index].charge.is_none()
+                {
+                    for (index, offset) in
+                        match_isotopes(self, &annotated, fragment, index, model, tolerance)
+                    {
+                        annotated.spectrum[index]
+                            .isotope_annotation
+                            .push((fragment.clone(), offset));
+                    }
+                }
             }
         }
 
         annotated
     }
 }
+
+/// The mass difference between two consecutive isotope peaks, the difference between 13C and 12C
+#[cfg(feature = "isotopes")]
+const ISOTOPE_SPACING: f64 = 1.003_354_835;
+
+/// Find the isotope peaks of a fragment of which the monoisotopic peak was found at the given index.
+///
+/// Returns the index in the annotated spectrum and the isotope offset of all found isotope peaks.
+/// If the model requires a minimal envelope similarity and this is not reached nothing is returned.
+#[cfg(feature = "isotopes")]
+fn match_isotopes<S: AnnotatableSpectrum + ?Sized>(
+    spectrum: &S,
+    annotated: &AnnotatedSpectrum,
+    fragment: &Fragment,
+    monoisotopic_index: usize,
+    model: &Model,
+    tolerance: S::Tolerance,
+) -> Vec<(usize, usize)> {
+    let distribution = fragment
+        .formula
+        .isotopic_distribution(model.isotopes.threshold);
+    let monoisotopic = fragment.mz(MassMode::Monoisotopic);
+    let charge = fragment.charge.value.unsigned_abs() as f64;
+
+    let mut found = Vec::new();
+    let mut observed = vec![*annotated.spectrum[monoisotopic_index].intensity];
+    let mut theoretical = vec![distribution[0]];
+    for (offset, abundance) in distribution.iter().enumerate().skip(1) {
+        if *abundance < model.isotopes.threshold {
+            continue;
+        }
+        let isotope_mz = monoisotopic
+            + MassOverCharge::new::<crate::system::mz>(offset as f64 * ISOTOPE_SPACING / charge);
+        if !model.mz_range.contains(&isotope_mz) {
+            continue;
+        }
+        let index = spectrum
+            .search(isotope_mz, tolerance)
+            .filter(|index| *index != monoisotopic_index);
+        observed.push(index.map_or(0.0, |index| *annotated.spectrum[index].intensity));
+        theoretical.push(*abundance);
+        if let Some(index) = index {
+            found.push((index, offset));
+        }
+    }
+
+    if let Some(minimal_similarity) = model.isotopes.minimal_similarity {
+        if cosine_similarity(&observed, &theoretical) < minimal_similarity {
+            return Vec::new();
+        }
+    }
+    found
+}
+
+/// The cosine similarity between two equally sized vectors, 0.0 if either vector is all zeros
+#[cfg(feature = "isotopes")]
+fn cosine_similarity(a: &[f64], b: &[f64]) -> f64 {
+    let dot: f64 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+    let norm_a = a.iter().map(|a| a * a).sum::<f64>().sqrt();
+    let norm_b = b.iter().map(|b| b * b).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
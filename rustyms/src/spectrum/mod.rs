@@ -1,6 +1,7 @@
 //! Spectrum related code
 
 mod annotated;
+mod deconvoluted;
 mod fdr;
 mod fragmentation;
 #[cfg(feature = "mzdata")]
@@ -8,6 +9,7 @@ mod mzdata;
 mod peaks;
 mod raw;
 mod scores;
+mod search_scores;
 
 pub use annotated::*;
 pub use fdr::*;
@@ -15,3 +17,4 @@ pub use fragmentation::*;
 pub use peaks::*;
 pub use raw::*;
 pub use scores::*;
+pub use search_scores::*;
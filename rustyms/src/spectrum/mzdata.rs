@@ -1,11 +1,20 @@
 use mzdata::{prelude::*, spectrum::RefPeakDataLevel};
 
 use crate::{
-    spectrum::{AnnotatableSpectrum, AnnotatedPeak, AnnotatedSpectrum},
-    system::MassOverCharge,
-    CompoundPeptidoform,
+    spectrum::{
+        deconvoluted::{closest_peak, DeconvolutedPeak},
+        AnnotatableSpectrum, AnnotatedPeak, AnnotatedSpectrum,
+    },
+    system::{isize::Charge, MassOverCharge},
+    CompoundPeptidoform, Fragment, MassMode,
 };
 
+/// The mass of a proton, used to convert the neutral masses of deconvoluted peaks to and from the
+/// mz of the singly protonated species
+fn proton_mass(mode: MassMode) -> f64 {
+    molecular_formula!(H 1 Electron -1).mass(mode).value
+}
+
 impl<S: SpectrumLike> AnnotatableSpectrum for S {
     type Tolerance = Tolerance;
 
@@ -30,9 +39,15 @@ impl<S: SpectrumLike> AnnotatableSpectrum for S {
                     .collect(),
                 RefPeakDataLevel::Deconvoluted(data) => data
                     .iter()
-                    .map(|p| {
-                        AnnotatedPeak::background(&super::RawPeak {
-                            mz: MassOverCharge::new::<crate::system::mz>(p.neutral_mass), // TODO: This is M (not MH+) which is not very well supported in the current matching
+                    .map(|p| AnnotatedPeak {
+                        charge: isize::try_from(p.charge)
+                            .ok()
+                            .filter(|c| *c != 0)
+                            .map(Charge::new::<crate::system::e>),
+                        ..AnnotatedPeak::background(&super::RawPeak {
+                            mz: MassOverCharge::new::<crate::system::mz>(
+                                p.neutral_mass + proton_mass(MassMode::Monoisotopic),
+                            ),
                             intensity: ordered_float::OrderedFloat(f64::from(p.intensity)),
                         })
                     })
@@ -41,12 +56,43 @@ impl<S: SpectrumLike> AnnotatableSpectrum for S {
         }
     }
 
+    /// For deconvoluted spectra the query is interpreted as the mz of the singly protonated species
     fn search(
         &self,
         query: crate::system::MassOverCharge,
         tolerance: Self::Tolerance,
     ) -> Option<usize> {
-        self.peaks().search(query.value, tolerance)
+        match self.peaks() {
+            RefPeakDataLevel::Deconvoluted(data) => {
+                data.search(query.value - proton_mass(MassMode::Monoisotopic), tolerance)
+            }
+            peaks => peaks.search(query.value, tolerance),
+        }
+    }
+
+    /// For deconvoluted spectra the neutral mass of the fragment (see [`Fragment::neutral_mass`])
+    /// is matched, only to peaks with the same charge as the fragment (or peaks with an unknown
+    /// charge)
+    fn search_fragment(
+        &self,
+        fragment: &Fragment,
+        mode: MassMode,
+        tolerance: Self::Tolerance,
+    ) -> Option<usize> {
+        match self.peaks() {
+            RefPeakDataLevel::Deconvoluted(data) => closest_peak(
+                data.all_peaks_for(fragment.neutral_mass(mode).value, tolerance)
+                    .iter()
+                    .map(|p| DeconvolutedPeak {
+                        index: p.index as usize,
+                        neutral_mass: p.neutral_mass,
+                        charge: isize::try_from(p.charge).unwrap_or_default(),
+                    }),
+                fragment,
+                mode,
+            ),
+            _ => self.search(fragment.mz(mode), tolerance),
+        }
     }
 }
 
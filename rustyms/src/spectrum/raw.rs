@@ -10,7 +10,7 @@ use crate::{
     spectrum::{AnnotatableSpectrum, AnnotatedPeak, PeakSpectrum},
     system::{
         f64::{Mass, MassOverCharge, Ratio, Time},
-        usize::Charge,
+        isize::Charge,
     },
     AnnotatedSpectrum, CompoundPeptidoform, Tolerance, WithinTolerance,
 };
@@ -26,7 +26,8 @@ pub struct RawSpectrum {
     pub rt: Option<Time>,
     /// The found precursor charge
     pub charge: Option<Charge>,
-    /// The found precursor mass
+    /// The found precursor m/z (as the PEPMASS in MGF files), stored as a mass because the
+    /// charge is not always known
     pub mass: Option<Mass>,
     /// The found precursor intensity
     pub intensity: Option<f64>,
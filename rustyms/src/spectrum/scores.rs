@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     fragment::{Fragment, FragmentKind},
     peptide::UnAmbiguous,
+    spectrum::AnnotatedPeak,
     AnnotatedSpectrum, LinearPeptide, MassMode, Model,
 };
 
@@ -35,8 +36,8 @@ impl AnnotatedSpectrum {
                     .iter()
                     .enumerate()
                     .map(|(peptide_index, peptide)| {
-                        let (recovered_fragments, peaks, intensity_annotated) = self
-                            .filtered_base_score(
+                        let (recovered_fragments, peaks, intensity_annotated, intensity_isotopes) =
+                            self.filtered_base_score(
                                 &fragments,
                                 Some(peptidoform_index),
                                 Some(peptide_index),
@@ -53,6 +54,10 @@ impl AnnotatedSpectrum {
                                 fragments: recovered_fragments,
                                 peaks,
                                 intensity: Recovered::new(intensity_annotated, total_intensity),
+                                isotope_intensity: Recovered::new(
+                                    intensity_isotopes,
+                                    total_intensity,
+                                ),
                                 theoretical_positions: Recovered::new(
                                     positions,
                                     peptide.len() as u32,
@@ -70,7 +75,7 @@ impl AnnotatedSpectrum {
             })
             .collect();
         // Get the statistics for the combined peptides
-        let (recovered_fragments, peaks, intensity_annotated) =
+        let (recovered_fragments, peaks, intensity_annotated, intensity_isotopes) =
             self.filtered_base_score(&fragments, None, None, None);
         let unique_formulas = self.score_unique_formulas(&fragments, None, None);
         (
@@ -79,6 +84,7 @@ impl AnnotatedSpectrum {
                     fragments: recovered_fragments,
                     peaks,
                     intensity: Recovered::new(intensity_annotated, total_intensity),
+                    isotope_intensity: Recovered::new(intensity_isotopes, total_intensity),
                     unique_formulas,
                 },
                 ions: self.score_individual_ions::<UnAmbiguous>(&fragments, None, total_intensity),
@@ -88,48 +94,59 @@ impl AnnotatedSpectrum {
     }
 
     /// Get the base score of this spectrum
-    /// (Fragments, peaks, intensity)
+    /// (Fragments, peaks, intensity, isotope intensity)
+    /// Peaks only annotated as isotope peak count towards the peaks and intensity, and
+    /// separately towards the isotope intensity.
     fn filtered_base_score(
         &self,
         fragments: &[&Fragment],
         peptidoform_index: Option<usize>,
         peptide_index: Option<usize>,
         ion: Option<FragmentKind>,
-    ) -> (Recovered<u32>, Recovered<u32>, f64) {
-        let (peaks_annotated, fragments_found, intensity_annotated) = self
+    ) -> (Recovered<u32>, Recovered<u32>, f64, f64) {
+        let filter = |a: &Fragment| {
+            peptidoform_index.map_or(true, |i| a.peptidoform_index == i)
+                && peptide_index.map_or(true, |i| a.peptide_index == i)
+                && ion.map_or(true, |kind| a.ion.kind() == kind)
+        };
+        let (peaks_annotated, fragments_found, intensity_annotated, intensity_isotopes) = self
             .spectrum
             .iter()
             .filter_map(|p| {
                 let number = p
-                    .annotation
-                    .iter()
-                    .filter(|a| {
-                        peptidoform_index.map_or(true, |i| a.peptidoform_index == i)
-                            && peptide_index.map_or(true, |i| a.peptide_index == i)
-                            && ion.map_or(true, |kind| a.ion.kind() == kind)
-                    })
+                    .charge_consistent_annotation()
+                    .filter(|a| filter(a))
                     .count() as u32;
                 if number == 0 {
-                    None
+                    p.isotope_annotation
+                        .iter()
+                        .any(|(a, _)| filter(a))
+                        .then_some((0, *p.intensity, true))
                 } else {
-                    Some((number, *p.intensity))
+                    Some((number, *p.intensity, false))
                 }
             })
-            .fold((0u32, 0u32, 0.0), |(n, f, intensity), p| {
-                (n + 1, f + p.0, intensity + p.1)
-            });
-        let total_fragments = fragments
-            .iter()
-            .filter(|f| {
-                peptidoform_index.map_or(true, |i| f.peptidoform_index == i)
-                    && peptide_index.map_or(true, |i| f.peptide_index == i)
-                    && ion.map_or(true, |kind| f.ion.kind() == kind)
-            })
-            .count() as u32;
+            .fold(
+                (0u32, 0u32, 0.0, 0.0),
+                |(n, f, intensity, isotopes), (number, peak_intensity, isotope)| {
+                    (
+                        n + 1,
+                        f + number,
+                        intensity + peak_intensity,
+                        if isotope {
+                            isotopes + peak_intensity
+                        } else {
+                            isotopes
+                        },
+                    )
+                },
+            );
+        let total_fragments = fragments.iter().filter(|f| filter(f)).count() as u32;
         (
             Recovered::new(fragments_found, total_fragments),
             Recovered::new(peaks_annotated, self.spectrum.len() as u32),
             intensity_annotated,
+            intensity_isotopes,
         )
     }
 
@@ -145,8 +162,7 @@ impl AnnotatedSpectrum {
             self.spectrum
                 .iter()
                 .flat_map(|p| {
-                    p.annotation
-                        .iter()
+                    p.charge_consistent_annotation()
                         .filter(|a| {
                             a.peptidoform_index == peptidoform_index
                                 && a.peptide_index == peptide_index
@@ -181,7 +197,7 @@ impl AnnotatedSpectrum {
             .spectrum
             .iter()
             .flat_map(|p| {
-                p.annotation.iter().filter(|a| {
+                p.charge_consistent_annotation().filter(|a| {
                     peptide_index.map_or(true, |i| a.peptide_index == i)
                         && ion.map_or(true, |kind| a.ion.kind() == kind)
                 })
@@ -222,12 +238,13 @@ impl AnnotatedSpectrum {
         .iter()
         .copied()
         .filter_map(|ion| {
-            let (recovered_fragments, peaks, intensity_annotated) = self.filtered_base_score(
-                fragments,
-                peptide.as_ref().map(|p| p.0),
-                peptide.as_ref().map(|p| p.1),
-                Some(ion),
-            );
+            let (recovered_fragments, peaks, intensity_annotated, intensity_isotopes) = self
+                .filtered_base_score(
+                    fragments,
+                    peptide.as_ref().map(|p| p.0),
+                    peptide.as_ref().map(|p| p.1),
+                    Some(ion),
+                );
             if let Some((peptidoform_index, peptide_index, peptide)) = peptide {
                 if recovered_fragments.total > 0 {
                     let (positions, expected_positions) = self.score_positions(
@@ -242,6 +259,7 @@ impl AnnotatedSpectrum {
                             fragments: recovered_fragments,
                             peaks,
                             intensity: Recovered::new(intensity_annotated, total_intensity),
+                            isotope_intensity: Recovered::new(intensity_isotopes, total_intensity),
                             theoretical_positions: Recovered::new(positions, peptide.len() as u32),
                             expected_positions: Recovered::new(positions, expected_positions),
                         },
@@ -257,6 +275,7 @@ impl AnnotatedSpectrum {
                         fragments: recovered_fragments,
                         peaks,
                         intensity: Recovered::new(intensity_annotated, total_intensity),
+                        isotope_intensity: Recovered::new(intensity_isotopes, total_intensity),
                         unique_formulas,
                     },
                 ))
@@ -266,22 +285,28 @@ impl AnnotatedSpectrum {
         })
         .chain(
             [
+                FragmentKind::A,
+                FragmentKind::C,
+                FragmentKind::X,
                 FragmentKind::Y,
+                FragmentKind::Z,
                 FragmentKind::Oxonium,
                 FragmentKind::immonium,
                 FragmentKind::m,
                 FragmentKind::diagnostic,
                 FragmentKind::precursor,
+                FragmentKind::ChargeReducedPrecursor,
             ]
             .iter()
             .copied()
             .filter_map(|ion| {
-                let (recovered_fragments, peaks, intensity_annotated) = self.filtered_base_score(
-                    fragments,
-                    peptide.as_ref().map(|p| p.0),
-                    peptide.as_ref().map(|p| p.1),
-                    Some(ion),
-                );
+                let (recovered_fragments, peaks, intensity_annotated, intensity_isotopes) = self
+                    .filtered_base_score(
+                        fragments,
+                        peptide.as_ref().map(|p| p.0),
+                        peptide.as_ref().map(|p| p.1),
+                        Some(ion),
+                    );
                 if recovered_fragments.total > 0 {
                     let unique_formulas = self.score_unique_formulas(
                         fragments,
@@ -294,6 +319,7 @@ impl AnnotatedSpectrum {
                             fragments: recovered_fragments,
                             peaks,
                             intensity: Recovered::new(intensity_annotated, total_intensity),
+                            isotope_intensity: Recovered::new(intensity_isotopes, total_intensity),
                             unique_formulas,
                         },
                     ))
@@ -306,6 +332,16 @@ impl AnnotatedSpectrum {
     }
 }
 
+impl AnnotatedPeak {
+    /// Get all annotations that have the same charge as this peak, or all annotations if the
+    /// charge of this peak is not known
+    pub(super) fn charge_consistent_annotation(&self) -> impl Iterator<Item = &Fragment> {
+        self.annotation
+            .iter()
+            .filter(|a| self.charge.map_or(true, |charge| a.charge == charge))
+    }
+}
+
 /// The scores for an annotated spectrum
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[non_exhaustive]
@@ -327,6 +363,9 @@ pub enum Score {
         peaks: Recovered<u32>,
         /// The fraction of the total intensity that could be annotated
         intensity: Recovered<f64>,
+        /// The fraction of the total intensity that could only be annotated as isotope peak of
+        /// an annotated fragment, this is included in the annotated intensity
+        isotope_intensity: Recovered<f64>,
         /// The fraction of the total positions (all positions on the peptide) that has at least one fragment found
         theoretical_positions: Recovered<u32>,
         /// The fraction of the total positions (all positions with fragments) that has at least one fragment found
@@ -340,6 +379,9 @@ pub enum Score {
         peaks: Recovered<u32>,
         /// The fraction of the total intensity that could be annotated
         intensity: Recovered<f64>,
+        /// The fraction of the total intensity that could only be annotated as isotope peak of
+        /// an annotated fragment, this is included in the annotated intensity
+        isotope_intensity: Recovered<f64>,
         /// The fraction of with unique formulas that has been found
         unique_formulas: Recovered<u32>,
     },
@@ -0,0 +1,367 @@
+//! Conventional peptide spectrum match scores, to rank candidate peptidoforms for a spectrum
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fragment::{Fragment, FragmentKind},
+    spectrum::{PeakSpectrum, RawSpectrum},
+    system::{f64::Ratio, ratio::ppm},
+    AnnotatedSpectrum, MassMode,
+};
+
+impl AnnotatedSpectrum {
+    /// Get the X!Tandem style hyperscore for this annotation.
+    ///
+    /// This is defined as the summed intensity of all peaks annotated with a b or y ion multiplied
+    /// by the factorials of the number of b and y annotated peaks. Every peak is counted once, a
+    /// peak annotated with both a b and a y ion is counted as b. The intensities are normalised to
+    /// a base peak of 100 and the score is returned as the log10 of the hyperscore. If no peaks are
+    /// annotated the score is 0.0.
+    pub fn hyperscore(&self) -> f64 {
+        let base_peak = self
+            .spectrum
+            .iter()
+            .map(|p| *p.intensity)
+            .fold(0.0, f64::max);
+        if base_peak == 0.0 {
+            return 0.0;
+        }
+        let mut intensity = 0.0;
+        let mut b: u32 = 0;
+        let mut y: u32 = 0;
+        for peak in &self.spectrum {
+            let is = |kind| {
+                peak.charge_consistent_annotation()
+                    .any(|f| f.ion.kind() == kind)
+            };
+            if is(FragmentKind::b) {
+                b += 1;
+            } else if is(FragmentKind::y) {
+                y += 1;
+            } else {
+                continue;
+            }
+            intensity += *peak.intensity / base_peak * 100.0;
+        }
+        let log_factorial = |n: u32| (2..=n).map(|n| f64::from(n).log10()).sum::<f64>();
+        if intensity == 0.0 {
+            0.0
+        } else {
+            intensity.log10() + log_factorial(b) + log_factorial(y)
+        }
+    }
+
+    /// Get the number of matched ions and the statistics on the mass errors of these matches. The
+    /// mass error for every annotation of every annotated peak is used (isotope annotations are
+    /// ignored).
+    pub fn matched_ions(&self, mass_mode: MassMode) -> MatchedIons {
+        let mut peaks = 0;
+        let mut errors = Vec::new();
+        for peak in &self.spectrum {
+            let before = errors.len();
+            errors.extend(peak.charge_consistent_annotation().map(|f| {
+                let theoretical = f.mz(mass_mode).value;
+                (peak.experimental_mz.value - theoretical) / theoretical * 1e6
+            }));
+            if errors.len() > before {
+                peaks += 1;
+            }
+        }
+        if errors.is_empty() {
+            return MatchedIons {
+                peaks,
+                fragments: 0,
+                mean_error: Ratio::default(),
+                mean_absolute_error: Ratio::default(),
+                standard_deviation: Ratio::default(),
+            };
+        }
+        let n = errors.len() as f64;
+        let mean = errors.iter().sum::<f64>() / n;
+        MatchedIons {
+            peaks,
+            fragments: errors.len(),
+            mean_error: Ratio::new::<ppm>(mean),
+            mean_absolute_error: Ratio::new::<ppm>(errors.iter().map(|e| e.abs()).sum::<f64>() / n),
+            standard_deviation: Ratio::new::<ppm>(
+                (errors.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / n).sqrt(),
+            ),
+        }
+    }
+
+    /// Get the normalised spectral angle between the observed intensities and the given predicted
+    /// intensities. The observed intensity of a fragment is the summed intensity of all peaks
+    /// annotated with a fragment with the same ion, charge, neutral loss, and peptide. Both
+    /// vectors are L2 normalised and the returned angle is `1 - 2 * acos(dot) / π`, so 1.0 for
+    /// identical spectra and 0.0 for orthogonal spectra. If either vector has no intensity the
+    /// angle is 0.0.
+    pub fn spectral_angle(&self, predicted: &[(Fragment, f64)]) -> f64 {
+        let observed = predicted
+            .iter()
+            .map(|(fragment, _)| {
+                self.spectrum
+                    .iter()
+                    .filter(|p| {
+                        p.charge_consistent_annotation().any(|a| {
+                            a.ion == fragment.ion
+                                && a.charge == fragment.charge
+                                && a.neutral_loss == fragment.neutral_loss
+                                && a.peptidoform_index == fragment.peptidoform_index
+                                && a.peptide_index == fragment.peptide_index
+                        })
+                    })
+                    .map(|p| *p.intensity)
+                    .sum::<f64>()
+            })
+            .collect_vec();
+        let observed_norm = observed.iter().map(|i| i * i).sum::<f64>().sqrt();
+        let predicted_norm = predicted.iter().map(|(_, i)| i * i).sum::<f64>().sqrt();
+        if observed_norm == 0.0 || predicted_norm == 0.0 {
+            return 0.0;
+        }
+        let dot = observed
+            .iter()
+            .zip(predicted)
+            .map(|(o, (_, p))| o / observed_norm * p / predicted_norm)
+            .sum::<f64>()
+            .clamp(-1.0, 1.0);
+        1.0 - 2.0 * dot.acos() / std::f64::consts::PI
+    }
+}
+
+/// The number of matched ions and the statistics on their mass errors
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct MatchedIons {
+    /// The number of peaks with at least one annotation
+    pub peaks: usize,
+    /// The total number of annotations
+    pub fragments: usize,
+    /// The mean mass error (experimental - theoretical), zero if nothing is matched
+    pub mean_error: Ratio,
+    /// The mean absolute mass error, zero if nothing is matched
+    pub mean_absolute_error: Ratio,
+    /// The standard deviation of the mass error, zero if nothing is matched
+    pub standard_deviation: Ratio,
+}
+
+impl RawSpectrum {
+    /// Preprocess this spectrum for Comet style fast XCorr scoring. The peaks are binned (with
+    /// the square root of the intensity, retaining the highest per bin), peaks below 5% of the
+    /// base peak are removed, the spectrum is normalised to 50 in ten windows, and lastly the
+    /// average of the surrounding 150 bins is subtracted from every bin. Comet uses a bin width
+    /// of 1.0005079 with offset 0.4 for low resolution and 0.02 with offset 0.0 for high
+    /// resolution fragment spectra.
+    pub fn fast_xcorr(&self, bin_width: f64, bin_offset: f64) -> FastXcorrSpectrum {
+        const OFFSET: usize = 75;
+        let bin = |mz: f64| (mz / bin_width + 1.0 - bin_offset).max(0.0) as usize;
+        let peaks = self.spectrum().collect_vec();
+        let Some(last) = peaks.last() else {
+            return FastXcorrSpectrum {
+                bins: Vec::new(),
+                bin_width,
+                bin_offset,
+            };
+        };
+        let mut binned = vec![0.0; bin(last.mz.value) + 1];
+        for peak in &peaks {
+            let index = bin(peak.mz.value);
+            binned[index] = f64::max(binned[index], peak.intensity.sqrt());
+        }
+
+        // Normalise in ten windows
+        let base_peak = binned.iter().copied().fold(0.0, f64::max);
+        let window_size = binned.len() / 10 + 1;
+        for window in binned.chunks_mut(window_size) {
+            let max = window.iter().copied().fold(0.0, f64::max);
+            for value in window.iter_mut() {
+                *value = if max > 0.0 && *value > base_peak * 0.05 {
+                    *value / max * 50.0
+                } else {
+                    0.0
+                };
+            }
+        }
+
+        // Subtract the local average, using the prefix sums to get the sum of any range of bins
+        let prefix: Vec<f64> = std::iter::once(0.0)
+            .chain(binned.iter().scan(0.0, |sum, value| {
+                *sum += value;
+                Some(*sum)
+            }))
+            .collect();
+        let bins = (0..binned.len())
+            .map(|index| {
+                let surrounding = prefix[(index + OFFSET + 1).min(binned.len())]
+                    - prefix[index.saturating_sub(OFFSET)]
+                    - binned[index];
+                binned[index] - surrounding / (OFFSET * 2) as f64
+            })
+            .collect();
+
+        FastXcorrSpectrum {
+            bins,
+            bin_width,
+            bin_offset,
+        }
+    }
+}
+
+/// A spectrum preprocessed for fast XCorr scoring, see [`RawSpectrum::fast_xcorr`]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct FastXcorrSpectrum {
+    bins: Vec<f64>,
+    bin_width: f64,
+    bin_offset: f64,
+}
+
+impl FastXcorrSpectrum {
+    /// Get the XCorr for the given theoretical fragments. Every bin is counted once, no matter
+    /// how many fragments fall in that bin. As in Comet the sum is scaled by 0.005.
+    pub fn xcorr(&self, fragments: &[Fragment], mass_mode: MassMode) -> f64 {
+        fragments
+            .iter()
+            .map(|f| (f.mz(mass_mode).value / self.bin_width + 1.0 - self.bin_offset) as usize)
+            .unique()
+            .filter_map(|index| self.bins.get(index))
+            .sum::<f64>()
+            * 0.005
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_panics_doc)]
+mod tests {
+    use ordered_float::OrderedFloat;
+
+    use crate::{
+        model::PrimaryIonSeries,
+        spectrum::{AnnotatableSpectrum, AnnotatedPeak, RawPeak},
+        system::{isize::Charge, mz, MassOverCharge},
+        CompoundPeptidoform, Model,
+    };
+
+    use super::*;
+
+    fn spectrum(fragments: &[Fragment]) -> RawSpectrum {
+        let mut spectrum = RawSpectrum::default();
+        for (index, fragment) in fragments.iter().enumerate() {
+            spectrum.add_peak(RawPeak {
+                mz: fragment.mz(MassMode::Monoisotopic),
+                intensity: OrderedFloat(10.0 * (index + 1) as f64),
+            });
+        }
+        spectrum.add_peak(RawPeak {
+            mz: MassOverCharge::new::<mz>(500.0),
+            intensity: OrderedFloat(1.0),
+        });
+        spectrum
+    }
+
+    #[test]
+    fn search_scores() {
+        let peptide = CompoundPeptidoform::pro_forma("PEPTIDE", None).unwrap();
+        let model = Model::none()
+            .b(PrimaryIonSeries::default())
+            .y(PrimaryIonSeries::default());
+        let fragments =
+            peptide.generate_theoretical_fragments(Charge::new::<crate::system::e>(1), &model);
+        let raw = spectrum(&fragments);
+        let annotated = raw.annotate(peptide, &fragments, &model, MassMode::Monoisotopic);
+
+        let matched = annotated.matched_ions(MassMode::Monoisotopic);
+        assert_eq!(matched.peaks, fragments.len());
+        assert_eq!(matched.fragments, fragments.len());
+        assert!(matched.mean_absolute_error.get::<ppm>() < 1e-6);
+
+        assert!(annotated.hyperscore() > 0.0);
+
+        let predicted = fragments
+            .iter()
+            .enumerate()
+            .map(|(index, f)| (f.clone(), (index + 1) as f64))
+            .collect_vec();
+        assert!((annotated.spectral_angle(&predicted) - 1.0).abs() < 1e-6);
+        let reversed = fragments
+            .iter()
+            .enumerate()
+            .map(|(index, f)| (f.clone(), (fragments.len() - index) as f64))
+            .collect_vec();
+        assert!(annotated.spectral_angle(&reversed) < 1.0);
+
+        let xcorr = raw.fast_xcorr(0.02, 0.0);
+        let other = CompoundPeptidoform::pro_forma("EDITPEP", None)
+            .unwrap()
+            .generate_theoretical_fragments(Charge::new::<crate::system::e>(1), &model);
+        assert!(
+            xcorr.xcorr(&fragments, MassMode::Monoisotopic)
+                > xcorr.xcorr(&other, MassMode::Monoisotopic)
+        );
+    }
+
+    #[test]
+    fn hyperscore_reference() {
+        let peptide = CompoundPeptidoform::pro_forma("PEPTIDE", None).unwrap();
+        let model = Model::none()
+            .a(PrimaryIonSeries::default())
+            .b(PrimaryIonSeries::default())
+            .y(PrimaryIonSeries::default());
+        let fragments =
+            peptide.generate_theoretical_fragments(Charge::new::<crate::system::e>(1), &model);
+        let find = |kind: FragmentKind| {
+            fragments
+                .iter()
+                .find(|f| f.ion.kind() == kind)
+                .unwrap()
+                .clone()
+        };
+        let peak = |intensity: f64, annotation: Vec<Fragment>| AnnotatedPeak {
+            experimental_mz: MassOverCharge::new::<mz>(intensity),
+            intensity: OrderedFloat(intensity),
+            charge: None,
+            annotation,
+            isotope_annotation: Vec::new(),
+        };
+        let mut annotated =
+            spectrum(&fragments).annotate(peptide, &fragments, &model, MassMode::Monoisotopic);
+        annotated.spectrum = vec![
+            peak(50.0, vec![find(FragmentKind::b)]),
+            peak(100.0, vec![find(FragmentKind::y)]),
+            peak(20.0, vec![find(FragmentKind::b), find(FragmentKind::y)]),
+            peak(80.0, vec![find(FragmentKind::a)]),
+            peak(10.0, Vec::new()),
+        ];
+        // Summed intensity 50 + 100 + 20 = 170 (base peak 100), with 2 b and 1 y peaks: log10(170
+        // * 2! * 1!) = log10(340)
+        assert!((annotated.hyperscore() - 2.531_478_917_042_255).abs() < 1e-12);
+    }
+
+    #[test]
+    fn fast_xcorr_reference() {
+        let mut raw = RawSpectrum::default();
+        for (position, intensity) in [(99.0, 400.0), (149.0, 100.0), (199.0, 4.0)] {
+            raw.add_peak(RawPeak {
+                mz: MassOverCharge::new::<mz>(position),
+                intensity: OrderedFloat(intensity),
+            });
+        }
+        let xcorr = raw.fast_xcorr(1.0, 0.0);
+        // The peaks land in bins 100, 150, and 200, which are all in separate normalisation
+        // windows (of 21 bins) so all become 50. Then 1/150th of every other peak within 75 bins
+        // is subtracted.
+        assert_eq!(xcorr.bins.len(), 201);
+        for (index, expected) in [
+            (100, 50.0 - 50.0 / 150.0),
+            (150, 50.0 - 100.0 / 150.0),
+            (200, 50.0 - 50.0 / 150.0),
+            (120, -100.0 / 150.0),
+            (0, 0.0),
+        ] {
+            assert!(
+                (xcorr.bins[index] - expected).abs() < 1e-9,
+                "bin {index} expected {expected} but found {}",
+                xcorr.bins[index]
+            );
+        }
+    }
+}